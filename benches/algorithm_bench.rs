@@ -46,7 +46,7 @@ fn benchmark_initialization(c: &mut Criterion) {
             let config = Config::new().with_min_pop_size(25).with_generation_size(40);
 
             b.iter(|| {
-                let mut algorithm = HgsAlgorithm::new(problem.clone(), config.clone());
+                let mut algorithm = HgsAlgorithm::new(problem.clone(), config.clone()).unwrap();
                 algorithm.initialize();
             });
         });
@@ -64,12 +64,17 @@ fn benchmark_local_search(c: &mut Criterion) {
             let problem = create_benchmark_problem(size);
             let config = Config::new().with_min_pop_size(25).with_generation_size(40);
 
-            let mut algorithm = HgsAlgorithm::new(problem.clone(), config.clone());
+            let mut algorithm = HgsAlgorithm::new(problem.clone(), config.clone()).unwrap();
             algorithm.initialize();
 
-            // Get a solution to improve
-            let individual = &algorithm.population.feasible_individuals[0];
-            let mut solution = individual.solution.clone();
+            // Get a solution to improve, feasible or not
+            let individual = algorithm
+                .population
+                .feasible_individuals
+                .first()
+                .or(algorithm.population.infeasible_individuals.first())
+                .expect("population is initialized");
+            let solution = individual.solution.clone();
 
             b.iter(|| {
                 let mut solution_clone = solution.clone();
@@ -98,8 +103,8 @@ fn benchmark_convergence(c: &mut Criterion) {
                 .with_time_limit(Duration::from_secs(10));
 
             b.iter(|| {
-                let mut algorithm = HgsAlgorithm::new(problem.clone(), config.clone());
-                algorithm.run();
+                let mut algorithm = HgsAlgorithm::new(problem.clone(), config.clone()).unwrap();
+                algorithm.run().unwrap();
             });
         });
     }
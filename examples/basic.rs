@@ -4,7 +4,6 @@ use hgs_cvrp::config::Config;
 use hgs_cvrp::problem::Problem;
 use hgs_cvrp::utils::{format_duration, print_solution_visualization, save_solution};
 use std::env;
-use std::path::Path;
 use std::time::{Duration, Instant};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -38,18 +37,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Create and run algorithm
     println!("Initializing algorithm");
-    let mut algorithm = hgs_cvrp::HgsAlgorithm::new(problem.clone(), config);
+    let mut algorithm = hgs_cvrp::HgsAlgorithm::new(problem.clone(), config)?;
 
     println!("Starting search (time limit: 60s)");
     let start_time = Instant::now();
-    let best_solution = algorithm.run();
+    let best_solution = algorithm.run()?;
     let runtime = start_time.elapsed();
 
     // Print results
     println!("Search completed in {}", format_duration(runtime));
-    println!("Best solution distance: {:.2}", best_solution.distance);
-    println!("Is feasible: {}", best_solution.is_feasible);
-    println!("Number of routes: {}", best_solution.routes.len());
+    print!("{}", best_solution.summary(&problem));
 
     // Save solution
     let output_path = format!("{}.sol", problem.name);
@@ -0,0 +1,64 @@
+//! Fitness landscape analysis of a population, e.g. to tune the diversity parameters of an
+//! instance family.
+
+use crate::individual::Individual;
+use crate::population::Population;
+use crate::solution::Solution;
+
+/// Broken-pairs distance to a reference solution and cost of an individual.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FitnessDistance {
+    /// Broken-pairs distance to the reference solution, in [0, 1]
+    pub distance: f64,
+    /// Cost of the individual
+    pub cost: f64,
+}
+
+/// Compute the distance to `best` and the cost of each individual.
+pub fn fitness_distances<'a>(
+    individuals: impl IntoIterator<Item = &'a Individual>,
+    best: &Solution,
+) -> Vec<FitnessDistance> {
+    let reference = Individual::new(best.clone());
+    individuals
+        .into_iter()
+        .map(|individual| FitnessDistance {
+            distance: individual.broken_pairs_distance(&reference),
+            cost: individual.get_cost(),
+        })
+        .collect()
+}
+
+/// Compute the Pearson correlation between the distances and the costs.
+///
+/// Returns `None` with fewer than two points, or if the distances or the costs are all equal.
+pub fn correlation(points: &[FitnessDistance]) -> Option<f64> {
+    if points.len() < 2 {
+        return None;
+    }
+
+    let count = points.len() as f64;
+    let mean_distance = points.iter().map(|point| point.distance).sum::<f64>() / count;
+    let mean_cost = points.iter().map(|point| point.cost).sum::<f64>() / count;
+
+    let (mut covariance, mut distance_variance, mut cost_variance) = (0.0, 0.0, 0.0);
+    for point in points {
+        let distance = point.distance - mean_distance;
+        let cost = point.cost - mean_cost;
+        covariance += distance * cost;
+        distance_variance += distance * distance;
+        cost_variance += cost * cost;
+    }
+
+    let scale = (distance_variance * cost_variance).sqrt();
+    (scale > 0.0).then_some((covariance / scale).clamp(-1.0, 1.0))
+}
+
+/// Compute the fitness-distance correlation of the feasible individuals with the best-known
+/// solution `best`.
+///
+/// A correlation close to 1 means that costs decrease towards the best-known solution, so the
+/// landscape is easy to search; values close to 0 or negative call for more diversity.
+pub fn fitness_distance_correlation(population: &Population, best: &Solution) -> Option<f64> {
+    correlation(&fitness_distances(&population.feasible_individuals, best))
+}
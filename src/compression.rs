@@ -0,0 +1,156 @@
+//! Reading of instance files, decompressing gzip and zip content with the `compression`
+//! feature.
+
+use crate::error::HgsError;
+use std::io::BufRead;
+#[cfg(feature = "compression")]
+use std::io::Read;
+
+/// First bytes of a gzip stream
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+/// First bytes of a zip archive, the signature of its first local file header
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+/// Read the text of an instance, decompressing it if it starts like a gzip stream or a zip
+/// archive, in which case the first file of the archive is read.
+///
+/// Compressed content is recognized by its first bytes rather than a file extension, so
+/// in-memory and network sources are decompressed too. Without the `compression` feature
+/// it is rejected with a parse error.
+pub fn read_to_string<R: BufRead>(mut reader: R) -> Result<String, HgsError> {
+    let start = reader.fill_buf()?;
+    let mut content = String::new();
+    if start.starts_with(GZIP_MAGIC) {
+        read_gzip(reader, &mut content)?;
+    } else if start.starts_with(ZIP_MAGIC) {
+        read_zip(reader, &mut content)?;
+    } else {
+        reader.read_to_string(&mut content)?;
+    }
+    Ok(content)
+}
+
+/// Decompress a gzip stream, possibly of several members.
+#[cfg(feature = "compression")]
+fn read_gzip<R: BufRead>(reader: R, content: &mut String) -> Result<(), HgsError> {
+    flate2::bufread::MultiGzDecoder::new(reader).read_to_string(content)?;
+    Ok(())
+}
+
+/// Signature of the end of central directory record of a zip archive
+#[cfg(feature = "compression")]
+const ZIP_END_MAGIC: &[u8] = b"PK\x05\x06";
+/// Signature of a central directory file header
+#[cfg(feature = "compression")]
+const ZIP_ENTRY_MAGIC: &[u8] = b"PK\x01\x02";
+
+/// Decompress the first file of a zip archive, stored or deflated.
+///
+/// The entries are listed from the central directory at the end of the archive, which holds
+/// their sizes even when a streaming tool wrote them after the data in a data descriptor.
+/// Directory entries are skipped; encrypted entries and zip64 archives are rejected.
+#[cfg(feature = "compression")]
+fn read_zip<R: BufRead>(mut reader: R, content: &mut String) -> Result<(), HgsError> {
+    let mut archive = Vec::new();
+    reader.read_to_end(&mut archive)?;
+    let invalid = |message: &str| HgsError::ParseError {
+        line: 0,
+        message: format!("invalid zip archive: {message}"),
+    };
+    let read_u16 = |offset: usize| {
+        archive
+            .get(offset..offset + 2)
+            .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+            .ok_or_else(|| invalid("truncated record"))
+    };
+    let read_u32 = |offset: usize| {
+        archive
+            .get(offset..offset + 4)
+            .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .ok_or_else(|| invalid("truncated record"))
+    };
+
+    // The end record is the last 22 bytes, followed by a comment of at most 64 KiB
+    let end = (0..archive.len().saturating_sub(21))
+        .rev()
+        .take(22 + usize::from(u16::MAX))
+        .find(|&offset| archive[offset..].starts_with(ZIP_END_MAGIC))
+        .ok_or_else(|| invalid("missing central directory"))?;
+    let entry_count = read_u16(end + 10)?;
+    let mut offset = read_u32(end + 16)? as usize;
+
+    for _ in 0..entry_count {
+        if !archive[offset.min(archive.len())..].starts_with(ZIP_ENTRY_MAGIC) {
+            return Err(invalid("corrupt central directory"));
+        }
+        let flags = read_u16(offset + 8)?;
+        let method = read_u16(offset + 10)?;
+        let compressed_size = read_u32(offset + 20)?;
+        let name_length = usize::from(read_u16(offset + 28)?);
+        let entry_length = 46
+            + name_length
+            + usize::from(read_u16(offset + 30)?)
+            + usize::from(read_u16(offset + 32)?);
+        let local_offset = read_u32(offset + 42)?;
+        let is_directory = archive
+            .get(offset + 46..offset + 46 + name_length)
+            .is_some_and(|name| name.ends_with(b"/"));
+        offset += entry_length;
+        if is_directory {
+            continue;
+        }
+        if flags & 0x01 != 0 {
+            return Err(invalid("encrypted entries are not supported"));
+        }
+        if compressed_size == u32::MAX || local_offset == u32::MAX {
+            return Err(invalid("zip64 archives are not supported"));
+        }
+
+        // The local header repeats the name and may carry a different extra field
+        let local_offset = local_offset as usize;
+        let data_start = local_offset
+            + 30
+            + usize::from(read_u16(local_offset + 26)?)
+            + usize::from(read_u16(local_offset + 28)?);
+        let mut data = archive
+            .get(data_start..data_start + compressed_size as usize)
+            .ok_or_else(|| invalid("truncated entry"))?;
+        match method {
+            0 => {
+                data.read_to_string(content)?;
+            }
+            8 => {
+                flate2::bufread::DeflateDecoder::new(data).read_to_string(content)?;
+            }
+            _ => {
+                return Err(HgsError::ParseError {
+                    line: 0,
+                    message: format!("unsupported zip compression method {method}"),
+                })
+            }
+        }
+        return Ok(());
+    }
+    Err(invalid("no file entry"))
+}
+
+/// Reject a gzip stream, which needs the `compression` feature.
+#[cfg(not(feature = "compression"))]
+fn read_gzip<R: BufRead>(_reader: R, _content: &mut String) -> Result<(), HgsError> {
+    Err(compression_disabled("gzip"))
+}
+
+/// Reject a zip archive, which needs the `compression` feature.
+#[cfg(not(feature = "compression"))]
+fn read_zip<R: BufRead>(_reader: R, _content: &mut String) -> Result<(), HgsError> {
+    Err(compression_disabled("zip"))
+}
+
+/// Error for compressed content read without the `compression` feature.
+#[cfg(not(feature = "compression"))]
+fn compression_disabled(format: &str) -> HgsError {
+    HgsError::ParseError {
+        line: 0,
+        message: format!("{format} instances need the `compression` feature"),
+    }
+}
@@ -1,8 +1,204 @@
 //! Configuration parameters for the HGS-CVRP algorithm.
 
+use crate::local_search::{Annealing, MoveKind, RoutePruning};
+use crate::population::ClonePolicy;
+use crate::problem::Problem;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::time::Duration;
 
+/// Configuration value that prevents the search from running, found by `Config::validate`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    /// A count that must be positive is 0
+    Zero { parameter: &'static str },
+    /// A probability or ratio is outside [0, 1]
+    NotAProbability { parameter: &'static str, value: f64 },
+    /// A penalty coefficient is not a positive number
+    NonPositivePenalty { parameter: &'static str, value: f64 },
+    /// A penalty factor is smaller than 1, so the penalty moves the wrong way
+    PenaltyFactorBelowOne { parameter: &'static str, value: f64 },
+    /// The largest penalty coefficient is smaller than the smallest one
+    PenaltyBounds { min: f64, max: f64 },
+    /// The granularity bounds are 0 or not ordered
+    GranularityBounds { min: usize, max: usize },
+    /// The tolerance is negative or undefined
+    InvalidTolerance(f64),
+    /// The fractions of the time limit given to the phases do not sum to 1
+    PhaseBudgetSum(f64),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Zero { parameter } => write!(f, "{parameter} must be at least 1"),
+            ConfigError::NotAProbability { parameter, value } => {
+                write!(
+                    f,
+                    "{parameter} is {value}, expected a value between 0 and 1"
+                )
+            }
+            ConfigError::NonPositivePenalty { parameter, value } => {
+                write!(f, "{parameter} is {value}, expected a positive coefficient")
+            }
+            ConfigError::PenaltyFactorBelowOne { parameter, value } => write!(
+                f,
+                "{parameter} is {value}, expected at least 1 (it divides the penalty when \
+                 decreasing it)"
+            ),
+            ConfigError::PenaltyBounds { min, max } => {
+                write!(f, "penalty_max {max} is smaller than penalty_min {min}")
+            }
+            ConfigError::GranularityBounds { min, max } => write!(
+                f,
+                "granularity bounds {min}..={max} must be positive and ordered"
+            ),
+            ConfigError::InvalidTolerance(tolerance) => {
+                write!(f, "tolerance {tolerance} is not a non-negative number")
+            }
+            ConfigError::PhaseBudgetSum(sum) => {
+                write!(f, "the phase budget fractions sum to {sum}, expected 1")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Configuration value that lets the search run but probably not as intended, found by
+/// `Config::validate`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigWarning {
+    /// Every individual of a minimal population is elite, so diversity has no weight
+    EliteExceedsPopulation { n_elite: usize, min_pop_size: usize },
+    /// Diversity is measured against fewer individuals than `n_closest`
+    ClosestExceedsPopulation {
+        n_closest: usize,
+        min_pop_size: usize,
+    },
+    /// Every customer is a neighbor of every other, so the granularity prunes nothing
+    GranularityExceedsCustomers {
+        granularity: usize,
+        customers: usize,
+    },
+    /// Neither a time limit nor a bound on the iterations guarantees that the search ends
+    NoTermination,
+    /// The phase budget divides the time limit, so without one it has no effect
+    PhaseBudgetWithoutTimeLimit,
+}
+
+impl fmt::Display for ConfigWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigWarning::EliteExceedsPopulation {
+                n_elite,
+                min_pop_size,
+            } => write!(
+                f,
+                "n_elite {n_elite} is larger than min_pop_size {min_pop_size}, so survivor \
+                 selection ignores diversity (lower it with `with_n_elite`)"
+            ),
+            ConfigWarning::ClosestExceedsPopulation {
+                n_closest,
+                min_pop_size,
+            } => write!(
+                f,
+                "n_closest {n_closest} is not smaller than min_pop_size {min_pop_size}, so \
+                 diversity is averaged over the whole subpopulation (lower it with \
+                 `with_n_closest`)"
+            ),
+            ConfigWarning::GranularityExceedsCustomers {
+                granularity,
+                customers,
+            } => write!(
+                f,
+                "granularity {granularity} is not smaller than the {customers} customers, so \
+                 the local search evaluates every pair of customers"
+            ),
+            ConfigWarning::NoTermination => write!(
+                f,
+                "no time limit and no bound on the iterations without improvement, so the \
+                 search may never end (set one with `with_time_limit`)"
+            ),
+            ConfigWarning::PhaseBudgetWithoutTimeLimit => write!(
+                f,
+                "a phase budget is set without a time limit, so the phases are not timed and \
+                 the final intensification is skipped"
+            ),
+        }
+    }
+}
+
+/// Division of the time limit between the phases of a run, as fractions summing to 1.
+///
+/// Each phase ends at a fixed fraction of the time limit, so time left over by a phase goes
+/// to the next one and short time limits still leave time for every phase.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PhaseBudget {
+    /// Fraction spent building the initial population
+    pub initialization: f64,
+    /// Fraction spent evolving the population
+    pub evolution: f64,
+    /// Fraction spent on extended educations of the best individuals once evolution ends
+    pub intensification: f64,
+}
+
+impl PhaseBudget {
+    /// Create a phase budget from the fraction of the time limit of each phase.
+    pub fn new(initialization: f64, evolution: f64, intensification: f64) -> Self {
+        PhaseBudget {
+            initialization,
+            evolution,
+            intensification,
+        }
+    }
+
+    /// Get the time from the start of a run at which initialization ends.
+    pub fn initialization_end(&self, time_limit: Duration) -> Duration {
+        time_limit.mul_f64(self.initialization.clamp(0.0, 1.0))
+    }
+
+    /// Get the time from the start of a run at which evolution ends.
+    pub fn evolution_end(&self, time_limit: Duration) -> Duration {
+        time_limit.mul_f64((self.initialization + self.evolution).clamp(0.0, 1.0))
+    }
+}
+
+/// Parameters that `Config::scaled_to` derives from the size of the instance, each cleared
+/// once the parameter is set with its builder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstanceScaling {
+    /// Lower the granularity to the number of other customers
+    pub granularity: bool,
+    /// Lower `n_closest` below the minimum population size
+    pub n_closest: bool,
+    /// Lower `n_elite` to the minimum population size
+    pub n_elite: bool,
+}
+
+impl InstanceScaling {
+    /// Scale every parameter, or none of them.
+    pub fn all(enabled: bool) -> Self {
+        InstanceScaling {
+            granularity: enabled,
+            n_closest: enabled,
+            n_elite: enabled,
+        }
+    }
+}
+
+impl Default for InstanceScaling {
+    fn default() -> Self {
+        InstanceScaling::all(true)
+    }
+}
+
+impl Default for PhaseBudget {
+    fn default() -> Self {
+        PhaseBudget::new(0.05, 0.85, 0.1)
+    }
+}
+
 /// Configuration settings for the HGS-CVRP algorithm.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -12,18 +208,110 @@ pub struct Config {
     pub generation_size: usize,
     /// Number of elite individuals considered in fitness calculation
     pub n_elite: usize,
+    /// Age in generations above which individuals that are not elite leave the population
+    pub max_individual_age: Option<u32>,
+    /// Handling of offspring that are clones of an individual of the population
+    pub clone_policy: ClonePolicy,
     /// Number of closest solutions considered in diversity calculation
     pub n_closest: usize,
     /// Granularity parameter for local search neighborhoods
     pub granularity: usize,
+    /// Parameters lowered to fit small instances when the search starts
+    pub instance_scaling: InstanceScaling,
+    /// Granularity of each neighborhood of the education by move kind, `granularity` if not set
+    pub operator_granularity: [Option<usize>; MoveKind::COUNT],
+    /// Smallest and largest granularity when it adapts to the progress of the search
+    pub granularity_bounds: Option<(usize, usize)>,
+    /// Number of iterations between two adaptations of the granularity
+    pub granularity_adjustment_period: u32,
     /// Target proportion of feasible individuals
     pub target_feasible_ratio: f64,
     /// Initial penalty coefficient for capacity violations
     pub initial_capacity_penalty: f64,
+    /// Factor applied to a penalty when too few individuals are feasible
+    pub penalty_increase_factor: f64,
+    /// Factor dividing a penalty when enough individuals are feasible
+    pub penalty_decrease_factor: f64,
+    /// Smallest allowed penalty coefficient
+    pub penalty_min: f64,
+    /// Optional largest allowed penalty coefficient
+    pub penalty_max: Option<f64>,
+    /// Number of iterations between two penalty adjustments, each based on the feasibility
+    /// of the offspring educated since the previous one
+    pub penalty_adjustment_period: u32,
+    /// Probability of repairing an infeasible offspring after education
+    pub repair_probability: f64,
+    /// Factor applied to the capacity penalty during repair
+    pub repair_penalty_multiplier: f64,
+    /// Proportion of initial individuals built from a sweep tour instead of a random one
+    pub sweep_initial_ratio: f64,
+    /// Mutation rate applied to each offspring (0 disables mutation)
+    pub mutation_rate: f64,
+    /// Passes of granular 2-opt on each offspring giant tour before Split (0 disables it)
+    pub tour_improvement_passes: usize,
     /// Maximum number of iterations without improvement
     pub max_iterations_without_improvement: u32,
     /// Optional time limit for the algorithm
     pub time_limit: Option<Duration>,
+    /// Time spent polishing once a feasible solution is found, after which the search stops
+    pub stop_on_feasible: Option<Duration>,
+    /// Optional division of the time limit between initialization, evolution, and a final
+    /// intensification
+    pub phase_budget: Option<PhaseBudget>,
+    /// Optional tolerance for cost comparisons, overriding the one of the problem
+    pub tolerance: Option<f64>,
+    /// Check every accepted local search move against a full re-evaluation
+    pub verify_deltas: bool,
+    /// Route pair pruning strategy for the SWAP* neighborhood
+    pub route_pruning: RoutePruning,
+    /// Track the non-dominated feasible solutions in number of routes and distance
+    pub track_pareto_front: bool,
+    /// Optional maximum number of moves applied when educating one individual
+    pub max_education_moves: Option<usize>,
+    /// Optional maximum duration of educating one individual
+    pub max_education_time: Option<Duration>,
+    /// Optional number of routes whose distance and load are cached during education
+    pub route_cache_size: Option<usize>,
+    /// Optional number of educated offspring cached by giant tour, to skip their education
+    pub education_cache_size: Option<usize>,
+    /// Probability of ruining and recreating each educated offspring (0 disables it)
+    pub lns_probability: f64,
+    /// Optional annealing schedule accepting worsening moves when educating offspring
+    pub education_annealing: Option<Annealing>,
+    /// Weight of the guided local search edge penalties (0 disables it)
+    pub gls_lambda: f64,
+    /// Iterations without improvement after which offspring are educated with edge penalties
+    pub gls_activation: u32,
+    /// Number of moves during which a customer may not return to the route it left (0 disables it)
+    pub tabu_tenure: usize,
+    /// Optional largest capacity penalty of the inter-route moves evaluated during education
+    pub load_slack_pruning: Option<f64>,
+    /// Run an extended education on every new best solution
+    pub elite_intensification: bool,
+    /// Granularity of the extended education on new best solutions
+    pub intensification_granularity: usize,
+    /// Number of customers from which the best solution is periodically decomposed
+    pub decomposition_threshold: Option<usize>,
+    /// Target number of customers in each decomposition subproblem
+    pub decomposition_subproblem_size: usize,
+    /// Number of iterations between two decompositions
+    pub decomposition_period: u32,
+    /// Maximum iterations without improvement of each nested subproblem run
+    pub decomposition_iterations: u32,
+    /// Number of iterations between two population snapshots (0 disables them)
+    pub snapshot_period: u32,
+    /// Optional duration of an extended education of the best solution once the search ends,
+    /// on top of the time limit
+    pub final_polish: Option<Duration>,
+    /// Optional largest cost increase, as a fraction of its cost, accepted to merge two routes
+    /// of the best solution once the search ends
+    pub route_merging: Option<f64>,
+    /// Seed of the random number generator of the thread running the search, if any
+    pub seed: Option<u64>,
+    /// Record every education and accepted move of the local search in a `SearchTrace`
+    pub record_trace: bool,
+    /// Costs at which the time the best solution first reaches them is recorded
+    pub cost_targets: Vec<f64>,
 }
 
 impl Default for Config {
@@ -32,12 +320,56 @@ impl Default for Config {
             min_pop_size: 25,
             generation_size: 40,
             n_elite: 4,
+            max_individual_age: None,
+            clone_policy: ClonePolicy::default(),
             n_closest: 5,
             granularity: 20,
+            instance_scaling: InstanceScaling::default(),
+            operator_granularity: [None; MoveKind::COUNT],
+            granularity_bounds: None,
+            granularity_adjustment_period: 500,
             target_feasible_ratio: 0.2,
             initial_capacity_penalty: 1.0,
+            penalty_increase_factor: 1.2,
+            penalty_decrease_factor: 1.2,
+            penalty_min: 0.1,
+            penalty_max: None,
+            penalty_adjustment_period: 100,
+            repair_probability: 0.5,
+            repair_penalty_multiplier: 10.0,
+            sweep_initial_ratio: 0.0,
+            mutation_rate: 0.0,
+            tour_improvement_passes: 0,
             max_iterations_without_improvement: 20000,
             time_limit: None,
+            stop_on_feasible: None,
+            phase_budget: None,
+            tolerance: None,
+            verify_deltas: false,
+            route_pruning: RoutePruning::default(),
+            track_pareto_front: false,
+            max_education_moves: None,
+            max_education_time: None,
+            route_cache_size: None,
+            education_cache_size: None,
+            lns_probability: 0.0,
+            education_annealing: None,
+            tabu_tenure: 0,
+            load_slack_pruning: None,
+            gls_lambda: 0.0,
+            gls_activation: 500,
+            elite_intensification: false,
+            intensification_granularity: 50,
+            decomposition_threshold: None,
+            decomposition_subproblem_size: 200,
+            decomposition_period: 5000,
+            decomposition_iterations: 1000,
+            snapshot_period: 0,
+            final_polish: None,
+            route_merging: None,
+            seed: None,
+            record_trace: false,
+            cost_targets: Vec::new(),
         }
     }
 }
@@ -48,6 +380,155 @@ impl Config {
         Config::default()
     }
 
+    /// Check the configuration for solving `problem`, returning the values that are valid but
+    /// probably unintended.
+    ///
+    /// Fails on the first value that would make the search panic or degenerate, e.g. a
+    /// granularity of 0 that leaves the local search without moves.
+    pub fn validate(&self, problem: &Problem) -> Result<Vec<ConfigWarning>, ConfigError> {
+        for (parameter, value) in [
+            ("min_pop_size", self.min_pop_size),
+            ("generation_size", self.generation_size),
+            ("granularity", self.granularity),
+            ("route_cache_size", self.route_cache_size.unwrap_or(1)),
+            (
+                "education_cache_size",
+                self.education_cache_size.unwrap_or(1),
+            ),
+        ] {
+            if value == 0 {
+                return Err(ConfigError::Zero { parameter });
+            }
+        }
+
+        for (parameter, value) in [
+            ("target_feasible_ratio", self.target_feasible_ratio),
+            ("repair_probability", self.repair_probability),
+            ("sweep_initial_ratio", self.sweep_initial_ratio),
+            ("mutation_rate", self.mutation_rate),
+            ("lns_probability", self.lns_probability),
+            ("route_merging", self.route_merging.unwrap_or(0.0)),
+        ] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(ConfigError::NotAProbability { parameter, value });
+            }
+        }
+
+        for (parameter, value) in [
+            ("initial_capacity_penalty", self.initial_capacity_penalty),
+            ("penalty_min", self.penalty_min),
+            ("repair_penalty_multiplier", self.repair_penalty_multiplier),
+        ] {
+            if value.is_nan() || value <= 0.0 {
+                return Err(ConfigError::NonPositivePenalty { parameter, value });
+            }
+        }
+
+        for (parameter, value) in [
+            ("penalty_increase_factor", self.penalty_increase_factor),
+            ("penalty_decrease_factor", self.penalty_decrease_factor),
+        ] {
+            if value.is_nan() || value < 1.0 {
+                return Err(ConfigError::PenaltyFactorBelowOne { parameter, value });
+            }
+        }
+
+        if let Some(max) = self.penalty_max {
+            if max.is_nan() || max < self.penalty_min {
+                return Err(ConfigError::PenaltyBounds {
+                    min: self.penalty_min,
+                    max,
+                });
+            }
+        }
+
+        if self.operator_granularity.contains(&Some(0)) {
+            return Err(ConfigError::Zero {
+                parameter: "operator_granularity",
+            });
+        }
+
+        if let Some((min, max)) = self.granularity_bounds {
+            if min == 0 || min > max {
+                return Err(ConfigError::GranularityBounds { min, max });
+            }
+        }
+
+        if let Some(tolerance) = self.tolerance {
+            if tolerance.is_nan() || tolerance < 0.0 {
+                return Err(ConfigError::InvalidTolerance(tolerance));
+            }
+        }
+
+        if let Some(budget) = self.phase_budget {
+            for (parameter, value) in [
+                ("phase_budget.initialization", budget.initialization),
+                ("phase_budget.evolution", budget.evolution),
+                ("phase_budget.intensification", budget.intensification),
+            ] {
+                if !(0.0..=1.0).contains(&value) {
+                    return Err(ConfigError::NotAProbability { parameter, value });
+                }
+            }
+            let sum = budget.initialization + budget.evolution + budget.intensification;
+            if (sum - 1.0).abs() > 1e-6 {
+                return Err(ConfigError::PhaseBudgetSum(sum));
+            }
+        }
+
+        let mut warnings = Vec::new();
+        if self.n_elite > self.min_pop_size {
+            warnings.push(ConfigWarning::EliteExceedsPopulation {
+                n_elite: self.n_elite,
+                min_pop_size: self.min_pop_size,
+            });
+        }
+        if self.n_closest >= self.min_pop_size {
+            warnings.push(ConfigWarning::ClosestExceedsPopulation {
+                n_closest: self.n_closest,
+                min_pop_size: self.min_pop_size,
+            });
+        }
+        let customers = problem.customers().count();
+        if customers > 1 && self.granularity >= customers {
+            warnings.push(ConfigWarning::GranularityExceedsCustomers {
+                granularity: self.granularity,
+                customers,
+            });
+        }
+        if self.time_limit.is_none()
+            && self.stop_on_feasible.is_none()
+            && self.max_iterations_without_improvement == u32::MAX
+        {
+            warnings.push(ConfigWarning::NoTermination);
+        }
+        if self.phase_budget.is_some() && self.time_limit.is_none() {
+            warnings.push(ConfigWarning::PhaseBudgetWithoutTimeLimit);
+        }
+        Ok(warnings)
+    }
+
+    /// Get the configuration with the parameters of `instance_scaling` fitted to `problem`.
+    ///
+    /// The granularity is lowered to `n - 1` for `n` customers, `n_closest` below the minimum
+    /// population size, and `n_elite` to it, which only changes the defaults on tiny instances
+    /// where they would degenerate. `HgsAlgorithm::new` applies it before `validate`.
+    pub fn scaled_to(&self, problem: &Problem) -> Config {
+        let mut config = self.clone();
+        let scaling = self.instance_scaling;
+        let customers = problem.customers().count();
+        if scaling.granularity && customers > 1 {
+            config.granularity = self.granularity.min(customers - 1);
+        }
+        if scaling.n_closest && self.min_pop_size > 1 {
+            config.n_closest = self.n_closest.min(self.min_pop_size - 1);
+        }
+        if scaling.n_elite {
+            config.n_elite = self.n_elite.min(self.min_pop_size);
+        }
+        config
+    }
+
     /// Set the minimum population size.
     pub fn with_min_pop_size(mut self, size: usize) -> Self {
         self.min_pop_size = size;
@@ -63,18 +544,71 @@ impl Config {
     /// Set the number of elite individuals.
     pub fn with_n_elite(mut self, n: usize) -> Self {
         self.n_elite = n;
+        self.instance_scaling.n_elite = false;
+        self
+    }
+
+    /// Remove the individuals that are not elite at survivor selection once they are older than
+    /// `age` generations.
+    pub fn with_max_individual_age(mut self, age: u32) -> Self {
+        self.max_individual_age = Some(age);
+        self
+    }
+
+    /// Set how offspring that are clones of an individual of the population are handled.
+    pub fn with_clone_policy(mut self, policy: ClonePolicy) -> Self {
+        self.clone_policy = policy;
         self
     }
 
     /// Set the number of closest solutions for diversity calculation.
     pub fn with_n_closest(mut self, n: usize) -> Self {
         self.n_closest = n;
+        self.instance_scaling.n_closest = false;
         self
     }
 
     /// Set the granularity parameter.
     pub fn with_granularity(mut self, g: usize) -> Self {
         self.granularity = g;
+        self.instance_scaling.granularity = false;
+        self
+    }
+
+    /// Search the moves of a kind among the `granularity` closest customers in the education,
+    /// e.g. fewer for SWAP*, while the other kinds keep the granularity.
+    ///
+    /// The shared neighbor lists are computed for the largest granularity and each kind uses
+    /// a prefix of them, also when the granularity adapts.
+    pub fn with_operator_granularity(mut self, kind: MoveKind, granularity: usize) -> Self {
+        self.operator_granularity[kind.index()] = Some(granularity);
+        self
+    }
+
+    /// Fit the granularity, `n_closest`, and `n_elite` to small instances, unless they were set.
+    pub fn with_instance_scaling(mut self, enabled: bool) -> Self {
+        self.instance_scaling = InstanceScaling::all(enabled);
+        self
+    }
+
+    /// Adapt the granularity between `min` and `max` during the run, growing it when the
+    /// search stagnates and shrinking it when the best solution improves often.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min` is 0 or larger than `max`.
+    pub fn with_adaptive_granularity(mut self, min: usize, max: usize) -> Self {
+        assert!(
+            min > 0 && min <= max,
+            "granularity bounds {min}..={max} must be positive and ordered"
+        );
+        self.granularity_bounds = Some((min, max));
+        self
+    }
+
+    /// Adapt the granularity every `period` iterations, to the improvements found in between.
+    pub fn with_granularity_adjustment_period(mut self, period: u32) -> Self {
+        self.granularity_adjustment_period = period.max(1);
         self
     }
 
@@ -90,6 +624,62 @@ impl Config {
         self
     }
 
+    /// Set the factor applied to a penalty when too few individuals are feasible.
+    pub fn with_penalty_increase_factor(mut self, factor: f64) -> Self {
+        self.penalty_increase_factor = factor;
+        self
+    }
+
+    /// Set the factor dividing a penalty when enough individuals are feasible.
+    pub fn with_penalty_decrease_factor(mut self, factor: f64) -> Self {
+        self.penalty_decrease_factor = factor;
+        self
+    }
+
+    /// Keep the penalty coefficients between `min` and `max`.
+    pub fn with_penalty_bounds(mut self, min: f64, max: f64) -> Self {
+        self.penalty_min = min;
+        self.penalty_max = Some(max);
+        self
+    }
+
+    /// Adjust the penalties every `period` iterations, to the feasibility of the offspring
+    /// educated in between.
+    pub fn with_penalty_adjustment_period(mut self, period: u32) -> Self {
+        self.penalty_adjustment_period = period.max(1);
+        self
+    }
+
+    /// Set the probability of repairing infeasible offspring.
+    pub fn with_repair_probability(mut self, probability: f64) -> Self {
+        self.repair_probability = probability;
+        self
+    }
+
+    /// Set the capacity penalty multiplier used during repair.
+    pub fn with_repair_penalty_multiplier(mut self, multiplier: f64) -> Self {
+        self.repair_penalty_multiplier = multiplier;
+        self
+    }
+
+    /// Set the proportion of initial individuals built from a sweep tour.
+    pub fn with_sweep_initial_ratio(mut self, ratio: f64) -> Self {
+        self.sweep_initial_ratio = ratio;
+        self
+    }
+
+    /// Set the mutation rate applied to each offspring.
+    pub fn with_mutation_rate(mut self, rate: f64) -> Self {
+        self.mutation_rate = rate;
+        self
+    }
+
+    /// Improve each offspring giant tour by up to `passes` passes of granular 2-opt before Split.
+    pub fn with_tour_improvement_passes(mut self, passes: usize) -> Self {
+        self.tour_improvement_passes = passes;
+        self
+    }
+
     /// Set the maximum iterations without improvement.
     pub fn with_max_iterations_without_improvement(mut self, iterations: u32) -> Self {
         self.max_iterations_without_improvement = iterations;
@@ -101,4 +691,179 @@ impl Config {
         self.time_limit = Some(duration);
         self
     }
+
+    /// Stop the search once a feasible solution was found and improved for `polish` more,
+    /// immediately with `Duration::ZERO`.
+    pub fn with_stop_on_feasible(mut self, polish: Duration) -> Self {
+        self.stop_on_feasible = Some(polish);
+        self
+    }
+
+    /// Divide the time limit between initialization, evolution, and a final intensification
+    /// of the best individuals, given as fractions summing to 1, e.g. 0.05, 0.85, and 0.1.
+    pub fn with_phase_budget(
+        mut self,
+        initialization: f64,
+        evolution: f64,
+        intensification: f64,
+    ) -> Self {
+        self.phase_budget = Some(PhaseBudget::new(initialization, evolution, intensification));
+        self
+    }
+
+    /// Set the tolerance for cost comparisons.
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = Some(tolerance);
+        self
+    }
+
+    /// Enable or disable verification of local search move deltas.
+    pub fn with_verify_deltas(mut self, verify: bool) -> Self {
+        self.verify_deltas = verify;
+        self
+    }
+
+    /// Set the route pair pruning strategy for the SWAP* neighborhood.
+    pub fn with_route_pruning(mut self, route_pruning: RoutePruning) -> Self {
+        self.route_pruning = route_pruning;
+        self
+    }
+
+    /// Enable or disable tracking of the (routes, distance) Pareto front.
+    pub fn with_track_pareto_front(mut self, track: bool) -> Self {
+        self.track_pareto_front = track;
+        self
+    }
+
+    /// Set the maximum number of moves applied when educating one individual.
+    pub fn with_max_education_moves(mut self, moves: usize) -> Self {
+        self.max_education_moves = Some(moves);
+        self
+    }
+
+    /// Set the maximum duration of educating one individual.
+    pub fn with_max_education_time(mut self, duration: Duration) -> Self {
+        self.max_education_time = Some(duration);
+        self
+    }
+
+    /// Cache the distance and load of up to `size` routes during education.
+    pub fn with_route_cache_size(mut self, size: usize) -> Self {
+        self.route_cache_size = Some(size);
+        self
+    }
+
+    /// Cache the educated solutions of up to `size` offspring giant tours, reused instead of
+    /// educating offspring with the same tour again.
+    pub fn with_education_cache_size(mut self, size: usize) -> Self {
+        self.education_cache_size = Some(size);
+        self
+    }
+
+    /// Set the probability of ruining and recreating each educated offspring, which is then
+    /// educated again and kept if it improves.
+    pub fn with_lns_probability(mut self, probability: f64) -> Self {
+        self.lns_probability = probability;
+        self
+    }
+
+    /// Educate offspring on an annealing schedule, accepting worsening moves at first.
+    pub fn with_education_annealing(mut self, annealing: Annealing) -> Self {
+        self.education_annealing = Some(annealing);
+        self
+    }
+
+    /// Forbid customers to return to the route they left for a number of moves during education.
+    pub fn with_tabu_tenure(mut self, tenure: usize) -> Self {
+        self.tabu_tenure = tenure;
+        self
+    }
+
+    /// Skip the inter-route moves of education whose capacity penalty change alone exceeds
+    /// `max_penalty`, see `LocalSearch::with_load_slack_pruning`.
+    pub fn with_load_slack_pruning(mut self, max_penalty: f64) -> Self {
+        self.load_slack_pruning = Some(max_penalty);
+        self
+    }
+
+    /// Enable guided local search once the search has not improved for `activation`
+    /// iterations, penalizing long edges with weight `lambda`.
+    pub fn with_guided_local_search(mut self, lambda: f64, activation: u32) -> Self {
+        self.gls_lambda = lambda;
+        self.gls_activation = activation;
+        self
+    }
+
+    /// Enable or disable the extended education of new best solutions.
+    pub fn with_elite_intensification(mut self, enabled: bool) -> Self {
+        self.elite_intensification = enabled;
+        self
+    }
+
+    /// Set the granularity of the extended education of new best solutions.
+    pub fn with_intensification_granularity(mut self, granularity: usize) -> Self {
+        self.intensification_granularity = granularity;
+        self
+    }
+
+    /// Decompose the best solution of instances with at least `threshold` customers into
+    /// subproblems of about `subproblem_size` customers.
+    pub fn with_decomposition(mut self, threshold: usize, subproblem_size: usize) -> Self {
+        self.decomposition_threshold = Some(threshold);
+        self.decomposition_subproblem_size = subproblem_size;
+        self
+    }
+
+    /// Set the number of iterations between two decompositions.
+    pub fn with_decomposition_period(mut self, period: u32) -> Self {
+        self.decomposition_period = period;
+        self
+    }
+
+    /// Set the maximum iterations without improvement of each subproblem run.
+    pub fn with_decomposition_iterations(mut self, iterations: u32) -> Self {
+        self.decomposition_iterations = iterations;
+        self
+    }
+
+    /// Seed the random number generator at the start of each run, so that runs bounded by
+    /// iterations rather than time are reproducible.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Polish the best solution once the search ends, for at most `duration` beyond the time
+    /// limit, with every neighborhood, the largest granularity, and plateau moves.
+    pub fn with_final_polish(mut self, duration: Duration) -> Self {
+        self.final_polish = Some(duration);
+        self
+    }
+
+    /// Merge pairs of lightly loaded routes of the best solution once the search ends, each
+    /// merge raising the cost by at most `max_increase` times the cost, e.g. 0.001.
+    pub fn with_route_merging(mut self, max_increase: f64) -> Self {
+        self.route_merging = Some(max_increase);
+        self
+    }
+
+    /// Record every education and accepted move of the local search, to replay them with
+    /// `SearchTrace::replay`; the trace grows with the number of educations.
+    pub fn with_search_trace(mut self, record: bool) -> Self {
+        self.record_trace = record;
+        self
+    }
+
+    /// Record the time at which the best solution of a run first reaches each cost target,
+    /// reported as time-to-target in `SearchStatistics`.
+    pub fn with_cost_targets(mut self, targets: Vec<f64>) -> Self {
+        self.cost_targets = targets;
+        self
+    }
+
+    /// Record a snapshot of the population every `period` iterations (0 disables them).
+    pub fn with_population_snapshots(mut self, period: u32) -> Self {
+        self.snapshot_period = period;
+        self
+    }
 }
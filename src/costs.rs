@@ -0,0 +1,45 @@
+//! Cost comparison helpers with a configurable tolerance.
+
+/// Default tolerance for comparing costs.
+pub const DEFAULT_TOLERANCE: f64 = 1e-6;
+
+/// Check whether `a` is smaller than `b` by more than `tolerance`.
+pub fn approx_lt(a: f64, b: f64, tolerance: f64) -> bool {
+    a < b - tolerance
+}
+
+/// Check whether `a` and `b` differ by at most `tolerance`.
+pub fn approx_eq(a: f64, b: f64, tolerance: f64) -> bool {
+    (a - b).abs() <= tolerance
+}
+
+/// Cost change of a move, split into the change of the travelled distance and that of the
+/// other terms: capacity penalties, fixed costs, and the custom objective.
+///
+/// With integer distances `Problem::is_cost_improvement` rounds the distance change to the
+/// nearest integer before comparing it, leaving the tolerance to the real-valued terms.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CostDelta {
+    /// Change of the travelled distance
+    pub distance: f64,
+    /// Change of the other cost terms
+    pub other: f64,
+}
+
+impl CostDelta {
+    /// Cost change of a move that cannot be applied.
+    pub const INFEASIBLE: CostDelta = CostDelta {
+        distance: f64::INFINITY,
+        other: 0.0,
+    };
+
+    /// Create a cost change from its distance and other parts.
+    pub fn new(distance: f64, other: f64) -> Self {
+        CostDelta { distance, other }
+    }
+
+    /// Get the total cost change.
+    pub fn total(self) -> f64 {
+        self.distance + self.other
+    }
+}
@@ -0,0 +1,279 @@
+//! Decomposition of large instances into subproblems solved by nested HGS runs.
+
+use crate::config::Config;
+use crate::costs;
+use crate::problem::{Node, Problem};
+use crate::rng::search_rng;
+use crate::solution::{Route, Solution};
+use crate::stochastic::DemandUncertainty;
+use crate::HgsAlgorithm;
+
+use rand::Rng;
+use std::time::{Duration, Instant};
+
+/// A subproblem made of the depot and a subset of the customers of a problem.
+pub struct Subproblem {
+    /// The subproblem, with the depot at index 0
+    pub problem: Problem,
+    /// Index in the original problem of each node of the subproblem
+    pub original_nodes: Vec<usize>,
+}
+
+impl Subproblem {
+    /// Build the subproblem serving the given customers.
+    ///
+    /// Distances are copied from the original matrix, so explicit and scaled distances are
+    /// kept. With a fleet limit, the subproblem may use at most `max_vehicles` routes.
+    pub fn new(problem: &Problem, customers: &[usize], max_vehicles: Option<usize>) -> Self {
+        let original_nodes: Vec<usize> = std::iter::once(problem.depot_index)
+            .chain(customers.iter().copied())
+            .collect();
+
+        let nodes = original_nodes
+            .iter()
+            .enumerate()
+            .map(|(id, &original)| {
+                let node = &problem.nodes[original];
+                Node::new(id, node.x, node.y, node.demand, id == 0)
+            })
+            .collect();
+
+        let distance_matrix = original_nodes
+            .iter()
+            .map(|&from| {
+                original_nodes
+                    .iter()
+                    .map(|&to| problem.get_distance(from, to))
+                    .collect()
+            })
+            .collect();
+
+        let mut subproblem = Problem::new(
+            problem.name.clone(),
+            nodes,
+            0,
+            problem.vehicle_capacity,
+            problem.max_vehicles.and(max_vehicles),
+        )
+        .with_distance_matrix(distance_matrix)
+        .with_tolerance(problem.tolerance)
+        .with_route_fixed_cost(problem.route_fixed_cost);
+        subproblem.integer_distances = problem.integer_distances;
+        subproblem.has_coordinates = problem.has_coordinates;
+        subproblem.euclidean_distances = problem.euclidean_distances;
+        subproblem.pins = problem.pins.for_nodes(&original_nodes);
+        subproblem.max_stops_per_route = problem.max_stops_per_route;
+        subproblem.demand_uncertainty = problem.demand_uncertainty.as_ref().map(|uncertainty| {
+            DemandUncertainty::new(
+                original_nodes
+                    .iter()
+                    .map(|&node| uncertainty.variance(node).sqrt())
+                    .collect(),
+                uncertainty.service_level,
+            )
+        });
+        subproblem.objective = problem.objective.clone();
+        if !problem.service_costs.is_empty() {
+            subproblem = subproblem.with_service_costs(
+                original_nodes
+                    .iter()
+                    .map(|&node| problem.get_service_cost(node))
+                    .collect(),
+            );
+        }
+
+        Subproblem {
+            problem: subproblem,
+            original_nodes,
+        }
+    }
+
+    /// Map routes of the original problem onto the subproblem.
+    ///
+    /// The routes must only visit customers of the subproblem.
+    pub fn to_subproblem(&self, routes: &[Route]) -> Solution {
+        let mut sub_index = vec![0; self.original_nodes.iter().max().map_or(0, |&max| max + 1)];
+        for (index, &original) in self.original_nodes.iter().enumerate() {
+            sub_index[original] = index;
+        }
+
+        let mut solution = Solution::new();
+        for route in routes {
+            let mut sub_route = Route::new();
+            sub_route.customers = route.customers.iter().map(|&c| sub_index[c]).collect();
+            solution.routes.push(sub_route);
+        }
+        solution.update_giant_tour();
+        solution
+    }
+
+    /// Map the routes of a subproblem solution back onto the original problem.
+    pub fn to_original(&self, solution: &Solution) -> Vec<Route> {
+        solution
+            .routes
+            .iter()
+            .filter(|route| !route.is_empty())
+            .map(|route| {
+                let mut original = Route::new();
+                original.customers = route
+                    .customers
+                    .iter()
+                    .map(|&c| self.original_nodes[c])
+                    .collect();
+                original
+            })
+            .collect()
+    }
+}
+
+/// Improves large solutions by solving clusters of their routes independently.
+pub struct Decomposition;
+
+impl Decomposition {
+    /// Partition the non-empty routes of a solution into clusters of about `subproblem_size`
+    /// customers, returned as lists of route indices.
+    ///
+    /// Routes are ordered by the polar angle of their barycenter around the depot, starting
+    /// from the `offset`-th route, so that different offsets give different clusters. Without
+    /// coordinates, the routes are taken in their order in the solution.
+    pub fn cluster_routes(
+        solution: &Solution,
+        problem: &Problem,
+        subproblem_size: usize,
+        offset: usize,
+    ) -> Vec<Vec<usize>> {
+        let mut routes: Vec<(usize, f64)> = solution
+            .routes
+            .iter()
+            .enumerate()
+            .filter(|(_, route)| !route.is_empty())
+            .map(|(r_idx, route)| (r_idx, Self::polar_angle(route, problem)))
+            .collect();
+        if routes.is_empty() {
+            return Vec::new();
+        }
+
+        if problem.has_coordinates {
+            routes.sort_by(|a, b| a.1.total_cmp(&b.1));
+        }
+        let len = routes.len();
+        routes.rotate_left(offset % len);
+
+        let mut clusters: Vec<Vec<usize>> = Vec::new();
+        let mut current = Vec::new();
+        let mut size = 0;
+        for (r_idx, _) in routes {
+            current.push(r_idx);
+            size += solution.routes[r_idx].customers.len();
+            if size >= subproblem_size {
+                clusters.push(std::mem::take(&mut current));
+                size = 0;
+            }
+        }
+
+        // Merge a small remainder into the last cluster
+        if !current.is_empty() {
+            match clusters.last_mut() {
+                Some(last) if 2 * size < subproblem_size => last.extend(current),
+                _ => clusters.push(current),
+            }
+        }
+
+        clusters
+    }
+
+    /// Polar angle of the barycenter of a route around the depot.
+    fn polar_angle(route: &Route, problem: &Problem) -> f64 {
+        let depot = problem.get_depot();
+        let count = route.customers.len() as f64;
+        let (sum_x, sum_y) = route.customers.iter().fold((0.0, 0.0), |(x, y), &c| {
+            (x + problem.nodes[c].x, y + problem.nodes[c].y)
+        });
+        (sum_y / count - depot.y).atan2(sum_x / count - depot.x)
+    }
+
+    /// Decompose a solution into clusters of routes, solve each cluster with a nested HGS run
+    /// warm-started from its current routes, and reassemble the improved clusters.
+    ///
+    /// A cluster is only replaced by a feasible subproblem solution of lower cost. The
+    /// returned solution keeps every customer but is not evaluated.
+    pub fn decompose(
+        solution: &Solution,
+        problem: &Problem,
+        config: &Config,
+        deadline: Option<Instant>,
+    ) -> Solution {
+        let offset = search_rng().gen_range(0..solution.routes.len().max(1));
+        let clusters = Self::cluster_routes(
+            solution,
+            problem,
+            config.decomposition_subproblem_size,
+            offset,
+        );
+
+        let mut routes = Vec::with_capacity(solution.routes.len());
+        for (index, cluster) in clusters.iter().enumerate() {
+            let cluster_routes: Vec<Route> = cluster
+                .iter()
+                .map(|&r_idx| solution.routes[r_idx].clone())
+                .collect();
+
+            // Each remaining cluster gets an equal share of the remaining time
+            let time_share = deadline.map(|deadline| {
+                deadline.saturating_duration_since(Instant::now()) / (clusters.len() - index) as u32
+            });
+
+            let improved = if cluster.len() > 1 && time_share.is_none_or(|t| !t.is_zero()) {
+                Self::solve_cluster(&cluster_routes, problem, config, time_share)
+            } else {
+                None
+            };
+            routes.extend(improved.unwrap_or(cluster_routes));
+        }
+
+        let mut decomposed = Solution::new();
+        decomposed.routes = routes;
+        decomposed.update_giant_tour();
+        decomposed
+    }
+
+    /// Solve the subproblem of a cluster and return its routes if they are an improvement.
+    fn solve_cluster(
+        cluster_routes: &[Route],
+        problem: &Problem,
+        config: &Config,
+        time_limit: Option<Duration>,
+    ) -> Option<Vec<Route>> {
+        let customers: Vec<usize> = cluster_routes
+            .iter()
+            .flat_map(|route| route.customers.iter().copied())
+            .collect();
+        let subproblem = Subproblem::new(problem, &customers, Some(cluster_routes.len()));
+
+        let mut initial = subproblem.to_subproblem(cluster_routes);
+        initial.evaluate(&subproblem.problem, config.initial_capacity_penalty);
+        if !initial.is_feasible {
+            return None;
+        }
+        let initial_cost = initial.get_feasible_cost();
+
+        let mut sub_config = config.clone();
+        sub_config.decomposition_threshold = None;
+        sub_config.track_pareto_front = false;
+        sub_config.max_iterations_without_improvement = config.decomposition_iterations;
+        sub_config.time_limit = time_limit;
+        // Seeding again would restart the random sequence of the outer run
+        sub_config.seed = None;
+
+        let mut algorithm = HgsAlgorithm::new(subproblem.problem.clone(), sub_config).ok()?;
+        let best = algorithm.run_from(initial).ok()?;
+
+        if best.is_feasible
+            && costs::approx_lt(best.get_feasible_cost(), initial_cost, problem.tolerance)
+        {
+            Some(subproblem.to_original(best))
+        } else {
+            None
+        }
+    }
+}
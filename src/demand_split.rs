@@ -0,0 +1,173 @@
+//! Customers served by several visits of fixed size, for oversized demands and deliveries
+//! shared between routes.
+
+use crate::problem::{Node, Problem, ProblemError};
+use crate::solution::Solution;
+
+/// A problem where the demand of some customers is divided among several visits.
+///
+/// A divided customer keeps its node index and the rest of its demand, and each extra visit
+/// is a virtual customer appended after the original nodes, at the same location and
+/// carrying one visit size. Split and every neighborhood then treat the visits as ordinary
+/// customers: cutting the giant tour between two visits or relocating a visit to another
+/// route reallocates the quantity of that visit between routes.
+pub struct DemandSplit {
+    /// The problem with the extra visits, whose demands all fit in a vehicle
+    pub problem: Problem,
+    /// Index in the original problem of each node of the split problem
+    pub original_nodes: Vec<usize>,
+}
+
+impl DemandSplit {
+    /// Split the demands that exceed the vehicle capacity of a problem.
+    ///
+    /// Distances of the extra visits are copied from their customer, and zero between the
+    /// visits of the same customer. Extra visits cost no service, are never pinned, and have
+    /// deterministic demands.
+    pub fn new(problem: &Problem) -> Self {
+        Self::with_visit_size(problem, problem.vehicle_capacity)
+    }
+
+    /// Divide the demand of every customer into chunks of `chunk_size`, which different
+    /// routes may deliver.
+    ///
+    /// This is not a full split-delivery VRP: the quantity of each visit is fixed, and the
+    /// search only chooses which route delivers each chunk. Smaller chunks let routes share
+    /// customers more finely, at the price of more nodes. Returns
+    /// `ProblemError::InvalidChunkSize` if `chunk_size` is not positive or exceeds the vehicle
+    /// capacity.
+    pub fn in_chunks(problem: &Problem, chunk_size: f64) -> Result<Self, ProblemError> {
+        if !(chunk_size > 0.0 && chunk_size <= problem.vehicle_capacity) {
+            return Err(ProblemError::InvalidChunkSize {
+                size: chunk_size,
+                capacity: problem.vehicle_capacity,
+            });
+        }
+        Ok(Self::with_visit_size(problem, chunk_size))
+    }
+
+    /// Divide the demands larger than `visit_size` into visits of that size.
+    fn with_visit_size(problem: &Problem, visit_size: f64) -> Self {
+        let mut split = problem.clone();
+        let mut original_nodes: Vec<usize> = (0..problem.nodes.len()).collect();
+
+        for customer in problem.customers() {
+            let demand = problem.nodes[customer].demand;
+            if demand - visit_size <= problem.tolerance {
+                continue;
+            }
+
+            // Full visits go to the virtual customers, the remainder stays with the customer
+            let visits = ((demand - problem.tolerance) / visit_size).ceil() as usize;
+            split.nodes[customer].demand = demand - (visits - 1) as f64 * visit_size;
+            for _ in 1..visits {
+                let node = &problem.nodes[customer];
+                let id = split.nodes.len();
+                split
+                    .nodes
+                    .push(Node::new(id, node.x, node.y, visit_size, false));
+                original_nodes.push(customer);
+            }
+        }
+
+        if let Some(neighbor_count) = problem.sparse_neighbor_count() {
+            // Visits share the location of their customer, so the sparse distances still hold
+            if split.nodes.len() > problem.nodes.len() {
+                split = split.with_sparse_distances(neighbor_count);
+            }
+        } else if split.nodes.len() > problem.nodes.len() {
+            let matrix = original_nodes
+                .iter()
+                .map(|&from| {
+                    original_nodes
+                        .iter()
+                        .map(|&to| {
+                            if from == to {
+                                0.0
+                            } else {
+                                problem.get_distance(from, to)
+                            }
+                        })
+                        .collect()
+                })
+                .collect();
+            // Visits share the location of their customer, so distances stay Euclidean and
+            // integral
+            split = split.with_distance_matrix(matrix);
+            split.euclidean_distances = problem.euclidean_distances;
+            split.integer_distances = problem.integer_distances;
+            if problem.distance_storage.is_some() {
+                split = split.with_compact_distances();
+            }
+        }
+        if split.nodes.len() > problem.nodes.len() {
+            if !split.service_costs.is_empty() {
+                split.service_costs.resize(split.nodes.len(), 0.0);
+            }
+            if let Some(uncertainty) = &mut split.demand_uncertainty {
+                uncertainty.std_devs.resize(split.nodes.len(), 0.0);
+            }
+        }
+
+        DemandSplit {
+            problem: split,
+            original_nodes,
+        }
+    }
+
+    /// Number of virtual customers added for the extra visits.
+    pub fn extra_visits(&self) -> usize {
+        self.original_nodes
+            .iter()
+            .enumerate()
+            .filter(|&(index, &original)| index != original)
+            .count()
+    }
+
+    /// Map the routes of a solution of the split problem onto the original customers, with
+    /// the quantity delivered at each visit.
+    pub fn to_original(&self, solution: &Solution) -> Vec<Vec<(usize, f64)>> {
+        solution
+            .routes
+            .iter()
+            .filter(|route| !route.is_empty())
+            .map(|route| {
+                route
+                    .customers
+                    .iter()
+                    .map(|&c| (self.original_nodes[c], self.problem.nodes[c].demand))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Map the routes of a solution onto the original customers, merging consecutive visits
+    /// of a customer into one delivery of their summed quantity.
+    pub fn deliveries(&self, solution: &Solution) -> Vec<Vec<(usize, f64)>> {
+        self.to_original(solution)
+            .into_iter()
+            .map(|visits| {
+                let mut deliveries: Vec<(usize, f64)> = Vec::with_capacity(visits.len());
+                for (customer, quantity) in visits {
+                    match deliveries.last_mut() {
+                        Some((last, total)) if *last == customer => *total += quantity,
+                        _ => deliveries.push((customer, quantity)),
+                    }
+                }
+                deliveries
+            })
+            .collect()
+    }
+
+    /// Total quantity delivered to each node of the original problem by a solution.
+    pub fn delivered_quantities(&self, solution: &Solution) -> Vec<f64> {
+        let node_count = self.original_nodes.len() - self.extra_visits();
+        let mut delivered = vec![0.0; node_count];
+        for route in &solution.routes {
+            for &c in &route.customers {
+                delivered[self.original_nodes[c]] += self.problem.nodes[c].demand;
+            }
+        }
+        delivered
+    }
+}
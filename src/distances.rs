@@ -0,0 +1,220 @@
+//! Reduced storage of the distances of large instances, in lower precision or for the
+//! granular neighbors only.
+
+use crate::problem::Node;
+use crate::spatial::KdTree;
+use serde::{Deserialize, Serialize};
+
+/// Storage of the distances of a problem in place of its full `f64` matrix.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DistanceStorage {
+    /// Every distance, in `f32`
+    Compact(CompactDistances),
+    /// Distances from the depot and to the closest customers, the others computed on demand
+    Sparse(SparseDistances),
+}
+
+impl DistanceStorage {
+    /// Get the distance between two nodes.
+    pub fn get(&self, from: usize, to: usize) -> f64 {
+        match self {
+            DistanceStorage::Compact(compact) => compact.get(from, to),
+            DistanceStorage::Sparse(sparse) => sparse.get(from, to),
+        }
+    }
+
+    /// Expand the distances into a full matrix, e.g. to edit them.
+    pub fn to_matrix(&self) -> Vec<Vec<f64>> {
+        match self {
+            DistanceStorage::Compact(compact) => compact.to_matrix(),
+            DistanceStorage::Sparse(sparse) => sparse.to_matrix(),
+        }
+    }
+
+    /// Get the memory allocated for the distances, in bytes.
+    pub fn memory_usage(&self) -> usize {
+        match self {
+            DistanceStorage::Compact(compact) => compact.memory_usage(),
+            DistanceStorage::Sparse(sparse) => sparse.memory_usage(),
+        }
+    }
+}
+
+/// Square distance matrix stored row by row in `f32`, half the memory of `Vec<Vec<f64>>`.
+///
+/// Distances are widened to `f64` when read, so every cost and move delta is still summed in
+/// double precision. Integral distances below 2^24 are stored exactly.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompactDistances {
+    /// Number of nodes, i.e. the length of each row
+    size: usize,
+    /// Distances from each node to every node, row by row
+    values: Vec<f32>,
+}
+
+impl CompactDistances {
+    /// Store a square distance matrix in reduced precision.
+    pub fn from_matrix(matrix: &[Vec<f64>]) -> Self {
+        let size = matrix.len();
+        debug_assert!(
+            matrix.iter().all(|row| row.len() == size),
+            "distance matrix must be square"
+        );
+        CompactDistances {
+            size,
+            values: matrix.iter().flatten().map(|&d| d as f32).collect(),
+        }
+    }
+
+    /// Get the distance between two nodes.
+    pub fn get(&self, from: usize, to: usize) -> f64 {
+        self.values[from * self.size + to] as f64
+    }
+
+    /// Expand the distances into a full matrix, e.g. to edit them.
+    pub fn to_matrix(&self) -> Vec<Vec<f64>> {
+        if self.size == 0 {
+            return Vec::new();
+        }
+        self.values
+            .chunks(self.size)
+            .map(|row| row.iter().map(|&d| d as f64).collect())
+            .collect()
+    }
+
+    /// Get the number of nodes.
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Check whether the matrix has no node.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Get the memory allocated for the distances, in bytes.
+    pub fn memory_usage(&self) -> usize {
+        self.values.capacity() * std::mem::size_of::<f32>()
+    }
+}
+
+/// Euclidean distances stored for the depot and the closest customers of every customer.
+///
+/// These are the pairs the granular neighborhoods evaluate, so the other distances are
+/// rarely needed and are computed from the coordinates when asked for. Memory is O(n k)
+/// instead of O(n^2).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SparseDistances {
+    /// Number of closest customers stored per customer
+    neighbor_count: usize,
+    /// Whether distances are rounded to integers
+    rounded: bool,
+    /// Location of each node
+    locations: Vec<(f64, f64)>,
+    /// Index of the depot
+    depot_index: usize,
+    /// Distance between the depot and each node
+    depot_distances: Vec<f64>,
+    /// Closest customers of each node with their distances, ordered by node index
+    neighbors: Vec<Vec<(usize, f64)>>,
+}
+
+impl SparseDistances {
+    /// Store the distances from the depot and to the `neighbor_count` closest customers of
+    /// every customer, rounded to integers if `rounded`.
+    pub fn new(nodes: &[Node], depot_index: usize, neighbor_count: usize, rounded: bool) -> Self {
+        let locations: Vec<(f64, f64)> = nodes.iter().map(|node| (node.x, node.y)).collect();
+        let tree = KdTree::new(
+            (0..nodes.len())
+                .filter(|&node| node != depot_index)
+                .map(|node| (node, locations[node].0, locations[node].1)),
+        );
+
+        let mut sparse = SparseDistances {
+            neighbor_count,
+            rounded,
+            locations,
+            depot_index,
+            depot_distances: Vec::new(),
+            neighbors: Vec::new(),
+        };
+        sparse.depot_distances = (0..nodes.len())
+            .map(|node| sparse.compute(depot_index, node))
+            .collect();
+        sparse.neighbors = (0..nodes.len())
+            .map(|node| {
+                if node == depot_index {
+                    return Vec::new();
+                }
+                let (x, y) = sparse.locations[node];
+                let mut closest: Vec<(usize, f64)> = tree
+                    .nearest(x, y)
+                    .filter(|&(other, _)| other != node)
+                    .take(neighbor_count)
+                    .map(|(other, _)| (other, sparse.compute(node, other)))
+                    .collect();
+                closest.sort_by_key(|&(other, _)| other);
+                closest
+            })
+            .collect();
+        sparse
+    }
+
+    /// Get the distance between two nodes.
+    pub fn get(&self, from: usize, to: usize) -> f64 {
+        if from == to {
+            0.0
+        } else if from == self.depot_index {
+            self.depot_distances[to]
+        } else if to == self.depot_index {
+            self.depot_distances[from]
+        } else {
+            let neighbors = &self.neighbors[from];
+            match neighbors.binary_search_by_key(&to, |&(other, _)| other) {
+                Ok(position) => neighbors[position].1,
+                Err(_) => self.compute(from, to),
+            }
+        }
+    }
+
+    /// Get the number of closest customers stored per customer.
+    pub fn neighbor_count(&self) -> usize {
+        self.neighbor_count
+    }
+
+    /// Expand the distances into a full matrix.
+    pub fn to_matrix(&self) -> Vec<Vec<f64>> {
+        let n = self.locations.len();
+        (0..n)
+            .map(|from| (0..n).map(|to| self.get(from, to)).collect())
+            .collect()
+    }
+
+    /// Get the memory allocated for the distances, in bytes.
+    pub fn memory_usage(&self) -> usize {
+        self.locations.capacity() * std::mem::size_of::<(f64, f64)>()
+            + self.depot_distances.capacity() * std::mem::size_of::<f64>()
+            + self.neighbors.capacity() * std::mem::size_of::<Vec<(usize, f64)>>()
+            + self
+                .neighbors
+                .iter()
+                .map(|list| list.capacity() * std::mem::size_of::<(usize, f64)>())
+                .sum::<usize>()
+    }
+
+    /// Compute a distance from the coordinates, like `Node::distance`.
+    fn compute(&self, from: usize, to: usize) -> f64 {
+        if from == to {
+            return 0.0;
+        }
+        let (x1, y1) = self.locations[from];
+        let (x2, y2) = self.locations[to];
+        let (dx, dy) = (x1 - x2, y1 - y2);
+        let distance = (dx * dx + dy * dy).sqrt();
+        if self.rounded {
+            distance.round()
+        } else {
+            distance
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//! Changes of a problem while it is being solved, for dynamic routing.
+
+use crate::local_search::utils::calculate_insertion_cost;
+use crate::problem::{Node, Problem};
+use crate::solution::{Route, Solution};
+
+/// Change of the customers of a problem.
+#[derive(Debug, Clone)]
+pub enum ProblemChange {
+    /// Add a customer, with its distances to the existing nodes if they do not follow from
+    /// the coordinates
+    AddCustomer {
+        node: Node,
+        distances: Option<Vec<f64>>,
+    },
+    /// Remove the customer at a node index, renumbering the nodes after it
+    RemoveCustomer(usize),
+    /// Change the demand of a customer
+    ChangeDemand { customer: usize, demand: f64 },
+}
+
+/// Where the nodes of a problem went after a change.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeMapping {
+    /// New index of each node of the original problem, `None` for a removed customer
+    pub new_index: Vec<Option<usize>>,
+    /// Index of the added customer, if any
+    pub added: Option<usize>,
+}
+
+impl NodeMapping {
+    /// Mapping of a change that keeps every node at its index.
+    pub fn identity(node_count: usize) -> Self {
+        NodeMapping {
+            new_index: (0..node_count).map(Some).collect(),
+            added: None,
+        }
+    }
+
+    /// Update the routes of a solution of the original problem for the changed one.
+    ///
+    /// Removed customers are dropped, the others renumbered, and an added customer is
+    /// inserted at its cheapest position given the capacity penalty and the stop limit,
+    /// possibly in a new route.
+    /// The solution is evaluated with the same penalty.
+    pub fn repair(&self, solution: &mut Solution, problem: &Problem, capacity_penalty: f64) {
+        for route in &mut solution.routes {
+            route.customers = route
+                .customers
+                .iter()
+                .filter_map(|&customer| self.new_index.get(customer).copied().flatten())
+                .collect();
+        }
+        solution.routes.retain(|route| !route.is_empty());
+        solution.evaluate(problem, capacity_penalty);
+
+        if let Some(customer) = self.added {
+            Self::insert_cheapest(solution, problem, customer, capacity_penalty);
+        }
+
+        solution.update_giant_tour();
+        solution.evaluate(problem, capacity_penalty);
+    }
+
+    /// Insert a customer where it increases the penalized cost the least.
+    fn insert_cheapest(
+        solution: &mut Solution,
+        problem: &Problem,
+        customer: usize,
+        capacity_penalty: f64,
+    ) {
+        let demand = problem.nodes[customer].demand;
+        let variance = problem.demand_variance(customer);
+
+        // Opening a new route, if the fleet allows it
+        let can_open_route = problem
+            .max_vehicles
+            .is_none_or(|max| solution.routes.len() < max);
+        let mut best = can_open_route.then(|| {
+            let cost = 2.0 * problem.get_distance(problem.depot_index, customer)
+                + problem.route_fixed_cost
+                + capacity_penalty * problem.load_excess(demand, variance);
+            (solution.routes.len(), 0, cost)
+        });
+
+        for (r_idx, route) in solution.routes.iter().enumerate() {
+            if !problem.allows_stops(route.customers.len() + 1) {
+                continue;
+            }
+            let load_cost = capacity_penalty
+                * (problem.load_excess(route.load + demand, route.load_variance + variance)
+                    - problem.load_excess(route.load, route.load_variance));
+            for position in 0..=route.customers.len() {
+                if !problem.pins.allows_insertion(route, position) {
+                    continue;
+                }
+                let cost = calculate_insertion_cost(route, customer, position, problem)
+                    - route.distance
+                    + load_cost;
+                if best.is_none_or(|(_, _, best_cost)| cost < best_cost) {
+                    best = Some((r_idx, position, cost));
+                }
+            }
+        }
+
+        match best {
+            Some((r_idx, position, _)) if r_idx < solution.routes.len() => {
+                solution.routes[r_idx].customers.insert(position, customer);
+                solution.routes[r_idx].modified = true;
+            }
+            _ => {
+                let mut route = Route::new();
+                route.customers.push(customer);
+                solution.routes.push(route);
+            }
+        }
+    }
+}
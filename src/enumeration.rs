@@ -0,0 +1,69 @@
+//! Exact solution of tiny instances by enumerating every set of routes.
+
+use crate::problem::Problem;
+use crate::solution::{Route, Solution};
+
+/// Largest number of customers of the instances that `HgsAlgorithm` solves by enumeration
+pub const ENUMERATION_MAX_CUSTOMERS: usize = 3;
+
+/// Find the cheapest solution by enumerating every partition of the customers into routes and
+/// every order of each route.
+///
+/// Solutions that are feasible, respect the pins, and use at most `max_vehicles` routes are
+/// preferred, then the lowest cost with `capacity_penalty`. The number of solutions grows
+/// faster than the factorial of the number of customers, so this is only practical for a
+/// handful of them. The returned solution is evaluated with `capacity_penalty`.
+pub fn solve_by_enumeration(problem: &Problem, capacity_penalty: f64) -> Solution {
+    let customers: Vec<usize> = problem.customers().collect();
+    let mut routes = Vec::new();
+    let mut best: Option<(bool, Solution)> = None;
+    enumerate(&customers, &mut routes, &mut |routes| {
+        let mut solution = Solution::new();
+        solution.routes = routes
+            .iter()
+            .map(|customers| {
+                let mut route = Route::new();
+                route.customers = customers.clone();
+                route
+            })
+            .collect();
+        solution.update_giant_tour();
+        solution.evaluate(problem, capacity_penalty);
+
+        let valid = solution.is_feasible
+            && problem.max_vehicles.is_none_or(|max| routes.len() <= max)
+            && problem.pins.is_respected(&solution);
+        let better = best.as_ref().is_none_or(|(best_valid, best)| {
+            (valid && !best_valid) || (valid == *best_valid && solution.cost < best.cost)
+        });
+        if better {
+            best = Some((valid, solution));
+        }
+    });
+
+    best.map_or_else(Solution::new, |(_, solution)| solution)
+}
+
+/// Call `visit` with every arrangement of `customers` appended to `routes`, inserting each
+/// customer at every position of every route, or in a new route.
+fn enumerate(
+    customers: &[usize],
+    routes: &mut Vec<Vec<usize>>,
+    visit: &mut impl FnMut(&[Vec<usize>]),
+) {
+    let Some((&customer, rest)) = customers.split_first() else {
+        visit(routes);
+        return;
+    };
+
+    for r in 0..routes.len() {
+        for position in 0..=routes[r].len() {
+            routes[r].insert(position, customer);
+            enumerate(rest, routes, visit);
+            routes[r].remove(position);
+        }
+    }
+    routes.push(vec![customer]);
+    enumerate(rest, routes, visit);
+    routes.pop();
+}
@@ -0,0 +1,70 @@
+//! Errors returned by the public entry points of the library.
+
+use crate::config::ConfigError;
+use crate::problem::ProblemError;
+use std::fmt;
+use std::io;
+
+/// Reason why a problem could not be loaded or solved.
+#[derive(Debug)]
+pub enum HgsError {
+    /// The search ended without finding a solution satisfying every constraint
+    NoFeasibleSolution,
+    /// Parents were to be selected from a population without individuals
+    EmptyPopulation,
+    /// An instance file is malformed, at a line counted from 1, or 0 for the file as a whole
+    ParseError { line: usize, message: String },
+    /// The problem data cannot be solved
+    InvalidProblem(ProblemError),
+    /// The configuration would make the search panic or degenerate
+    InvalidConfig(ConfigError),
+    /// An instance file could not be read
+    Io(io::Error),
+}
+
+impl fmt::Display for HgsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HgsError::NoFeasibleSolution => write!(f, "no feasible solution was found"),
+            HgsError::EmptyPopulation => {
+                write!(f, "cannot select parents from an empty population")
+            }
+            HgsError::ParseError { line: 0, message } => write!(f, "invalid instance: {message}"),
+            HgsError::ParseError { line, message } => {
+                write!(f, "invalid instance at line {line}: {message}")
+            }
+            HgsError::InvalidProblem(err) => write!(f, "invalid problem: {err}"),
+            HgsError::InvalidConfig(err) => write!(f, "invalid configuration: {err}"),
+            HgsError::Io(err) => write!(f, "cannot read instance: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for HgsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HgsError::InvalidProblem(err) => Some(err),
+            HgsError::InvalidConfig(err) => Some(err),
+            HgsError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ProblemError> for HgsError {
+    fn from(err: ProblemError) -> Self {
+        HgsError::InvalidProblem(err)
+    }
+}
+
+impl From<ConfigError> for HgsError {
+    fn from(err: ConfigError) -> Self {
+        HgsError::InvalidConfig(err)
+    }
+}
+
+impl From<io::Error> for HgsError {
+    fn from(err: io::Error) -> Self {
+        HgsError::Io(err)
+    }
+}
@@ -1,23 +1,52 @@
 //! Genetic operators for the HGS-CVRP algorithm.
 
 use crate::individual::Individual;
+use crate::neighbors::NeighborLists;
+use crate::problem::Problem;
+use crate::rng::search_rng;
 use crate::solution::Solution;
-use rand::{seq::SliceRandom, thread_rng, Rng};
+use rand::Rng;
 use std::collections::HashSet;
 
-/// Implements the genetic operators (crossover, mutation) for the HGS-CVRP.
-pub struct Genetic;
+/// A crossover operator producing an offspring giant tour from two parents.
+pub trait CrossoverOperator {
+    /// Create an offspring from two parents. Only the giant tour of the result needs to be set,
+    /// the routes are created afterwards by the Split algorithm.
+    fn crossover(&self, parent1: &Individual, parent2: &Individual) -> Solution;
+}
 
-impl Genetic {
-    /// Perform ordered crossover (OX) between two parent solutions.
-    pub fn crossover(&self, parent1: &Individual, parent2: &Individual) -> Solution {
-        let mut rng = thread_rng();
+/// A mutation operator modifying the giant tour of an individual.
+pub trait MutationOperator {
+    /// Mutate the giant tour of an individual in place.
+    fn mutate(&self, individual: &mut Individual, mutation_rate: f64);
+}
 
-        let p1_tour = &parent1.solution.giant_tour;
-        let p2_tour = &parent2.solution.giant_tour;
+/// Implements the default genetic operators for the HGS-CVRP: ordered crossover (OX)
+/// and swap mutation.
+pub struct Genetic;
 
-        if p1_tour.is_empty() || p2_tour.is_empty() {
-            return Solution::new();
+impl CrossoverOperator for Genetic {
+    /// Perform ordered crossover (OX) between two parent solutions.
+    ///
+    /// The giant tours of the parents are rebuilt from their routes when out of sync. If the
+    /// parents still do not visit the same number of customers, the offspring copies the tour
+    /// of the first one.
+    fn crossover(&self, parent1: &Individual, parent2: &Individual) -> Solution {
+        let mut rng = search_rng();
+
+        let p1_tour = parent1.solution.synced_giant_tour();
+        let p2_tour = parent2.solution.synced_giant_tour();
+
+        if p1_tour.is_empty() || p2_tour.is_empty() || p1_tour.len() != p2_tour.len() {
+            if p1_tour.len() != p2_tour.len() {
+                log::warn!(
+                    "crossover parents visit {} and {} customers, copying one of them",
+                    p1_tour.len(),
+                    p2_tour.len()
+                );
+            }
+            let tour = if p1_tour.is_empty() { p2_tour } else { p1_tour };
+            return Self::create_solution_from_tour(tour.into_owned());
         }
 
         // Determine cutting points for OX
@@ -35,15 +64,10 @@ impl Genetic {
         let mut offspring_tour = vec![0; tour_size];
 
         // Step 2: Copy segment from first parent
-        for i in start..=end {
-            offspring_tour[i] = p1_tour[i];
-        }
+        offspring_tour[start..=end].copy_from_slice(&p1_tour[start..=end]);
 
         // Create a set of customers already in the offspring
-        let mut used = HashSet::new();
-        for i in start..=end {
-            used.insert(p1_tour[i]);
-        }
+        let mut used: HashSet<usize> = p1_tour[start..=end].iter().copied().collect();
 
         // Step 3: Fill remaining positions from the second parent
         let mut j = (end + 1) % tour_size;
@@ -59,20 +83,24 @@ impl Genetic {
         }
 
         // Create a new solution from the offspring tour
-        Self::create_solution_from_tour(offspring_tour, parent1.solution.routes.len())
+        Self::create_solution_from_tour(offspring_tour)
     }
+}
 
+impl Genetic {
     /// Create a solution from a giant tour.
-    fn create_solution_from_tour(giant_tour: Vec<usize>, route_count: usize) -> Solution {
+    fn create_solution_from_tour(giant_tour: Vec<usize>) -> Solution {
         let mut solution = Solution::new();
         solution.giant_tour = giant_tour;
         // The Split algorithm will be applied separately to create routes
         solution
     }
+}
 
+impl MutationOperator for Genetic {
     /// Implement a simple swap mutation operator.
-    pub fn mutate(&self, individual: &mut Individual, mutation_rate: f64) {
-        let mut rng = thread_rng();
+    fn mutate(&self, individual: &mut Individual, mutation_rate: f64) {
+        let mut rng = search_rng();
 
         if individual.solution.giant_tour.is_empty() {
             return;
@@ -92,3 +120,79 @@ impl Genetic {
         }
     }
 }
+
+/// Shorten a giant tour by 2-opt moves creating an edge between granular neighbors.
+///
+/// The tour is seen as a cycle through the depot, and improving moves are applied for at
+/// most `max_passes` passes over the customers, so Split starts from a tour with fewer long
+/// edges to cut. Reversals assume symmetric distances, like the 2-opt neighborhood.
+/// Returns the number of moves applied.
+pub fn improve_giant_tour(
+    tour: &mut [usize],
+    problem: &Problem,
+    neighbors: &NeighborLists,
+    max_passes: usize,
+) -> usize {
+    let mut cycle = Vec::with_capacity(tour.len() + 1);
+    cycle.push(problem.depot_index);
+    cycle.extend_from_slice(tour);
+    let len = cycle.len();
+    if len < 4 {
+        return 0;
+    }
+
+    let mut position = vec![None; problem.nodes.len()];
+    for (index, &node) in cycle.iter().enumerate() {
+        position[node] = Some(index);
+    }
+    let distance = |from: usize, to: usize| problem.get_distance(from, to);
+    let tolerance = problem.tolerance;
+
+    let mut moves = 0;
+    for _ in 0..max_passes {
+        let mut improved = false;
+        for a in 0..problem.nodes.len() {
+            for &c in neighbors.get(a) {
+                let (Some(pa), Some(pc)) = (position[a], position.get(c).copied().flatten()) else {
+                    continue;
+                };
+                let (p, q) = (pa.min(pc), pa.max(pc));
+                if p == 0 || q < p + 2 {
+                    continue;
+                }
+
+                // Connect the two nodes by reversing the segment after the first one, or
+                // the segment before the second one
+                let (after_p, after_q) = (cycle[p + 1], cycle[(q + 1) % len]);
+                let (before_p, before_q) = (cycle[p - 1], cycle[q - 1]);
+                let shared = distance(cycle[p], cycle[q]);
+                let successors = shared + distance(after_p, after_q)
+                    - distance(cycle[p], after_p)
+                    - distance(cycle[q], after_q);
+                let predecessors = shared + distance(before_p, before_q)
+                    - distance(before_p, cycle[p])
+                    - distance(before_q, cycle[q]);
+                let (start, end) = if successors < -tolerance && successors <= predecessors {
+                    (p + 1, q)
+                } else if predecessors < -tolerance {
+                    (p, q - 1)
+                } else {
+                    continue;
+                };
+
+                cycle[start..=end].reverse();
+                for (index, &node) in cycle.iter().enumerate().take(end + 1).skip(start) {
+                    position[node] = Some(index);
+                }
+                moves += 1;
+                improved = true;
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+
+    tour.copy_from_slice(&cycle[1..]);
+    moves
+}
@@ -0,0 +1,121 @@
+//! Guided local search: penalties on the long edges of local optima, added to the distances
+//! during education to push the search out of them without a restart.
+
+use crate::problem::Problem;
+use crate::solution::Solution;
+use std::collections::HashMap;
+
+/// Edge penalties accumulated on the local optima of the search, and the augmented problem
+/// whose distances include them.
+///
+/// Each call to `penalize` increments the penalty of the edges of a solution with the largest
+/// utility `distance / (1 + penalty)`, so long edges that keep appearing become costlier. The
+/// augmented problem stores its distances in a full matrix.
+#[derive(Debug, Clone)]
+pub struct GuidedLocalSearch {
+    /// Weight of a penalty, as a fraction of the average edge length of the first penalized
+    /// solution
+    pub lambda: f64,
+    /// Number of times each edge was penalized, keyed by its endpoints in increasing order
+    penalties: HashMap<(usize, usize), u32>,
+    /// Cost added to an edge per penalty, fixed by the first penalized solution
+    penalty_cost: Option<f64>,
+    /// Copy of the problem whose distances include the penalties
+    augmented: Option<Problem>,
+}
+
+impl GuidedLocalSearch {
+    /// Create a guided local search with the given penalty weight, typically 0.1 to 0.3.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lambda` is not positive.
+    pub fn new(lambda: f64) -> Self {
+        assert!(lambda > 0.0, "guided local search needs a positive lambda");
+        GuidedLocalSearch {
+            lambda,
+            penalties: HashMap::new(),
+            penalty_cost: None,
+            augmented: None,
+        }
+    }
+
+    /// Get the number of times an edge was penalized, in either direction.
+    pub fn penalty(&self, from: usize, to: usize) -> u32 {
+        self.penalties
+            .get(&(from.min(to), from.max(to)))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Get the number of edges with a penalty.
+    pub fn penalized_edges(&self) -> usize {
+        self.penalties.len()
+    }
+
+    /// Get the problem whose distances include the penalties, once a solution was penalized.
+    pub fn augmented_problem(&self) -> Option<&Problem> {
+        self.augmented.as_ref()
+    }
+
+    /// Penalize the edges of a local optimum with the largest utility, returning how many.
+    pub fn penalize(&mut self, solution: &Solution, problem: &Problem) -> usize {
+        let mut edges: Vec<(usize, usize)> = Vec::new();
+        for route in solution.routes.iter().filter(|route| !route.is_empty()) {
+            let mut previous = problem.depot_index;
+            for &customer in route.customers.iter().chain([&problem.depot_index]) {
+                edges.push((previous.min(customer), previous.max(customer)));
+                previous = customer;
+            }
+        }
+        edges.sort_unstable();
+        edges.dedup();
+        if edges.is_empty() {
+            return 0;
+        }
+
+        let penalty_cost = *self
+            .penalty_cost
+            .get_or_insert(self.lambda * solution.distance / edges.len() as f64);
+        let augmented = self.augmented.get_or_insert_with(|| {
+            let matrix = (0..problem.nodes.len())
+                .map(|from| {
+                    (0..problem.nodes.len())
+                        .map(|to| problem.get_distance(from, to))
+                        .collect()
+                })
+                .collect();
+            let mut augmented = problem.clone().with_distance_matrix(matrix);
+            augmented.euclidean_distances = problem.euclidean_distances;
+            augmented
+        });
+
+        // Penalize every edge within the tolerance of the largest utility
+        let utility = |&(a, b): &(usize, usize)| {
+            let penalty = self.penalties.get(&(a, b)).copied().unwrap_or(0);
+            problem.get_distance(a, b) / (1.0 + penalty as f64)
+        };
+        let max_utility = edges.iter().map(utility).fold(f64::NEG_INFINITY, f64::max);
+        let penalized: Vec<(usize, usize)> = edges
+            .iter()
+            .filter(|edge| utility(edge) >= max_utility - problem.tolerance)
+            .copied()
+            .collect();
+
+        for &(a, b) in &penalized {
+            let penalty = self.penalties.entry((a, b)).or_insert(0);
+            *penalty += 1;
+            let cost = penalty_cost * *penalty as f64;
+            augmented.distance_matrix[a][b] = problem.get_distance(a, b) + cost;
+            augmented.distance_matrix[b][a] = problem.get_distance(b, a) + cost;
+        }
+        penalized.len()
+    }
+
+    /// Forget all penalties, e.g. after the problem changed.
+    pub fn reset(&mut self) {
+        self.penalties.clear();
+        self.penalty_cost = None;
+        self.augmented = None;
+    }
+}
@@ -1,7 +1,11 @@
 //! Individual representation for the genetic algorithm population.
 
+use crate::costs;
 use crate::solution::Solution;
 use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
 
 /// An individual in the genetic algorithm population.
 #[derive(Clone)]
@@ -14,8 +18,14 @@ pub struct Individual {
     pub rank_diversity: usize,
     /// The biased fitness based on both ranks
     pub biased_fitness: f64,
-    /// Number of common pairs of consecutive customers with other individuals
-    pub common_pairs: Vec<usize>,
+    /// Identifier assigned by the population on insertion (0 if not inserted)
+    pub id: usize,
+    /// Number of generations since the individual was inserted in the population
+    pub age: u32,
+    /// Cached pairs of consecutive customers in the giant tour
+    pub pair_set: HashSet<(usize, usize)>,
+    /// Cached hash of the solution structure, see `structure_hash`
+    pub cached_structure_hash: Option<u64>,
 }
 
 impl Individual {
@@ -26,10 +36,73 @@ impl Individual {
             rank_feasibility: 0,
             rank_diversity: 0,
             biased_fitness: 0.0,
-            common_pairs: Vec::new(),
+            id: 0,
+            age: 0,
+            pair_set: HashSet::new(),
+            cached_structure_hash: None,
         }
     }
 
+    /// Cache the hash of the solution structure.
+    pub fn update_structure_hash(&mut self) {
+        self.cached_structure_hash = Some(Self::compute_structure_hash(&self.solution));
+    }
+
+    /// Get the hash of the solution structure, computed if it is not cached.
+    ///
+    /// Solutions with the same set of directed edges have the same hash, whatever the order
+    /// of their routes. As for the broken-pairs distance, reversed routes are distinct.
+    pub fn structure_hash(&self) -> u64 {
+        self.cached_structure_hash
+            .unwrap_or_else(|| Self::compute_structure_hash(&self.solution))
+    }
+
+    /// Hash the sorted set of edges of the routes, or of the giant tour if the solution has
+    /// no routes.
+    pub fn compute_structure_hash(solution: &Solution) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        Self::structure_edges(solution).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Get the sorted edges of the routes, or of the giant tour if the solution has no routes.
+    pub fn structure_edges(solution: &Solution) -> Vec<(usize, usize)> {
+        let mut edges: Vec<(usize, usize)> = if solution.routes.is_empty() {
+            solution
+                .giant_tour
+                .windows(2)
+                .map(|pair| (pair[0], pair[1]))
+                .collect()
+        } else {
+            // Every route starts and ends at the depot, represented by `usize::MAX`
+            solution
+                .routes
+                .iter()
+                .filter(|route| !route.is_empty())
+                .flat_map(|route| {
+                    let stops =
+                        || std::iter::once(usize::MAX).chain(route.customers.iter().copied());
+                    stops().zip(stops().skip(1).chain(std::iter::once(usize::MAX)))
+                })
+                .collect()
+        };
+        edges.sort_unstable();
+        edges
+    }
+
+    /// Rebuild the cached set of consecutive customer pairs from the giant tour.
+    pub fn update_pair_set(&mut self) {
+        self.pair_set = Self::build_pair_set(&self.solution.giant_tour);
+    }
+
+    /// Build the set of consecutive customer pairs of a giant tour.
+    fn build_pair_set(giant_tour: &[usize]) -> HashSet<(usize, usize)> {
+        giant_tour
+            .windows(2)
+            .map(|pair| (pair[0], pair[1]))
+            .collect()
+    }
+
     /// Calculate the biased fitness of the individual based on ranks.
     pub fn calculate_biased_fitness(&mut self, elite_proportion: f64) {
         // Fitness is based on quality rank and diversity rank
@@ -39,60 +112,55 @@ impl Individual {
             self.rank_feasibility as f64 + penalizing_factor * self.rank_diversity as f64;
     }
 
-    /// Calculate the average distance to the closest solutions.
-    pub fn calculate_diversity_contribution(&self, closest_count: usize) -> f64 {
-        if self.common_pairs.is_empty() || closest_count == 0 {
-            return 0.0;
-        }
-
-        // Sort in ascending order (higher common pairs = closer solutions)
-        let mut sorted_pairs = self.common_pairs.clone();
-        sorted_pairs.sort_unstable_by(|a, b| b.cmp(a));
-
-        // Take average of n closest
-        let count = std::cmp::min(closest_count, sorted_pairs.len());
-        let sum: usize = sorted_pairs.iter().take(count).sum();
-
-        sum as f64 / count as f64
-    }
-
-    /// Check if this individual is a clone of another.
+    /// Check if this individual is a clone of another, i.e. has the same penalized cost and
+    /// structure.
     pub fn is_clone_of(&self, other: &Individual) -> bool {
-        if self.solution.giant_tour.len() != other.solution.giant_tour.len() {
-            return false;
-        }
+        self.is_clone_of_solution(&other.solution, other.structure_hash())
+    }
 
-        self.solution.giant_tour == other.solution.giant_tour
+    /// Check if this individual is a clone of `solution`, whose structure hash is `hash`.
+    ///
+    /// The hashes are compared first, and the edges only when they match, so that a hash
+    /// collision does not make distinct solutions clones.
+    pub fn is_clone_of_solution(&self, solution: &Solution, hash: u64) -> bool {
+        self.solution.giant_tour.len() == solution.giant_tour.len()
+            && self.structure_hash() == hash
+            && costs::approx_eq(self.solution.cost, solution.cost, costs::DEFAULT_TOLERANCE)
+            && Self::structure_edges(&self.solution) == Self::structure_edges(solution)
     }
 
     /// Calculate the number of common pairs of consecutive customers with another individual.
+    /// Uses the cached pair set of `other` when available.
     pub fn calculate_common_pairs(&self, other: &Individual) -> usize {
-        let mut common_count = 0;
-
         if self.solution.giant_tour.is_empty() || other.solution.giant_tour.is_empty() {
             return 0;
         }
 
-        // Create a set of customer pairs in the other solution
-        let mut other_pairs = std::collections::HashSet::new();
-
-        for i in 0..other.solution.giant_tour.len() - 1 {
-            let pair = (
-                other.solution.giant_tour[i],
-                other.solution.giant_tour[i + 1],
-            );
-            other_pairs.insert(pair);
-        }
+        let built;
+        let other_pairs = if other.pair_set.is_empty() {
+            built = Self::build_pair_set(&other.solution.giant_tour);
+            &built
+        } else {
+            &other.pair_set
+        };
 
         // Count common pairs in this solution
-        for i in 0..self.solution.giant_tour.len() - 1 {
-            let pair = (self.solution.giant_tour[i], self.solution.giant_tour[i + 1]);
-            if other_pairs.contains(&pair) {
-                common_count += 1;
-            }
+        self.solution
+            .giant_tour
+            .windows(2)
+            .filter(|pair| other_pairs.contains(&(pair[0], pair[1])))
+            .count()
+    }
+
+    /// Calculate the broken-pairs distance to another individual, normalized to [0, 1].
+    pub fn broken_pairs_distance(&self, other: &Individual) -> f64 {
+        let pair_count = self.solution.giant_tour.len().saturating_sub(1);
+        if pair_count == 0 {
+            return 0.0;
         }
 
-        common_count
+        let common = self.calculate_common_pairs(other);
+        (pair_count - common.min(pair_count)) as f64 / pair_count as f64
     }
 
     /// Get the cost of the solution.
@@ -128,8 +196,6 @@ impl PartialOrd for Individual {
 impl Ord for Individual {
     fn cmp(&self, other: &Self) -> Ordering {
         // For the genetic algorithm, we compare individuals based on their fitness
-        self.biased_fitness
-            .partial_cmp(&other.biased_fitness)
-            .unwrap_or(Ordering::Equal)
+        self.biased_fitness.total_cmp(&other.biased_fitness)
     }
 }
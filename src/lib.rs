@@ -9,28 +9,70 @@
 //! The algorithm combines genetic search with local improvement heuristics and
 //! strategic management of population diversity to efficiently solve CVRP instances.
 
+pub mod analysis;
+pub mod compression;
 pub mod config;
+pub mod costs;
+pub mod decomposition;
+pub mod demand_split;
+pub mod distances;
+pub mod dynamic;
+pub mod enumeration;
+pub mod error;
 pub mod genetic;
+pub mod guided;
 pub mod individual;
-pub mod local_search; // Now a directory with modules
+pub mod lns;
+pub mod local_search;
+pub mod merging;
+pub mod multi_run;
+pub mod neighbors;
+pub mod objective;
+pub mod params;
+pub mod pareto;
+pub mod penalty;
+pub mod pinning;
 pub mod population;
 pub mod problem;
+pub mod progress;
+pub mod rng;
 pub mod solution;
+pub mod spatial;
 pub mod split;
+pub mod stochastic;
+pub mod tuning;
 pub mod utils;
 
 use individual::Individual;
 
 use crate::config::Config;
-use crate::genetic::Genetic;
-use crate::local_search::LocalSearch;
-use crate::population::Population;
+use crate::decomposition::Decomposition;
+use crate::dynamic::ProblemChange;
+use crate::enumeration::ENUMERATION_MAX_CUSTOMERS;
+use crate::error::HgsError;
+use crate::genetic::{improve_giant_tour, CrossoverOperator, Genetic, MutationOperator};
+use crate::guided::GuidedLocalSearch;
+use crate::lns::Lns;
+use crate::local_search::{EducationCache, LocalSearch, Neighborhood, RoutePruning};
+use crate::merging::merge_routes;
+use crate::multi_run::{MultiRunReport, RunReport};
+use crate::params::{ParamHandle, TunableParams};
+use crate::pareto::ParetoFront;
+use crate::population::{ClonePolicy, Population, PopulationSnapshot};
 use crate::problem::Problem;
+use crate::progress::{Progress, ProgressCallback};
+use crate::rng::search_rng;
 use crate::solution::Solution;
 use crate::split::Split;
+use crate::utils::SearchStatistics;
 
+use rand::Rng;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+/// Largest granularity of the final polish, bounding its neighbor lists on large instances
+pub const FINAL_POLISH_MAX_GRANULARITY: usize = 200;
+
 /// The main algorithm structure that orchestrates the hybrid genetic search.
 pub struct HgsAlgorithm {
     pub problem: Problem,
@@ -40,90 +82,1068 @@ pub struct HgsAlgorithm {
     pub run_time: Duration,
     pub iterations: u32,
     pub iterations_without_improvement: u32,
-    pub genetic: Genetic,
+    pub crossover: Box<dyn CrossoverOperator>,
+    pub mutation: Box<dyn MutationOperator>,
     pub split: Split,
     pub local_search: LocalSearch,
+    /// Ruin and recreate applied to offspring with `lns_probability`
+    pub lns: Lns,
+    /// Educated offspring by giant tour, if enabled with `education_cache_size`
+    pub education_cache: Option<EducationCache>,
+    /// Edge penalties of the guided local search, if enabled with `gls_lambda`
+    pub guided_local_search: Option<GuidedLocalSearch>,
     pub start_time: Instant,
+    /// Non-dominated (routes, distance) solutions, tracked with `track_pareto_front`
+    pub pareto_front: ParetoFront,
+    /// Called with the progress of the search after each generation
+    pub progress_callback: Option<ProgressCallback>,
+    /// Population snapshots taken every `snapshot_period` iterations, with their iteration
+    pub population_snapshots: Vec<(u32, PopulationSnapshot)>,
+    /// Cost and time of the first feasible solution, used for the improvement rate
+    first_best: Option<(f64, Duration)>,
+    /// Local search with a larger granularity and no pruning, used for elite intensification
+    pub intensification_search: Option<LocalSearch>,
+    /// Sum of the population sizes after each iteration, for the average size
+    population_size_sum: usize,
+    /// Parameters that can be changed while the search runs
+    params: ParamHandle,
+    /// Version of the parameters last applied to the search
+    params_version: u64,
+    /// New best solutions found since the granularity was last adapted
+    recent_improvements: u32,
+    /// Time into the current run at which each cost target was first reached
+    target_times: Vec<Option<Duration>>,
 }
 
 impl HgsAlgorithm {
     /// Create a new HGS instance for the given problem and configuration.
-    pub fn new(problem: Problem, config: Config) -> Self {
-        HgsAlgorithm {
+    ///
+    /// The configuration is first fitted to the instance with `Config::scaled_to`. Returns
+    /// `HgsError::InvalidConfig` if it then fails `Config::validate`, whose warnings are
+    /// logged. Problems that cannot be solved feasibly are only logged, and the search returns
+    /// the least infeasible solution it finds.
+    pub fn new(mut problem: Problem, config: Config) -> Result<Self, HgsError> {
+        let config = config.scaled_to(&problem);
+        for warning in config.validate(&problem)? {
+            log::warn!("{}", warning);
+        }
+        if let Some(tolerance) = config.tolerance {
+            problem.tolerance = tolerance;
+        }
+        if let Err(err) = problem.validate() {
+            log::warn!(
+                "problem {} cannot be solved feasibly: {}",
+                problem.name,
+                err
+            );
+        }
+        let colocated = problem.colocated_nodes();
+        if !colocated.is_empty() {
+            log::info!(
+                "problem {} has {} nodes sharing their location with another one, in {} groups",
+                problem.name,
+                colocated.iter().map(Vec::len).sum::<usize>(),
+                colocated.len()
+            );
+        }
+
+        let pareto_front = ParetoFront::new(problem.tolerance);
+        let mut local_search = LocalSearch::new(config.granularity);
+        local_search.operator_granularity = config.operator_granularity;
+        let neighbors = Arc::new(problem.neighbor_lists(local_search.neighbor_list_size()));
+        let mut local_search = local_search
+            .with_neighbor_lists(neighbors)
+            .with_verify_deltas(config.verify_deltas)
+            .with_route_pruning(config.route_pruning)
+            .with_max_moves(config.max_education_moves)
+            .with_max_time(config.max_education_time)
+            .with_route_cache(config.route_cache_size)
+            .with_tabu_tenure(config.tabu_tenure)
+            .with_load_slack_pruning(config.load_slack_pruning)
+            .with_trace(config.record_trace);
+        // The lists may be longer than the granularity, for the operator granularities
+        local_search.granularity = config.granularity;
+        let population = Population::new(&config);
+        let params = ParamHandle::new(TunableParams {
+            granularity: config.granularity,
+            target_feasible_ratio: config.target_feasible_ratio,
+            penalty_min: population.penalties.capacity.min_coefficient,
+            penalty_max: population.penalties.capacity.max_coefficient,
+        });
+
+        Ok(HgsAlgorithm {
             problem,
-            population: Population::new(&config),
+            population,
             config: config.clone(),
             best_solution: None,
             run_time: Duration::from_secs(0),
             iterations: 0,
             iterations_without_improvement: 0,
-            genetic: Genetic,
+            crossover: Box::new(Genetic),
+            mutation: Box::new(Genetic),
             split: Split,
-            local_search: LocalSearch::new(config.granularity),
+            local_search,
+            lns: Lns::default(),
+            education_cache: config.education_cache_size.map(EducationCache::new),
+            guided_local_search: (config.gls_lambda > 0.0)
+                .then(|| GuidedLocalSearch::new(config.gls_lambda)),
             start_time: Instant::now(),
+            pareto_front,
+            progress_callback: None,
+            population_snapshots: Vec::new(),
+            first_best: None,
+            target_times: vec![None; config.cost_targets.len()],
+            intensification_search: None,
+            population_size_sum: 0,
+            params,
+            params_version: 0,
+            recent_improvements: 0,
+        })
+    }
+
+    /// Explore a custom neighborhood after the built-in ones when educating offspring.
+    ///
+    /// The extended education of new best solutions keeps the built-in neighborhoods only.
+    pub fn with_neighborhood(mut self, neighborhood: impl Neighborhood + 'static) -> Self {
+        self.local_search = self.local_search.with_neighborhood(neighborhood);
+        self
+    }
+
+    /// Use custom ruin and recreate settings for the offspring, see `Config::with_lns_probability`.
+    pub fn with_lns(mut self, lns: Lns) -> Self {
+        self.lns = lns;
+        self
+    }
+
+    /// Use a custom crossover operator instead of the ordered crossover.
+    pub fn with_crossover(mut self, crossover: impl CrossoverOperator + 'static) -> Self {
+        self.crossover = Box::new(crossover);
+        self
+    }
+
+    /// Use a custom mutation operator instead of the swap mutation.
+    pub fn with_mutation(mut self, mutation: impl MutationOperator + 'static) -> Self {
+        self.mutation = Box::new(mutation);
+        self
+    }
+
+    /// Report the progress of the search to a callback after each generation.
+    pub fn with_progress_callback(mut self, callback: impl FnMut(&Progress) + 'static) -> Self {
+        self.progress_callback = Some(Box::new(callback));
+        self
+    }
+
+    /// Get a handle to change the granularity, target feasible ratio, and penalty bounds of
+    /// the search while it runs. Changes apply at the start of the next iteration.
+    pub fn param_handle(&self) -> ParamHandle {
+        self.params.clone()
+    }
+
+    /// Apply the parameters changed through the handle since the last iteration.
+    fn apply_param_updates(&mut self) {
+        let version = self.params.version();
+        if version == self.params_version {
+            return;
+        }
+        self.params_version = version;
+        let params = self.params.get();
+
+        if params.granularity != self.config.granularity {
+            self.set_granularity(params.granularity);
+        }
+
+        self.config.target_feasible_ratio = params.target_feasible_ratio;
+        let capacity = &mut self.population.penalties.capacity;
+        capacity.target_feasible_ratio = params.target_feasible_ratio;
+        capacity.min_coefficient = params.penalty_min;
+        capacity.max_coefficient = params.penalty_max;
+        capacity.coefficient = capacity
+            .coefficient
+            .max(params.penalty_min)
+            .min(params.penalty_max);
+    }
+
+    /// Change the granularity of the education and recompute its neighbor lists.
+    fn set_granularity(&mut self, granularity: usize) {
+        self.config.granularity = granularity;
+        self.local_search.granularity = granularity;
+        self.local_search.preprocess_neighbors(&self.problem);
+    }
+
+    /// Grow the granularity by a quarter after a period without improvement, and shrink it
+    /// by a fifth after a period with several, within the configured bounds.
+    fn adapt_granularity(&mut self) {
+        let Some((min, max)) = self.config.granularity_bounds else {
+            return;
+        };
+        let current = self.config.granularity;
+        let granularity = match std::mem::take(&mut self.recent_improvements) {
+            0 => current + current.div_ceil(4),
+            1 => current,
+            _ => current - current / 5,
+        }
+        .clamp(min, max);
+        if granularity == current {
+            return;
+        }
+
+        self.set_granularity(granularity);
+        // Keep the handle in sync, without skipping updates made through it meanwhile
+        let pending = self.params.version() != self.params_version;
+        self.params
+            .update(|params| params.granularity = granularity);
+        if !pending {
+            self.params_version = self.params.version();
         }
     }
 
     /// Initialize the population with random solutions.
     pub fn initialize(&mut self) {
-        self.population.initialize(&self.problem, &self.config);
+        let deadline = self.initialization_deadline();
+        self.population
+            .initialize_until(&self.problem, &self.config, deadline);
         self.best_solution = self.population.get_best_feasible_solution().cloned();
+
+        if self.config.track_pareto_front {
+            for individual in &self.population.feasible_individuals {
+                self.pareto_front.insert(&individual.solution);
+            }
+        }
     }
 
     /// Run the algorithm until the termination criteria are met.
-    pub fn run(&mut self) -> &Solution {
+    ///
+    /// Returns `HgsError::NoFeasibleSolution` if no solution satisfied every constraint.
+    ///
+    /// Instances with at most `enumeration::ENUMERATION_MAX_CUSTOMERS` customers are solved
+    /// exactly by enumeration instead, without a population.
+    pub fn run(&mut self) -> Result<&Solution, HgsError> {
+        self.seed_rng();
+        self.start_clock();
+        if self.is_tiny() {
+            self.enumerate_best();
+        } else {
+            self.initialize();
+            self.search()?;
+        }
+
+        self.best_solution
+            .as_ref()
+            .ok_or(HgsError::NoFeasibleSolution)
+    }
+
+    /// Continue the search from the current population, e.g. after `apply_change`.
+    ///
+    /// The time limit and the iterations without improvement count from the call, and the
+    /// routes of the best solution inherit the identifiers of the previous best routes.
+    pub fn resume(&mut self) -> Result<&Solution, HgsError> {
+        self.start_clock();
+        self.iterations_without_improvement = 0;
+
+        let previous = self.best_solution.clone();
+        if self.is_tiny() {
+            self.enumerate_best();
+        } else {
+            self.search()?;
+        }
+        if let (Some(best), Some(previous)) = (self.best_solution.as_mut(), &previous) {
+            best.inherit_route_ids(previous);
+        }
+
+        self.best_solution
+            .as_ref()
+            .ok_or(HgsError::NoFeasibleSolution)
+    }
+
+    /// Change the problem being solved and repair the population and best solution for it.
+    ///
+    /// Solutions lose removed customers and get added ones at their cheapest position. The
+    /// best solution is kept in the population if it remains feasible, and the search continues
+    /// with `resume`.
+    pub fn apply_change(&mut self, change: &ProblemChange) {
+        let mapping = self.problem.apply_change(change);
+        let capacity_penalty = self.population.penalties.capacity_penalty();
+
+        // Neighbor lists and edge penalties refer to the old node indices
+        self.local_search.preprocess_neighbors(&self.problem);
+        if let Some(guided) = &mut self.guided_local_search {
+            guided.reset();
+        }
+        if let Some(cache) = &mut self.education_cache {
+            cache.clear();
+        }
+        self.intensification_search = None;
+
+        for mut solution in self.population.drain_solutions() {
+            mapping.repair(&mut solution, &self.problem, capacity_penalty);
+            self.population.insert_individual(Individual::new(solution));
+        }
+        self.population.update_ranks();
+
+        let best = self.best_solution.take().and_then(|mut best| {
+            mapping.repair(&mut best, &self.problem, capacity_penalty);
+            best.is_feasible.then_some(best)
+        });
+        self.best_solution = match (best, self.population.get_best_feasible_solution()) {
+            (Some(best), Some(other)) if other.cost < best.cost => Some(other.clone()),
+            (None, other) => other.cloned(),
+            (best, _) => best,
+        };
+        self.retain_best();
+
+        self.pareto_front = ParetoFront::new(self.problem.tolerance);
+        if self.config.track_pareto_front {
+            for individual in &self.population.feasible_individuals {
+                self.pareto_front.insert(&individual.solution);
+            }
+            if let Some(best) = &self.best_solution {
+                self.pareto_front.insert(best);
+            }
+        }
+    }
+
+    /// Run the algorithm from an initial solution, kept in the population as is and educated.
+    ///
+    /// The routes of the initial solution must visit every customer exactly once. The routes
+    /// of the best solution inherit the identifiers of the initial routes they overlap most.
+    pub fn run_from(&mut self, mut initial: Solution) -> Result<&Solution, HgsError> {
+        self.seed_rng();
+        self.start_clock();
+        let reference = initial.clone();
+        if self.is_tiny() {
+            self.enumerate_best();
+        } else {
+            self.initialize();
+            self.problem.pins.enforce(&mut initial, &self.problem);
+            initial.evaluate(&self.problem, self.population.penalties.capacity_penalty());
+            self.population
+                .insert_and_update(Individual::new(initial.clone()));
+            self.educate_and_insert(initial);
+            self.best_solution = self.population.get_best_feasible_solution().cloned();
+            self.search()?;
+        }
+        if let Some(best) = self.best_solution.as_mut() {
+            best.inherit_route_ids(&reference);
+        }
+
+        self.best_solution
+            .as_ref()
+            .ok_or(HgsError::NoFeasibleSolution)
+    }
+
+    /// Run the search `n_runs` times from scratch, one after the other, and keep the best
+    /// solution over all runs.
+    ///
+    /// Run `i` is seeded with `seeds[i]`, or a random seed beyond the given ones, which the
+    /// report lists to reproduce a run with `Config::with_seed`. Each run starts from the state
+    /// of a new algorithm with the configuration of this one, keeping only its genetic
+    /// operators, neighborhoods, ruin and recreate, progress callback, trace, and parameter
+    /// handle, so that a run reaches the same solution as a new algorithm with its seed.
+    ///
+    /// Returns `HgsError::NoFeasibleSolution` if no run found a feasible solution.
+    pub fn run_multi(&mut self, n_runs: usize, seeds: &[u64]) -> Result<MultiRunReport, HgsError> {
+        self.seed_rng();
+        let config = self.config.clone();
+        let mut runs = Vec::with_capacity(n_runs);
+        for seed in multi_run::run_seeds(n_runs, seeds) {
+            let mut run_config = config.clone();
+            run_config.seed = Some(seed);
+            self.restart(run_config)?;
+            let solution = self.run().ok().cloned();
+            runs.push(RunReport {
+                seed,
+                solution,
+                statistics: self.statistics(),
+            });
+        }
+        self.config.seed = config.seed;
+        self.seed_rng();
+
+        let report = MultiRunReport::from_runs(runs)?;
+        self.best_solution = Some(report.best.clone());
+        Ok(report)
+    }
+
+    /// Run the search `n_runs` times from scratch on parallel threads, as `run_multi` does.
+    ///
+    /// Each run uses a new algorithm with the problem, configuration, and ruin and recreate of
+    /// this one, and the default genetic operators: custom crossovers, mutations,
+    /// neighborhoods, and progress callbacks are not used.
+    pub fn run_multi_parallel(
+        &mut self,
+        n_runs: usize,
+        seeds: &[u64],
+    ) -> Result<MultiRunReport, HgsError> {
+        self.seed_rng();
+        let seeds = multi_run::run_seeds(n_runs, seeds);
+        let threads = std::thread::available_parallelism().map_or(1, usize::from);
+        let (problem, config, lns) = (&self.problem, &self.config, &self.lns);
+
+        let runs = std::thread::scope(|scope| {
+            let handles: Vec<_> = seeds
+                .chunks(seeds.len().div_ceil(threads).max(1))
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|&seed| {
+                                let mut config = config.clone();
+                                config.seed = Some(seed);
+                                let mut algorithm = HgsAlgorithm::new(problem.clone(), config)?
+                                    .with_lns(lns.clone());
+                                let solution = algorithm.run().ok().cloned();
+                                Ok(RunReport {
+                                    seed,
+                                    solution,
+                                    statistics: algorithm.statistics(),
+                                })
+                            })
+                            .collect::<Result<Vec<_>, HgsError>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                })
+                .collect::<Result<Vec<_>, HgsError>>()
+        })?;
+
+        let report = MultiRunReport::from_runs(runs.into_iter().flatten().collect())?;
+        self.best_solution = Some(report.best.clone());
+        Ok(report)
+    }
+
+    /// Check whether the instance is small enough to be solved by enumeration.
+    fn is_tiny(&self) -> bool {
+        self.problem.customers().count() <= ENUMERATION_MAX_CUSTOMERS
+    }
+
+    /// Solve the instance by enumeration, keeping the optimum as the best solution if it is
+    /// feasible.
+    fn enumerate_best(&mut self) {
+        let capacity_penalty = self.population.penalties.capacity_penalty();
+        let optimum = enumeration::solve_by_enumeration(&self.problem, capacity_penalty);
+        if optimum.violations(&self.problem).is_empty() && self.problem.pins.is_respected(&optimum)
+        {
+            if self.config.track_pareto_front {
+                self.pareto_front.insert(&optimum);
+            }
+            self.best_solution = Some(optimum);
+        }
+        self.record_targets();
+        self.run_time = self.start_time.elapsed();
+    }
+
+    /// Record the current time for the cost targets that the best solution reaches for the
+    /// first time in this run.
+    fn record_targets(&mut self) {
+        let Some(best) = self.best_solution.as_ref().filter(|best| best.is_feasible) else {
+            return;
+        };
+        let elapsed = self.start_time.elapsed();
+        let tolerance = self.problem.tolerance;
+        for (time, &target) in self.target_times.iter_mut().zip(&self.config.cost_targets) {
+            if time.is_none() && !costs::approx_lt(target, best.cost, tolerance) {
+                *time = Some(elapsed);
+            }
+        }
+    }
+
+    /// Seed the random number generator of the thread with the seed of the configuration, or
+    /// draw from `rand::thread_rng` again without one, so that an earlier seeded run does not
+    /// make the next ones deterministic.
+    fn seed_rng(&self) {
+        match self.config.seed {
+            Some(seed) => crate::rng::seed(seed),
+            None => crate::rng::unseed(),
+        }
+    }
+
+    /// Replace the state of the search by that of a new algorithm with `config`, keeping the
+    /// genetic operators, neighborhoods, ruin and recreate, progress callback, trace, and
+    /// parameter handle.
+    fn restart(&mut self, config: Config) -> Result<(), HgsError> {
+        let mut fresh = HgsAlgorithm::new(self.problem.clone(), config)?;
+        std::mem::swap(&mut fresh.crossover, &mut self.crossover);
+        std::mem::swap(&mut fresh.mutation, &mut self.mutation);
+        std::mem::swap(&mut fresh.lns, &mut self.lns);
+        std::mem::swap(
+            &mut fresh.local_search.neighborhoods,
+            &mut self.local_search.neighborhoods,
+        );
+        if self.local_search.trace.is_some() {
+            fresh.local_search.trace = self.local_search.trace.take();
+        }
+        fresh.progress_callback = self.progress_callback.take();
+        // Parameters changed through the handle apply again to the new state
+        fresh.params = self.params.clone();
+        *self = fresh;
+        Ok(())
+    }
+
+    /// Start timing a run, so that educations also stop at the end of the time limit, or of
+    /// the evolution phase with a phase budget.
+    fn start_clock(&mut self) {
         self.start_time = Instant::now();
+        self.first_best = None;
+        self.target_times = vec![None; self.config.cost_targets.len()];
+        self.population_size_sum = 0;
+
+        let deadline = self.evolution_deadline();
+        self.local_search.deadline = deadline;
+        if let Some(local_search) = self.intensification_search.as_mut() {
+            local_search.deadline = deadline;
+        }
+    }
+
+    /// Instant at which the time limit of the current run ends, if it has one.
+    fn deadline(&self) -> Option<Instant> {
+        self.config
+            .time_limit
+            .and_then(|limit| self.start_time.checked_add(limit))
+    }
+
+    /// Instant at which the initialization phase of the current run ends, if it is timed.
+    fn initialization_deadline(&self) -> Option<Instant> {
+        let (budget, limit) = (self.config.phase_budget?, self.config.time_limit?);
+        self.start_time
+            .checked_add(budget.initialization_end(limit))
+    }
 
-        self.initialize();
+    /// Instant at which the evolution of the current run ends, if it is timed.
+    fn evolution_deadline(&self) -> Option<Instant> {
+        match (self.config.phase_budget, self.config.time_limit) {
+            (Some(budget), Some(limit)) => self.start_time.checked_add(budget.evolution_end(limit)),
+            _ => self.deadline(),
+        }
+    }
 
+    /// Evolve the initialized population until the termination criteria are met.
+    fn search(&mut self) -> Result<(), HgsError> {
         while !self.should_terminate() {
+            self.apply_param_updates();
+            if let Some(trace) = self.local_search.trace.as_mut() {
+                trace.iteration = self.iterations;
+            }
+
+            if self.first_best.is_none() {
+                let elapsed = self.start_time.elapsed();
+                self.first_best = self.best_solution.as_ref().map(|best| (best.cost, elapsed));
+            }
+            self.record_targets();
+
             // Select parents
-            let (parent1, parent2) = self.population.select_parents();
+            let (parent1, parent2) = self.population.select_parents()?;
 
             // Apply crossover to produce offspring
-            let mut offspring = self.genetic.crossover(parent1, parent2);
+            let mut offspring = self.crossover.crossover(parent1, parent2);
+
+            // Optionally mutate the offspring tour
+            if self.config.mutation_rate > 0.0 {
+                let mut individual = Individual::new(offspring);
+                self.mutation
+                    .mutate(&mut individual, self.config.mutation_rate);
+                offspring = individual.solution;
+            }
+
+            // Optionally shorten the offspring tour before delimiting its routes
+            if self.config.tour_improvement_passes > 0 {
+                improve_giant_tour(
+                    &mut offspring.giant_tour,
+                    &self.problem,
+                    &self.local_search.customer_neighbors,
+                    self.config.tour_improvement_passes,
+                );
+            }
 
             // Apply split algorithm to determine routes
             Split::split(&mut offspring, &self.problem);
 
-            // Improve the offspring with local search
-            self.local_search.educate(
-                &mut offspring,
-                &self.problem,
-                self.population.capacity_penalty,
-            );
-
-            // Add the offspring to the population
-            let previous_best = self.population.get_best_feasible_solution().cloned();
-            self.population
-                .insert_individual(Individual::new(offspring));
+            // Add the educated offspring (and its repaired version) to the population
+            self.educate_and_insert(offspring);
 
             // Update iteration counters
             self.iterations += 1;
+            self.population.increment_ages();
 
-            // Check if we have a new best solution
-            let current_best = self.population.get_best_feasible_solution().cloned();
+            // Periodically improve the best solution of large instances by decomposition
+            if self.should_decompose() {
+                self.decompose();
+            }
 
-            if let (Some(prev), Some(curr)) = (previous_best, current_best.clone()) {
-                if curr.cost < prev.cost {
-                    self.best_solution = Some(curr);
-                    self.iterations_without_improvement = 0;
-                } else {
-                    self.iterations_without_improvement += 1;
+            // Check if we have a new best solution, cloning it only when it improves
+            let best_cost = self.best_cost();
+            let tolerance = self.problem.tolerance;
+            let new_best = self
+                .population
+                .get_best_feasible_solution()
+                .filter(|curr| costs::approx_lt(curr.cost, best_cost, tolerance));
+            if let Some(best) = new_best {
+                self.best_solution = Some(best.clone());
+                self.iterations_without_improvement = 0;
+                self.recent_improvements += 1;
+                if self.config.elite_intensification {
+                    self.intensify_best();
                 }
+            } else {
+                // Also counted before the first feasible solution, so a run on an instance
+                // without any still stops on stagnation
+                self.iterations_without_improvement += 1;
             }
 
             // Manage population size if needed
             if self.population.should_manage_size() {
                 self.population.select_survivors();
+                self.retain_best();
             }
 
             // Adjust penalty parameters
-            self.population.adjust_penalties();
+            if self
+                .iterations
+                .is_multiple_of(self.config.penalty_adjustment_period.max(1))
+            {
+                self.population.adjust_penalties();
+            }
+
+            // Adapt the granularity to how often the best solution improves
+            if self
+                .iterations
+                .is_multiple_of(self.config.granularity_adjustment_period.max(1))
+            {
+                self.adapt_granularity();
+            }
+            self.population_size_sum += self.population.get_pop_size();
+
+            if self.config.snapshot_period > 0
+                && self.iterations.is_multiple_of(self.config.snapshot_period)
+            {
+                let snapshot = self.population.snapshot();
+                self.population_snapshots.push((self.iterations, snapshot));
+            }
+
+            if self.progress_callback.is_some() {
+                let progress = self.progress();
+                if let Some(callback) = self.progress_callback.as_mut() {
+                    callback(&progress);
+                }
+            }
+        }
+
+        if self.config.phase_budget.is_some() {
+            self.intensify_elite();
+        }
+        if let Some(budget) = self.config.final_polish {
+            self.polish_best(budget);
+        }
+        if let Some(max_increase) = self.config.route_merging {
+            self.merge_best_routes(max_increase);
         }
 
+        self.record_targets();
         self.run_time = self.start_time.elapsed();
-        self.best_solution.as_ref().unwrap()
+        Ok(())
+    }
+
+    /// Gather statistics about the search so far.
+    pub fn statistics(&self) -> SearchStatistics {
+        let best = self.best_solution.as_ref();
+        let tracking_memory_bytes = self.local_search.tracking_memory_usage()
+            + self
+                .intensification_search
+                .as_ref()
+                .map_or(0, LocalSearch::tracking_memory_usage);
+
+        let mut neighborhoods = self.local_search.neighborhood_stats;
+        if let Some(local_search) = &self.intensification_search {
+            neighborhoods.merge(&local_search.neighborhood_stats);
+        }
+
+        SearchStatistics {
+            iterations: self.iterations,
+            runtime: if self.run_time.is_zero() {
+                self.start_time.elapsed()
+            } else {
+                self.run_time
+            },
+            best_solution_cost: self.best_cost(),
+            best_solution_distance: best.map_or(f64::INFINITY, |best| best.distance),
+            best_solution_is_feasible: best.is_some_and(|best| best.is_feasible),
+            best_solution_routes: best.map_or(0, Solution::get_used_route_count),
+            best_solution_utilization: best
+                .and_then(|best| best.min_max_utilization(self.problem.vehicle_capacity)),
+            average_population_size: if self.iterations > 0 {
+                self.population_size_sum / self.iterations as usize
+            } else {
+                self.population.get_pop_size()
+            },
+            final_capacity_penalty: self.population.penalties.capacity_penalty(),
+            tracking_memory_bytes,
+            route_cache: self.local_search.route_cache_stats(),
+            education_cache: self.education_cache.as_ref().map(EducationCache::stats),
+            neighborhoods,
+            time_to_target: self
+                .config
+                .cost_targets
+                .iter()
+                .copied()
+                .zip(self.target_times.iter().copied())
+                .collect(),
+        }
+    }
+
+    /// Compute the progress of the search towards its termination criteria.
+    pub fn progress(&self) -> Progress {
+        let elapsed = self.start_time.elapsed();
+        let best_cost = self.best_solution.as_ref().map(|best| best.cost);
+
+        // Measure improvements from the first feasible solution onwards
+        let improvement_rate = match (self.first_best, best_cost) {
+            (Some((first_cost, first_time)), Some(cost)) if elapsed > first_time => {
+                (first_cost - cost) / (elapsed - first_time).as_secs_f64()
+            }
+            _ => 0.0,
+        };
+
+        let max_stagnation = self.config.max_iterations_without_improvement;
+        Progress {
+            iterations: self.iterations,
+            elapsed,
+            time_fraction: self
+                .config
+                .time_limit
+                .map(|limit| elapsed.as_secs_f64() / limit.as_secs_f64().max(f64::EPSILON)),
+            stagnation_fraction: self.iterations_without_improvement as f64
+                / max_stagnation.max(1) as f64,
+            stagnation_iterations_left: max_stagnation
+                .saturating_sub(self.iterations_without_improvement),
+            best_cost,
+            improvement_rate,
+            neighborhoods: self.local_search.neighborhood_stats,
+        }
+    }
+
+    /// Check whether the best solution should be decomposed at this iteration.
+    fn should_decompose(&self) -> bool {
+        self.config
+            .decomposition_threshold
+            .is_some_and(|threshold| {
+                self.problem.get_customer_count() >= threshold
+                    && self.config.decomposition_period > 0
+                    && self
+                        .iterations
+                        .is_multiple_of(self.config.decomposition_period)
+                    && self.best_solution.is_some()
+            })
+    }
+
+    /// Decompose the best solution, solve its clusters of routes with nested runs, and
+    /// insert the reassembled solution into the population.
+    pub fn decompose(&mut self) {
+        let Some(best) = self.best_solution.as_ref() else {
+            return;
+        };
+
+        let deadline = self.evolution_deadline();
+        let decomposed = Decomposition::decompose(best, &self.problem, &self.config, deadline);
+        debug_assert!(decomposed.has_complete_routes(&self.problem));
+
+        self.educate_and_insert(decomposed);
+    }
+
+    /// Educate an offspring and insert it into the population.
+    ///
+    /// The offspring is first improved with the current capacity penalty. If the result is
+    /// infeasible, it is repaired with probability `repair_probability` by a second education
+    /// using a multiplied penalty (multiplied again if still infeasible), and the repaired
+    /// solution is inserted as well when feasible.
+    ///
+    /// With `ClonePolicy::Reject`, offspring that are clones of an individual of their
+    /// subpopulation are discarded before their education and before their insertion. With
+    /// `ClonePolicy::Mutate`, their giant tour is mutated and split again before education.
+    /// With an education cache, offspring whose giant tour was already educated reuse the
+    /// cached solution instead.
+    pub fn educate_and_insert(&mut self, mut offspring: Solution) {
+        // Spare the education of offspring that are already in the population
+        match self.config.clone_policy {
+            ClonePolicy::Accept => {}
+            _ if !self.population.contains_clone(&offspring) => {}
+            ClonePolicy::Reject => return,
+            ClonePolicy::Mutate => {
+                let rate =
+                    (2.0 / offspring.giant_tour.len().max(1) as f64).max(self.config.mutation_rate);
+                let mut individual = Individual::new(offspring);
+                self.mutation.mutate(&mut individual, rate);
+                offspring = individual.solution;
+                Split::split(&mut offspring, &self.problem);
+            }
+        }
+
+        let capacity_penalty = self.population.penalties.capacity_penalty();
+        let cached = self
+            .education_cache
+            .as_mut()
+            .and_then(|cache| cache.get(&offspring.giant_tour))
+            .cloned();
+        match cached {
+            // The penalty may have changed since the solution was cached
+            Some(educated) => {
+                offspring = educated;
+                offspring.evaluate(&self.problem, capacity_penalty);
+            }
+            None => {
+                let giant_tour = self
+                    .education_cache
+                    .is_some()
+                    .then(|| offspring.giant_tour.clone());
+                offspring = self.educate_offspring(offspring, capacity_penalty);
+                if let (Some(cache), Some(giant_tour)) = (&mut self.education_cache, giant_tour) {
+                    cache.insert(giant_tour, &offspring);
+                }
+            }
+        }
+        self.population.record_offspring(offspring.is_feasible);
+
+        // Education may also lead to an individual of the population
+        if self.config.clone_policy == ClonePolicy::Reject
+            && self.population.contains_clone(&offspring)
+        {
+            return;
+        }
+
+        let repaired = if !offspring.is_feasible
+            && search_rng().gen_bool(self.config.repair_probability.clamp(0.0, 1.0))
+        {
+            let mut repaired = offspring.clone();
+            let multiplier = self.config.repair_penalty_multiplier;
+            let repair_penalty = capacity_penalty * multiplier;
+            // Escalate the penalty once more if the first repair is not enough
+            if self
+                .local_search
+                .repair_with_penalty(&mut repaired, &self.problem, repair_penalty)
+                || self.local_search.repair_with_penalty(
+                    &mut repaired,
+                    &self.problem,
+                    repair_penalty * multiplier,
+                )
+            {
+                Some(repaired)
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
+        if self.config.track_pareto_front {
+            self.pareto_front.insert(&offspring);
+        }
+        self.population
+            .insert_and_update(Individual::new(offspring));
+        if let Some(mut repaired) = repaired {
+            // Evaluate with the population penalty so costs stay comparable
+            repaired.evaluate(&self.problem, capacity_penalty);
+            if self.config.track_pareto_front {
+                self.pareto_front.insert(&repaired);
+            }
+            self.population.insert_and_update(Individual::new(repaired));
+        }
+    }
+
+    /// Educate an offspring, then optionally ruin and recreate it and apply the guided local
+    /// search, returning the best solution found.
+    fn educate_offspring(&mut self, mut offspring: Solution, capacity_penalty: f64) -> Solution {
+        match self.config.education_annealing {
+            Some(annealing) => self.local_search.educate_with_annealing(
+                &mut offspring,
+                &self.problem,
+                capacity_penalty,
+                annealing,
+            ),
+            None => self
+                .local_search
+                .educate(&mut offspring, &self.problem, capacity_penalty),
+        };
+
+        // Optionally ruin and recreate the educated offspring, keeping the result if better
+        if self.config.lns_probability > 0.0
+            && search_rng().gen_bool(self.config.lns_probability.clamp(0.0, 1.0))
+        {
+            let mut candidate = offspring.clone();
+            self.lns
+                .ruin_and_recreate(&mut candidate, &self.problem, capacity_penalty);
+            self.local_search
+                .educate(&mut candidate, &self.problem, capacity_penalty);
+            if costs::approx_lt(candidate.cost, offspring.cost, self.problem.tolerance) {
+                offspring = candidate;
+            }
+        }
+
+        // Once the search stagnates, penalize the long edges of the offspring and educate it on
+        // the augmented distances, then on the true ones, keeping the result if better
+        let stagnating = self.iterations_without_improvement >= self.config.gls_activation;
+        if let Some(guided) = self.guided_local_search.as_mut().filter(|_| stagnating) {
+            guided.penalize(&offspring, &self.problem);
+            if let Some(augmented) = guided.augmented_problem() {
+                let mut candidate = offspring.clone();
+                // Cached route distances would mix the true and augmented distances
+                let route_cache = self.local_search.route_cache.take();
+                self.local_search
+                    .educate(&mut candidate, augmented, capacity_penalty);
+                self.local_search.route_cache = route_cache;
+                self.local_search
+                    .educate(&mut candidate, &self.problem, capacity_penalty);
+                if costs::approx_lt(candidate.cost, offspring.cost, self.problem.tolerance) {
+                    offspring = candidate;
+                }
+            }
+        }
+        offspring
+    }
+
+    /// Run an extended education on the best solution, with a larger granularity and SWAP*
+    /// considering all pairs of routes, and keep the result if it improves the best solution.
+    pub fn intensify_best(&mut self) {
+        let Some(best) = self.best_solution.clone() else {
+            return;
+        };
+        let deadline = self.evolution_deadline();
+        self.intensify(best, deadline);
+    }
+
+    /// Spend the rest of the time limit, after the evolution phase of a phase budget, on
+    /// extended educations of the elite feasible individuals, best first.
+    fn intensify_elite(&mut self) {
+        let Some(deadline) = self.deadline() else {
+            return;
+        };
+        let mut elite: Vec<Solution> = self
+            .population
+            .feasible_individuals
+            .iter()
+            .map(|individual| individual.solution.clone())
+            .collect();
+        elite.sort_by(|a, b| a.cost.total_cmp(&b.cost));
+        elite.truncate(self.config.n_elite.max(1));
+
+        for solution in elite {
+            if Instant::now() >= deadline {
+                break;
+            }
+            self.intensify(solution, Some(deadline));
+        }
+    }
+
+    /// Educate the best solution for at most `budget` with the strongest settings, since it
+    /// may never have been educated with them.
+    ///
+    /// Every built-in neighborhood is explored without SWAP* pruning, each customer has every
+    /// other one as neighbor (up to `FINAL_POLISH_MAX_GRANULARITY`), and as many moves
+    /// leaving the cost unchanged as there are customers are accepted.
+    pub fn polish_best(&mut self, budget: Duration) {
+        let Some(mut solution) = self.best_solution.clone() else {
+            return;
+        };
+        let customers = self.problem.get_customer_count();
+        let granularity = customers
+            .saturating_sub(1)
+            .clamp(1, FINAL_POLISH_MAX_GRANULARITY);
+        let mut local_search = LocalSearch::new(granularity)
+            .with_deadline(Instant::now().checked_add(budget))
+            .with_verify_deltas(self.config.verify_deltas)
+            .with_route_pruning(RoutePruning::None);
+        let capacity_penalty = self.population.penalties.capacity_penalty();
+        local_search.educate_with_plateau(
+            &mut solution,
+            &self.problem,
+            capacity_penalty,
+            customers,
+        );
+        self.keep_if_best(solution);
+    }
+
+    /// Merge pairs of lightly loaded routes of the best solution, accepting a cost increase
+    /// of at most `max_increase` times its cost for each merge.
+    ///
+    /// The merged solution becomes the best solution even if its cost is slightly higher, and
+    /// joins the population.
+    pub fn merge_best_routes(&mut self, max_increase: f64) {
+        let Some(mut solution) = self.best_solution.clone() else {
+            return;
+        };
+        let capacity_penalty = self.population.penalties.capacity_penalty();
+        let merges = merge_routes(
+            &mut solution,
+            &self.problem,
+            &self.local_search.customer_neighbors,
+            capacity_penalty,
+            max_increase,
+        );
+        if merges == 0 {
+            return;
+        }
+
+        if self.config.track_pareto_front {
+            self.pareto_front.insert(&solution);
+        }
+        self.best_solution = Some(solution.clone());
+        self.population.insert_and_update(Individual::new(solution));
+    }
+
+    /// Run an extended education of `solution` until `deadline`, keeping the result if it
+    /// improves the best solution.
+    fn intensify(&mut self, mut solution: Solution, deadline: Option<Instant>) {
+        let config = &self.config;
+        let local_search = self.intensification_search.get_or_insert_with(|| {
+            LocalSearch::new(config.intensification_granularity)
+                .with_verify_deltas(config.verify_deltas)
+                .with_route_pruning(RoutePruning::None)
+        });
+        local_search.deadline = deadline;
+        let capacity_penalty = self.population.penalties.capacity_penalty();
+        local_search.educate(&mut solution, &self.problem, capacity_penalty);
+        self.keep_if_best(solution);
+    }
+
+    /// Make a solution the best one and insert it into the population if it is feasible and
+    /// improves the best solution.
+    fn keep_if_best(&mut self, solution: Solution) {
+        if solution.is_feasible
+            && costs::approx_lt(solution.cost, self.best_cost(), self.problem.tolerance)
+        {
+            if self.config.track_pareto_front {
+                self.pareto_front.insert(&solution);
+            }
+            self.best_solution = Some(solution.clone());
+            self.population.insert_and_update(Individual::new(solution));
+        }
+    }
+
+    /// Reinsert the best solution into the population if it no longer holds it, e.g. after
+    /// survivor selection removed it.
+    fn retain_best(&mut self) {
+        let Some(best) = self.best_solution.as_ref() else {
+            return;
+        };
+        let tolerance = self.problem.tolerance;
+        let held = self
+            .population
+            .get_best_feasible_solution()
+            .is_some_and(|curr| !costs::approx_lt(best.cost, curr.cost, tolerance));
+        if !held {
+            self.population
+                .insert_and_update(Individual::new(best.clone()));
+        }
+    }
+
+    /// Cost of the best feasible solution found so far, infinite before the first one.
+    fn best_cost(&self) -> f64 {
+        self.best_solution
+            .as_ref()
+            .map_or(f64::INFINITY, |best| best.cost)
     }
 
     /// Check if the termination criteria are met.
@@ -133,9 +1153,19 @@ impl HgsAlgorithm {
             return true;
         }
 
-        // Terminate if we've reached the time limit
-        if let Some(time_limit) = self.config.time_limit {
-            if Instant::now().duration_since(self.start_time) >= time_limit {
+        // Terminate if we've reached the time limit, or the end of the evolution phase
+        if self
+            .evolution_deadline()
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return true;
+        }
+
+        // Terminate once the first feasible solution was polished long enough
+        if let (Some(polish), Some(_)) = (self.config.stop_on_feasible, &self.best_solution) {
+            let elapsed = self.start_time.elapsed();
+            let found = self.first_best.map_or(elapsed, |(_, found)| found);
+            if elapsed.saturating_sub(found) >= polish {
                 return true;
             }
         }
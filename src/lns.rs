@@ -0,0 +1,480 @@
+//! Large neighborhood search: ruin and recreate moves for large instances.
+
+use crate::costs;
+use crate::problem::Problem;
+use crate::rng::search_rng;
+use crate::solution::{Route, Solution};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Strategy choosing the customers removed from a solution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RuinOperator {
+    /// Uniformly random customers
+    Random,
+    /// Customers whose removal saves the most distance, with some randomization
+    Worst,
+    /// Customers close to a random seed customer (Shaw removal)
+    Related,
+    /// Strings of consecutive customers from the routes closest to a random seed customer,
+    /// as in SISR
+    String,
+}
+
+impl RuinOperator {
+    /// Every ruin operator.
+    pub const ALL: [RuinOperator; 4] = [
+        RuinOperator::Random,
+        RuinOperator::Worst,
+        RuinOperator::Related,
+        RuinOperator::String,
+    ];
+}
+
+/// Exponent biasing the randomized choices of the worst and related removals towards the
+/// first candidates; the higher, the more deterministic.
+const SELECTION_EXPONENT: i32 = 3;
+
+/// Ruin and recreate: remove part of the customers of a solution and reinsert them by
+/// regret insertion.
+///
+/// Pinned customers and customers of pinned sequences are never removed, and reinsertions
+/// never split a pinned sequence. Insertion costs are estimated from the distance and the
+/// penalized excess load, and the solution is evaluated in full afterwards.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lns {
+    /// Ruin operators, one drawn at random for each ruin
+    pub operators: Vec<RuinOperator>,
+    /// Fraction of the customers removed by each ruin
+    pub removal_fraction: f64,
+    /// Maximum number of customers removed by each ruin
+    pub max_removals: usize,
+    /// Maximum length of the strings removed by the string operator
+    pub max_string_len: usize,
+    /// Number of insertion options compared by the regret insertion, 1 for greedy insertion
+    pub regret: usize,
+}
+
+impl Default for Lns {
+    fn default() -> Self {
+        Lns {
+            operators: RuinOperator::ALL.to_vec(),
+            removal_fraction: 0.15,
+            max_removals: 60,
+            max_string_len: 10,
+            regret: 2,
+        }
+    }
+}
+
+impl Lns {
+    /// Create a ruin and recreate with the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use only the given ruin operators.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `operators` is empty.
+    pub fn with_operators(mut self, operators: &[RuinOperator]) -> Self {
+        assert!(
+            !operators.is_empty(),
+            "at least one ruin operator is needed"
+        );
+        self.operators = operators.to_vec();
+        self
+    }
+
+    /// Set the fraction of the customers removed by each ruin.
+    pub fn with_removal_fraction(mut self, fraction: f64) -> Self {
+        self.removal_fraction = fraction;
+        self
+    }
+
+    /// Set the maximum number of customers removed by each ruin.
+    pub fn with_max_removals(mut self, removals: usize) -> Self {
+        self.max_removals = removals;
+        self
+    }
+
+    /// Set the maximum length of the strings removed by the string operator.
+    pub fn with_max_string_len(mut self, len: usize) -> Self {
+        self.max_string_len = len.max(1);
+        self
+    }
+
+    /// Set the number of insertion options compared by the regret insertion.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `regret` is 0.
+    pub fn with_regret(mut self, regret: usize) -> Self {
+        assert!(regret > 0, "regret insertion compares at least one option");
+        self.regret = regret;
+        self
+    }
+
+    /// Ruin and recreate a solution for a number of iterations, keeping each result that
+    /// lowers its penalized cost.
+    ///
+    /// Returns the number of improvements.
+    pub fn run(
+        &self,
+        solution: &mut Solution,
+        problem: &Problem,
+        iterations: usize,
+        capacity_penalty: f64,
+    ) -> usize {
+        solution.evaluate(problem, capacity_penalty);
+        let mut improvements = 0;
+        for _ in 0..iterations {
+            let mut candidate = solution.clone();
+            self.ruin_and_recreate(&mut candidate, problem, capacity_penalty);
+            if costs::approx_lt(candidate.cost, solution.cost, problem.tolerance) {
+                *solution = candidate;
+                improvements += 1;
+            }
+        }
+        improvements
+    }
+
+    /// Ruin a solution with a random operator and recreate it, whether or not it improves.
+    ///
+    /// Returns the operator used.
+    pub fn ruin_and_recreate(
+        &self,
+        solution: &mut Solution,
+        problem: &Problem,
+        capacity_penalty: f64,
+    ) -> RuinOperator {
+        let operator = *self
+            .operators
+            .choose(&mut search_rng())
+            .unwrap_or(&RuinOperator::Random);
+        let removed = self.ruin(solution, problem, operator, capacity_penalty);
+        self.recreate(solution, problem, removed, capacity_penalty);
+        operator
+    }
+
+    /// Remove customers from a solution with the given operator, dropping the routes left
+    /// empty.
+    ///
+    /// Returns the removed customers, which the solution no longer visits.
+    pub fn ruin(
+        &self,
+        solution: &mut Solution,
+        problem: &Problem,
+        operator: RuinOperator,
+        capacity_penalty: f64,
+    ) -> Vec<usize> {
+        let mut rng = search_rng();
+        let pins = &problem.pins;
+        let candidates: Vec<usize> = solution
+            .routes
+            .iter()
+            .flat_map(|route| route.customers.iter().copied())
+            .filter(|&c| !pins.is_pinned(c) && !pins.is_in_sequence(c))
+            .collect();
+        let count = ((candidates.len() as f64 * self.removal_fraction).ceil() as usize)
+            .min(self.max_removals)
+            .min(candidates.len());
+        if count == 0 {
+            return Vec::new();
+        }
+
+        let removed = match operator {
+            RuinOperator::Random => {
+                let mut removed = candidates;
+                removed.shuffle(&mut rng);
+                removed.truncate(count);
+                removed
+            }
+            RuinOperator::Worst => {
+                solution.evaluate(problem, capacity_penalty);
+                let mut savings: Vec<(usize, f64)> = Vec::with_capacity(candidates.len());
+                for route in &solution.routes {
+                    for (position, &c) in route.customers.iter().enumerate() {
+                        if !pins.is_pinned(c) && !pins.is_in_sequence(c) {
+                            savings.push((c, -Self::removal_delta(route, position, problem)));
+                        }
+                    }
+                }
+                savings.sort_by(|a, b| b.1.total_cmp(&a.1));
+                let order = savings.into_iter().map(|(c, _)| c).collect();
+                Self::biased_selection(order, count, &mut rng)
+            }
+            RuinOperator::Related => {
+                let seed = candidates[rng.gen_range(0..candidates.len())];
+                let mut order = candidates;
+                order.sort_by(|&a, &b| {
+                    problem
+                        .get_distance(seed, a)
+                        .total_cmp(&problem.get_distance(seed, b))
+                });
+                Self::biased_selection(order, count, &mut rng)
+            }
+            RuinOperator::String => self.string_removal(solution, problem, &candidates, count),
+        };
+
+        let mut is_removed = vec![false; problem.nodes.len()];
+        for &c in &removed {
+            is_removed[c] = true;
+        }
+        for route in &mut solution.routes {
+            route.customers.retain(|&c| !is_removed[c]);
+            route.modified = true;
+        }
+        solution.routes.retain(|route| !route.is_empty());
+        solution.evaluate(problem, capacity_penalty);
+        removed
+    }
+
+    /// Insert customers into a solution by regret insertion, opening routes when the fleet
+    /// allows it, and evaluate the result.
+    ///
+    /// The customer inserted next is the one whose best insertion is the most ahead of its
+    /// next `regret - 1` alternatives in other routes, ties going to the cheapest insertion.
+    pub fn recreate(
+        &self,
+        solution: &mut Solution,
+        problem: &Problem,
+        mut pending: Vec<usize>,
+        capacity_penalty: f64,
+    ) {
+        solution.evaluate(problem, capacity_penalty);
+
+        // Cheapest insertion of each pending customer into each route
+        let mut options: Vec<Vec<Option<(f64, usize)>>> = pending
+            .iter()
+            .map(|&c| {
+                solution
+                    .routes
+                    .iter()
+                    .map(|route| Self::best_insertion(route, c, problem, capacity_penalty))
+                    .collect()
+            })
+            .collect();
+
+        while !pending.is_empty() {
+            let can_open_route = problem
+                .max_vehicles
+                .is_none_or(|max| solution.routes.len() < max);
+
+            let mut chosen: Option<(usize, f64, f64)> = None;
+            for (index, &c) in pending.iter().enumerate() {
+                let mut costs: Vec<f64> = options[index]
+                    .iter()
+                    .filter_map(|option| option.map(|(cost, _)| cost))
+                    .collect();
+                if can_open_route {
+                    costs.push(Self::new_route_cost(c, problem, capacity_penalty));
+                }
+                costs.sort_by(f64::total_cmp);
+                let Some(&best) = costs.first() else {
+                    continue;
+                };
+                // Missing alternatives count as infinitely worse, so such customers go first
+                let regret: f64 = (1..self.regret)
+                    .map(|h| {
+                        costs
+                            .get(h)
+                            .map_or(f64::MAX / self.regret as f64, |&x| x - best)
+                    })
+                    .sum();
+                let better = chosen.is_none_or(|(_, best_regret, best_cost)| {
+                    regret > best_regret || (regret == best_regret && best < best_cost)
+                });
+                if better {
+                    chosen = Some((index, regret, best));
+                }
+            }
+
+            // A customer no route can take is appended to the last route, and penalized there
+            let index = chosen.map_or(0, |(index, _, _)| index);
+            let customer = pending.swap_remove(index);
+            let customer_options = options.swap_remove(index);
+            let best_route = customer_options
+                .iter()
+                .enumerate()
+                .filter_map(|(r, option)| option.map(|(cost, position)| (r, cost, position)))
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            let new_route_cost = Self::new_route_cost(customer, problem, capacity_penalty);
+            let can_open_route = problem
+                .max_vehicles
+                .is_none_or(|max| solution.routes.len() < max);
+
+            let r_idx = match best_route {
+                Some((r, cost, position)) if !can_open_route || cost <= new_route_cost => {
+                    solution.routes[r].customers.insert(position, customer);
+                    r
+                }
+                _ if can_open_route || solution.routes.is_empty() => {
+                    let mut route = Route::new();
+                    route.customers.push(customer);
+                    solution.routes.push(route);
+                    for customer_options in &mut options {
+                        customer_options.push(None);
+                    }
+                    solution.routes.len() - 1
+                }
+                _ => {
+                    let r = solution.routes.len() - 1;
+                    solution.routes[r].customers.push(customer);
+                    r
+                }
+            };
+
+            let route = &mut solution.routes[r_idx];
+            route.recalculate(problem);
+            for (index, &c) in pending.iter().enumerate() {
+                options[index][r_idx] = Self::best_insertion(route, c, problem, capacity_penalty);
+            }
+        }
+
+        for route in &mut solution.routes {
+            route.modified = true;
+        }
+        solution.update_giant_tour();
+        solution.evaluate(problem, capacity_penalty);
+    }
+
+    /// Remove strings of consecutive candidates from the routes closest to a seed customer.
+    fn string_removal(
+        &self,
+        solution: &Solution,
+        problem: &Problem,
+        candidates: &[usize],
+        count: usize,
+    ) -> Vec<usize> {
+        let mut rng = search_rng();
+        let mut is_candidate = vec![false; problem.nodes.len()];
+        for &c in candidates {
+            is_candidate[c] = true;
+        }
+        let mut location = vec![None; problem.nodes.len()];
+        for (r, route) in solution.routes.iter().enumerate() {
+            for (position, &c) in route.customers.iter().enumerate() {
+                location[c] = Some((r, position));
+            }
+        }
+
+        let seed = candidates[rng.gen_range(0..candidates.len())];
+        let mut order = candidates.to_vec();
+        order.sort_by(|&a, &b| {
+            problem
+                .get_distance(seed, a)
+                .total_cmp(&problem.get_distance(seed, b))
+        });
+
+        let mut removed = Vec::with_capacity(count);
+        let mut ruined_routes = vec![false; solution.routes.len()];
+        for c in order {
+            if removed.len() >= count {
+                break;
+            }
+            let Some((r, position)) = location[c] else {
+                continue;
+            };
+            if ruined_routes[r] {
+                continue;
+            }
+            ruined_routes[r] = true;
+
+            // A string of random length containing the customer, within its route
+            let customers = &solution.routes[r].customers;
+            let len = rng
+                .gen_range(1..=self.max_string_len.min(customers.len()))
+                .min(count - removed.len());
+            let first = rng.gen_range(position.saturating_sub(len - 1)..=position);
+            let last = (first + len).min(customers.len());
+            removed.extend(
+                customers[first..last]
+                    .iter()
+                    .copied()
+                    .filter(|&other| is_candidate[other]),
+            );
+        }
+        removed
+    }
+
+    /// Pick `count` elements of `order`, preferring the first ones.
+    fn biased_selection(mut order: Vec<usize>, count: usize, rng: &mut impl Rng) -> Vec<usize> {
+        let mut selected = Vec::with_capacity(count);
+        while selected.len() < count && !order.is_empty() {
+            let y: f64 = rng.gen();
+            let index = (y.powi(SELECTION_EXPONENT) * order.len() as f64) as usize;
+            selected.push(order.remove(index.min(order.len() - 1)));
+        }
+        selected
+    }
+
+    /// Distance change of removing the customer at `position` of a route.
+    fn removal_delta(route: &Route, position: usize, problem: &Problem) -> f64 {
+        let depot = problem.depot_index;
+        let customers = &route.customers;
+        let prev = if position > 0 {
+            customers[position - 1]
+        } else {
+            depot
+        };
+        let next = customers.get(position + 1).copied().unwrap_or(depot);
+        let c = customers[position];
+        problem.get_distance(prev, next)
+            - problem.get_distance(prev, c)
+            - problem.get_distance(c, next)
+    }
+
+    /// Cheapest position to insert a customer into a route, with its cost, if any is allowed.
+    fn best_insertion(
+        route: &Route,
+        customer: usize,
+        problem: &Problem,
+        capacity_penalty: f64,
+    ) -> Option<(f64, usize)> {
+        if !problem.allows_stops(route.customers.len() + 1) {
+            return None;
+        }
+        let depot = problem.depot_index;
+        let load_cost = capacity_penalty
+            * (problem.load_excess(
+                route.load + problem.nodes[customer].demand,
+                route.load_variance + problem.demand_variance(customer),
+            ) - problem.load_excess(route.load, route.load_variance));
+
+        let mut best: Option<(f64, usize)> = None;
+        for position in 0..=route.customers.len() {
+            if !problem.pins.allows_insertion(route, position) {
+                continue;
+            }
+            let prev = if position > 0 {
+                route.customers[position - 1]
+            } else {
+                depot
+            };
+            let next = route.customers.get(position).copied().unwrap_or(depot);
+            let cost = problem.get_distance(prev, customer) + problem.get_distance(customer, next)
+                - problem.get_distance(prev, next)
+                + load_cost;
+            if best.is_none_or(|(best_cost, _)| cost < best_cost) {
+                best = Some((cost, position));
+            }
+        }
+        best
+    }
+
+    /// Cost of serving a customer by a new route.
+    fn new_route_cost(customer: usize, problem: &Problem, capacity_penalty: f64) -> f64 {
+        let depot = problem.depot_index;
+        problem.get_distance(depot, customer)
+            + problem.get_distance(customer, depot)
+            + problem.route_fixed_cost
+            + capacity_penalty
+                * problem.load_excess(
+                    problem.nodes[customer].demand,
+                    problem.demand_variance(customer),
+                )
+    }
+}
@@ -0,0 +1,55 @@
+//! Simulated annealing acceptance of worsening moves, for diversification.
+
+use serde::{Deserialize, Serialize};
+
+/// Schedule accepting worsening moves with probability `exp(-delta / temperature)`.
+///
+/// The temperature is multiplied by the cooling rate after every applied move, and once it
+/// falls below the final temperature only improving moves are accepted again, so the
+/// education still ends in a local optimum, possibly worse than the starting solution.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Annealing {
+    /// Temperature at the start of the education, in cost units
+    pub initial_temperature: f64,
+    /// Factor applied to the temperature after each applied move, in (0, 1)
+    pub cooling_rate: f64,
+    /// Temperature below which only improving moves are accepted
+    pub final_temperature: f64,
+}
+
+impl Annealing {
+    /// Create a schedule starting at `initial_temperature` and cooling by `cooling_rate`
+    /// after each move, down to a final temperature of 1% of the initial one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the temperature is not positive or the cooling rate is not in (0, 1).
+    pub fn new(initial_temperature: f64, cooling_rate: f64) -> Self {
+        assert!(
+            initial_temperature > 0.0 && cooling_rate > 0.0 && cooling_rate < 1.0,
+            "annealing needs a positive temperature and a cooling rate in (0, 1)"
+        );
+        Annealing {
+            initial_temperature,
+            cooling_rate,
+            final_temperature: 0.01 * initial_temperature,
+        }
+    }
+
+    /// Set the temperature below which only improving moves are accepted.
+    pub fn with_final_temperature(mut self, temperature: f64) -> Self {
+        self.final_temperature = temperature;
+        self
+    }
+
+    /// Probability of accepting a move worsening the cost by `delta` at a temperature.
+    pub fn acceptance_probability(delta: f64, temperature: f64) -> f64 {
+        if delta <= 0.0 {
+            1.0
+        } else if delta.is_finite() {
+            (-delta / temperature).exp()
+        } else {
+            0.0
+        }
+    }
+}
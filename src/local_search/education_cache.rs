@@ -0,0 +1,122 @@
+//! Cache of educated solutions keyed by giant tour, for offspring that crossover reproduces.
+
+use super::route_cache::RouteCacheStats;
+use crate::solution::Solution;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// Educated solution of a giant tour, with the tour it was computed for.
+#[derive(Debug, Clone)]
+struct CachedEducation {
+    giant_tour: Vec<usize>,
+    educated: Solution,
+    last_used: u64,
+}
+
+/// Least recently used cache from the giant tour of an offspring to its educated solution.
+///
+/// Split is deterministic, so an offspring with a cached giant tour would be educated from
+/// the same routes again. Late in a run crossover often reproduces known tours, and reusing
+/// their education saves most of the time of a generation. The penalties may have changed
+/// since, so the caller re-evaluates the cached solution. When the cache is full, the least
+/// recently used half of its solutions is evicted at once.
+#[derive(Debug, Clone)]
+pub struct EducationCache {
+    capacity: usize,
+    entries: HashMap<u64, CachedEducation>,
+    clock: u64,
+    stats: RouteCacheStats,
+}
+
+impl EducationCache {
+    /// Create an empty cache holding at most `capacity` educated solutions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "education cache capacity must be positive");
+        EducationCache {
+            capacity,
+            entries: HashMap::with_capacity(capacity),
+            clock: 0,
+            stats: RouteCacheStats::default(),
+        }
+    }
+
+    /// Get the educated solution of a giant tour, if it is cached.
+    pub fn get(&mut self, giant_tour: &[usize]) -> Option<&Solution> {
+        let key = Self::tour_hash(giant_tour);
+        self.clock += 1;
+
+        // Hash collisions are told apart by comparing the tours
+        match self
+            .entries
+            .get_mut(&key)
+            .filter(|cached| cached.giant_tour == giant_tour)
+        {
+            Some(cached) => {
+                cached.last_used = self.clock;
+                self.stats.hits += 1;
+                Some(&cached.educated)
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Store the educated solution of a giant tour.
+    pub fn insert(&mut self, giant_tour: Vec<usize>, educated: &Solution) {
+        let key = Self::tour_hash(&giant_tour);
+        self.clock += 1;
+
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            self.evict();
+        }
+        self.entries.insert(
+            key,
+            CachedEducation {
+                giant_tour,
+                educated: educated.clone(),
+                last_used: self.clock,
+            },
+        );
+    }
+
+    /// Get the hit and miss counts since the cache was created or cleared.
+    pub fn stats(&self) -> RouteCacheStats {
+        RouteCacheStats {
+            entries: self.entries.len(),
+            ..self.stats
+        }
+    }
+
+    /// Forget every solution and reset the counts, e.g. after the problem changed.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.stats = RouteCacheStats::default();
+    }
+
+    /// Evict the least recently used half of the solutions.
+    fn evict(&mut self) {
+        let mut stamps: Vec<u64> = self
+            .entries
+            .values()
+            .map(|cached| cached.last_used)
+            .collect();
+        let median = (stamps.len() - 1) / 2;
+        let (_, &mut threshold, _) = stamps.select_nth_unstable(median);
+        self.entries
+            .retain(|_, cached| cached.last_used > threshold);
+    }
+
+    /// Hash a giant tour.
+    fn tour_hash(giant_tour: &[usize]) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        giant_tour.hash(&mut hasher);
+        hasher.finish()
+    }
+}
@@ -0,0 +1,179 @@
+//! Standalone improvement of existing solutions, independent of the genetic algorithm.
+
+use crate::problem::Problem;
+use crate::solution::Solution;
+use std::time::{Duration, Instant};
+
+use super::LocalSearch;
+
+/// Options for improving a single solution with `LocalSearch::improve`.
+#[derive(Debug, Clone)]
+pub struct ImproveOptions {
+    /// Capacity penalty coefficient; derived from the problem when not set
+    pub capacity_penalty: Option<f64>,
+    /// Maximum number of moves applied during the improvement
+    pub max_moves: Option<usize>,
+    /// Maximum duration of the improvement
+    pub max_time: Option<Duration>,
+    /// Repair the solution with a multiplied penalty if it is still infeasible
+    pub repair: bool,
+    /// Factor applied to the capacity penalty during repair
+    pub repair_penalty_multiplier: f64,
+}
+
+impl Default for ImproveOptions {
+    fn default() -> Self {
+        ImproveOptions {
+            capacity_penalty: None,
+            max_moves: None,
+            max_time: None,
+            repair: true,
+            repair_penalty_multiplier: 10.0,
+        }
+    }
+}
+
+impl ImproveOptions {
+    /// Create the default improvement options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the capacity penalty coefficient.
+    pub fn with_capacity_penalty(mut self, penalty: f64) -> Self {
+        self.capacity_penalty = Some(penalty);
+        self
+    }
+
+    /// Limit the number of moves applied during the improvement.
+    pub fn with_max_moves(mut self, max_moves: usize) -> Self {
+        self.max_moves = Some(max_moves);
+        self
+    }
+
+    /// Limit the duration of the improvement.
+    pub fn with_max_time(mut self, duration: Duration) -> Self {
+        self.max_time = Some(duration);
+        self
+    }
+
+    /// Enable or disable the repair of solutions that are still infeasible.
+    pub fn with_repair(mut self, repair: bool) -> Self {
+        self.repair = repair;
+        self
+    }
+
+    /// Set the capacity penalty multiplier used during repair.
+    pub fn with_repair_penalty_multiplier(mut self, multiplier: f64) -> Self {
+        self.repair_penalty_multiplier = multiplier;
+        self
+    }
+
+    /// Get the capacity penalty, derived from the problem when not set.
+    ///
+    /// The default trades one unit of excess load for the longest edge divided by the
+    /// largest demand, bounded to `[0.1, 1000]`.
+    pub fn capacity_penalty(&self, problem: &Problem) -> f64 {
+        self.capacity_penalty.unwrap_or_else(|| {
+            let max_distance = problem.max_distance();
+            let max_demand = problem
+                .customers()
+                .map(|customer| problem.nodes[customer].demand)
+                .fold(0.0_f64, f64::max);
+            if max_demand > 0.0 {
+                (max_distance / max_demand).clamp(0.1, 1000.0)
+            } else {
+                1.0
+            }
+        })
+    }
+}
+
+/// Outcome of `LocalSearch::improve`.
+#[derive(Debug, Clone)]
+pub struct ImprovementReport {
+    /// Penalized cost before the improvement
+    pub initial_cost: f64,
+    /// Penalized cost after the improvement
+    pub final_cost: f64,
+    /// Distance before the improvement
+    pub initial_distance: f64,
+    /// Distance after the improvement
+    pub final_distance: f64,
+    /// Capacity penalty coefficient used for the costs
+    pub capacity_penalty: f64,
+    /// Number of moves applied, including those of the repair
+    pub moves: usize,
+    /// Whether the solution was made feasible by the repair
+    pub repaired: bool,
+    /// Whether the improved solution is feasible
+    pub is_feasible: bool,
+    /// Time spent improving the solution
+    pub elapsed: Duration,
+}
+
+impl LocalSearch {
+    /// Improve an existing solution, such as a hand-made or imported one, without running
+    /// the genetic algorithm.
+    ///
+    /// The routes must visit every customer exactly once. Their loads, distances and the
+    /// giant tour are recomputed, so only the customer sequences need to be filled in.
+    pub fn improve(
+        &mut self,
+        solution: &mut Solution,
+        problem: &Problem,
+        options: &ImproveOptions,
+    ) -> ImprovementReport {
+        let start = Instant::now();
+        let capacity_penalty = options.capacity_penalty(problem);
+
+        solution.evaluate(problem, capacity_penalty);
+        let initial_cost = solution.cost;
+        let initial_distance = solution.distance;
+
+        // The neighbors may have been computed for another problem
+        if self.customer_neighbors.node_count() != problem.nodes.len() {
+            self.preprocess_neighbors(problem);
+        }
+
+        // Apply the budget of the options for this call only
+        let max_moves = std::mem::replace(&mut self.max_moves, options.max_moves);
+        let max_time = std::mem::replace(&mut self.max_time, options.max_time);
+
+        let mut moves = self.educate(solution, problem, capacity_penalty);
+
+        let mut repaired = false;
+        if options.repair && !solution.is_feasible {
+            self.max_moves = options.max_moves.map(|max| max.saturating_sub(moves));
+            self.max_time = options
+                .max_time
+                .map(|max| max.saturating_sub(start.elapsed()));
+            let mut candidate = solution.clone();
+            moves += self.educate(
+                &mut candidate,
+                problem,
+                capacity_penalty * options.repair_penalty_multiplier,
+            );
+            candidate.evaluate(problem, capacity_penalty);
+            if candidate.is_feasible {
+                *solution = candidate;
+                repaired = true;
+            }
+        }
+
+        self.max_moves = max_moves;
+        self.max_time = max_time;
+
+        ImprovementReport {
+            initial_cost,
+            final_cost: solution.cost,
+            initial_distance,
+            final_distance: solution.distance,
+            capacity_penalty,
+            moves,
+            repaired,
+            is_feasible: solution.is_feasible,
+            elapsed: start.elapsed(),
+        }
+    }
+}
@@ -1,32 +1,179 @@
 //! Local search operators for the HGS-CVRP algorithm.
 
+pub mod annealing;
+pub mod education_cache;
+pub mod improve;
+pub mod moves;
+pub mod neighborhood;
+pub mod node_index;
+pub mod pool;
 pub mod relocate;
+pub mod route_cache;
+pub mod stats;
 pub mod swap;
 pub mod swap_star;
+pub mod tabu;
+pub mod timestamps;
+pub mod trace;
 pub mod two_opt;
 pub mod two_opt_star;
 pub mod utils;
 
+use crate::costs::{self, CostDelta};
+use crate::neighbors::NeighborLists;
 use crate::problem::Problem;
-use crate::solution::{Route, Solution};
-use std::collections::HashMap;
+use crate::rng::search_rng;
+use crate::solution::{Route, RouteNode, Solution};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::f64;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+pub use self::annealing::Annealing;
+pub use self::education_cache::EducationCache;
+pub use self::improve::{ImproveOptions, ImprovementReport};
+pub use self::moves::Move;
+pub use self::neighborhood::{AppliedMove, Neighborhood, NeighborhoodContext};
+pub use self::node_index::NodeIndex;
+pub use self::pool::LocalSearchPool;
+pub use self::route_cache::{RouteCache, RouteCacheStats};
+pub use self::stats::{NeighborhoodStats, OperatorStats};
+pub use self::tabu::TabuList;
+use self::timestamps::MoveTimestamps;
+pub use self::trace::{Divergence, SearchTrace, TraceRecord};
 use self::utils::RouteInfo;
 
+/// Strategy used by SWAP* to skip pairs of routes that are unlikely to yield improvements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum RoutePruning {
+    /// Routes whose polar sectors around the depot intersect (needs coordinates)
+    #[default]
+    Sector,
+    /// Routes where a customer of one has a granular neighbor in the other
+    NeighborCount,
+    /// All pairs of routes
+    None,
+}
+
+/// Kind of a local search move, i.e. the neighborhood it belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum MoveKind {
+    /// Move a customer after another one
+    Relocate,
+    /// Exchange two customers
+    Swap,
+    /// Reverse a segment of a route
+    TwoOpt,
+    /// Exchange the ends of two routes
+    TwoOptStar,
+    /// Exchange two customers of different routes, each at its best position
+    SwapStar,
+}
+
+impl MoveKind {
+    /// Every kind of move, in the order the neighborhoods are searched.
+    pub const ALL: [MoveKind; 5] = [
+        MoveKind::Relocate,
+        MoveKind::Swap,
+        MoveKind::TwoOpt,
+        MoveKind::TwoOptStar,
+        MoveKind::SwapStar,
+    ];
+
+    /// Number of kinds of moves.
+    pub const COUNT: usize = Self::ALL.len();
+
+    /// Position of the kind in `ALL`, e.g. to index per-neighborhood arrays.
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Short name of the neighborhood, e.g. `"2-opt*"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            MoveKind::Relocate => "relocate",
+            MoveKind::Swap => "swap",
+            MoveKind::TwoOpt => "2-opt",
+            MoveKind::TwoOptStar => "2-opt*",
+            MoveKind::SwapStar => "SWAP*",
+        }
+    }
+
+    /// Find a kind by the name of its neighborhood.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    /// Neighborhood searching the moves of this kind.
+    fn neighborhood(self) -> NeighborhoodFn {
+        match self {
+            MoveKind::Relocate => LocalSearch::relocate_neighborhood,
+            MoveKind::Swap => LocalSearch::swap_neighborhood,
+            MoveKind::TwoOpt => LocalSearch::two_opt_neighborhood,
+            MoveKind::TwoOptStar => LocalSearch::two_opt_star_neighborhood,
+            MoveKind::SwapStar => LocalSearch::swap_star_neighborhood,
+        }
+    }
+}
+
+impl std::fmt::Display for MoveKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Signature shared by the built-in neighborhoods: returns true if a move was applied.
+type NeighborhoodFn = fn(&mut LocalSearch, &mut Solution, &Problem, f64) -> bool;
+
 /// Manages the local search phase of the HGS-CVRP algorithm.
 pub struct LocalSearch {
     pub granularity: usize,
+    /// Granularity of each neighborhood by move kind, `granularity` if not set
+    pub operator_granularity: [Option<usize>; MoveKind::COUNT],
     /// Timestamp for route modifications, used for efficient move testing
     pub route_timestamps: Vec<usize>,
     /// Timestamp for move testing per customer
-    pub move_timestamps: HashMap<(usize, usize, usize), usize>,
+    pub move_timestamps: MoveTimestamps,
     /// Current move count, used as timestamp
     pub move_count: usize,
     /// SWAP* route polar sectors for pruning
     pub route_sectors: Vec<RouteInfo>,
-    /// Preprocessed neighbors for each customer
-    pub customer_neighbors: HashMap<usize, Vec<usize>>,
+    /// Preprocessed neighbors for each customer, possibly shared with other instances
+    pub customer_neighbors: Arc<NeighborLists>,
+    /// Check every accepted move against a full re-evaluation
+    pub verify_deltas: bool,
+    /// Route pair pruning strategy for SWAP*
+    pub route_pruning: RoutePruning,
+    /// Route node metadata of each customer, for constant-time lookups of their route and
+    /// position
+    pub nodes: NodeIndex,
+    /// Whether `nodes` follows the moves of the current education
+    tracking_nodes: bool,
+    /// Maximum number of moves applied in one education
+    pub max_moves: Option<usize>,
+    /// Maximum duration of one education
+    pub max_time: Option<Duration>,
+    /// Instant at which every education stops, e.g. the end of the time limit of a run
+    pub deadline: Option<Instant>,
+    /// Cache of the metrics of the routes evaluated so far, if enabled
+    pub route_cache: Option<RouteCache>,
+    /// Moves evaluated and accepted by each neighborhood since creation or the last reset
+    pub neighborhood_stats: NeighborhoodStats,
+    /// Neighborhoods explored by `educate`, in order
+    pub neighborhoods: Vec<Box<dyn Neighborhood>>,
+    /// Last move applied by a built-in neighborhood, reported by `explore`
+    last_move: Option<Move>,
+    /// Current temperature and schedule while educating with annealing
+    annealing: Option<(f64, Annealing)>,
+    /// Moves that leave the cost unchanged still accepted while educating with plateau moves
+    plateau_moves: usize,
+    /// Routes customers may not return to yet, if enabled
+    pub tabu: Option<TabuList>,
+    /// Record of the educations and accepted moves, if enabled
+    pub trace: Option<SearchTrace>,
+    /// Largest capacity penalty accepted from a move before evaluating it, if enabled
+    pub load_slack_pruning: Option<f64>,
 }
 
 impl LocalSearch {
@@ -34,18 +181,170 @@ impl LocalSearch {
     pub fn new(granularity: usize) -> Self {
         LocalSearch {
             granularity,
+            operator_granularity: [None; MoveKind::COUNT],
             route_timestamps: Vec::new(),
-            move_timestamps: HashMap::new(),
+            move_timestamps: MoveTimestamps::new(),
             move_count: 0,
             route_sectors: Vec::new(),
-            customer_neighbors: HashMap::new(),
+            customer_neighbors: Arc::default(),
+            verify_deltas: false,
+            route_pruning: RoutePruning::default(),
+            nodes: NodeIndex::default(),
+            tracking_nodes: false,
+            max_moves: None,
+            max_time: None,
+            deadline: None,
+            route_cache: None,
+            neighborhood_stats: NeighborhoodStats::default(),
+            neighborhoods: MoveKind::ALL
+                .into_iter()
+                .map(|kind| Box::new(kind) as Box<dyn Neighborhood>)
+                .collect(),
+            last_move: None,
+            annealing: None,
+            plateau_moves: 0,
+            tabu: None,
+            trace: None,
+            load_slack_pruning: None,
         }
     }
 
-    /// Run local search to improve a solution.
-    pub fn educate(&mut self, solution: &mut Solution, problem: &Problem, capacity_penalty: f64) {
+    /// Explore a custom neighborhood after the current ones in every education.
+    pub fn with_neighborhood(mut self, neighborhood: impl Neighborhood + 'static) -> Self {
+        self.neighborhoods.push(Box::new(neighborhood));
+        self
+    }
+
+    /// Use precomputed neighbor lists, e.g. shared by several local search instances.
+    ///
+    /// The granularity becomes the one the lists were computed for.
+    pub fn with_neighbor_lists(mut self, neighbors: Arc<NeighborLists>) -> Self {
+        self.granularity = neighbors.granularity();
+        self.customer_neighbors = neighbors;
+        self
+    }
+
+    /// Search the moves of a kind among the `granularity` closest customers only, e.g. fewer
+    /// for SWAP*, taken as a prefix of the neighbor lists.
+    ///
+    /// Neighbor lists preprocessed by the local search are long enough for every kind, while
+    /// lists set with `with_neighbor_lists` cap the granularity of each kind at their own.
+    pub fn with_operator_granularity(mut self, kind: MoveKind, granularity: usize) -> Self {
+        self.operator_granularity[kind.index()] = Some(granularity);
+        self
+    }
+
+    /// Get the length of the neighbor lists needed by the granularity of every move kind.
+    pub fn neighbor_list_size(&self) -> usize {
+        self.operator_granularity
+            .iter()
+            .flatten()
+            .fold(self.granularity, |size, &granularity| size.max(granularity))
+    }
+
+    /// Enable or disable verification of move deltas.
+    pub fn with_verify_deltas(mut self, verify: bool) -> Self {
+        self.verify_deltas = verify;
+        self
+    }
+
+    /// Set the route pair pruning strategy for SWAP*.
+    pub fn with_route_pruning(mut self, route_pruning: RoutePruning) -> Self {
+        self.route_pruning = route_pruning;
+        self
+    }
+
+    /// Limit the number of moves applied in one education.
+    pub fn with_max_moves(mut self, max_moves: Option<usize>) -> Self {
+        self.max_moves = max_moves;
+        self
+    }
+
+    /// Limit the duration of one education.
+    pub fn with_max_time(mut self, max_time: Option<Duration>) -> Self {
+        self.max_time = max_time;
+        self
+    }
+
+    /// Stop every education at the given instant, even in the middle of a pass.
+    pub fn with_deadline(mut self, deadline: Option<Instant>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Cache the distance and load of up to `capacity` routes, so that route sequences
+    /// evaluated again are not recomputed.
+    pub fn with_route_cache(mut self, capacity: Option<usize>) -> Self {
+        self.route_cache = capacity.map(RouteCache::new);
+        self
+    }
+
+    /// Forbid customers to move back to the route they left for `tenure` applied moves of an
+    /// education, against cycling when worsening moves are accepted (0 disables it).
+    ///
+    /// Improving moves are restricted too, so an education may end before a local optimum.
+    pub fn with_tabu_tenure(mut self, tenure: usize) -> Self {
+        self.tabu = (tenure > 0).then(|| TabuList::new(tenure));
+        self
+    }
+
+    /// Record every education and the moves of the built-in neighborhoods it applies, to
+    /// replay them with `SearchTrace::replay`.
+    pub fn with_trace(mut self, enabled: bool) -> Self {
+        self.trace = enabled.then(SearchTrace::new);
+        self
+    }
+
+    /// Skip the inter-route moves of Relocate, Swap, and SWAP* whose change of capacity
+    /// penalty alone exceeds `max_penalty`, before evaluating their distances.
+    ///
+    /// The penalty change only depends on the loads of the two routes, so a receiving route
+    /// without the slack for the demand it takes is ruled out in constant time. With
+    /// `max_penalty` at least the distance a move can save, no improving move is skipped.
+    pub fn with_load_slack_pruning(mut self, max_penalty: Option<f64>) -> Self {
+        self.load_slack_pruning = max_penalty;
+        self
+    }
+
+    /// Get the hit and miss counts of the route cache, if enabled.
+    pub fn route_cache_stats(&self) -> Option<RouteCacheStats> {
+        self.route_cache.as_ref().map(RouteCache::stats)
+    }
+
+    /// Forget the neighborhood statistics gathered so far.
+    pub fn reset_neighborhood_stats(&mut self) {
+        self.neighborhood_stats = NeighborhoodStats::default();
+    }
+
+    /// Forget the statistics, last move, and tabu routes of the previous educations, keeping
+    /// the settings, caches, and allocations, e.g. to reuse the instance from a pool.
+    pub fn reset(&mut self) {
+        self.reset_neighborhood_stats();
+        self.last_move = None;
+        if let Some(tabu) = &mut self.tabu {
+            tabu.clear();
+        }
+    }
+
+    /// Run local search to improve a solution and return the number of applied moves.
+    ///
+    /// Education stops at a local optimum, or earlier once `max_moves` or `max_time` is
+    /// reached or the `deadline` has passed.
+    pub fn educate(
+        &mut self,
+        solution: &mut Solution,
+        problem: &Problem,
+        capacity_penalty: f64,
+    ) -> usize {
+        Self::ensure_empty_route(solution, problem);
+        if let Some(trace) = &mut self.trace {
+            trace.start_education(solution, capacity_penalty);
+        }
+
         // Initialize our tracking structures
         self.initialize_tracking(solution);
+        self.nodes.load(solution, problem);
+        self.tracking_nodes = true;
 
         // Preprocess neighbors if not already done
         if self.customer_neighbors.is_empty() {
@@ -53,70 +352,443 @@ impl LocalSearch {
         }
 
         // Initial evaluation
-        solution.evaluate(problem, capacity_penalty);
+        self.evaluate(solution, problem, capacity_penalty);
 
-        // Main local search loop
+        // Main local search loop, with the neighborhoods moved out while they borrow the search
+        let mut neighborhoods = std::mem::take(&mut self.neighborhoods);
+        let start = Instant::now();
+        let mut moves = 0;
         let mut improvement = true;
         while improvement {
             improvement = false;
 
-            // Try all neighborhoods
-            improvement |= self.relocate_neighborhood(solution, problem, capacity_penalty);
-            improvement |= self.swap_neighborhood(solution, problem, capacity_penalty);
-            improvement |= self.two_opt_neighborhood(solution, problem, capacity_penalty);
-            improvement |= self.two_opt_star_neighborhood(solution, problem, capacity_penalty);
-            improvement |= self.swap_star_neighborhood(solution, problem, capacity_penalty);
+            // A move may have filled the empty route
+            if Self::ensure_empty_route(solution, problem) {
+                self.nodes.push_route();
+            }
+
+            // Try all neighborhoods, each applying at most one move per call
+            for neighborhood in neighborhoods.iter_mut() {
+                if self.is_budget_exhausted(moves, start) {
+                    improvement = false;
+                    break;
+                }
+                let applied =
+                    self.explore(neighborhood.as_mut(), solution, problem, capacity_penalty);
+                if applied.is_some() {
+                    improvement = true;
+                    moves += 1;
+                }
+            }
+        }
+        self.neighborhoods = neighborhoods;
+        self.tracking_nodes = false;
+
+        // Drop the routes left empty and keep the giant tour in sync with the routes
+        solution.routes.retain(|route| !route.is_empty());
+        solution.update_giant_tour();
+        self.evaluate(solution, problem, capacity_penalty);
+
+        moves
+    }
+
+    /// Educate a solution accepting worsening moves on an annealing schedule, to escape the
+    /// local optimum of a tightly clustered solution.
+    ///
+    /// Once the schedule has cooled down, only improving moves are accepted as in `educate`.
+    pub fn educate_with_annealing(
+        &mut self,
+        solution: &mut Solution,
+        problem: &Problem,
+        capacity_penalty: f64,
+        annealing: Annealing,
+    ) -> usize {
+        self.annealing = Some((annealing.initial_temperature, annealing));
+        let moves = self.educate(solution, problem, capacity_penalty);
+        self.annealing = None;
+        moves
+    }
+
+    /// Educate a solution also accepting up to `plateau_moves` moves that do not change its
+    /// cost, to drift along a plateau of equal costs towards an improving move.
+    ///
+    /// Only moves without any cost increase are accepted, ignoring the tolerance of the
+    /// problem, so the cost never grows. Once the plateau moves are used up, only improving
+    /// moves are accepted as in `educate`.
+    pub fn educate_with_plateau(
+        &mut self,
+        solution: &mut Solution,
+        problem: &Problem,
+        capacity_penalty: f64,
+        plateau_moves: usize,
+    ) -> usize {
+        self.plateau_moves = plateau_moves;
+        let moves = self.educate(solution, problem, capacity_penalty);
+        self.plateau_moves = 0;
+        moves
+    }
+
+    /// Check whether a move with the given delta should be applied: improving moves always,
+    /// worsening ones with the probability of the current annealing temperature, moves
+    /// leaving the cost unchanged while plateau moves are left, and none if it takes a
+    /// customer back to a tabu route.
+    fn accepts(
+        &self,
+        solution: &Solution,
+        problem: &Problem,
+        candidate: &Move,
+        delta: CostDelta,
+    ) -> bool {
+        let accepted = problem.is_cost_improvement(delta)
+            || (self.plateau_moves > 0 && delta.total() <= 0.0)
+            || self.annealing.is_some_and(|(temperature, _)| {
+                search_rng().gen::<f64>()
+                    < Annealing::acceptance_probability(delta.total(), temperature)
+            });
+        accepted
+            && self.tabu.as_ref().is_none_or(|tabu| {
+                candidate
+                    .transfers(solution)
+                    .into_iter()
+                    .all(|(customer, _, to)| !tabu.is_tabu(customer, to))
+            })
+    }
+
+    /// Search one neighborhood for an improving move, recording the statistics of the
+    /// built-in ones.
+    pub fn explore(
+        &mut self,
+        neighborhood: &mut dyn Neighborhood,
+        solution: &mut Solution,
+        problem: &Problem,
+        capacity_penalty: f64,
+    ) -> Option<AppliedMove> {
+        let searched = Instant::now();
+        let mut ctx = NeighborhoodContext {
+            search: self,
+            capacity_penalty,
+        };
+        let applied = neighborhood.explore(solution, problem, &mut ctx);
+        if self.tracking_nodes && applied.is_some_and(|applied| applied.change.is_none()) {
+            // Custom neighborhoods change the routes without reporting how
+            self.nodes.load(solution, problem);
+        }
+        if let Some(kind) = neighborhood.kind() {
+            let stats = &mut self.neighborhood_stats.operators[kind.index()];
+            stats.time += searched.elapsed();
+            if applied.is_some() {
+                stats.accepted += 1;
+            }
+        }
+        applied
+    }
+
+    /// Evaluate a solution, through the route cache if enabled.
+    fn evaluate(&mut self, solution: &mut Solution, problem: &Problem, capacity_penalty: f64) {
+        match self.route_cache.as_mut() {
+            Some(cache) => solution.evaluate_with(problem, capacity_penalty, |route| {
+                cache.evaluate(route, problem)
+            }),
+            None => solution.evaluate(problem, capacity_penalty),
+        }
+    }
+
+    /// Re-evaluate the routes modified by a move, through the route cache if enabled.
+    fn evaluate_modified(
+        &mut self,
+        solution: &mut Solution,
+        problem: &Problem,
+        capacity_penalty: f64,
+    ) {
+        if let Some(cache) = self.route_cache.as_mut() {
+            for route in solution.routes.iter_mut().filter(|route| route.modified) {
+                cache.evaluate(route, problem);
+            }
         }
+        solution.evaluate_modified(problem, capacity_penalty);
+    }
+
+    /// Check whether the move or time budget of the current education is used up.
+    fn is_budget_exhausted(&self, moves: usize, start: Instant) -> bool {
+        self.max_moves.is_some_and(|max| moves >= max)
+            || self.max_time.is_some_and(|max| start.elapsed() >= max)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Apply an improving move, re-evaluate the routes it modified, and verify its delta.
+    fn apply_move(
+        &mut self,
+        solution: &mut Solution,
+        problem: &Problem,
+        applied: Move,
+        delta: CostDelta,
+        capacity_penalty: f64,
+    ) {
+        let cost_before = solution.cost;
+        if let Some(tabu) = &mut self.tabu {
+            let transfers = applied.transfers(solution);
+            tabu.record(
+                transfers
+                    .into_iter()
+                    .map(|(customer, from, _)| (customer, from)),
+            );
+        }
+        applied.apply(solution);
+
+        let [r1_idx, r2_idx] = applied.routes();
+        if self.tracking_nodes {
+            self.nodes.mark_stale(r1_idx);
+            self.nodes.mark_stale(r2_idx);
+        }
+        self.update_route_timestamp(r1_idx);
+        if r2_idx != r1_idx {
+            self.update_route_timestamp(r2_idx);
+        }
+
+        self.evaluate_modified(solution, problem, capacity_penalty);
+        self.verify_delta(solution, problem, &applied, delta.total(), cost_before);
+        self.verify_nodes(solution, problem, &applied);
+        self.last_move = Some(applied);
+        if let Some(trace) = &mut self.trace {
+            trace.record_move(applied, delta.total());
+        }
+        if self.plateau_moves > 0 && !problem.is_cost_improvement(delta) {
+            self.plateau_moves -= 1;
+        }
+
+        // Cool down, and stop accepting worsening moves below the final temperature
+        if let Some((temperature, annealing)) = self.annealing {
+            let temperature = temperature * annealing.cooling_rate;
+            self.annealing =
+                (temperature >= annealing.final_temperature).then_some((temperature, annealing));
+        }
+    }
+
+    /// Check that the predicted delta of an accepted move matches the actual cost change.
+    /// Only active with `verify_deltas`; the solution must have been re-evaluated already.
+    fn verify_delta(
+        &self,
+        solution: &Solution,
+        problem: &Problem,
+        applied: &Move,
+        predicted: f64,
+        cost_before: f64,
+    ) {
+        if !self.verify_deltas {
+            return;
+        }
+
+        let actual = solution.cost - cost_before;
+        // Allow for rounding errors that grow with the magnitude of the cost
+        let tolerance = problem.tolerance.max(1e-9 * cost_before.abs());
+        if !costs::approx_eq(predicted, actual, tolerance) {
+            // The move is logged in full so it can be replayed on the solution it came from
+            log::error!(
+                "{} move {:?}: predicted delta {}, actual change {}",
+                applied.kind(),
+                applied,
+                predicted,
+                actual
+            );
+            panic!(
+                "{} move on routes {:?} has a wrong delta: predicted {}, actual {}",
+                applied.kind(),
+                applied.routes(),
+                predicted,
+                actual
+            );
+        }
+    }
+
+    /// Check that the node index follows the routes of the solution after a move.
+    /// Only active with `verify_deltas` during an education.
+    fn verify_nodes(&mut self, solution: &Solution, problem: &Problem, applied: &Move) {
+        if !self.verify_deltas || !self.tracking_nodes {
+            return;
+        }
+
+        self.nodes.refresh(solution, problem);
+        let index = solution.node_index(problem);
+        for customer in problem.customers() {
+            assert_eq!(
+                self.nodes.node(customer),
+                index[customer],
+                "{} move {:?} left the node index of customer {} out of sync",
+                applied.kind(),
+                applied,
+                customer
+            );
+        }
+    }
+
+    /// Keep one empty route available so customers can be moved to a new vehicle, and
+    /// return whether a route was added.
+    fn ensure_empty_route(solution: &mut Solution, problem: &Problem) -> bool {
+        let can_open_route = problem
+            .max_vehicles
+            .is_none_or(|max| solution.routes.len() < max);
+        let add = can_open_route && solution.routes.iter().all(|route| !route.is_empty());
+        if add {
+            solution.routes.push(Route::new());
+        }
+        add
     }
 
     /// Preprocess neighbors for all customers based on granularity.
     /// This significantly improves performance by avoiding repeated distance calculations.
+    ///
+    /// The route cache is cleared as well, since it may hold routes of another problem.
     pub fn preprocess_neighbors(&mut self, problem: &Problem) {
-        self.customer_neighbors.clear();
-
-        // For each customer (excluding depot)
-        for i in 0..problem.nodes.len() {
-            if i != problem.depot_index {
-                // Calculate and store its neighbors
-                let neighbors = utils::get_neighbors(i, problem, self.granularity);
-                self.customer_neighbors.insert(i, neighbors);
-            }
+        self.customer_neighbors = Arc::new(problem.neighbor_lists(self.neighbor_list_size()));
+        if let Some(cache) = self.route_cache.as_mut() {
+            cache.clear();
+        }
+    }
+
+    /// Get the `granularity` closest customers of a customer, preprocessing the neighbor
+    /// lists if that was not done yet.
+    pub fn get_customer_neighbors(&mut self, customer: usize, problem: &Problem) -> Vec<usize> {
+        self.neighbors_within(customer, self.granularity, problem)
+    }
+
+    /// Get the neighbors of a customer searched by the moves of a kind, following its
+    /// operator granularity.
+    pub fn operator_neighbors(
+        &mut self,
+        customer: usize,
+        kind: MoveKind,
+        problem: &Problem,
+    ) -> Vec<usize> {
+        let granularity = self.operator_granularity[kind.index()].unwrap_or(self.granularity);
+        self.neighbors_within(customer, granularity, problem)
+    }
+
+    /// Get the first `count` neighbors of a customer, preprocessing them if needed.
+    fn neighbors_within(&mut self, customer: usize, count: usize, problem: &Problem) -> Vec<usize> {
+        if self.customer_neighbors.is_empty() {
+            self.preprocess_neighbors(problem);
+        }
+
+        let neighbors = self.customer_neighbors.get(customer);
+        neighbors[..count.min(neighbors.len())].to_vec()
+    }
+
+    /// Bring the node index up to date with a solution. During an education, only the
+    /// routes modified since the last call are refreshed; otherwise all routes are indexed
+    /// again.
+    pub fn index_nodes(&mut self, solution: &Solution, problem: &Problem) {
+        if self.tracking_nodes && self.nodes.route_count() == solution.routes.len() {
+            self.nodes.refresh(solution, problem);
+        } else {
+            self.nodes.load(solution, problem);
         }
     }
 
+    /// Get the route and position of a customer from the node index.
+    pub fn route_node(&self, customer: usize) -> Option<RouteNode> {
+        self.nodes.node(customer)
+    }
+
     /// Initialize the tracking structures for the local search.
     pub fn initialize_tracking(&mut self, solution: &Solution) {
         self.route_timestamps = vec![0; solution.routes.len()];
         self.move_count = 0;
-        self.move_timestamps.clear();
+        self.move_timestamps.reset(solution.routes.len());
         self.route_sectors.clear();
+        if let Some(tabu) = &mut self.tabu {
+            tabu.clear();
+        }
     }
 
     /// Update timestamps when a route is modified.
     pub fn update_route_timestamp(&mut self, route_idx: usize) {
+        self.ensure_route_timestamp(route_idx);
         self.move_count += 1;
         self.route_timestamps[route_idx] = self.move_count;
     }
 
     /// Check if a move has been tested before and is still valid.
-    pub fn is_move_valid(&mut self, customer: usize, move_type: usize, route_idx: usize) -> bool {
-        let key = (customer, move_type, route_idx);
-        let route_ts = self.route_timestamps[route_idx];
+    pub fn is_move_valid(&mut self, customer: usize, kind: MoveKind, route_idx: usize) -> bool {
+        self.is_move_valid_between(customer, kind, route_idx, route_idx)
+    }
+
+    /// Check if a move of a customer from `from_route` involving `to_route` has been tested
+    /// before and is still valid. The move must be retested once either route is modified.
+    pub fn is_move_valid_between(
+        &mut self,
+        customer: usize,
+        kind: MoveKind,
+        from_route: usize,
+        to_route: usize,
+    ) -> bool {
+        self.ensure_route_timestamp(from_route);
+        self.ensure_route_timestamp(to_route);
+        let route_ts = self.route_timestamps[from_route].max(self.route_timestamps[to_route]);
 
-        if let Some(move_ts) = self.move_timestamps.get(&key) {
+        if let Some(move_ts) = self.move_timestamps.get(customer, kind, to_route) {
             // If the move timestamp is more recent than the route timestamp,
             // this move has been tested after the last route modification
-            if *move_ts > route_ts {
+            if move_ts > route_ts {
                 return false;
             }
         }
 
         // Update the move timestamp
+        self.neighborhood_stats.operators[kind.index()].evaluated += 1;
         self.move_count += 1;
-        self.move_timestamps.insert(key, self.move_count);
+        self.move_timestamps
+            .insert(customer, kind, to_route, self.move_count);
         true
     }
 
+    /// Check whether load slack pruning rules out a move sending `load` and `variance` of
+    /// demand from `from_route` to `to_route`, counting it as pruned.
+    ///
+    /// Negative amounts move demand the other way, e.g. the net demand of a swap.
+    pub fn exceeds_load_slack(
+        &mut self,
+        solution: &Solution,
+        problem: &Problem,
+        kind: MoveKind,
+        (from_route, to_route): (usize, usize),
+        (load, variance): (f64, f64),
+        capacity_penalty: f64,
+    ) -> bool {
+        let Some(max_penalty) = self.load_slack_pruning else {
+            return false;
+        };
+        let (from, to) = (&solution.routes[from_route], &solution.routes[to_route]);
+        let excess_change = problem.load_excess(to.load + load, to.load_variance + variance)
+            - problem.load_excess(to.load, to.load_variance)
+            + problem.load_excess(from.load - load, from.load_variance - variance)
+            - problem.load_excess(from.load, from.load_variance);
+
+        let pruned = capacity_penalty * excess_change > max_penalty;
+        if pruned {
+            self.neighborhood_stats.operators[kind.index()].pruned += 1;
+        }
+        pruned
+    }
+
+    /// Get the memory allocated for the tracking structures, in bytes.
+    ///
+    /// Shared neighbor lists are not included.
+    pub fn tracking_memory_usage(&self) -> usize {
+        self.move_timestamps.memory_usage()
+            + self.route_timestamps.capacity() * std::mem::size_of::<usize>()
+            + self.route_sectors.capacity() * std::mem::size_of::<RouteInfo>()
+            + self.nodes.memory_usage()
+    }
+
+    /// Grow the route timestamps when a neighborhood is used outside of `educate`.
+    fn ensure_route_timestamp(&mut self, route_idx: usize) {
+        if route_idx >= self.route_timestamps.len() {
+            self.route_timestamps.resize(route_idx + 1, 0);
+        }
+    }
+
     /// Try to repair an infeasible solution.
     pub fn repair(&mut self, solution: &mut Solution, problem: &Problem) {
         // Use local search with much higher capacity penalty
@@ -126,7 +798,19 @@ impl LocalSearch {
             1000.0 // High default value if excess is 0
         };
 
+        self.repair_with_penalty(solution, problem, high_penalty);
+    }
+
+    /// Try to repair an infeasible solution with the given capacity penalty.
+    /// Returns whether the repaired solution is feasible.
+    pub fn repair_with_penalty(
+        &mut self,
+        solution: &mut Solution,
+        problem: &Problem,
+        capacity_penalty: f64,
+    ) -> bool {
         // Run local search with high penalty to focus on removing capacity violations
-        self.educate(solution, problem, high_penalty);
+        self.educate(solution, problem, capacity_penalty);
+        solution.is_feasible
     }
 }
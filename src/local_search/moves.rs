@@ -0,0 +1,294 @@
+//! Moves of the built-in neighborhoods as values, to apply, undo, or replay them.
+
+use super::MoveKind;
+use crate::solution::Solution;
+use serde::{Deserialize, Serialize};
+
+/// A move of one of the built-in neighborhoods, by route index and customer position.
+///
+/// Applying a move only changes the customer sequences and marks the routes as modified,
+/// so the solution must be evaluated again afterwards, e.g. with `evaluate_modified`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Move {
+    /// Move the customer at `position` of route `from` to `insert_position` of route `to`,
+    /// counted once the customer has been removed
+    Relocate {
+        from: usize,
+        position: usize,
+        to: usize,
+        insert_position: usize,
+    },
+    /// Exchange the customers at `position1` of `route1` and `position2` of `route2`
+    Swap {
+        route1: usize,
+        position1: usize,
+        route2: usize,
+        position2: usize,
+    },
+    /// Reverse the customers after position `i` up to position `j` of a route
+    TwoOpt { route: usize, i: usize, j: usize },
+    /// Exchange the customers after position `i` of `route1` with those after position `j`
+    /// of `route2`
+    TwoOptStar {
+        route1: usize,
+        route2: usize,
+        i: usize,
+        j: usize,
+    },
+    /// Join the head of `route1` up to `i` with the reversed head of `route2` up to `j`,
+    /// and the reversed tail of `route1` with the tail of `route2`
+    TwoOptStarCross {
+        route1: usize,
+        route2: usize,
+        i: usize,
+        j: usize,
+        /// Number of customers of `route1` after `i`, needed to undo the move
+        tail_len: usize,
+    },
+    /// Remove the customers at `position1` of `route1` and `position2` of `route2`, and
+    /// insert each in the other route, at positions counted after the removals
+    SwapStar {
+        route1: usize,
+        route2: usize,
+        position1: usize,
+        position2: usize,
+        insert_position1: usize,
+        insert_position2: usize,
+    },
+}
+
+impl Move {
+    /// Get the neighborhood the move belongs to.
+    pub fn kind(&self) -> MoveKind {
+        match self {
+            Move::Relocate { .. } => MoveKind::Relocate,
+            Move::Swap { .. } => MoveKind::Swap,
+            Move::TwoOpt { .. } => MoveKind::TwoOpt,
+            Move::TwoOptStar { .. } | Move::TwoOptStarCross { .. } => MoveKind::TwoOptStar,
+            Move::SwapStar { .. } => MoveKind::SwapStar,
+        }
+    }
+
+    /// Get the routes modified by the move, the same one twice for intra-route moves.
+    pub fn routes(&self) -> [usize; 2] {
+        match *self {
+            Move::Relocate { from, to, .. } => [from, to],
+            Move::Swap { route1, route2, .. }
+            | Move::TwoOptStar { route1, route2, .. }
+            | Move::TwoOptStarCross { route1, route2, .. }
+            | Move::SwapStar { route1, route2, .. } => [route1, route2],
+            Move::TwoOpt { route, .. } => [route, route],
+        }
+    }
+
+    /// Get each customer the move would take to another route, with its current and new
+    /// routes, from a solution the move has not been applied to.
+    pub fn transfers(&self, solution: &Solution) -> Vec<(usize, usize, usize)> {
+        let customers = |route: usize| &solution.routes[route].customers;
+        match *self {
+            Move::Relocate {
+                from, position, to, ..
+            } if from != to => vec![(customers(from)[position], from, to)],
+            Move::Swap {
+                route1,
+                position1: p1,
+                route2,
+                position2: p2,
+            }
+            | Move::SwapStar {
+                route1,
+                route2,
+                position1: p1,
+                position2: p2,
+                ..
+            } if route1 != route2 => vec![
+                (customers(route1)[p1], route1, route2),
+                (customers(route2)[p2], route2, route1),
+            ],
+            Move::TwoOptStar {
+                route1,
+                route2,
+                i,
+                j,
+            } => {
+                let tail1 = customers(route1)[i + 1..]
+                    .iter()
+                    .map(|&c| (c, route1, route2));
+                let tail2 = customers(route2)[j + 1..]
+                    .iter()
+                    .map(|&c| (c, route2, route1));
+                tail1.chain(tail2).collect()
+            }
+            Move::TwoOptStarCross {
+                route1,
+                route2,
+                i,
+                j,
+                ..
+            } => {
+                let tail1 = customers(route1)[i + 1..]
+                    .iter()
+                    .map(|&c| (c, route1, route2));
+                let head2 = customers(route2)[..=j].iter().map(|&c| (c, route2, route1));
+                tail1.chain(head2).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Apply the move to the customer sequences of a solution.
+    pub fn apply(&self, solution: &mut Solution) {
+        match *self {
+            Move::Relocate {
+                from,
+                position,
+                to,
+                insert_position,
+            } => {
+                let customer = solution.routes[from].customers.remove(position);
+                solution.routes[to]
+                    .customers
+                    .insert(insert_position, customer);
+            }
+            Move::Swap { .. } | Move::TwoOpt { .. } | Move::TwoOptStar { .. } => {
+                self.exchange(solution)
+            }
+            Move::TwoOptStarCross {
+                route1,
+                route2,
+                i,
+                j,
+                ..
+            } => {
+                let r1_tail: Vec<usize> =
+                    solution.routes[route1].customers.drain(i + 1..).collect();
+                let r2_tail: Vec<usize> =
+                    solution.routes[route2].customers.drain(j + 1..).collect();
+                let r2_head: Vec<usize> = solution.routes[route2].customers.drain(..).collect();
+
+                // route1 becomes its head followed by the reversed head of route2
+                solution.routes[route1]
+                    .customers
+                    .extend(r2_head.into_iter().rev());
+
+                // route2 becomes the reversed tail of route1 followed by its own tail
+                solution.routes[route2]
+                    .customers
+                    .extend(r1_tail.into_iter().rev());
+                solution.routes[route2].customers.extend(r2_tail);
+            }
+            Move::SwapStar {
+                route1,
+                route2,
+                position1,
+                position2,
+                insert_position1,
+                insert_position2,
+            } => {
+                let customer1 = solution.routes[route1].customers.remove(position1);
+                let customer2 = solution.routes[route2].customers.remove(position2);
+                solution.routes[route1]
+                    .customers
+                    .insert(insert_position1, customer2);
+                solution.routes[route2]
+                    .customers
+                    .insert(insert_position2, customer1);
+            }
+        }
+        self.mark_modified(solution);
+    }
+
+    /// Restore the customer sequences a solution had before the move was applied to it.
+    pub fn undo(&self, solution: &mut Solution) {
+        match *self {
+            Move::Relocate {
+                from,
+                position,
+                to,
+                insert_position,
+            } => {
+                let customer = solution.routes[to].customers.remove(insert_position);
+                solution.routes[from].customers.insert(position, customer);
+            }
+            Move::Swap { .. } | Move::TwoOpt { .. } | Move::TwoOptStar { .. } => {
+                self.exchange(solution)
+            }
+            Move::TwoOptStarCross {
+                route1,
+                route2,
+                i,
+                tail_len,
+                ..
+            } => {
+                let r2_head: Vec<usize> =
+                    solution.routes[route1].customers.drain(i + 1..).collect();
+                let r1_tail: Vec<usize> = solution.routes[route2]
+                    .customers
+                    .drain(..tail_len)
+                    .collect();
+                solution.routes[route1]
+                    .customers
+                    .extend(r1_tail.into_iter().rev());
+                let r2_tail = std::mem::take(&mut solution.routes[route2].customers);
+                let customers = &mut solution.routes[route2].customers;
+                customers.extend(r2_head.into_iter().rev());
+                customers.extend(r2_tail);
+            }
+            Move::SwapStar {
+                route1,
+                route2,
+                position1,
+                position2,
+                insert_position1,
+                insert_position2,
+            } => {
+                let customer2 = solution.routes[route1].customers.remove(insert_position1);
+                let customer1 = solution.routes[route2].customers.remove(insert_position2);
+                solution.routes[route1]
+                    .customers
+                    .insert(position1, customer1);
+                solution.routes[route2]
+                    .customers
+                    .insert(position2, customer2);
+            }
+        }
+        self.mark_modified(solution);
+    }
+
+    /// Apply one of the moves that are their own inverse.
+    fn exchange(&self, solution: &mut Solution) {
+        match *self {
+            Move::Swap {
+                route1,
+                position1,
+                route2,
+                position2,
+            } => {
+                let customer = solution.routes[route1].customers[position1];
+                solution.routes[route1].customers[position1] =
+                    solution.routes[route2].customers[position2];
+                solution.routes[route2].customers[position2] = customer;
+            }
+            Move::TwoOpt { route, i, j } => solution.routes[route].customers[i + 1..=j].reverse(),
+            Move::TwoOptStar {
+                route1,
+                route2,
+                i,
+                j,
+            } => {
+                let mut r1_tail = solution.routes[route1].customers.split_off(i + 1);
+                let mut r2_tail = solution.routes[route2].customers.split_off(j + 1);
+                solution.routes[route1].customers.append(&mut r2_tail);
+                solution.routes[route2].customers.append(&mut r1_tail);
+            }
+            _ => unreachable!("{self:?} is not its own inverse"),
+        }
+    }
+
+    /// Mark the routes of the move as modified.
+    fn mark_modified(&self, solution: &mut Solution) {
+        for route in self.routes() {
+            solution.routes[route].modified = true;
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//! Extension point for the neighborhoods searched by the local search.
+
+use super::{LocalSearch, Move, MoveKind};
+use crate::problem::Problem;
+use crate::solution::Solution;
+
+/// State of the local search available to a neighborhood while it is explored.
+pub struct NeighborhoodContext<'a> {
+    /// The local search, for its neighbor lists and move timestamps
+    pub search: &'a mut LocalSearch,
+    /// Penalty per unit of excess load
+    pub capacity_penalty: f64,
+}
+
+/// Move applied by a neighborhood.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AppliedMove {
+    /// Kind of the move, `None` for custom neighborhoods
+    pub kind: Option<MoveKind>,
+    /// Change of the penalized cost of the solution
+    pub delta: f64,
+    /// The move itself, to undo or replay it, `None` for custom neighborhoods
+    pub change: Option<Move>,
+}
+
+/// A neighborhood of the local search, explored in turn with the others until none improves.
+///
+/// Custom neighborhoods can be registered with `LocalSearch::with_neighborhood`.
+pub trait Neighborhood {
+    /// Name of the neighborhood, for logs.
+    fn name(&self) -> &str;
+
+    /// Kind of the moves, whose statistics are recorded, `None` for custom neighborhoods.
+    fn kind(&self) -> Option<MoveKind> {
+        None
+    }
+
+    /// Apply at most one improving move to the solution.
+    ///
+    /// An applied move must leave the solution evaluated, and mark the routes it modified
+    /// with `LocalSearch::update_route_timestamp` so the other neighborhoods retest them.
+    fn explore(
+        &mut self,
+        solution: &mut Solution,
+        problem: &Problem,
+        ctx: &mut NeighborhoodContext<'_>,
+    ) -> Option<AppliedMove>;
+}
+
+impl Neighborhood for MoveKind {
+    fn name(&self) -> &str {
+        MoveKind::name(*self)
+    }
+
+    fn kind(&self) -> Option<MoveKind> {
+        Some(*self)
+    }
+
+    fn explore(
+        &mut self,
+        solution: &mut Solution,
+        problem: &Problem,
+        ctx: &mut NeighborhoodContext<'_>,
+    ) -> Option<AppliedMove> {
+        let cost = solution.cost;
+        ctx.search.last_move = None;
+        let applied = self.neighborhood()(ctx.search, solution, problem, ctx.capacity_penalty);
+        applied.then(|| AppliedMove {
+            kind: Some(*self),
+            delta: solution.cost - cost,
+            change: ctx.search.last_move.take(),
+        })
+    }
+}
@@ -0,0 +1,162 @@
+//! Route node metadata of each customer, looked up by the neighborhoods during education.
+
+use crate::problem::Problem;
+use crate::solution::{RouteNode, Solution};
+
+/// Route, position, predecessor, successor, and cumulative load and distance of each
+/// customer of a solution, as the reference HGS implementation keeps per node.
+///
+/// This is lookup metadata only: routes keep their customers in `Route::customers`, where
+/// moves are applied and evaluated. `mark_stale` flags the routes a move changed, and
+/// `refresh` indexes them again from their `Vec`s in time linear in their lengths, like their
+/// re-evaluation, so no move application or delta becomes constant time.
+#[derive(Debug, Clone, Default)]
+pub struct NodeIndex {
+    /// Metadata of each node, `None` for the nodes no route visits
+    nodes: Vec<Option<RouteNode>>,
+    /// Load of the route up to and including each customer
+    cumulative_load: Vec<f64>,
+    /// Distance travelled from the depot to each customer
+    cumulative_distance: Vec<f64>,
+    /// Whether each route changed since the last refresh
+    stale_routes: Vec<bool>,
+    /// Routes changed since the last refresh
+    stale: Vec<usize>,
+}
+
+impl NodeIndex {
+    /// Index the routes of a solution.
+    pub fn new(solution: &Solution, problem: &Problem) -> Self {
+        let mut index = NodeIndex::default();
+        index.load(solution, problem);
+        index
+    }
+
+    /// Index the routes of a solution in place of the current ones, keeping the allocations.
+    pub fn load(&mut self, solution: &Solution, problem: &Problem) {
+        let nodes = problem.nodes.len();
+        self.nodes.clear();
+        self.nodes.resize(nodes, None);
+        self.cumulative_load.clear();
+        self.cumulative_load.resize(nodes, 0.0);
+        self.cumulative_distance.clear();
+        self.cumulative_distance.resize(nodes, 0.0);
+        self.stale_routes.clear();
+        self.stale.clear();
+
+        for _ in &solution.routes {
+            let route = self.push_route();
+            self.mark_stale(route);
+        }
+        self.refresh(solution, problem);
+    }
+
+    /// Add an empty route after the others and return its index.
+    pub fn push_route(&mut self) -> usize {
+        self.stale_routes.push(false);
+        self.stale_routes.len() - 1
+    }
+
+    /// Get the number of routes.
+    pub fn route_count(&self) -> usize {
+        self.stale_routes.len()
+    }
+
+    /// Mark a route as changed in the solution since the last refresh.
+    pub fn mark_stale(&mut self, route: usize) {
+        if !self.stale_routes[route] {
+            self.stale_routes[route] = true;
+            self.stale.push(route);
+        }
+    }
+
+    /// Check whether a route was changed since the last `refresh`.
+    pub fn is_stale(&self, route: usize) -> bool {
+        self.stale_routes[route]
+    }
+
+    /// Index the stale routes again from the customers of the solution, in time linear in
+    /// their lengths.
+    pub fn refresh(&mut self, solution: &Solution, problem: &Problem) {
+        while let Some(route) = self.stale.pop() {
+            let customers = &solution.routes[route].customers;
+            let mut load = 0.0;
+            let mut distance = 0.0;
+            let mut previous = problem.depot_index;
+            for (position, &customer) in customers.iter().enumerate() {
+                load += problem.nodes[customer].demand;
+                distance += problem.get_distance(previous, customer);
+                self.nodes[customer] = Some(RouteNode {
+                    route,
+                    position,
+                    predecessor: previous,
+                    successor: customers
+                        .get(position + 1)
+                        .copied()
+                        .unwrap_or(problem.depot_index),
+                });
+                self.cumulative_load[customer] = load;
+                self.cumulative_distance[customer] = distance;
+                previous = customer;
+            }
+            self.stale_routes[route] = false;
+        }
+    }
+
+    /// Get the route, position, predecessor, and successor of a customer, `None` if no
+    /// route visits it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the route of the customer is stale.
+    pub fn node(&self, customer: usize) -> Option<RouteNode> {
+        let node = self.nodes.get(customer).copied().flatten()?;
+        self.assert_fresh(node.route);
+        Some(node)
+    }
+
+    /// Get the load of the route of a customer up to and including the customer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no route visits the customer, or if its route is stale.
+    pub fn cumulative_load(&self, customer: usize) -> f64 {
+        self.assert_routed(customer);
+        self.cumulative_load[customer]
+    }
+
+    /// Get the distance travelled from the depot to a customer along its route.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no route visits the customer, or if its route is stale.
+    pub fn cumulative_distance(&self, customer: usize) -> f64 {
+        self.assert_routed(customer);
+        self.cumulative_distance[customer]
+    }
+
+    /// Get the memory allocated for the index, in bytes.
+    pub fn memory_usage(&self) -> usize {
+        self.nodes.capacity() * std::mem::size_of::<Option<RouteNode>>()
+            + (self.cumulative_load.capacity() + self.cumulative_distance.capacity())
+                * std::mem::size_of::<f64>()
+            + self.stale_routes.capacity() * std::mem::size_of::<bool>()
+            + self.stale.capacity() * std::mem::size_of::<usize>()
+    }
+
+    /// Check that a route was not changed since the last refresh.
+    fn assert_fresh(&self, route: usize) {
+        assert!(
+            !self.stale_routes[route],
+            "route {route} changed since the last refresh"
+        );
+    }
+
+    /// Check that a route visits a customer and was not changed since the last refresh.
+    fn assert_routed(&self, customer: usize) {
+        assert!(
+            self.node(customer).is_some(),
+            "no route visits customer {customer}"
+        );
+    }
+}
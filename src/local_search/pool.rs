@@ -0,0 +1,116 @@
+//! Pool of local search instances reused across educations, kept per thread.
+
+use super::LocalSearch;
+use crate::neighbors::NeighborLists;
+use crate::problem::Problem;
+use crate::solution::Solution;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Identifier of the next pool, keying its idle instances on each thread
+static NEXT_POOL_ID: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    /// Idle instances of each pool on the current thread
+    static IDLE: RefCell<HashMap<u64, Vec<LocalSearch>>> = RefCell::new(HashMap::new());
+}
+
+/// Builder of the instances of a pool from the shared neighbor lists
+type Builder = dyn Fn(Arc<NeighborLists>) -> LocalSearch + Send + Sync;
+
+/// Local search instances handed out to the educations of any thread, sharing one set of
+/// immutable neighbor lists.
+///
+/// Building a local search allocates its timestamps, node index, and caches, so instances
+/// are kept after use and handed out again on the same thread, since custom neighborhoods
+/// may not be sent between threads. The pool is shared by reference, e.g. with scoped
+/// threads. Dropping it frees the idle instances of the current thread; those of other
+/// threads are freed when the threads end.
+pub struct LocalSearchPool {
+    id: u64,
+    neighbors: Arc<NeighborLists>,
+    build: Arc<Builder>,
+}
+
+impl LocalSearchPool {
+    /// Create a pool of default local search instances using `neighbors`.
+    pub fn new(neighbors: Arc<NeighborLists>) -> Self {
+        LocalSearchPool {
+            id: NEXT_POOL_ID.fetch_add(1, Ordering::Relaxed),
+            neighbors,
+            build: Arc::new(|neighbors: Arc<NeighborLists>| {
+                LocalSearch::new(neighbors.granularity()).with_neighbor_lists(neighbors)
+            }),
+        }
+    }
+
+    /// Build the instances with `build`, e.g. to set their pruning or cache, from the shared
+    /// neighbor lists.
+    pub fn with_builder(
+        self,
+        build: impl Fn(Arc<NeighborLists>) -> LocalSearch + Send + Sync + 'static,
+    ) -> Self {
+        // A new identifier leaves the instances of the previous builder behind
+        LocalSearchPool {
+            id: NEXT_POOL_ID.fetch_add(1, Ordering::Relaxed),
+            neighbors: Arc::clone(&self.neighbors),
+            build: Arc::new(build),
+        }
+    }
+
+    /// Get the neighbor lists shared by the instances.
+    pub fn neighbors(&self) -> &Arc<NeighborLists> {
+        &self.neighbors
+    }
+
+    /// Run `f` with an instance of the current thread, built if none is idle.
+    ///
+    /// The instance is handed out with `LocalSearch::reset` applied, so its statistics only
+    /// cover what `f` does, and is kept for later calls unless `f` panics. Calls may nest,
+    /// each using its own instance.
+    pub fn with_local_search<T>(&self, f: impl FnOnce(&mut LocalSearch) -> T) -> T {
+        let idle = IDLE.with(|idle| {
+            idle.borrow_mut()
+                .get_mut(&self.id)
+                .and_then(|instances| instances.pop())
+        });
+        let mut local_search = idle.unwrap_or_else(|| (self.build)(Arc::clone(&self.neighbors)));
+        local_search.reset();
+
+        let result = f(&mut local_search);
+        IDLE.with(|idle| {
+            idle.borrow_mut()
+                .entry(self.id)
+                .or_default()
+                .push(local_search)
+        });
+        result
+    }
+
+    /// Educate a solution with an instance of the current thread, returning the number of
+    /// applied moves.
+    pub fn educate(
+        &self,
+        solution: &mut Solution,
+        problem: &Problem,
+        capacity_penalty: f64,
+    ) -> usize {
+        self.with_local_search(|local_search| {
+            local_search.educate(solution, problem, capacity_penalty)
+        })
+    }
+
+    /// Get the number of idle instances of the current thread.
+    pub fn idle_count(&self) -> usize {
+        IDLE.with(|idle| idle.borrow().get(&self.id).map_or(0, Vec::len))
+    }
+}
+
+impl Drop for LocalSearchPool {
+    fn drop(&mut self) {
+        // The thread-local storage may already be gone while the thread ends
+        let _ = IDLE.try_with(|idle| idle.borrow_mut().remove(&self.id));
+    }
+}
@@ -1,15 +1,15 @@
 //! Relocate neighborhood for local search.
 
+use crate::costs::CostDelta;
+use crate::objective::RouteChange;
 use crate::problem::Problem;
+use crate::rng::search_rng;
 use crate::solution::Solution;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
 use std::f64;
 
-use super::utils::{
-    self, calculate_insertion_cost, calculate_removal_cost, find_route_for_customer, get_neighbors,
-};
-use super::LocalSearch;
+use super::utils::{calculate_insertion_cost, calculate_removal_cost};
+use super::{LocalSearch, Move, MoveKind};
 
 impl LocalSearch {
     /// Implement the Relocate neighborhood.
@@ -20,7 +20,8 @@ impl LocalSearch {
         capacity_penalty: f64,
     ) -> bool {
         let mut improvement = false;
-        let mut rng = thread_rng();
+        let mut rng = search_rng();
+        self.index_nodes(solution, problem);
 
         // Consider all routes
         let routes = solution.routes.len();
@@ -42,29 +43,46 @@ impl LocalSearch {
             for &c_pos in &customer_indices {
                 let customer = r1.customers[c_pos];
 
+                // Pinned customers stay in their route
+                if problem.pins.is_pinned(customer) {
+                    continue;
+                }
+
                 // Use preprocessed neighbors instead of recalculating them
-                let maybe_neighbors = self.customer_neighbors.get(&customer);
-                let neighbors = match maybe_neighbors {
-                    Some(neighbors) => neighbors.clone(),
-                    None => {
-                        let neighbors = utils::get_neighbors(customer, problem, self.granularity);
-                        self.customer_neighbors.insert(customer, neighbors.clone());
-                        neighbors
+                let neighbors = self.operator_neighbors(customer, MoveKind::Relocate, problem);
+
+                // Candidate routes: those of the neighbors, then an empty route if available
+                let mut candidate_routes: Vec<usize> = neighbors
+                    .iter()
+                    .filter_map(|&neighbor| self.route_node(neighbor).map(|node| node.route))
+                    .collect();
+                if r1.customers.len() > 1 {
+                    if let Some(empty_idx) = solution.routes.iter().position(|r| r.is_empty()) {
+                        candidate_routes.push(empty_idx);
                     }
-                };
-
-                for &neighbor in &neighbors {
-                    // Find which route contains this neighbor
-                    let r2_idx = find_route_for_customer(solution, neighbor);
+                }
 
-                    if r2_idx.is_none() || r2_idx.unwrap() == r1_idx {
+                for r2_idx in candidate_routes {
+                    if r2_idx == r1_idx {
+                        continue;
+                    }
+                    let demand = (
+                        problem.nodes[customer].demand,
+                        problem.demand_variance(customer),
+                    );
+                    if self.exceeds_load_slack(
+                        solution,
+                        problem,
+                        MoveKind::Relocate,
+                        (r1_idx, r2_idx),
+                        demand,
+                        capacity_penalty,
+                    ) {
                         continue;
                     }
-
-                    let r2_idx = r2_idx.unwrap();
 
                     // Check if this move has been tested before
-                    if !self.is_move_valid(customer, 0, r2_idx) {
+                    if !self.is_move_valid_between(customer, MoveKind::Relocate, r1_idx, r2_idx) {
                         continue;
                     }
 
@@ -78,17 +96,14 @@ impl LocalSearch {
                         capacity_penalty,
                     );
 
-                    if delta < -1e-6 {
-                        // Apply the move
-                        self.apply_relocate(solution, r1_idx, r2_idx, c_pos, insert_pos);
-
-                        // Update route timestamps
-                        self.update_route_timestamp(r1_idx);
-                        self.update_route_timestamp(r2_idx);
-
-                        // Re-evaluate the solution
-                        solution.evaluate(problem, capacity_penalty);
-
+                    let relocate = Move::Relocate {
+                        from: r1_idx,
+                        position: c_pos,
+                        to: r2_idx,
+                        insert_position: insert_pos,
+                    };
+                    if self.accepts(solution, problem, &relocate, delta) {
+                        self.apply_move(solution, problem, relocate, delta, capacity_penalty);
                         improvement = true;
                         break;
                     }
@@ -116,11 +131,24 @@ impl LocalSearch {
         r2_idx: usize,
         c_pos: usize,
         capacity_penalty: f64,
-    ) -> (f64, usize) {
+    ) -> (CostDelta, usize) {
         let r1 = &solution.routes[r1_idx];
         let r2 = &solution.routes[r2_idx];
         let customer = r1.customers[c_pos];
         let demand = problem.nodes[customer].demand;
+        let variance = problem.demand_variance(customer);
+
+        // The target route must have room for one more stop
+        if !problem.allows_stops(r2.customers.len() + 1) {
+            return (CostDelta::INFEASIBLE, 0);
+        }
+
+        // Opening an empty route adds its fixed cost
+        let opening_cost = if r2.is_empty() {
+            problem.route_fixed_cost
+        } else {
+            0.0
+        };
 
         // Check if removing customer from r1 makes it empty
         if r1.customers.len() == 1 {
@@ -128,29 +156,54 @@ impl LocalSearch {
             let r1_cost = r1.distance;
 
             // Find best insertion position in r2
-            let mut best_delta = f64::INFINITY;
+            let mut best_delta = CostDelta::INFEASIBLE;
             let mut best_pos = 0;
 
             for i in 0..=r2.customers.len() {
+                if !problem.pins.allows_insertion(r2, i) {
+                    continue;
+                }
                 let new_distance = calculate_insertion_cost(r2, customer, i, problem);
                 let delta = new_distance - r2.distance;
 
                 // Include capacity considerations
                 let new_load = r2.load + demand;
-                let original_excess = (r2.load - problem.vehicle_capacity).max(0.0);
-                let new_excess = (new_load - problem.vehicle_capacity).max(0.0);
+                let original_excess = problem.load_excess(r2.load, r2.load_variance);
+                let new_excess = problem.load_excess(new_load, r2.load_variance + variance);
                 let penalty_delta = capacity_penalty * (new_excess - original_excess);
 
-                let total_delta = delta + penalty_delta;
-
-                if total_delta < best_delta {
+                let objective_delta = problem.objective_delta(
+                    solution,
+                    &[
+                        RouteChange {
+                            route: r1_idx,
+                            distance: 0.0,
+                            load: 0.0,
+                        },
+                        RouteChange {
+                            route: r2_idx,
+                            distance: new_distance,
+                            load: new_load,
+                        },
+                    ],
+                );
+
+                let total_delta = CostDelta::new(delta, penalty_delta + objective_delta);
+
+                if total_delta.total() < best_delta.total() {
                     best_delta = total_delta;
                     best_pos = i;
                 }
             }
 
-            // Total cost change
-            return (best_delta - r1_cost, best_pos);
+            // Total cost change, saving the fixed cost of the emptied route
+            return (
+                CostDelta::new(
+                    best_delta.distance - r1_cost,
+                    best_delta.other + opening_cost - problem.route_fixed_cost,
+                ),
+                best_pos,
+            );
         }
 
         // Normal case: r1 will still have customers after removal
@@ -158,53 +211,55 @@ impl LocalSearch {
 
         // Check load changes for r1
         let r1_new_load = r1.load - demand;
-        let r1_original_excess = (r1.load - problem.vehicle_capacity).max(0.0);
-        let r1_new_excess = (r1_new_load - problem.vehicle_capacity).max(0.0);
+        let r1_original_excess = problem.load_excess(r1.load, r1.load_variance);
+        let r1_new_excess = problem.load_excess(r1_new_load, r1.load_variance - variance);
         let r1_penalty_delta = capacity_penalty * (r1_new_excess - r1_original_excess);
 
         // Find best insertion position in r2
-        let mut best_delta = f64::INFINITY;
+        let mut best_delta = CostDelta::INFEASIBLE;
         let mut best_pos = 0;
 
         for i in 0..=r2.customers.len() {
-            let r2_delta = calculate_insertion_cost(r2, customer, i, problem) - r2.distance;
+            if !problem.pins.allows_insertion(r2, i) {
+                continue;
+            }
+            let r2_new_distance = calculate_insertion_cost(r2, customer, i, problem);
+            let r2_delta = r2_new_distance - r2.distance;
 
             // Check load changes for r2
             let r2_new_load = r2.load + demand;
-            let r2_original_excess = (r2.load - problem.vehicle_capacity).max(0.0);
-            let r2_new_excess = (r2_new_load - problem.vehicle_capacity).max(0.0);
+            let r2_original_excess = problem.load_excess(r2.load, r2.load_variance);
+            let r2_new_excess = problem.load_excess(r2_new_load, r2.load_variance + variance);
             let r2_penalty_delta = capacity_penalty * (r2_new_excess - r2_original_excess);
 
-            let total_delta = r1_delta + r1_penalty_delta + r2_delta + r2_penalty_delta;
-
-            if total_delta < best_delta {
+            let objective_delta = problem.objective_delta(
+                solution,
+                &[
+                    RouteChange {
+                        route: r1_idx,
+                        distance: r1.distance + r1_delta,
+                        load: r1_new_load,
+                    },
+                    RouteChange {
+                        route: r2_idx,
+                        distance: r2_new_distance,
+                        load: r2_new_load,
+                    },
+                ],
+            );
+
+            let total_delta = CostDelta::new(
+                r1_delta + r2_delta,
+                r1_penalty_delta + r2_penalty_delta + objective_delta,
+            );
+
+            if total_delta.total() < best_delta.total() {
                 best_delta = total_delta;
                 best_pos = i;
             }
         }
 
+        best_delta.other += opening_cost;
         (best_delta, best_pos)
     }
-
-    /// Apply a Relocate move.
-    fn apply_relocate(
-        &mut self,
-        solution: &mut Solution,
-        r1_idx: usize,
-        r2_idx: usize,
-        c_pos: usize,
-        insert_pos: usize,
-    ) {
-        // Remove customer from r1
-        let customer = solution.routes[r1_idx].customers.remove(c_pos);
-
-        // Insert into r2
-        solution.routes[r2_idx]
-            .customers
-            .insert(insert_pos, customer);
-
-        // Mark routes as modified
-        solution.routes[r1_idx].modified = true;
-        solution.routes[r2_idx].modified = true;
-    }
 }
@@ -0,0 +1,143 @@
+//! Cache of route metrics keyed by customer sequence, for routes that reappear during the search.
+
+use crate::problem::Problem;
+use crate::solution::Route;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// Hit and miss counts of a route cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RouteCacheStats {
+    /// Routes whose metrics were found in the cache
+    pub hits: usize,
+    /// Routes whose metrics had to be computed
+    pub misses: usize,
+    /// Routes currently held by the cache
+    pub entries: usize,
+}
+
+impl RouteCacheStats {
+    /// Fraction of the lookups answered by the cache, 0 before the first lookup.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// Metrics of an evaluated route, with the sequence they were computed for.
+#[derive(Debug, Clone)]
+struct CachedRoute {
+    customers: Vec<usize>,
+    distance: f64,
+    load: f64,
+    load_variance: f64,
+    last_used: u64,
+}
+
+/// Least recently used cache from the customer sequence of a route to its distance and load.
+///
+/// A hit leaves the route exactly as `calculate_distance` and `calculate_load` would, its
+/// prefix sums to be computed when needed. When the cache is full, the least recently used
+/// half of its routes is evicted at once.
+#[derive(Debug, Clone)]
+pub struct RouteCache {
+    capacity: usize,
+    entries: HashMap<u64, CachedRoute>,
+    clock: u64,
+    stats: RouteCacheStats,
+}
+
+impl RouteCache {
+    /// Create an empty cache holding at most `capacity` routes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "route cache capacity must be positive");
+        RouteCache {
+            capacity,
+            entries: HashMap::with_capacity(capacity),
+            clock: 0,
+            stats: RouteCacheStats::default(),
+        }
+    }
+
+    /// Compute the distance and load of a route, reusing those of an identical sequence.
+    pub fn evaluate(&mut self, route: &mut Route, problem: &Problem) {
+        let key = Self::sequence_hash(&route.customers);
+        self.clock += 1;
+
+        // Hash collisions are told apart by comparing the sequences
+        if let Some(cached) = self
+            .entries
+            .get_mut(&key)
+            .filter(|cached| cached.customers == route.customers)
+        {
+            cached.last_used = self.clock;
+            route.distance = cached.distance;
+            route.load = cached.load;
+            route.load_variance = cached.load_variance;
+            route.modified = false;
+            route.prefix_sums_valid = false;
+            self.stats.hits += 1;
+            return;
+        }
+
+        route.recalculate(problem);
+        self.stats.misses += 1;
+
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            self.evict();
+        }
+        self.entries.insert(
+            key,
+            CachedRoute {
+                customers: route.customers.clone(),
+                distance: route.distance,
+                load: route.load,
+                load_variance: route.load_variance,
+                last_used: self.clock,
+            },
+        );
+    }
+
+    /// Get the hit and miss counts since the cache was created or cleared.
+    pub fn stats(&self) -> RouteCacheStats {
+        RouteCacheStats {
+            entries: self.entries.len(),
+            ..self.stats
+        }
+    }
+
+    /// Forget every route and reset the counts, e.g. after the problem changed.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.stats = RouteCacheStats::default();
+    }
+
+    /// Evict the least recently used half of the routes.
+    fn evict(&mut self) {
+        let mut stamps: Vec<u64> = self
+            .entries
+            .values()
+            .map(|cached| cached.last_used)
+            .collect();
+        let median = (stamps.len() - 1) / 2;
+        let (_, &mut threshold, _) = stamps.select_nth_unstable(median);
+        self.entries
+            .retain(|_, cached| cached.last_used > threshold);
+    }
+
+    /// Hash a customer sequence.
+    fn sequence_hash(customers: &[usize]) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        customers.hash(&mut hasher);
+        hasher.finish()
+    }
+}
@@ -0,0 +1,90 @@
+//! Counts of the moves evaluated and accepted by each neighborhood, for tuning.
+
+use super::MoveKind;
+use std::fmt;
+use std::ops::Index;
+use std::time::Duration;
+
+/// Moves evaluated and accepted by one neighborhood, and the time spent in it.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct OperatorStats {
+    /// Moves evaluated, not counting those skipped by the move timestamps
+    pub evaluated: usize,
+    /// Moves applied
+    pub accepted: usize,
+    /// Moves skipped by load slack pruning before their evaluation
+    pub pruned: usize,
+    /// Time spent searching the neighborhood
+    pub time: Duration,
+}
+
+impl OperatorStats {
+    /// Fraction of the evaluated moves that were applied, 0 before the first evaluation.
+    pub fn acceptance_rate(&self) -> f64 {
+        if self.evaluated == 0 {
+            0.0
+        } else {
+            self.accepted as f64 / self.evaluated as f64
+        }
+    }
+}
+
+/// Statistics of every neighborhood of a local search, indexed by move kind.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct NeighborhoodStats {
+    pub operators: [OperatorStats; MoveKind::COUNT],
+}
+
+impl NeighborhoodStats {
+    /// Get the statistics of a neighborhood by name, e.g. `"2-opt*"`.
+    pub fn get(&self, name: &str) -> Option<&OperatorStats> {
+        MoveKind::from_name(name).map(|kind| &self[kind])
+    }
+
+    /// Iterate over the neighborhoods with their move kinds.
+    pub fn iter(&self) -> impl Iterator<Item = (MoveKind, &OperatorStats)> {
+        MoveKind::ALL.into_iter().zip(self.operators.iter())
+    }
+
+    /// Add the statistics of another local search, e.g. the intensification one.
+    pub fn merge(&mut self, other: &NeighborhoodStats) {
+        for (stats, other) in self.operators.iter_mut().zip(other.operators.iter()) {
+            stats.evaluated += other.evaluated;
+            stats.accepted += other.accepted;
+            stats.pruned += other.pruned;
+            stats.time += other.time;
+        }
+    }
+
+    /// Total number of moves applied by all neighborhoods.
+    pub fn total_accepted(&self) -> usize {
+        self.operators.iter().map(|stats| stats.accepted).sum()
+    }
+}
+
+impl Index<MoveKind> for NeighborhoodStats {
+    type Output = OperatorStats;
+
+    fn index(&self, kind: MoveKind) -> &OperatorStats {
+        &self.operators[kind.index()]
+    }
+}
+
+impl fmt::Display for NeighborhoodStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, (kind, stats)) in self.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(
+                f,
+                "{:<8} {:>10} evaluated {:>8} accepted {:>9.3}s",
+                kind.name(),
+                stats.evaluated,
+                stats.accepted,
+                stats.time.as_secs_f64()
+            )?;
+        }
+        Ok(())
+    }
+}
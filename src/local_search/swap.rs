@@ -1,13 +1,15 @@
 //! Swap neighborhood for local search.
 
+use crate::costs::CostDelta;
+use crate::objective::RouteChange;
 use crate::problem::Problem;
+use crate::rng::search_rng;
 use crate::solution::{Route, Solution};
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use std::collections::HashMap;
 use std::f64;
 
-use super::utils::{find_route_for_customer, get_neighbors};
-use super::LocalSearch;
+use super::{LocalSearch, Move, MoveKind};
 
 impl LocalSearch {
     /// Implement the Swap neighborhood.
@@ -18,7 +20,8 @@ impl LocalSearch {
         capacity_penalty: f64,
     ) -> bool {
         let mut improvement = false;
-        let mut rng = thread_rng();
+        let mut rng = search_rng();
+        self.index_nodes(solution, problem);
 
         // Consider all pairs of routes
         let routes = solution.routes.len();
@@ -40,25 +43,56 @@ impl LocalSearch {
             for &c1_pos in &customer_indices {
                 let customer1 = r1.customers[c1_pos];
 
-                // Use preprocessed neighbors
-                let neighbors = &self.customer_neighbors[&customer1].clone();
-
-                for &neighbor in neighbors {
-                    // Find which route contains this neighbor
-                    let r2_idx = find_route_for_customer(solution, neighbor);
+                // Pinned customers stay in their route
+                if problem.pins.is_pinned(customer1) {
+                    continue;
+                }
 
-                    if r2_idx.is_none() || r2_idx.unwrap() == r1_idx {
+                // Use preprocessed neighbors
+                let neighbors = self.operator_neighbors(customer1, MoveKind::Swap, problem);
+
+                // Routes checked for this customer, so that every neighbor in a valid route is tried
+                let mut checked_routes: HashMap<usize, bool> = HashMap::new();
+
+                for &neighbor in &neighbors {
+                    // Find the route and position of this neighbor
+                    let (r2_idx, c2_pos) = match self.route_node(neighbor) {
+                        Some(node) if node.route != r1_idx => (node.route, node.position),
+                        _ => continue,
+                    };
+                    if problem.pins.is_pinned(neighbor) {
+                        continue;
+                    }
+                    let net_demand = (
+                        problem.nodes[customer1].demand - problem.nodes[neighbor].demand,
+                        problem.demand_variance(customer1) - problem.demand_variance(neighbor),
+                    );
+                    if self.exceeds_load_slack(
+                        solution,
+                        problem,
+                        MoveKind::Swap,
+                        (r1_idx, r2_idx),
+                        net_demand,
+                        capacity_penalty,
+                    ) {
                         continue;
                     }
-
-                    let r2_idx = r2_idx.unwrap();
-                    let r2 = &solution.routes[r2_idx];
-
-                    // Find the position of the neighbor in r2
-                    let c2_pos = r2.customers.iter().position(|&c| c == neighbor).unwrap();
 
                     // Check if this move has been tested before
-                    if !self.is_move_valid(customer1, 1, r2_idx) {
+                    let valid = match checked_routes.get(&r2_idx) {
+                        Some(&valid) => valid,
+                        None => {
+                            let valid = self.is_move_valid_between(
+                                customer1,
+                                MoveKind::Swap,
+                                r1_idx,
+                                r2_idx,
+                            );
+                            checked_routes.insert(r2_idx, valid);
+                            valid
+                        }
+                    };
+                    if !valid {
                         continue;
                     }
 
@@ -73,17 +107,14 @@ impl LocalSearch {
                         capacity_penalty,
                     );
 
-                    if delta < -1e-6 {
-                        // Apply the move
-                        self.apply_swap(solution, r1_idx, r2_idx, c1_pos, c2_pos);
-
-                        // Update route timestamps
-                        self.update_route_timestamp(r1_idx);
-                        self.update_route_timestamp(r2_idx);
-
-                        // Re-evaluate the solution
-                        solution.evaluate(problem, capacity_penalty);
-
+                    let swap = Move::Swap {
+                        route1: r1_idx,
+                        position1: c1_pos,
+                        route2: r2_idx,
+                        position2: c2_pos,
+                    };
+                    if self.accepts(solution, problem, &swap, delta) {
+                        self.apply_move(solution, problem, swap, delta, capacity_penalty);
                         improvement = true;
                         break;
                     }
@@ -103,6 +134,7 @@ impl LocalSearch {
     }
 
     /// Evaluate a Swap move (exchanging two customers between different routes).
+    #[allow(clippy::too_many_arguments)]
     fn evaluate_swap(
         &self,
         solution: &Solution,
@@ -112,7 +144,7 @@ impl LocalSearch {
         c1_pos: usize,
         c2_pos: usize,
         capacity_penalty: f64,
-    ) -> f64 {
+    ) -> CostDelta {
         let r1 = &solution.routes[r1_idx];
         let r2 = &solution.routes[r2_idx];
         let customer1 = r1.customers[c1_pos];
@@ -130,16 +162,37 @@ impl LocalSearch {
         let r2_new_load = r2.load - demand2 + demand1;
 
         // Calculate capacity penalties
-        let r1_original_excess = (r1.load - problem.vehicle_capacity).max(0.0);
-        let r1_new_excess = (r1_new_load - problem.vehicle_capacity).max(0.0);
+        let variance_change =
+            problem.demand_variance(customer2) - problem.demand_variance(customer1);
+        let r1_original_excess = problem.load_excess(r1.load, r1.load_variance);
+        let r1_new_excess = problem.load_excess(r1_new_load, r1.load_variance + variance_change);
         let r1_penalty_delta = capacity_penalty * (r1_new_excess - r1_original_excess);
 
-        let r2_original_excess = (r2.load - problem.vehicle_capacity).max(0.0);
-        let r2_new_excess = (r2_new_load - problem.vehicle_capacity).max(0.0);
+        let r2_original_excess = problem.load_excess(r2.load, r2.load_variance);
+        let r2_new_excess = problem.load_excess(r2_new_load, r2.load_variance - variance_change);
         let r2_penalty_delta = capacity_penalty * (r2_new_excess - r2_original_excess);
 
+        let objective_delta = problem.objective_delta(
+            solution,
+            &[
+                RouteChange {
+                    route: r1_idx,
+                    distance: r1.distance + r1_delta,
+                    load: r1_new_load,
+                },
+                RouteChange {
+                    route: r2_idx,
+                    distance: r2.distance + r2_delta,
+                    load: r2_new_load,
+                },
+            ],
+        );
+
         // Total cost change
-        r1_delta + r2_delta + r1_penalty_delta + r2_penalty_delta
+        CostDelta::new(
+            r1_delta + r2_delta,
+            r1_penalty_delta + r2_penalty_delta + objective_delta,
+        )
     }
 
     /// Calculate the cost change when swapping a customer in a route.
@@ -153,11 +206,6 @@ impl LocalSearch {
         let customers = &route.customers;
         let n = customers.len();
 
-        if n <= 1 {
-            // Special case: single-customer route
-            return 0.0;
-        }
-
         // Calculate the change in distance
         let prev_idx = if pos > 0 {
             customers[pos - 1]
@@ -182,23 +230,4 @@ impl LocalSearch {
         // The delta is the new distance minus the old distance
         new_distance - old_distance
     }
-
-    /// Apply a Swap move.
-    fn apply_swap(
-        &mut self,
-        solution: &mut Solution,
-        r1_idx: usize,
-        r2_idx: usize,
-        c1_pos: usize,
-        c2_pos: usize,
-    ) {
-        // Swap the customers
-        let temp = solution.routes[r1_idx].customers[c1_pos];
-        solution.routes[r1_idx].customers[c1_pos] = solution.routes[r2_idx].customers[c2_pos];
-        solution.routes[r2_idx].customers[c2_pos] = temp;
-
-        // Mark routes as modified
-        solution.routes[r1_idx].modified = true;
-        solution.routes[r2_idx].modified = true;
-    }
 }
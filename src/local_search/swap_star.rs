@@ -1,13 +1,14 @@
 //! SWAP* neighborhood for local search.
 
+use crate::costs::CostDelta;
+use crate::objective::RouteChange;
 use crate::problem::Problem;
 use crate::solution::{Route, Solution};
+use std::collections::HashSet;
 use std::f64;
 
-use super::utils::{
-    calculate_insertion_cost, calculate_removal_cost, create_temp_route, get_neighbors, RouteInfo,
-};
-use super::LocalSearch;
+use super::utils::{calculate_insertion_cost, calculate_replacement_cost, RouteInfo};
+use super::{LocalSearch, Move, MoveKind, RoutePruning};
 
 impl LocalSearch {
     /// Implement the SWAP* neighborhood.
@@ -19,10 +20,10 @@ impl LocalSearch {
     ) -> bool {
         let mut improvement = false;
 
-        // First, calculate route polar sectors for pruning
-        self.calculate_route_sectors(solution, problem);
+        // First, determine which route pairs are worth considering
+        let candidates = self.route_pair_candidates(solution, problem);
 
-        // Consider all pairs of routes with intersecting polar sectors
+        // Consider all pairs of routes that pass the pruning strategy
         for r1_idx in 0..solution.routes.len() {
             let r1 = &solution.routes[r1_idx].clone();
 
@@ -30,8 +31,6 @@ impl LocalSearch {
                 continue;
             }
 
-            let r1_info = &self.route_sectors[r1_idx].clone();
-
             for r2_idx in 0..solution.routes.len() {
                 if r1_idx == r2_idx {
                     continue;
@@ -43,17 +42,19 @@ impl LocalSearch {
                     continue;
                 }
 
-                let r2_info = &self.route_sectors[r2_idx];
-
-                // Check if route sectors intersect (for pruning)
-                if !self.sectors_intersect(r1_info, r2_info) {
+                if !candidates.contains(&(r1_idx, r2_idx)) {
                     continue;
                 }
 
                 // For each customer in r1, try to swap with each customer in r2
                 for (pos1, &customer1) in r1.customers.iter().enumerate() {
+                    // Pinned customers stay in their route
+                    if problem.pins.is_pinned(customer1) {
+                        continue;
+                    }
+
                     // Check if this move has been tested before
-                    if !self.is_move_valid(customer1, 4, r2_idx) {
+                    if !self.is_move_valid_between(customer1, MoveKind::SwapStar, r1_idx, r2_idx) {
                         continue;
                     }
 
@@ -62,6 +63,24 @@ impl LocalSearch {
                         self.find_top_insertion_positions(customer1, r2, problem);
 
                     for (pos2, &customer2) in r2.customers.iter().enumerate() {
+                        if problem.pins.is_pinned(customer2) {
+                            continue;
+                        }
+                        let net_demand = (
+                            problem.nodes[customer1].demand - problem.nodes[customer2].demand,
+                            problem.demand_variance(customer1) - problem.demand_variance(customer2),
+                        );
+                        if self.exceeds_load_slack(
+                            solution,
+                            problem,
+                            MoveKind::SwapStar,
+                            (r1_idx, r2_idx),
+                            net_demand,
+                            capacity_penalty,
+                        ) {
+                            continue;
+                        }
+
                         // Preprocess: find top 3 best insertion positions in r1 for customer2
                         let top_positions_in_r1 =
                             self.find_top_insertion_positions(customer2, r1, problem);
@@ -79,19 +98,16 @@ impl LocalSearch {
                             capacity_penalty,
                         );
 
-                        if delta < -1e-6 {
-                            // Apply the move
-                            self.apply_swap_star(
-                                solution, r1_idx, r2_idx, pos1, pos2, best_pos1, best_pos2,
-                            );
-
-                            // Update route timestamps
-                            self.update_route_timestamp(r1_idx);
-                            self.update_route_timestamp(r2_idx);
-
-                            // Re-evaluate the solution
-                            solution.evaluate(problem, capacity_penalty);
-
+                        let swap_star = Move::SwapStar {
+                            route1: r1_idx,
+                            route2: r2_idx,
+                            position1: pos1,
+                            position2: pos2,
+                            insert_position1: best_pos1,
+                            insert_position2: best_pos2,
+                        };
+                        if self.accepts(solution, problem, &swap_star, delta) {
+                            self.apply_move(solution, problem, swap_star, delta, capacity_penalty);
                             improvement = true;
                             break;
                         }
@@ -115,6 +131,55 @@ impl LocalSearch {
         improvement
     }
 
+    /// Determine the pairs of routes that SWAP* should consider.
+    ///
+    /// Sector pruning needs coordinates and falls back to neighbor-count pruning without them.
+    fn route_pair_candidates(
+        &mut self,
+        solution: &Solution,
+        problem: &Problem,
+    ) -> HashSet<(usize, usize)> {
+        let routes = solution.routes.len();
+        let all_pairs = (0..routes).flat_map(|r1| (0..routes).map(move |r2| (r1, r2)));
+
+        let pruning = match self.route_pruning {
+            RoutePruning::Sector if !problem.has_coordinates => RoutePruning::NeighborCount,
+            pruning => pruning,
+        };
+
+        match pruning {
+            RoutePruning::Sector => {
+                self.calculate_route_sectors(solution, problem);
+                all_pairs
+                    .filter(|&(r1, r2)| {
+                        self.sectors_intersect(&self.route_sectors[r1], &self.route_sectors[r2])
+                    })
+                    .collect()
+            }
+            RoutePruning::NeighborCount => {
+                self.index_nodes(solution, problem);
+
+                // Routes are close if a customer of one has a granular neighbor in the other
+                let mut candidates = HashSet::new();
+                for (r1, route) in solution.routes.iter().enumerate() {
+                    for &customer in &route.customers {
+                        for neighbor in
+                            self.operator_neighbors(customer, MoveKind::SwapStar, problem)
+                        {
+                            if let Some(node) = self.route_node(neighbor) {
+                                let r2 = node.route;
+                                candidates.insert((r1, r2));
+                                candidates.insert((r2, r1));
+                            }
+                        }
+                    }
+                }
+                candidates
+            }
+            RoutePruning::None => all_pairs.collect(),
+        }
+    }
+
     /// Calculate route polar sectors for SWAP* pruning.
     fn calculate_route_sectors(&mut self, solution: &Solution, problem: &Problem) {
         self.route_sectors.clear();
@@ -124,11 +189,11 @@ impl LocalSearch {
 
         for (r_idx, route) in solution.routes.iter().enumerate() {
             if route.is_empty() {
-                // Empty routes have no sector
+                // Empty routes have no sector, marked by bounds in the wrong order
                 self.route_sectors.push(RouteInfo {
                     route_index: r_idx,
-                    polar_min: 0.0,
-                    polar_max: 0.0,
+                    polar_min: f64::INFINITY,
+                    polar_max: f64::NEG_INFINITY,
                 });
                 continue;
             }
@@ -136,12 +201,19 @@ impl LocalSearch {
             // Calculate polar angle for each customer in the route
             let mut min_angle = std::f64::consts::PI * 2.0;
             let mut max_angle: f64 = 0.0;
+            let mut has_angle = false;
 
             for &customer in &route.customers {
                 let node = &problem.nodes[customer];
                 let dx = node.x - depot_x;
                 let dy = node.y - depot_y;
 
+                // Customers at the depot have no direction
+                if dx == 0.0 && dy == 0.0 {
+                    continue;
+                }
+                has_angle = true;
+
                 // Calculate polar angle (0 to 2π)
                 let angle = dy.atan2(dx);
                 let normalized_angle = if angle < 0.0 {
@@ -154,6 +226,17 @@ impl LocalSearch {
                 max_angle = max_angle.max(normalized_angle);
             }
 
+            // A route that only visits the depot location is close to every sector, including
+            // those shifted past 2π
+            if !has_angle {
+                self.route_sectors.push(RouteInfo {
+                    route_index: r_idx,
+                    polar_min: 0.0,
+                    polar_max: 4.0 * std::f64::consts::PI,
+                });
+                continue;
+            }
+
             // Handle cases where the sector crosses the 0/2π boundary
             if max_angle - min_angle > std::f64::consts::PI {
                 // The sector crosses the boundary, adjust
@@ -171,8 +254,8 @@ impl LocalSearch {
 
     /// Check if two route sectors intersect.
     fn sectors_intersect(&self, s1: &RouteInfo, s2: &RouteInfo) -> bool {
-        // Special case: empty routes
-        if s1.polar_min == s1.polar_max || s2.polar_min == s2.polar_max {
+        // Special case: empty routes, while a single direction is a sector of width 0
+        if s1.polar_min > s1.polar_max || s2.polar_min > s2.polar_max {
             return false;
         }
 
@@ -195,7 +278,7 @@ impl LocalSearch {
         }
 
         // Sort by cost (ascending)
-        positions.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+        positions.sort_by(|a, b| a.1.total_cmp(&b.1));
 
         // Take top 3 (or fewer if route is small)
         positions.truncate(3.min(positions.len()));
@@ -203,6 +286,7 @@ impl LocalSearch {
     }
 
     /// Evaluate a SWAP* move.
+    #[allow(clippy::too_many_arguments)]
     fn evaluate_swap_star(
         &self,
         solution: &Solution,
@@ -214,7 +298,7 @@ impl LocalSearch {
         top_positions_in_r1: &[(usize, f64)],
         top_positions_in_r2: &[(usize, f64)],
         capacity_penalty: f64,
-    ) -> (f64, usize, usize) {
+    ) -> (CostDelta, usize, usize) {
         let r1 = &solution.routes[r1_idx];
         let r2 = &solution.routes[r2_idx];
         let customer1 = r1.customers[pos1];
@@ -222,124 +306,87 @@ impl LocalSearch {
         let demand1 = problem.nodes[customer1].demand;
         let demand2 = problem.nodes[customer2].demand;
 
-        let mut best_delta = f64::INFINITY;
-        let mut best_pos1 = 0;
-        let mut best_pos2 = 0;
-
-        // Calculate the cost of removing both customers
-        let r1_remove_delta = calculate_removal_cost(r1, pos1, problem);
-        let r2_remove_delta = calculate_removal_cost(r2, pos2, problem);
-
-        // Try the best insertion positions and also the original positions
-        let mut check_positions = Vec::new();
-
-        // Customer1 in r2 (without customer2)
-        for &(insert_pos, _) in top_positions_in_r2 {
-            check_positions.push((insert_pos, insert_pos == pos2));
-        }
-        // Also check if customer1 can replace customer2 directly
-        if !check_positions.iter().any(|&(_, is_original)| is_original) {
-            check_positions.push((pos2, true));
-        }
-
-        for (insert_pos2, is_original_pos2) in check_positions.drain(..) {
-            // Customer2 in r1 (without customer1)
-            let mut check_positions_r1 = Vec::new();
+        // Load changes do not depend on the insertion positions
+        let r1_new_load = r1.load - demand1 + demand2;
+        let r2_new_load = r2.load - demand2 + demand1;
+
+        // Calculate capacity penalties
+        let variance_change =
+            problem.demand_variance(customer2) - problem.demand_variance(customer1);
+        let r1_original_excess = problem.load_excess(r1.load, r1.load_variance);
+        let r1_new_excess = problem.load_excess(r1_new_load, r1.load_variance + variance_change);
+        let r1_penalty_delta = capacity_penalty * (r1_new_excess - r1_original_excess);
+
+        let r2_original_excess = problem.load_excess(r2.load, r2.load_variance);
+        let r2_new_excess = problem.load_excess(r2_new_load, r2.load_variance - variance_change);
+        let r2_penalty_delta = capacity_penalty * (r2_new_excess - r2_original_excess);
+
+        // Best position for customer2 in r1 (without customer1), and vice versa
+        let (r1_delta, best_pos1) =
+            Self::best_replacement(r1, pos1, customer2, top_positions_in_r1, problem);
+        let (r2_delta, best_pos2) =
+            Self::best_replacement(r2, pos2, customer1, top_positions_in_r2, problem);
+
+        let objective_delta = problem.objective_delta(
+            solution,
+            &[
+                RouteChange {
+                    route: r1_idx,
+                    distance: r1.distance + r1_delta,
+                    load: r1_new_load,
+                },
+                RouteChange {
+                    route: r2_idx,
+                    distance: r2.distance + r2_delta,
+                    load: r2_new_load,
+                },
+            ],
+        );
+
+        // Total cost change
+        let total_delta = CostDelta::new(
+            r1_delta + r2_delta,
+            r1_penalty_delta + r2_penalty_delta + objective_delta,
+        );
+
+        (total_delta, best_pos1, best_pos2)
+    }
 
-            for &(insert_pos, _) in top_positions_in_r1 {
-                check_positions_r1.push((insert_pos, insert_pos == pos1));
-            }
-            // Also check if customer2 can replace customer1 directly
-            if !check_positions_r1
-                .iter()
-                .any(|&(_, is_original)| is_original)
-            {
-                check_positions_r1.push((pos1, true));
+    /// Find the best position to insert `customer` into `route` once the customer at
+    /// `remove_pos` is removed. Candidates are the top insertion positions of the full
+    /// route, mapped onto the shortened route, plus the position of the removed customer.
+    /// Returns the distance change and the position in the shortened route.
+    fn best_replacement(
+        route: &Route,
+        remove_pos: usize,
+        customer: usize,
+        top_positions: &[(usize, f64)],
+        problem: &Problem,
+    ) -> (f64, usize) {
+        let mut best_delta =
+            calculate_replacement_cost(route, remove_pos, customer, remove_pos, problem);
+        let mut best_pos = remove_pos;
+
+        for &(insert_pos, _) in top_positions {
+            // Removing an unpinned customer leaves pinned sequences intact, so the position
+            // can be checked in the full route
+            if !problem.pins.allows_insertion(route, insert_pos) {
+                continue;
             }
-
-            for (insert_pos1, is_original_pos1) in check_positions_r1 {
-                // Calculate r1 delta (remove customer1, insert customer2)
-                let r1_insert_pos = if insert_pos1 > pos1 {
-                    insert_pos1 - 1
-                } else {
-                    insert_pos1
-                };
-                let r1_temp = create_temp_route(r1, pos1, customer2, r1_insert_pos);
-                let r1_delta = r1_temp.distance - r1.distance;
-
-                // Calculate r2 delta (remove customer2, insert customer1)
-                let r2_insert_pos = if insert_pos2 > pos2 {
-                    insert_pos2 - 1
-                } else {
-                    insert_pos2
-                };
-                let r2_temp = create_temp_route(r2, pos2, customer1, r2_insert_pos);
-                let r2_delta = r2_temp.distance - r2.distance;
-
-                // Calculate load changes
-                let r1_new_load = r1.load - demand1 + demand2;
-                let r2_new_load = r2.load - demand2 + demand1;
-
-                // Calculate capacity penalties
-                let r1_original_excess = (r1.load - problem.vehicle_capacity).max(0.0);
-                let r1_new_excess = (r1_new_load - problem.vehicle_capacity).max(0.0);
-                let r1_penalty_delta = capacity_penalty * (r1_new_excess - r1_original_excess);
-
-                let r2_original_excess = (r2.load - problem.vehicle_capacity).max(0.0);
-                let r2_new_excess = (r2_new_load - problem.vehicle_capacity).max(0.0);
-                let r2_penalty_delta = capacity_penalty * (r2_new_excess - r2_original_excess);
-
-                // Total cost change
-                let total_delta = r1_delta + r2_delta + r1_penalty_delta + r2_penalty_delta;
-
-                if total_delta < best_delta {
-                    best_delta = total_delta;
-                    best_pos1 = insert_pos1;
-                    best_pos2 = insert_pos2;
-                }
+            let reduced_pos = if insert_pos > remove_pos {
+                insert_pos - 1
+            } else {
+                insert_pos
+            };
+
+            let delta =
+                calculate_replacement_cost(route, remove_pos, customer, reduced_pos, problem);
+            if delta < best_delta {
+                best_delta = delta;
+                best_pos = reduced_pos;
             }
         }
 
-        (best_delta, best_pos1, best_pos2)
-    }
-
-    /// Apply a SWAP* move.
-    fn apply_swap_star(
-        &mut self,
-        solution: &mut Solution,
-        r1_idx: usize,
-        r2_idx: usize,
-        pos1: usize,
-        pos2: usize,
-        insert_pos1: usize,
-        insert_pos2: usize,
-    ) {
-        // Remove customers from their original routes
-        let customer1 = solution.routes[r1_idx].customers.remove(pos1);
-        let customer2 = solution.routes[r2_idx].customers.remove(pos2);
-
-        // Insert customers at their new positions
-        // Adjust positions if needed due to removed customers
-        let adjusted_pos1 = if insert_pos1 > pos1 {
-            insert_pos1 - 1
-        } else {
-            insert_pos1
-        };
-        let adjusted_pos2 = if insert_pos2 > pos2 {
-            insert_pos2 - 1
-        } else {
-            insert_pos2
-        };
-
-        solution.routes[r1_idx]
-            .customers
-            .insert(adjusted_pos1, customer2);
-        solution.routes[r2_idx]
-            .customers
-            .insert(adjusted_pos2, customer1);
-
-        // Mark routes as modified
-        solution.routes[r1_idx].modified = true;
-        solution.routes[r2_idx].modified = true;
+        (best_delta, best_pos)
     }
 }
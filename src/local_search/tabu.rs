@@ -0,0 +1,55 @@
+//! Short-term memory forbidding customers to move back to the route they just left.
+
+/// Route each customer left recently, and until which move it may not return to it.
+///
+/// With worsening moves accepted, e.g. during annealing, the neighborhoods can otherwise
+/// cycle by moving a customer out of a route and straight back into it.
+#[derive(Debug, Clone, Default)]
+pub struct TabuList {
+    /// Number of applied moves during which a customer may not return to its previous route
+    tenure: usize,
+    /// Number of moves applied since the last reset
+    moves: usize,
+    /// Previous route of each customer and the move count at which the restriction expires
+    entries: Vec<Option<(usize, usize)>>,
+}
+
+impl TabuList {
+    /// Create a tabu list forbidding returns for `tenure` applied moves.
+    pub fn new(tenure: usize) -> Self {
+        TabuList {
+            tenure,
+            ..Self::default()
+        }
+    }
+
+    /// Get the number of applied moves during which a return is forbidden.
+    pub fn tenure(&self) -> usize {
+        self.tenure
+    }
+
+    /// Check whether a customer may not be moved into a route.
+    pub fn is_tabu(&self, customer: usize, route: usize) -> bool {
+        matches!(
+            self.entries.get(customer).copied().flatten(),
+            Some((previous, expires)) if previous == route && self.moves < expires
+        )
+    }
+
+    /// Count one applied move, which moved each `(customer, previous route)` of `left`.
+    pub fn record(&mut self, left: impl IntoIterator<Item = (usize, usize)>) {
+        self.moves += 1;
+        for (customer, route) in left {
+            if customer >= self.entries.len() {
+                self.entries.resize(customer + 1, None);
+            }
+            self.entries[customer] = Some((route, self.moves + self.tenure));
+        }
+    }
+
+    /// Forget every restriction, e.g. before educating another solution.
+    pub fn clear(&mut self) {
+        self.moves = 0;
+        self.entries.clear();
+    }
+}
@@ -0,0 +1,86 @@
+//! Dense storage of the move timestamps used to skip moves tested since the last change.
+
+use super::MoveKind;
+
+/// Last time each move was tested, by customer, move kind, and route.
+///
+/// The timestamps are stored in one array whose size is bounded by the number of customers,
+/// move kinds, and routes, and which is compacted on every reset, so memory does not creep
+/// up over long runs.
+#[derive(Debug, Clone, Default)]
+pub struct MoveTimestamps {
+    /// Number of routes each (customer, move kind) row has room for
+    route_stride: usize,
+    /// Timestamps, 0 for moves that were never tested
+    values: Vec<usize>,
+}
+
+impl MoveTimestamps {
+    /// Create an empty set of timestamps.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forget all timestamps and prepare room for the given number of routes.
+    pub fn reset(&mut self, route_count: usize) {
+        let used = self.values.len();
+        self.values.clear();
+        self.route_stride = route_count.max(1);
+
+        // Release memory left over from a much larger solution
+        if self.values.capacity() > 2 * used.max(1) {
+            self.values.shrink_to(used);
+        }
+    }
+
+    /// Get the last time a move was tested, if it was.
+    pub fn get(&self, customer: usize, kind: MoveKind, route: usize) -> Option<usize> {
+        if route >= self.route_stride {
+            return None;
+        }
+        let value = *self.values.get(self.index(customer, kind, route))?;
+        (value > 0).then_some(value)
+    }
+
+    /// Record the time a move was tested.
+    pub fn insert(&mut self, customer: usize, kind: MoveKind, route: usize, timestamp: usize) {
+        if route >= self.route_stride {
+            self.grow_routes(route + 1);
+        }
+
+        let index = self.index(customer, kind, route);
+        if index >= self.values.len() {
+            // Make room for all move kinds of the customer at once
+            let rows = (customer + 1) * MoveKind::COUNT;
+            self.values.resize(rows * self.route_stride, 0);
+        }
+        self.values[index] = timestamp;
+    }
+
+    /// Check whether no move has been recorded since the last reset.
+    pub fn is_empty(&self) -> bool {
+        self.values.iter().all(|&value| value == 0)
+    }
+
+    /// Get the memory allocated for the timestamps, in bytes.
+    pub fn memory_usage(&self) -> usize {
+        self.values.capacity() * std::mem::size_of::<usize>()
+    }
+
+    /// Position of a move in the array.
+    fn index(&self, customer: usize, kind: MoveKind, route: usize) -> usize {
+        (customer * MoveKind::COUNT + kind.index()) * self.route_stride + route
+    }
+
+    /// Widen every row to hold at least `route_count` routes.
+    fn grow_routes(&mut self, route_count: usize) {
+        let new_stride = route_count.max(2 * self.route_stride);
+        let rows = self.values.len() / self.route_stride.max(1);
+        let mut values = vec![0; rows * new_stride];
+        for (row, old) in self.values.chunks(self.route_stride.max(1)).enumerate() {
+            values[row * new_stride..row * new_stride + old.len()].copy_from_slice(old);
+        }
+        self.values = values;
+        self.route_stride = new_stride;
+    }
+}
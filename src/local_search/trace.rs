@@ -0,0 +1,199 @@
+//! Trace of the moves accepted by the local search, to replay an education step by step.
+
+use super::moves::Move;
+use crate::costs;
+use crate::error::HgsError;
+use crate::problem::Problem;
+use crate::solution::{Route, Solution};
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, Write};
+
+/// Entry of a search trace.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TraceRecord {
+    /// Start of an education, with the routes it starts from
+    Education {
+        /// Number of the education, counted from 0 since the trace was created
+        education: usize,
+        /// Iteration of the search during which the education ran
+        iteration: u32,
+        /// Capacity penalty of the education
+        capacity_penalty: f64,
+        /// Customers of each route of the educated solution, empty routes included
+        routes: Vec<Vec<usize>>,
+    },
+    /// Move accepted by a built-in neighborhood of the current education
+    Move {
+        /// Iteration of the search during which the move was applied
+        iteration: u32,
+        /// Move applied, naming its neighborhood and routes
+        applied: Move,
+        /// Cost change predicted for the move
+        delta: f64,
+    },
+}
+
+/// First move of a replay whose cost change differs from the recorded one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Divergence {
+    /// Position of the move among the moves of the education
+    pub move_index: usize,
+    /// Move that diverged
+    pub applied: Move,
+    /// Cost change recorded in the trace
+    pub recorded_delta: f64,
+    /// Cost change observed when replaying the move
+    pub replayed_delta: f64,
+}
+
+/// Recorder of every move accepted by the built-in neighborhoods of a local search, with
+/// the solution each education starts from.
+///
+/// Written as JSON lines, one record per line, a trace can be replayed later to reproduce
+/// the educated solutions one move at a time, and to find the first move whose cost change
+/// differs, e.g. between two builds that diverge. Moves of custom neighborhoods are not
+/// recorded, so educations using them cannot be replayed, and the educations of the guided
+/// local search on its augmented distances only replay on those distances.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchTrace {
+    /// Records in the order they were made
+    pub records: Vec<TraceRecord>,
+    /// Iteration of the search stamped on the next records
+    pub iteration: u32,
+    educations: usize,
+}
+
+impl SearchTrace {
+    /// Create an empty trace.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the number of educations recorded.
+    pub fn education_count(&self) -> usize {
+        self.educations
+    }
+
+    /// Record the start of an education of `solution`.
+    pub fn start_education(&mut self, solution: &Solution, capacity_penalty: f64) {
+        self.records.push(TraceRecord::Education {
+            education: self.educations,
+            iteration: self.iteration,
+            capacity_penalty,
+            routes: solution
+                .routes
+                .iter()
+                .map(|route| route.customers.clone())
+                .collect(),
+        });
+        self.educations += 1;
+    }
+
+    /// Record a move accepted by the current education.
+    pub fn record_move(&mut self, applied: Move, delta: f64) {
+        self.records.push(TraceRecord::Move {
+            iteration: self.iteration,
+            applied,
+            delta,
+        });
+    }
+
+    /// Write the trace as JSON lines.
+    pub fn write_jsonl<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        for record in &self.records {
+            serde_json::to_writer(&mut writer, record)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()
+    }
+
+    /// Read a trace written by `write_jsonl`, skipping blank lines.
+    pub fn read_jsonl<R: BufRead>(reader: R) -> Result<Self, HgsError> {
+        let mut trace = SearchTrace::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record: TraceRecord =
+                serde_json::from_str(&line).map_err(|err| HgsError::ParseError {
+                    line: index + 1,
+                    message: format!("invalid trace record: {err}"),
+                })?;
+            if let TraceRecord::Education { iteration, .. } = record {
+                trace.educations += 1;
+                trace.iteration = iteration;
+            }
+            trace.records.push(record);
+        }
+        Ok(trace)
+    }
+
+    /// Replay an education of the trace, returning the solution it produced.
+    ///
+    /// The moves are applied to the recorded starting routes, with an empty route opened
+    /// when a move uses one, as the local search does. Returns the first move whose cost
+    /// change differs from the recorded one by more than the tolerance of the problem, and
+    /// `None` if the trace has no such education.
+    pub fn replay(
+        &self,
+        education: usize,
+        problem: &Problem,
+    ) -> Option<Result<Solution, Divergence>> {
+        let start = self.records.iter().position(|record| {
+            matches!(record, TraceRecord::Education { education: e, .. } if *e == education)
+        })?;
+        let TraceRecord::Education {
+            capacity_penalty,
+            routes,
+            ..
+        } = &self.records[start]
+        else {
+            return None;
+        };
+
+        let mut solution = Solution::new();
+        solution.routes = routes
+            .iter()
+            .map(|customers| {
+                let mut route = Route::new();
+                route.customers = customers.clone();
+                route
+            })
+            .collect();
+        solution.evaluate(problem, *capacity_penalty);
+
+        let moves = self.records[start + 1..]
+            .iter()
+            .map_while(|record| match record {
+                TraceRecord::Move { applied, delta, .. } => Some((*applied, *delta)),
+                TraceRecord::Education { .. } => None,
+            });
+        for (move_index, (applied, recorded_delta)) in moves.enumerate() {
+            let [r1, r2] = applied.routes();
+            while solution.routes.len() <= r1.max(r2) {
+                solution.routes.push(Route::new());
+            }
+
+            let cost_before = solution.cost;
+            applied.apply(&mut solution);
+            solution.evaluate_modified(problem, *capacity_penalty);
+            let replayed_delta = solution.cost - cost_before;
+
+            let tolerance = problem.tolerance.max(1e-9 * cost_before.abs());
+            if !costs::approx_eq(recorded_delta, replayed_delta, tolerance) {
+                return Some(Err(Divergence {
+                    move_index,
+                    applied,
+                    recorded_delta,
+                    replayed_delta,
+                }));
+            }
+        }
+
+        solution.routes.retain(|route| !route.is_empty());
+        solution.update_giant_tour();
+        solution.evaluate(problem, *capacity_penalty);
+        Some(Ok(solution))
+    }
+}
@@ -1,15 +1,18 @@
 //! 2-Opt neighborhood for local search (intra-route).
 
+use crate::costs::CostDelta;
+use crate::objective::RouteChange;
 use crate::problem::Problem;
+use crate::rng::search_rng;
 use crate::solution::Solution;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
 use std::f64;
 
-use super::LocalSearch;
+use super::{LocalSearch, Move, MoveKind};
 
 impl LocalSearch {
     /// Implement the 2-Opt neighborhood for intra-route improvements.
+    /// Only reversals creating an edge between granular neighbors are considered.
     pub fn two_opt_neighborhood(
         &mut self,
         solution: &mut Solution,
@@ -17,7 +20,8 @@ impl LocalSearch {
         capacity_penalty: f64,
     ) -> bool {
         let mut improvement = false;
-        let mut rng = thread_rng();
+        let mut rng = search_rng();
+        self.index_nodes(solution, problem);
 
         // Consider all routes
         let routes = solution.routes.len();
@@ -32,34 +36,42 @@ impl LocalSearch {
                 continue;
             }
 
-            // Try all pairs of edges
             let n = route.customers.len();
+
             let mut positions: Vec<usize> = (0..n - 1).collect();
             positions.shuffle(&mut rng);
 
             for &i in &positions {
-                let mut positions_j: Vec<usize> = (i + 2..n).collect();
+                let i_node = route.customers[i];
+
+                // Check if this move has been tested before
+                if !self.is_move_valid(i_node, MoveKind::TwoOpt, r_idx) {
+                    continue;
+                }
+
+                // The new edge (i, j) must connect granular neighbors in the same route
+                let neighbors = self.operator_neighbors(i_node, MoveKind::TwoOpt, problem);
+                let mut positions_j: Vec<usize> = neighbors
+                    .iter()
+                    .filter_map(|&neighbor| self.route_node(neighbor))
+                    .filter(|node| node.route == r_idx)
+                    .map(|node| node.position)
+                    .filter(|&j| j >= i + 2)
+                    .collect();
                 positions_j.shuffle(&mut rng);
 
                 for &j in &positions_j {
-                    // Check if this move has been tested before
-                    if !self.is_move_valid(route.customers[i], 2, r_idx) {
+                    // Pinned sequences keep their direction
+                    if !problem.pins.allows_reversal(&route.customers[i + 1..=j]) {
                         continue;
                     }
 
                     // Evaluate 2-opt move
                     let delta = self.evaluate_two_opt(solution, problem, r_idx, i, j);
 
-                    if delta < -1e-6 {
-                        // Apply the move
-                        self.apply_two_opt(solution, r_idx, i, j);
-
-                        // Update route timestamp
-                        self.update_route_timestamp(r_idx);
-
-                        // Re-evaluate the solution
-                        solution.evaluate(problem, capacity_penalty);
-
+                    let two_opt = Move::TwoOpt { route: r_idx, i, j };
+                    if self.accepts(solution, problem, &two_opt, delta) {
+                        self.apply_move(solution, problem, two_opt, delta, capacity_penalty);
                         improvement = true;
                         break;
                     }
@@ -86,7 +98,7 @@ impl LocalSearch {
         r_idx: usize,
         i: usize,
         j: usize,
-    ) -> f64 {
+    ) -> CostDelta {
         let route = &solution.routes[r_idx];
         let customers = &route.customers;
 
@@ -106,18 +118,17 @@ impl LocalSearch {
         // Calculate new edge costs after 2-opt
         let new_cost = problem.get_distance(i_node, j_node) + problem.get_distance(i_next, j_next);
 
-        // Return delta
-        new_cost - old_cost
-    }
+        let delta = new_cost - old_cost;
+        let objective_delta = problem.objective_delta(
+            solution,
+            &[RouteChange {
+                route: r_idx,
+                distance: route.distance + delta,
+                load: route.load,
+            }],
+        );
 
-    /// Apply a 2-Opt move.
-    fn apply_two_opt(&mut self, solution: &mut Solution, r_idx: usize, i: usize, j: usize) {
-        // Reverse the segment from i+1 to j
-        let route = &mut solution.routes[r_idx];
-        let segment = &mut route.customers[i + 1..=j];
-        segment.reverse();
-
-        // Mark route as modified
-        route.modified = true;
+        // Return delta
+        CostDelta::new(delta, objective_delta)
     }
 }
@@ -1,13 +1,14 @@
 //! 2-Opt* neighborhood for local search (inter-route).
 
+use crate::costs::CostDelta;
+use crate::objective::RouteChange;
 use crate::problem::Problem;
+use crate::rng::search_rng;
 use crate::solution::Solution;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
 use std::f64;
 
-use super::utils::{find_route_for_customer, get_neighbors};
-use super::LocalSearch;
+use super::{LocalSearch, Move, MoveKind};
 
 impl LocalSearch {
     /// Implement the 2-Opt* neighborhood for inter-route improvements.
@@ -18,23 +19,29 @@ impl LocalSearch {
         capacity_penalty: f64,
     ) -> bool {
         let mut improvement = false;
-        let mut rng = thread_rng();
+        let mut rng = search_rng();
+        self.index_nodes(solution, problem);
+        // The head and tail loads come from the prefix sums of the routes
+        solution.update_prefix_sums(problem);
 
         // Consider all pairs of routes
         let routes = solution.routes.len();
         let mut route_indices: Vec<usize> = (0..routes).collect();
         route_indices.shuffle(&mut rng);
 
-        for r1_pos in 0..route_indices.len() {
-            let r1_idx = route_indices[r1_pos];
+        for &r1_idx in &route_indices {
             let r1 = &solution.routes[r1_idx].clone();
 
             if r1.is_empty() {
                 continue;
             }
 
-            for r2_pos in r1_pos + 1..route_indices.len() {
-                let r2_idx = route_indices[r2_pos];
+            // Neighbor lists are not symmetric, so both orders of a route pair are considered
+            for &r2_idx in &route_indices {
+                if r2_idx == r1_idx {
+                    continue;
+                }
+
                 let r2 = &solution.routes[r2_idx].clone();
 
                 if r2.is_empty() {
@@ -46,38 +53,97 @@ impl LocalSearch {
                     let customer1 = r1.customers[i];
 
                     // Use preprocessed neighbors
-                    let neighbors = &self.customer_neighbors[&customer1].clone();
+                    let neighbors =
+                        self.operator_neighbors(customer1, MoveKind::TwoOptStar, problem);
+
+                    // Check once per customer whether this route pair has been tested before
+                    let mut valid = None;
 
-                    for &neighbor in neighbors {
+                    for &neighbor in &neighbors {
                         // Find this neighbor in r2
-                        if let Some(j) = r2.customers.iter().position(|&c| c == neighbor) {
-                            // Check if this move has been tested before
-                            if !self.is_move_valid(customer1, 3, r2_idx) {
+                        if let Some(j) = self
+                            .route_node(neighbor)
+                            .filter(|node| node.route == r2_idx)
+                            .map(|node| node.position)
+                        {
+                            let is_valid = *valid.get_or_insert_with(|| {
+                                self.is_move_valid_between(
+                                    customer1,
+                                    MoveKind::TwoOptStar,
+                                    r1_idx,
+                                    r2_idx,
+                                )
+                            });
+                            if !is_valid {
                                 continue;
                             }
 
-                            // Evaluate 2-Opt* move
-                            let delta = self.evaluate_two_opt_star(
-                                solution,
-                                problem,
-                                r1_idx,
-                                r2_idx,
-                                i,
-                                j,
-                                capacity_penalty,
-                            );
-
-                            if delta < -1e-6 {
-                                // Apply the move
-                                self.apply_two_opt_star(solution, r1_idx, r2_idx, i, j);
-
-                                // Update route timestamps
-                                self.update_route_timestamp(r1_idx);
-                                self.update_route_timestamp(r2_idx);
-
-                                // Re-evaluate the solution
-                                solution.evaluate(problem, capacity_penalty);
-
+                            // Evaluate both 2-Opt* variants, unless they move pinned customers
+                            // or exceed the stop limit
+                            let pins = &problem.pins;
+                            let (len1, len2) = (r1.customers.len(), r2.customers.len());
+                            let delta = if pins.any_pinned(&r1.customers[i + 1..])
+                                || pins.any_pinned(&r2.customers[j + 1..])
+                                || !problem.allows_stops(i + len2 - j)
+                                || !problem.allows_stops(j + len1 - i)
+                            {
+                                CostDelta::INFEASIBLE
+                            } else {
+                                self.evaluate_two_opt_star(
+                                    solution,
+                                    problem,
+                                    r1_idx,
+                                    r2_idx,
+                                    i,
+                                    j,
+                                    capacity_penalty,
+                                )
+                            };
+                            let cross_delta = if pins.any_pinned(&r1.customers[i + 1..])
+                                || pins.any_pinned(&r2.customers[..=j])
+                                || !problem.allows_stops(i + j + 2)
+                                || !problem.allows_stops(len1 + len2 - i - j - 2)
+                            {
+                                CostDelta::INFEASIBLE
+                            } else {
+                                self.evaluate_two_opt_star_cross(
+                                    solution,
+                                    problem,
+                                    r1_idx,
+                                    r2_idx,
+                                    i,
+                                    j,
+                                    capacity_penalty,
+                                )
+                            };
+
+                            // Consider the better variant
+                            let (two_opt_star, delta) = if delta.total() <= cross_delta.total() {
+                                let two_opt_star = Move::TwoOptStar {
+                                    route1: r1_idx,
+                                    route2: r2_idx,
+                                    i,
+                                    j,
+                                };
+                                (two_opt_star, delta)
+                            } else {
+                                let cross = Move::TwoOptStarCross {
+                                    route1: r1_idx,
+                                    route2: r2_idx,
+                                    i,
+                                    j,
+                                    tail_len: len1 - i - 1,
+                                };
+                                (cross, cross_delta)
+                            };
+                            if self.accepts(solution, problem, &two_opt_star, delta) {
+                                self.apply_move(
+                                    solution,
+                                    problem,
+                                    two_opt_star,
+                                    delta,
+                                    capacity_penalty,
+                                );
                                 improvement = true;
                                 break;
                             }
@@ -103,6 +169,7 @@ impl LocalSearch {
     }
 
     /// Evaluate a 2-Opt* move (exchanging tails between two routes).
+    #[allow(clippy::too_many_arguments)]
     fn evaluate_two_opt_star(
         &self,
         solution: &Solution,
@@ -112,7 +179,7 @@ impl LocalSearch {
         i: usize,
         j: usize,
         capacity_penalty: f64,
-    ) -> f64 {
+    ) -> CostDelta {
         let r1 = &solution.routes[r1_idx];
         let r2 = &solution.routes[r2_idx];
 
@@ -121,22 +188,17 @@ impl LocalSearch {
         let customer2 = r2.customers[j];
 
         // Calculate new loads
-        let r1_tail_load: f64 = r1
-            .customers
-            .iter()
-            .skip(i + 1)
-            .map(|&c| problem.nodes[c].demand)
-            .sum();
-        let r2_tail_load: f64 = r2
-            .customers
-            .iter()
-            .skip(j + 1)
-            .map(|&c| problem.nodes[c].demand)
-            .sum();
+        let r1_tail_load = r1.segment_load(i + 1, r1.customers.len());
+        let r2_tail_load = r2.segment_load(j + 1, r2.customers.len());
 
         let r1_new_load = r1.load - r1_tail_load + r2_tail_load;
         let r2_new_load = r2.load - r2_tail_load + r1_tail_load;
 
+        let r1_tail_variance = r1.segment_variance(i + 1, r1.customers.len());
+        let r2_tail_variance = r2.segment_variance(j + 1, r2.customers.len());
+        let r1_new_variance = r1.load_variance - r1_tail_variance + r2_tail_variance;
+        let r2_new_variance = r2.load_variance - r2_tail_variance + r1_tail_variance;
+
         // Calculate distance changes
         let next1 = if i + 1 < r1.customers.len() {
             r1.customers[i + 1]
@@ -160,37 +222,146 @@ impl LocalSearch {
         let distance_delta = new_dist - old_dist;
 
         // Calculate capacity penalties
-        let r1_original_excess = (r1.load - problem.vehicle_capacity).max(0.0);
-        let r1_new_excess = (r1_new_load - problem.vehicle_capacity).max(0.0);
+        let r1_original_excess = problem.load_excess(r1.load, r1.load_variance);
+        let r1_new_excess = problem.load_excess(r1_new_load, r1_new_variance);
         let r1_penalty_delta = capacity_penalty * (r1_new_excess - r1_original_excess);
 
-        let r2_original_excess = (r2.load - problem.vehicle_capacity).max(0.0);
-        let r2_new_excess = (r2_new_load - problem.vehicle_capacity).max(0.0);
+        let r2_original_excess = problem.load_excess(r2.load, r2.load_variance);
+        let r2_new_excess = problem.load_excess(r2_new_load, r2_new_variance);
         let r2_penalty_delta = capacity_penalty * (r2_new_excess - r2_original_excess);
 
+        // Each route keeps its head and takes the tail of the other one
+        let r1_tail_distance =
+            r1.distance - r1.cumulative_distance[i + 1] - problem.get_distance(customer1, next1);
+        let r2_tail_distance =
+            r2.distance - r2.cumulative_distance[j + 1] - problem.get_distance(customer2, next2);
+        let objective_delta = problem.objective_delta(
+            solution,
+            &[
+                RouteChange {
+                    route: r1_idx,
+                    distance: r1.cumulative_distance[i + 1]
+                        + problem.get_distance(customer1, next2)
+                        + r2_tail_distance,
+                    load: r1_new_load,
+                },
+                RouteChange {
+                    route: r2_idx,
+                    distance: r2.cumulative_distance[j + 1]
+                        + problem.get_distance(customer2, next1)
+                        + r1_tail_distance,
+                    load: r2_new_load,
+                },
+            ],
+        );
+
         // Total cost change
-        distance_delta + r1_penalty_delta + r2_penalty_delta
+        CostDelta::new(
+            distance_delta,
+            r1_penalty_delta + r2_penalty_delta + objective_delta,
+        )
     }
 
-    /// Apply a 2-Opt* move.
-    fn apply_two_opt_star(
-        &mut self,
-        solution: &mut Solution,
+    /// Evaluate the cross variant of a 2-Opt* move: the head of r1 is connected
+    /// to the reversed head of r2, and the reversed tail of r1 to the tail of r2.
+    #[allow(clippy::too_many_arguments)]
+    fn evaluate_two_opt_star_cross(
+        &self,
+        solution: &Solution,
+        problem: &Problem,
         r1_idx: usize,
         r2_idx: usize,
         i: usize,
         j: usize,
-    ) {
-        // Get tails
-        let r1_tail: Vec<usize> = solution.routes[r1_idx].customers.drain(i + 1..).collect();
-        let r2_tail: Vec<usize> = solution.routes[r2_idx].customers.drain(j + 1..).collect();
-
-        // Swap tails
-        solution.routes[r1_idx].customers.extend(r2_tail);
-        solution.routes[r2_idx].customers.extend(r1_tail);
-
-        // Mark routes as modified
-        solution.routes[r1_idx].modified = true;
-        solution.routes[r2_idx].modified = true;
+        capacity_penalty: f64,
+    ) -> CostDelta {
+        let r1 = &solution.routes[r1_idx];
+        let r2 = &solution.routes[r2_idx];
+
+        // Get customers at the cutting points
+        let customer1 = r1.customers[i];
+        let customer2 = r2.customers[j];
+
+        // Calculate new loads (heads together, tails together)
+        let r1_head_load = r1.segment_load(0, i + 1);
+        let r2_head_load = r2.segment_load(0, j + 1);
+
+        let r1_new_load = r1_head_load + r2_head_load;
+        let r2_new_load = (r1.load - r1_head_load) + (r2.load - r2_head_load);
+
+        let r1_head_variance = r1.segment_variance(0, i + 1);
+        let r2_head_variance = r2.segment_variance(0, j + 1);
+        let r1_new_variance = r1_head_variance + r2_head_variance;
+        let r2_new_variance =
+            (r1.load_variance - r1_head_variance) + (r2.load_variance - r2_head_variance);
+
+        // Calculate distance changes
+        let next1 = if i + 1 < r1.customers.len() {
+            r1.customers[i + 1]
+        } else {
+            problem.depot_index
+        };
+        let next2 = if j + 1 < r2.customers.len() {
+            r2.customers[j + 1]
+        } else {
+            problem.depot_index
+        };
+
+        // Old connections
+        let old_dist =
+            problem.get_distance(customer1, next1) + problem.get_distance(customer2, next2);
+
+        // New connections (reversed segments keep their length on symmetric distances)
+        let new_dist =
+            problem.get_distance(customer1, customer2) + problem.get_distance(next1, next2);
+
+        let distance_delta = new_dist - old_dist;
+
+        // Joining both tails empties r2 when both cuts are at the route ends
+        let fixed_delta = if i + 1 == r1.customers.len() && j + 1 == r2.customers.len() {
+            -problem.route_fixed_cost
+        } else {
+            0.0
+        };
+
+        // Calculate capacity penalties
+        let r1_original_excess = problem.load_excess(r1.load, r1.load_variance);
+        let r1_new_excess = problem.load_excess(r1_new_load, r1_new_variance);
+        let r1_penalty_delta = capacity_penalty * (r1_new_excess - r1_original_excess);
+
+        let r2_original_excess = problem.load_excess(r2.load, r2.load_variance);
+        let r2_new_excess = problem.load_excess(r2_new_load, r2_new_variance);
+        let r2_penalty_delta = capacity_penalty * (r2_new_excess - r2_original_excess);
+
+        // r1 joins both heads, r2 both tails
+        let r1_tail_distance =
+            r1.distance - r1.cumulative_distance[i + 1] - problem.get_distance(customer1, next1);
+        let r2_tail_distance =
+            r2.distance - r2.cumulative_distance[j + 1] - problem.get_distance(customer2, next2);
+        let objective_delta = problem.objective_delta(
+            solution,
+            &[
+                RouteChange {
+                    route: r1_idx,
+                    distance: r1.cumulative_distance[i + 1]
+                        + problem.get_distance(customer1, customer2)
+                        + r2.cumulative_distance[j + 1],
+                    load: r1_new_load,
+                },
+                RouteChange {
+                    route: r2_idx,
+                    distance: r1_tail_distance
+                        + problem.get_distance(next1, next2)
+                        + r2_tail_distance,
+                    load: r2_new_load,
+                },
+            ],
+        );
+
+        // Total cost change
+        CostDelta::new(
+            distance_delta,
+            fixed_delta + r1_penalty_delta + r2_penalty_delta + objective_delta,
+        )
     }
 }
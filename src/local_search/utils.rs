@@ -23,8 +23,8 @@ pub fn get_neighbors(customer: usize, problem: &Problem, granularity: usize) ->
         }
     }
 
-    // Sort by distance
-    distances.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+    // Sort by distance, ties by index so that co-located customers have a fixed order
+    distances.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
 
     // Take the closest `granularity` neighbors
     let count = std::cmp::min(granularity, distances.len());
@@ -115,7 +115,48 @@ pub fn calculate_removal_cost(route: &Route, pos: usize, problem: &Problem) -> f
     new_distance - old_distance
 }
 
-/// Create a temporary route for evaluation purposes (used in SWAP*).
+/// Calculate the distance change when the customer at `remove_pos` is removed from a route
+/// and `customer` is inserted at `insert_pos` of the shortened route (used in SWAP*).
+pub fn calculate_replacement_cost(
+    route: &Route,
+    remove_pos: usize,
+    customer: usize,
+    insert_pos: usize,
+    problem: &Problem,
+) -> f64 {
+    let customers = &route.customers;
+    let remaining = customers.len() - 1;
+
+    // Node at a given position of the route without the removed customer
+    let node_at = |pos: usize| {
+        if pos < remove_pos {
+            customers[pos]
+        } else {
+            customers[pos + 1]
+        }
+    };
+
+    let prev_idx = if insert_pos > 0 {
+        node_at(insert_pos - 1)
+    } else {
+        problem.depot_index
+    };
+    let next_idx = if insert_pos < remaining {
+        node_at(insert_pos)
+    } else {
+        problem.depot_index
+    };
+
+    // Insertion into the shortened route
+    let insertion_delta = problem.get_distance(prev_idx, customer)
+        + problem.get_distance(customer, next_idx)
+        - problem.get_distance(prev_idx, next_idx);
+
+    calculate_removal_cost(route, remove_pos, problem) + insertion_delta
+}
+
+/// Create a temporary route for evaluation purposes.
+/// The load and distance are left at zero; compute them before use.
 pub fn create_temp_route(
     route: &Route,
     remove_pos: usize,
@@ -0,0 +1,117 @@
+//! Post-optimization merging pairs of lightly loaded routes of a solution.
+
+use crate::genetic::improve_giant_tour;
+use crate::neighbors::NeighborLists;
+use crate::problem::Problem;
+use crate::solution::{Route, Solution};
+
+/// Passes of 2-opt on each merged route
+const MERGE_TWO_OPT_PASSES: usize = 50;
+
+/// Merge pairs of routes whose loads fit in one vehicle, keeping each merge that raises the
+/// cost by at most `max_increase` times the cost of the solution.
+///
+/// Routes are tried by increasing load. Two routes are concatenated in the orientation with
+/// the shortest joining edge, and the result is shortened by 2-opt between granular
+/// neighbors. Routes with pinned customers are left alone. Returns the number of merges, and
+/// leaves the solution evaluated with `capacity_penalty`.
+pub fn merge_routes(
+    solution: &mut Solution,
+    problem: &Problem,
+    neighbors: &NeighborLists,
+    capacity_penalty: f64,
+    max_increase: f64,
+) -> usize {
+    solution.evaluate(problem, capacity_penalty);
+    let mut merges = 0;
+
+    while let Some(merged) =
+        best_merge(solution, problem, neighbors, capacity_penalty, max_increase)
+    {
+        *solution = merged;
+        merges += 1;
+    }
+    merges
+}
+
+/// Find the first acceptable merge of two routes, trying the least loaded routes first.
+fn best_merge(
+    solution: &Solution,
+    problem: &Problem,
+    neighbors: &NeighborLists,
+    capacity_penalty: f64,
+    max_increase: f64,
+) -> Option<Solution> {
+    let mut order: Vec<usize> = (0..solution.routes.len())
+        .filter(|&r| {
+            let route = &solution.routes[r];
+            !route.is_empty() && !problem.pins.any_pinned(&route.customers)
+        })
+        .collect();
+    order.sort_by(|&a, &b| solution.routes[a].load.total_cmp(&solution.routes[b].load));
+
+    let allowed = solution.cost + max_increase * solution.cost.abs() + problem.tolerance;
+    for (i, &a) in order.iter().enumerate() {
+        for &b in &order[i + 1..] {
+            let (first, second) = (&solution.routes[a], &solution.routes[b]);
+            let stops = first.customers.len() + second.customers.len();
+            let excess = problem.load_excess(
+                first.load + second.load,
+                first.load_variance + second.load_variance,
+            );
+            if excess > problem.tolerance || !problem.allows_stops(stops) {
+                continue;
+            }
+
+            let mut merged = solution.clone();
+            merged.routes[a] = join(first, second, problem, neighbors);
+            merged.routes[b] = Route::new();
+            merged.update_giant_tour();
+            merged.evaluate(problem, capacity_penalty);
+            if merged.is_feasible && merged.cost <= allowed {
+                return Some(merged);
+            }
+        }
+    }
+    None
+}
+
+/// Concatenate two routes with the shortest joining edge and shorten the result by 2-opt.
+fn join(first: &Route, second: &Route, problem: &Problem, neighbors: &NeighborLists) -> Route {
+    let (a, b) = (&first.customers, &second.customers);
+    let (a_start, a_end) = (a[0], a[a.len() - 1]);
+    let (b_start, b_end) = (b[0], b[b.len() - 1]);
+
+    // Each orientation joins an end of the head route to an end of the tail route
+    let joins = [
+        (problem.get_distance(a_end, b_start), a, false, b, false),
+        (problem.get_distance(a_end, b_end), a, false, b, true),
+        (problem.get_distance(a_start, b_start), a, true, b, false),
+        (problem.get_distance(b_end, a_start), b, false, a, false),
+    ];
+    let mut shortest = joins[0];
+    for join in &joins[1..] {
+        if join.0 < shortest.0 {
+            shortest = *join;
+        }
+    }
+    let (_, head, reverse_head, tail, reverse_tail) = shortest;
+
+    let oriented = |customers: &Vec<usize>, reverse: bool| -> Vec<usize> {
+        if reverse {
+            customers.iter().rev().copied().collect()
+        } else {
+            customers.clone()
+        }
+    };
+    let mut customers = oriented(head, reverse_head);
+    customers.extend(oriented(tail, reverse_tail));
+    improve_giant_tour(&mut customers, problem, neighbors, MERGE_TWO_OPT_PASSES);
+
+    // The merged route carries on the vehicle of the first route
+    let mut route = Route::new();
+    route.id = first.id;
+    route.customers = customers;
+    route.recalculate(problem);
+    route
+}
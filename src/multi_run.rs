@@ -0,0 +1,65 @@
+//! Reports of repeated runs with different seeds, see `HgsAlgorithm::run_multi`.
+
+use crate::error::HgsError;
+use crate::rng::search_rng;
+use crate::solution::Solution;
+use crate::utils::SearchStatistics;
+use rand::Rng;
+
+/// Outcome of one of several runs.
+#[derive(Debug, Clone)]
+pub struct RunReport {
+    /// Seed of the random number generator of the run
+    pub seed: u64,
+    /// Best feasible solution of the run, if it found one
+    pub solution: Option<Solution>,
+    /// Statistics of the run
+    pub statistics: SearchStatistics,
+}
+
+/// Outcome of several runs, with the best solution over all of them.
+#[derive(Debug, Clone)]
+pub struct MultiRunReport {
+    /// Best feasible solution over all runs
+    pub best: Solution,
+    /// Index in `runs` of the run that found `best`
+    pub best_run: usize,
+    /// Every run, in the order of their seeds
+    pub runs: Vec<RunReport>,
+}
+
+impl MultiRunReport {
+    /// Aggregate runs, failing if none found a feasible solution.
+    pub fn from_runs(runs: Vec<RunReport>) -> Result<Self, HgsError> {
+        let (best_run, best) = runs
+            .iter()
+            .enumerate()
+            .filter_map(|(i, run)| run.solution.as_ref().map(|solution| (i, solution)))
+            .min_by(|(_, a), (_, b)| a.cost.total_cmp(&b.cost))
+            .ok_or(HgsError::NoFeasibleSolution)?;
+
+        Ok(MultiRunReport {
+            best: best.clone(),
+            best_run,
+            runs,
+        })
+    }
+
+    /// Get the average cost of the runs that found a feasible solution.
+    pub fn average_cost(&self) -> f64 {
+        let costs: Vec<f64> = self
+            .runs
+            .iter()
+            .filter_map(|run| run.solution.as_ref().map(|solution| solution.cost))
+            .collect();
+        costs.iter().sum::<f64>() / costs.len().max(1) as f64
+    }
+}
+
+/// Take the seed of each of `n_runs` runs from `seeds`, drawing random ones beyond them.
+pub(crate) fn run_seeds(n_runs: usize, seeds: &[u64]) -> Vec<u64> {
+    let mut rng = search_rng();
+    (0..n_runs)
+        .map(|i| seeds.get(i).copied().unwrap_or_else(|| rng.gen()))
+        .collect()
+}
@@ -0,0 +1,114 @@
+//! Granular neighbor lists shared between local search instances.
+
+use crate::local_search::utils;
+use crate::problem::Problem;
+use crate::spatial::KdTree;
+
+/// The closest customers of every customer, computed once for a given granularity.
+#[derive(Debug, Clone, Default)]
+pub struct NeighborLists {
+    /// Number of neighbors kept per customer
+    granularity: usize,
+    /// Neighbors of each node, empty for the depot
+    lists: Vec<Vec<usize>>,
+    /// Index of the depot
+    depot_index: usize,
+}
+
+impl NeighborLists {
+    /// Compute the `granularity` closest customers of every customer.
+    ///
+    /// With Euclidean distances the customers are found through a k-d tree in about
+    /// O(n log n) instead of sorting all distances of every customer, with the same result.
+    pub fn new(problem: &Problem, granularity: usize) -> Self {
+        let tree = (problem.euclidean_distances && problem.has_coordinates).then(|| {
+            KdTree::new(problem.customers().map(|customer| {
+                (
+                    customer,
+                    problem.nodes[customer].x,
+                    problem.nodes[customer].y,
+                )
+            }))
+        });
+        let lists = (0..problem.nodes.len())
+            .map(|node| match &tree {
+                _ if node == problem.depot_index => Vec::new(),
+                Some(tree) => Self::nearest_customers(tree, node, problem, granularity),
+                None => utils::get_neighbors(node, problem, granularity),
+            })
+            .collect();
+
+        NeighborLists {
+            granularity,
+            lists,
+            depot_index: problem.depot_index,
+        }
+    }
+
+    /// Find the closest customers of a customer in a k-d tree of the customers.
+    ///
+    /// Distances may be rounded, so customers are taken by increasing Euclidean distance
+    /// until their distance exceeds that of the `granularity`-th one, and then ordered like
+    /// `get_neighbors`: by distance, ties by index.
+    fn nearest_customers(
+        tree: &KdTree,
+        customer: usize,
+        problem: &Problem,
+        granularity: usize,
+    ) -> Vec<usize> {
+        if granularity == 0 {
+            return Vec::new();
+        }
+
+        let node = &problem.nodes[customer];
+        let mut found: Vec<(usize, f64)> = Vec::with_capacity(granularity + 1);
+        for (other, _) in tree.nearest(node.x, node.y) {
+            if other == customer {
+                continue;
+            }
+            let distance = problem.get_distance(customer, other);
+            if found.len() >= granularity && distance > found[granularity - 1].1 {
+                break;
+            }
+            found.push((other, distance));
+        }
+
+        found.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+        found.truncate(granularity);
+        found.into_iter().map(|(other, _)| other).collect()
+    }
+
+    /// Get the number of neighbors kept per customer.
+    pub fn granularity(&self) -> usize {
+        self.granularity
+    }
+
+    /// Get the number of nodes the lists were computed for, including the depot.
+    pub fn node_count(&self) -> usize {
+        self.lists.len()
+    }
+
+    /// Get the neighbors of a customer, ordered by increasing distance.
+    pub fn get(&self, customer: usize) -> &[usize] {
+        self.lists.get(customer).map_or(&[], Vec::as_slice)
+    }
+
+    /// Get the number of customers with a neighbor list.
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Check whether no neighbor list has been computed.
+    pub fn is_empty(&self) -> bool {
+        self.lists.is_empty()
+    }
+
+    /// Iterate over the customers and their neighbors.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &[usize])> {
+        self.lists
+            .iter()
+            .enumerate()
+            .filter(move |&(node, _)| node != self.depot_index)
+            .map(|(node, list)| (node, list.as_slice()))
+    }
+}
@@ -0,0 +1,101 @@
+//! Custom objective terms added to the cost of a solution.
+
+use crate::problem::Problem;
+use crate::solution::Solution;
+use std::fmt;
+
+/// Distance and load a route would have once a move is applied.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RouteChange {
+    /// Index of the route in the solution
+    pub route: usize,
+    /// Distance of the route after the move
+    pub distance: f64,
+    /// Load of the route after the move
+    pub load: f64,
+}
+
+/// Additional cost term, e.g. a soft constraint, evaluated with every solution.
+///
+/// The term is added to the cost of every solution, and the local search adds its `delta` to
+/// the cost change of every move, so all neighborhoods optimize it without modification.
+/// Decomposition subproblems share the model of their problem but renumber the customers.
+pub trait ObjectiveModel: fmt::Debug + Send + Sync {
+    /// Evaluate the term for a solution whose routes have up-to-date distances and loads.
+    fn evaluate(&self, solution: &Solution, problem: &Problem) -> f64;
+
+    /// Compute the change of the term when the given routes take new distances and loads,
+    /// the other routes staying as they are.
+    fn delta(&self, solution: &Solution, problem: &Problem, changes: &[RouteChange]) -> f64;
+}
+
+/// Measure of the imbalance between the distances of the routes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BalanceMeasure {
+    /// Distance of the longest route minus that of the shortest one
+    #[default]
+    Range,
+    /// Variance of the route distances
+    Variance,
+}
+
+/// Penalty on unbalanced workloads, measured on the distances of the used routes.
+///
+/// Routes with neither distance nor load are unused and do not count.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkloadBalance {
+    /// Weight of the imbalance in the cost
+    pub weight: f64,
+    /// How the imbalance is measured
+    pub measure: BalanceMeasure,
+}
+
+impl WorkloadBalance {
+    /// Create a balance term with the given weight and measure.
+    pub fn new(weight: f64, measure: BalanceMeasure) -> Self {
+        WorkloadBalance { weight, measure }
+    }
+
+    /// Weighted imbalance of the distances of the used routes.
+    fn imbalance(&self, routes: impl Iterator<Item = (f64, f64)>) -> f64 {
+        let distances: Vec<f64> = routes
+            .filter(|&(distance, load)| distance > 0.0 || load > 0.0)
+            .map(|(distance, _)| distance)
+            .collect();
+        if distances.len() < 2 {
+            return 0.0;
+        }
+
+        let imbalance = match self.measure {
+            BalanceMeasure::Range => {
+                let max = distances.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+                let min = distances.iter().cloned().fold(f64::INFINITY, f64::min);
+                max - min
+            }
+            BalanceMeasure::Variance => {
+                let count = distances.len() as f64;
+                let mean = distances.iter().sum::<f64>() / count;
+                distances.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / count
+            }
+        };
+        self.weight * imbalance
+    }
+}
+
+impl ObjectiveModel for WorkloadBalance {
+    fn evaluate(&self, solution: &Solution, _problem: &Problem) -> f64 {
+        self.imbalance(solution.routes.iter().map(|r| (r.distance, r.load)))
+    }
+
+    fn delta(&self, solution: &Solution, problem: &Problem, changes: &[RouteChange]) -> f64 {
+        let after = self.imbalance(solution.routes.iter().enumerate().map(|(index, route)| {
+            changes
+                .iter()
+                .find(|change| change.route == index)
+                .map_or((route.distance, route.load), |change| {
+                    (change.distance, change.load)
+                })
+        }));
+        after - self.evaluate(solution, problem)
+    }
+}
@@ -0,0 +1,76 @@
+//! Parameters that can be tuned while the algorithm is running.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Parameters applied to a running search at the start of the next iteration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TunableParams {
+    /// Granularity parameter for local search neighborhoods
+    pub granularity: usize,
+    /// Target proportion of feasible individuals
+    pub target_feasible_ratio: f64,
+    /// Smallest allowed capacity penalty coefficient
+    pub penalty_min: f64,
+    /// Largest allowed capacity penalty coefficient
+    pub penalty_max: f64,
+}
+
+/// Shared state behind a parameter handle.
+#[derive(Debug)]
+struct SharedParams {
+    /// Incremented with every update
+    version: AtomicU64,
+    /// Latest parameters
+    params: Mutex<TunableParams>,
+}
+
+/// Handle shared with a running algorithm to change some of its parameters mid-run.
+///
+/// Clones of a handle refer to the same parameters and can be sent to other threads.
+#[derive(Debug, Clone)]
+pub struct ParamHandle {
+    shared: Arc<SharedParams>,
+}
+
+impl ParamHandle {
+    /// Create a handle holding the given parameters.
+    pub fn new(params: TunableParams) -> Self {
+        ParamHandle {
+            shared: Arc::new(SharedParams {
+                version: AtomicU64::new(0),
+                params: Mutex::new(params),
+            }),
+        }
+    }
+
+    /// Get the latest parameters.
+    pub fn get(&self) -> TunableParams {
+        self.lock().clone()
+    }
+
+    /// Replace the parameters.
+    pub fn set(&self, params: TunableParams) {
+        self.update(|current| *current = params);
+    }
+
+    /// Modify the parameters in place.
+    pub fn update(&self, change: impl FnOnce(&mut TunableParams)) {
+        let mut params = self.lock();
+        change(&mut params);
+        self.shared.version.fetch_add(1, Ordering::Release);
+    }
+
+    /// Get the number of updates made so far.
+    pub fn version(&self) -> u64 {
+        self.shared.version.load(Ordering::Acquire)
+    }
+
+    /// Lock the parameters, recovering them if a thread panicked while holding the lock.
+    fn lock(&self) -> std::sync::MutexGuard<'_, TunableParams> {
+        self.shared
+            .params
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
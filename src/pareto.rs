@@ -0,0 +1,70 @@
+//! Pareto front of feasible solutions trading off fleet size against distance.
+
+use crate::costs;
+use crate::solution::Solution;
+use std::collections::BTreeMap;
+
+/// Non-dominated feasible solutions with respect to (number of routes, distance).
+#[derive(Debug, Clone)]
+pub struct ParetoFront {
+    /// Best solution for each number of routes, keyed by that number
+    entries: BTreeMap<usize, Solution>,
+    /// Tolerance used when comparing distances
+    tolerance: f64,
+}
+
+impl ParetoFront {
+    /// Create an empty Pareto front.
+    pub fn new(tolerance: f64) -> Self {
+        ParetoFront {
+            entries: BTreeMap::new(),
+            tolerance,
+        }
+    }
+
+    /// Offer a solution to the front.
+    ///
+    /// Returns true if the solution is feasible and not dominated, in which case it is stored
+    /// and the solutions it dominates are removed.
+    pub fn insert(&mut self, solution: &Solution) -> bool {
+        if !solution.is_feasible {
+            return false;
+        }
+
+        let routes = solution.get_used_route_count();
+        let distance = solution.distance;
+
+        // Dominated by a solution with at most as many routes and no longer distance
+        let dominated = self
+            .entries
+            .range(..=routes)
+            .any(|(_, other)| !costs::approx_lt(distance, other.distance, self.tolerance));
+        if dominated {
+            return false;
+        }
+
+        // Remove the solutions that use at least as many routes for a longer distance
+        let tolerance = self.tolerance;
+        self.entries.retain(|&other_routes, other| {
+            other_routes < routes || costs::approx_lt(other.distance, distance, tolerance)
+        });
+        self.entries.insert(routes, solution.clone());
+
+        true
+    }
+
+    /// Get the non-dominated solutions, ordered by increasing number of routes.
+    pub fn solutions(&self) -> Vec<&Solution> {
+        self.entries.values().collect()
+    }
+
+    /// Get the number of solutions on the front.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check whether the front contains no solution.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -0,0 +1,131 @@
+//! Adaptive penalty coefficients for constraint violations.
+
+use crate::config::Config;
+
+/// Penalty coefficient of a single constraint, adapted towards a target feasibility ratio.
+#[derive(Debug, Clone)]
+pub struct ConstraintPenalty {
+    /// Current penalty coefficient
+    pub coefficient: f64,
+    /// Target ratio of individuals satisfying the constraint
+    pub target_feasible_ratio: f64,
+    /// Factor applied to the coefficient when too few individuals satisfy the constraint
+    pub increase_factor: f64,
+    /// Factor dividing the coefficient when enough individuals satisfy the constraint
+    pub decrease_factor: f64,
+    /// Smallest allowed coefficient
+    pub min_coefficient: f64,
+    /// Largest allowed coefficient
+    pub max_coefficient: f64,
+    /// Number of recorded individuals satisfying the constraint since the last adjustment
+    satisfied_count: usize,
+    /// Number of recorded individuals since the last adjustment
+    recorded_count: usize,
+}
+
+impl ConstraintPenalty {
+    /// Create a penalty with the given initial coefficient and target feasibility ratio.
+    pub fn new(coefficient: f64, target_feasible_ratio: f64) -> Self {
+        ConstraintPenalty {
+            coefficient,
+            target_feasible_ratio,
+            increase_factor: 1.2,
+            decrease_factor: 1.2,
+            min_coefficient: 0.1,
+            max_coefficient: f64::INFINITY,
+            satisfied_count: 0,
+            recorded_count: 0,
+        }
+    }
+
+    /// Set the factor applied when the coefficient increases.
+    pub fn with_increase_factor(mut self, factor: f64) -> Self {
+        self.increase_factor = factor;
+        self
+    }
+
+    /// Set the factor applied when the coefficient decreases.
+    pub fn with_decrease_factor(mut self, factor: f64) -> Self {
+        self.decrease_factor = factor;
+        self
+    }
+
+    /// Set the smallest allowed coefficient.
+    pub fn with_min_coefficient(mut self, min: f64) -> Self {
+        self.min_coefficient = min;
+        self
+    }
+
+    /// Set the largest allowed coefficient.
+    pub fn with_max_coefficient(mut self, max: f64) -> Self {
+        self.max_coefficient = max;
+        self
+    }
+
+    /// Record whether a new individual satisfies the constraint.
+    pub fn record(&mut self, satisfied: bool) {
+        self.satisfied_count += satisfied as usize;
+        self.recorded_count += 1;
+    }
+
+    /// Get the ratio of recorded individuals satisfying the constraint, if any was recorded
+    /// since the last adjustment.
+    pub fn recorded_ratio(&self) -> Option<f64> {
+        (self.recorded_count > 0).then(|| self.satisfied_count as f64 / self.recorded_count as f64)
+    }
+
+    /// Adapt the coefficient to the ratio of recorded individuals satisfying the constraint,
+    /// or to `fallback_ratio` if none was recorded, and start a new recording period.
+    pub fn adjust_to_recorded(&mut self, fallback_ratio: f64) {
+        let ratio = self.recorded_ratio().unwrap_or(fallback_ratio);
+        self.adjust(ratio);
+        self.satisfied_count = 0;
+        self.recorded_count = 0;
+    }
+
+    /// Adapt the coefficient to the observed ratio of individuals satisfying the constraint.
+    pub fn adjust(&mut self, feasible_ratio: f64) {
+        if feasible_ratio < self.target_feasible_ratio {
+            // Increase penalty to favor feasible solutions
+            self.coefficient *= self.increase_factor;
+        } else {
+            // Decrease penalty to explore more infeasible solutions
+            self.coefficient /= self.decrease_factor;
+        }
+
+        // Keep the penalty within its bounds
+        self.coefficient = self
+            .coefficient
+            .max(self.min_coefficient)
+            .min(self.max_coefficient);
+    }
+}
+
+/// Penalty coefficients of all constraints, each adapted independently.
+#[derive(Debug, Clone)]
+pub struct PenaltyManager {
+    /// Penalty for exceeding the vehicle capacity
+    pub capacity: ConstraintPenalty,
+}
+
+impl PenaltyManager {
+    /// Create the penalties with the initial values, factors, and bounds of the
+    /// configuration.
+    pub fn new(config: &Config) -> Self {
+        PenaltyManager {
+            capacity: ConstraintPenalty::new(
+                config.initial_capacity_penalty,
+                config.target_feasible_ratio,
+            )
+            .with_increase_factor(config.penalty_increase_factor)
+            .with_decrease_factor(config.penalty_decrease_factor)
+            .with_min_coefficient(config.penalty_min)
+            .with_max_coefficient(config.penalty_max.unwrap_or(f64::INFINITY)),
+        }
+    }
+
+    /// Get the current capacity penalty coefficient.
+    pub fn capacity_penalty(&self) -> f64 {
+        self.capacity.coefficient
+    }
+}
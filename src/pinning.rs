@@ -0,0 +1,249 @@
+//! Customers pinned to a route or to a fixed sequence, e.g. already dispatched orders.
+
+use crate::problem::Problem;
+use crate::solution::{Route, Solution};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Assignments of customers to routes and to fixed sequences within them.
+///
+/// Customers pinned to the same route label are visited by one route, and different labels
+/// by different routes. Customers of a pinned sequence are visited consecutively in order.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Pins {
+    /// Route label of each node, empty while nothing is pinned
+    route_labels: Vec<Option<usize>>,
+    /// Next customer of the pinned sequence of each node
+    successors: Vec<Option<usize>>,
+    /// Previous customer of the pinned sequence of each node
+    predecessors: Vec<Option<usize>>,
+}
+
+impl Pins {
+    /// Pin a customer to the route with the given label.
+    pub fn pin_customer(&mut self, node_count: usize, customer: usize, route: usize) {
+        self.reserve(node_count);
+        self.route_labels[customer] = Some(route);
+    }
+
+    /// Pin customers to the route with the given label, visited consecutively in this order.
+    pub fn pin_sequence(&mut self, node_count: usize, customers: &[usize], route: usize) {
+        debug_assert!(
+            customers.iter().collect::<HashSet<_>>().len() == customers.len(),
+            "pinned sequence visits a customer twice"
+        );
+        for &customer in customers {
+            self.pin_customer(node_count, customer, route);
+        }
+        for pair in customers.windows(2) {
+            self.successors[pair[0]] = Some(pair[1]);
+            self.predecessors[pair[1]] = Some(pair[0]);
+        }
+    }
+
+    /// Check whether no customer is pinned.
+    pub fn is_empty(&self) -> bool {
+        self.route_labels.is_empty()
+    }
+
+    /// Get the route label a customer is pinned to.
+    pub fn route_label(&self, customer: usize) -> Option<usize> {
+        self.route_labels.get(customer).copied().flatten()
+    }
+
+    /// Check whether a customer is pinned to a route.
+    pub fn is_pinned(&self, customer: usize) -> bool {
+        self.route_label(customer).is_some()
+    }
+
+    /// Check whether any of the customers is pinned to a route.
+    pub fn any_pinned(&self, customers: &[usize]) -> bool {
+        !self.is_empty() && customers.iter().any(|&customer| self.is_pinned(customer))
+    }
+
+    /// Check whether a customer belongs to a pinned sequence.
+    pub fn is_in_sequence(&self, customer: usize) -> bool {
+        self.successor(customer).is_some() || self.predecessor(customer).is_some()
+    }
+
+    /// Check whether `from` is directly followed by `to` in a pinned sequence.
+    pub fn is_sequence_edge(&self, from: usize, to: usize) -> bool {
+        self.successor(from) == Some(to)
+    }
+
+    /// Check whether a customer may be inserted before `position` of a route.
+    pub fn allows_insertion(&self, route: &Route, position: usize) -> bool {
+        if self.is_empty() || position == 0 || position >= route.customers.len() {
+            return true;
+        }
+        !self.is_sequence_edge(route.customers[position - 1], route.customers[position])
+    }
+
+    /// Check whether a segment of customers may be reversed.
+    pub fn allows_reversal(&self, segment: &[usize]) -> bool {
+        self.is_empty() || !segment.iter().any(|&c| self.is_in_sequence(c))
+    }
+
+    /// Check whether a solution visits the pinned customers as required.
+    pub fn is_respected(&self, solution: &Solution) -> bool {
+        let mut label_routes: HashMap<usize, usize> = HashMap::new();
+        let mut route_labels: HashMap<usize, usize> = HashMap::new();
+
+        for (r_idx, route) in solution.routes.iter().enumerate() {
+            for (pos, &customer) in route.customers.iter().enumerate() {
+                if let Some(label) = self.route_label(customer) {
+                    if *label_routes.entry(label).or_insert(r_idx) != r_idx
+                        || *route_labels.entry(r_idx).or_insert(label) != label
+                    {
+                        return false;
+                    }
+                }
+                let next = route.customers.get(pos + 1).copied();
+                if self.successor(customer).is_some_and(|s| Some(s) != next) {
+                    return false;
+                }
+            }
+        }
+
+        // A customer missing from the routes has no route for its label
+        (0..self.route_labels.len())
+            .filter(|&customer| self.is_pinned(customer))
+            .all(|customer| {
+                solution
+                    .routes
+                    .iter()
+                    .any(|route| route.customers.contains(&customer))
+            })
+    }
+
+    /// Move the pinned customers of a solution to their routes and sequences.
+    ///
+    /// Each label keeps the route holding most of its customers, or a new route if all of
+    /// them are taken, and the pinned customers are reinserted there at their cheapest
+    /// position. Route metrics must be recomputed afterwards.
+    pub fn enforce(&self, solution: &mut Solution, problem: &Problem) {
+        if self.is_empty() {
+            return;
+        }
+
+        // Count the customers of each label in each route
+        let mut counts: HashMap<(usize, usize), usize> = HashMap::new();
+        for (r_idx, route) in solution.routes.iter().enumerate() {
+            for &customer in &route.customers {
+                if let Some(label) = self.route_label(customer) {
+                    *counts.entry((label, r_idx)).or_insert(0) += 1;
+                }
+            }
+        }
+        let mut candidates: Vec<((usize, usize), usize)> = counts.into_iter().collect();
+        candidates.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        // Give each label its own route
+        let mut targets: HashMap<usize, usize> = HashMap::new();
+        let mut taken: HashSet<usize> = HashSet::new();
+        for ((label, r_idx), _) in candidates {
+            if !targets.contains_key(&label) && taken.insert(r_idx) {
+                targets.insert(label, r_idx);
+            }
+        }
+        let labels: BTreeSet<usize> = self.route_labels.iter().flatten().copied().collect();
+        for label in labels {
+            targets.entry(label).or_insert_with(|| {
+                solution.routes.push(Route::new());
+                solution.routes.len() - 1
+            });
+        }
+
+        // Reinsert the pinned customers, sequences as a whole
+        for route in &mut solution.routes {
+            route
+                .customers
+                .retain(|&customer| !self.is_pinned(customer));
+            route.modified = true;
+        }
+        for block in self.blocks() {
+            let route = &mut solution.routes[targets[&self.route_labels[block[0]].unwrap()]];
+            let position = self.cheapest_block_position(route, &block, problem);
+            route.customers.splice(position..position, block);
+        }
+
+        solution.routes.retain(|route| !route.is_empty());
+        solution.update_giant_tour();
+    }
+
+    /// Restrict the pins to a subset of the nodes, renumbered by their position in `nodes`.
+    pub fn for_nodes(&self, nodes: &[usize]) -> Pins {
+        let mut pins = Pins::default();
+        if self.is_empty() {
+            return pins;
+        }
+
+        let mut index = HashMap::with_capacity(nodes.len());
+        for (new, &node) in nodes.iter().enumerate() {
+            index.insert(node, new);
+            if let Some(label) = self.route_label(node) {
+                pins.pin_customer(nodes.len(), new, label);
+            }
+        }
+        for (new, &node) in nodes.iter().enumerate() {
+            if let Some(&next) = self.successor(node).and_then(|s| index.get(&s)) {
+                pins.successors[new] = Some(next);
+                pins.predecessors[next] = Some(new);
+            }
+        }
+        pins
+    }
+
+    /// Pinned customers grouped into sequences, single customers forming their own.
+    fn blocks(&self) -> Vec<Vec<usize>> {
+        (0..self.route_labels.len())
+            .filter(|&customer| self.is_pinned(customer) && self.predecessor(customer).is_none())
+            .map(|first| {
+                let mut block = vec![first];
+                while let Some(next) = self.successor(*block.last().unwrap()) {
+                    block.push(next);
+                }
+                block
+            })
+            .collect()
+    }
+
+    /// Find the cheapest position of a route to insert a block without splitting a sequence.
+    fn cheapest_block_position(&self, route: &Route, block: &[usize], problem: &Problem) -> usize {
+        let depot = problem.depot_index;
+        let (first, last) = (block[0], block[block.len() - 1]);
+
+        (0..=route.customers.len())
+            .filter(|&position| self.allows_insertion(route, position))
+            .map(|position| {
+                let prev = position
+                    .checked_sub(1)
+                    .map_or(depot, |p| route.customers[p]);
+                let next = route.customers.get(position).copied().unwrap_or(depot);
+                let cost = problem.get_distance(prev, first) + problem.get_distance(last, next)
+                    - problem.get_distance(prev, next);
+                (position, cost)
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(route.customers.len(), |(position, _)| position)
+    }
+
+    /// Next customer of the pinned sequence of a customer.
+    fn successor(&self, customer: usize) -> Option<usize> {
+        self.successors.get(customer).copied().flatten()
+    }
+
+    /// Previous customer of the pinned sequence of a customer.
+    fn predecessor(&self, customer: usize) -> Option<usize> {
+        self.predecessors.get(customer).copied().flatten()
+    }
+
+    /// Make room for every node.
+    fn reserve(&mut self, node_count: usize) {
+        if self.route_labels.len() < node_count {
+            self.route_labels.resize(node_count, None);
+            self.successors.resize(node_count, None);
+            self.predecessors.resize(node_count, None);
+        }
+    }
+}
@@ -1,11 +1,79 @@
 //! Population management for the genetic algorithm.
 
+use crate::config::Config;
+use crate::error::HgsError;
 use crate::individual::Individual;
+use crate::penalty::PenaltyManager;
 use crate::problem::Problem;
+use crate::rng::search_rng;
 use crate::solution::Solution;
-use crate::{config::Config, individual};
+use crate::split::Split;
 use rand::{seq::SliceRandom, Rng};
-use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
+
+/// Summary statistics describing the diversity of a population.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiversityStats {
+    /// Average broken-pairs distance over all pairs of individuals
+    pub average_broken_pairs_distance: f64,
+    /// Number of individuals that are clones of another individual
+    pub clone_count: usize,
+    /// Lowest cost in the population
+    pub min_cost: f64,
+    /// Highest cost in the population
+    pub max_cost: f64,
+    /// Standard deviation of the costs
+    pub cost_std_dev: f64,
+    /// Number of feasible individuals
+    pub feasible_count: usize,
+    /// Number of infeasible individuals
+    pub infeasible_count: usize,
+}
+
+/// Handling of offspring that are clones of an individual of the population.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ClonePolicy {
+    /// Insert clones, which survivor selection removes first
+    #[default]
+    Accept,
+    /// Discard clones before educating or inserting them
+    Reject,
+    /// Mutate the giant tour of clones before educating them, then insert the result
+    Mutate,
+}
+
+/// State of an individual of the population, as recorded by `Population::snapshot`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndividualSnapshot {
+    /// Penalized cost of the solution
+    pub cost: f64,
+    /// Whether the solution satisfies every constraint
+    pub feasible: bool,
+    /// Giant tour of the solution
+    pub giant_tour: Vec<usize>,
+    /// Rank by cost within its subpopulation
+    pub rank_feasibility: usize,
+    /// Rank by diversity contribution within its subpopulation
+    pub rank_diversity: usize,
+    /// Biased fitness combining both ranks
+    pub biased_fitness: f64,
+    /// Generations since the individual was inserted
+    pub age: u32,
+    /// Average common pairs with the closest individuals of its subpopulation
+    pub diversity_contribution: f64,
+}
+
+/// Serializable state of a population, to study its dynamics offline.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PopulationSnapshot {
+    /// Penalty coefficient of the excess load
+    pub capacity_penalty: f64,
+    /// Feasible individuals followed by the infeasible ones, each sorted by cost once the
+    /// ranks are updated
+    pub individuals: Vec<IndividualSnapshot>,
+}
 
 /// Manages the population of individuals for the genetic algorithm.
 pub struct Population {
@@ -13,18 +81,22 @@ pub struct Population {
     pub feasible_individuals: Vec<Individual>,
     /// Infeasible individuals
     pub infeasible_individuals: Vec<Individual>,
-    /// The penalty coefficient for capacity violations
-    pub capacity_penalty: f64,
+    /// Adaptive penalty coefficients of the constraints
+    pub penalties: PenaltyManager,
     /// Minimum population size
     pub min_pop_size: usize,
     /// Maximum population size before survivor selection
     pub max_pop_size: usize,
     /// Number of closest solutions to consider for diversity
     pub n_closest: usize,
-    /// Target ratio of feasible individuals
-    pub target_feasible_ratio: f64,
     /// Number of elite individuals to preserve
     pub n_elite: usize,
+    /// Age above which survivor selection removes the individuals that are not elite
+    pub max_age: Option<u32>,
+    /// Common pairs between individuals of the same subpopulation, keyed by individual id
+    pub common_pairs: HashMap<usize, HashMap<usize, usize>>,
+    /// Identifier given to the next inserted individual
+    next_id: usize,
 }
 
 impl Population {
@@ -35,30 +107,62 @@ impl Population {
             infeasible_individuals: Vec::with_capacity(
                 config.min_pop_size + config.generation_size,
             ),
-            capacity_penalty: config.initial_capacity_penalty,
+            penalties: PenaltyManager::new(config),
             min_pop_size: config.min_pop_size,
             max_pop_size: config.min_pop_size + config.generation_size,
             n_closest: config.n_closest,
-            target_feasible_ratio: config.target_feasible_ratio,
             n_elite: config.n_elite,
+            max_age: config.max_individual_age,
+            common_pairs: HashMap::new(),
+            next_id: 1,
         }
     }
 
     /// Initialize the population with random individuals.
+    ///
+    /// A proportion `sweep_initial_ratio` of them starts from a sweep tour at a random angle
+    /// instead, when the problem has coordinates.
     pub fn initialize(&mut self, problem: &Problem, config: &Config) {
-        let initial_size = 4 * self.min_pop_size;
+        self.initialize_until(problem, config, None);
+    }
 
-        for _ in 0..initial_size {
-            // Generate a random giant tour
-            let mut giant_tour: Vec<usize> = (0..problem.get_customer_count()).collect();
-            let mut rng = rand::thread_rng();
-            giant_tour.shuffle(&mut rng);
+    /// Initialize the population as `initialize` does, stopping early at `deadline` once it
+    /// holds two individuals to select parents from.
+    pub fn initialize_until(
+        &mut self,
+        problem: &Problem,
+        config: &Config,
+        deadline: Option<Instant>,
+    ) {
+        let initial_size = 4 * self.min_pop_size;
+        let sweep_count = if problem.has_coordinates {
+            (config.sweep_initial_ratio.clamp(0.0, 1.0) * initial_size as f64).round() as usize
+        } else {
+            0
+        };
+        let mut rng = search_rng();
 
-            // Create a solution from the giant tour
+        for i in 0..initial_size {
+            if i >= 2 && deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                break;
+            }
+            let giant_tour = if i < sweep_count {
+                let start_angle = rng.gen_range(0.0..2.0 * std::f64::consts::PI);
+                Self::sweep_giant_tour(problem, start_angle)
+            } else {
+                // Generate a random giant tour
+                let mut giant_tour: Vec<usize> = problem.customers().collect();
+                giant_tour.shuffle(&mut rng);
+                giant_tour
+            };
+
+            // Create a solution from the giant tour and split it into routes
             let mut solution = Solution::from_giant_tour(giant_tour, problem);
+            debug_assert!(solution.has_complete_giant_tour(problem));
+            Split::split(&mut solution, problem);
 
             // Evaluate the solution
-            solution.evaluate(problem, self.capacity_penalty);
+            solution.evaluate(problem, self.penalties.capacity_penalty());
 
             // Create an individual and add to the appropriate subpopulation
             let individual = Individual::new(solution);
@@ -69,87 +173,222 @@ impl Population {
         self.update_ranks();
     }
 
+    /// Order the customers by polar angle around the depot, starting from `start_angle`.
+    pub fn sweep_giant_tour(problem: &Problem, start_angle: f64) -> Vec<usize> {
+        let depot = problem.get_depot();
+        let two_pi = 2.0 * std::f64::consts::PI;
+        let angle_from_start = |customer: usize| {
+            let node = &problem.nodes[customer];
+            let angle = (node.y - depot.y).atan2(node.x - depot.x);
+            (angle - start_angle).rem_euclid(two_pi)
+        };
+
+        let mut giant_tour: Vec<usize> = problem.customers().collect();
+        giant_tour.sort_by(|&a, &b| angle_from_start(a).total_cmp(&angle_from_start(b)));
+        giant_tour
+    }
+
     /// Insert a new individual into the appropriate subpopulation.
-    pub fn insert_individual(&mut self, individual: Individual) {
-        if individual.is_feasible() {
-            self.feasible_individuals.push(individual);
+    pub fn insert_individual(&mut self, mut individual: Individual) {
+        individual.id = self.next_id;
+        individual.age = 0;
+        self.next_id += 1;
+        individual.update_pair_set();
+        individual.update_structure_hash();
+
+        let subpop = if individual.is_feasible() {
+            &mut self.feasible_individuals
         } else {
-            self.infeasible_individuals.push(individual);
+            &mut self.infeasible_individuals
+        };
+
+        // Compute the common pairs with the rest of the subpopulation once
+        let mut row = HashMap::with_capacity(subpop.len());
+        for other in subpop.iter() {
+            let common = individual.calculate_common_pairs(other);
+            row.insert(other.id, common);
+            self.common_pairs
+                .entry(other.id)
+                .or_default()
+                .insert(individual.id, common);
         }
-    }
+        self.common_pairs.insert(individual.id, row);
 
-    /// Update the ranks of all individuals in the population.
-    pub fn update_ranks(&mut self) {
-        self.update_feasibility_ranks();
-        self.update_diversity_measures();
-        self.update_biased_fitness();
+        subpop.push(individual);
     }
 
-    /// Update the feasibility ranks of all individuals.
-    fn update_feasibility_ranks(&mut self) {
-        // Sort feasible individuals by their cost
-        self.feasible_individuals
-            .sort_by(|a, b| a.get_cost().partial_cmp(&b.get_cost()).unwrap());
+    /// Insert a new individual and update the ranks and biased fitness of its subpopulation.
+    ///
+    /// The individual is placed at its position by cost, and `insert_individual` already
+    /// recorded its common pairs, so only the diversity ranks and the fitness are recomputed.
+    /// The subpopulation is fully re-ranked if it was modified without updating its ranks.
+    pub fn insert_and_update(&mut self, individual: Individual) {
+        let feasible = individual.is_feasible();
+        let previous_len = self.subpop_mut(feasible).len();
+        let ranked = {
+            let individuals = match feasible {
+                true => &self.feasible_individuals,
+                false => &self.infeasible_individuals,
+            };
+            let common_pairs = &self.common_pairs;
+            individuals.iter().enumerate().all(|(i, individual)| {
+                individual.id != 0
+                    && individual.rank_feasibility == i
+                    && common_pairs
+                        .get(&individual.id)
+                        .is_some_and(|row| row.len() == previous_len - 1)
+            }) && individuals
+                .windows(2)
+                .all(|pair| pair[0].get_cost() <= pair[1].get_cost())
+        };
 
-        // Assign ranks
-        for (i, individual) in self.feasible_individuals.iter_mut().enumerate() {
-            individual.rank_feasibility = i;
+        self.insert_individual(individual);
+        if !ranked {
+            self.update_subpop_ranks(feasible);
+            return;
         }
 
-        // Sort infeasible individuals by their cost
-        self.infeasible_individuals
-            .sort_by(|a, b| a.get_cost().partial_cmp(&b.get_cost()).unwrap());
+        let individuals = match feasible {
+            true => &mut self.feasible_individuals,
+            false => &mut self.infeasible_individuals,
+        };
+        let new = individuals.pop().unwrap();
 
-        // Assign ranks
-        for (i, individual) in self.infeasible_individuals.iter_mut().enumerate() {
+        // Ties go after the existing individuals, as with a stable sort
+        let position = individuals.partition_point(|other| other.get_cost() <= new.get_cost());
+        individuals.insert(position, new);
+
+        for (i, individual) in individuals.iter_mut().enumerate().skip(position) {
             individual.rank_feasibility = i;
         }
+
+        self.assign_diversity_ranks(feasible);
+        self.update_biased_fitness(feasible);
     }
 
-    /// Update the diversity measures for all individuals.
-    fn update_diversity_measures(&mut self) {
-        // First, clear previous common pairs data
-        for individual in self.feasible_individuals.iter_mut() {
-            individual.common_pairs.clear();
-        }
+    /// Remove every individual and return their solutions.
+    pub fn drain_solutions(&mut self) -> Vec<Solution> {
+        self.common_pairs.clear();
+        self.feasible_individuals
+            .drain(..)
+            .chain(self.infeasible_individuals.drain(..))
+            .map(|individual| individual.solution)
+            .collect()
+    }
 
-        for individual in self.infeasible_individuals.iter_mut() {
-            individual.common_pairs.clear();
+    /// Forget the common pairs of an individual leaving the population, and return them.
+    fn forget_individual(
+        common_pairs: &mut HashMap<usize, HashMap<usize, usize>>,
+        id: usize,
+    ) -> HashMap<usize, usize> {
+        let row = common_pairs.remove(&id).unwrap_or_default();
+        for other_id in row.keys() {
+            if let Some(other_row) = common_pairs.get_mut(other_id) {
+                other_row.remove(&id);
+            }
         }
+        row
+    }
+
+    /// Update the ranks of all individuals in the population.
+    pub fn update_ranks(&mut self) {
+        self.update_subpop_ranks(true);
+        self.update_subpop_ranks(false);
+    }
 
-        // Calculate common pairs for feasible individuals
-        self.calculate_common_pairs(true);
+    /// Update the feasibility ranks, diversity ranks and biased fitness of a subpopulation.
+    fn update_subpop_ranks(&mut self, feasible: bool) {
+        self.update_feasibility_ranks(feasible);
+        self.sync_common_pairs(feasible);
+        self.assign_diversity_ranks(feasible);
+        self.update_biased_fitness(feasible);
+    }
 
-        // Calculate common pairs for infeasible individuals
-        self.calculate_common_pairs(false);
+    /// Sort a subpopulation by cost and assign the feasibility ranks.
+    fn update_feasibility_ranks(&mut self, feasible: bool) {
+        let individuals = self.subpop_mut(feasible);
 
-        // Assign diversity ranks for feasible individuals
-        self.assign_diversity_ranks(true);
+        individuals.sort_by(|a, b| a.get_cost().total_cmp(&b.get_cost()));
 
-        // Assign diversity ranks for infeasible individuals
-        self.assign_diversity_ranks(false);
+        for (i, individual) in individuals.iter_mut().enumerate() {
+            individual.rank_feasibility = i;
+        }
     }
 
-    /// Calculate common pairs between all individuals in a subpopulation.
-    fn calculate_common_pairs(&mut self, feasible: bool) {
+    /// Get a mutable reference to the feasible or infeasible subpopulation.
+    fn subpop_mut(&mut self, feasible: bool) -> &mut Vec<Individual> {
+        match feasible {
+            true => &mut self.feasible_individuals,
+            false => &mut self.infeasible_individuals,
+        }
+    }
+
+    /// Make the common pairs cover exactly the pairs of individuals of a subpopulation.
+    ///
+    /// `insert_individual` and survivor selection keep them up to date, so this only computes
+    /// the pairs of individuals added without `insert_individual`, which are given an id here,
+    /// and drops the pairs with individuals that left the subpopulation otherwise.
+    fn sync_common_pairs(&mut self, feasible: bool) {
         let individuals = match feasible {
             true => &mut self.feasible_individuals,
             false => &mut self.infeasible_individuals,
         };
-        let count = individuals.len();
+        for individual in individuals
+            .iter_mut()
+            .filter(|individual| individual.id == 0)
+        {
+            individual.id = self.next_id;
+            self.next_id += 1;
+        }
 
-        for i in 0..count {
-            individuals[i].common_pairs = vec![0; count];
+        let ids: HashSet<usize> = individuals.iter().map(|individual| individual.id).collect();
+        for individual in individuals.iter() {
+            self.common_pairs
+                .entry(individual.id)
+                .or_default()
+                .retain(|id, _| ids.contains(id));
+        }
 
-            for j in 0..count {
-                if i != j {
-                    let common = individuals[i].calculate_common_pairs(&individuals[j]);
-                    individuals[i].common_pairs[j] = common;
+        for (i, a) in individuals.iter().enumerate() {
+            for b in &individuals[i + 1..] {
+                if !self.common_pairs[&a.id].contains_key(&b.id) {
+                    let common = a.calculate_common_pairs(b);
+                    self.common_pairs
+                        .get_mut(&a.id)
+                        .unwrap()
+                        .insert(b.id, common);
+                    self.common_pairs
+                        .get_mut(&b.id)
+                        .unwrap()
+                        .insert(a.id, common);
                 }
             }
         }
     }
 
+    /// Get the diversity contribution of an individual: the average number of common pairs
+    /// with its `n_closest` closest individuals of the subpopulation.
+    pub fn diversity_contribution(&self, id: usize) -> f64 {
+        Self::closest_contribution(self.common_pairs.get(&id), self.n_closest).0
+    }
+
+    /// Get the diversity contribution of the individual with the common pairs `row`, along
+    /// with the fewest common pairs among its `n_closest` closest individuals, or `usize::MAX`
+    /// if it has none.
+    fn closest_contribution(row: Option<&HashMap<usize, usize>>, n_closest: usize) -> (f64, usize) {
+        let mut pairs: Vec<usize> =
+            row.map_or_else(Vec::new, |row| row.values().copied().collect());
+        let count = n_closest.min(pairs.len());
+        if count == 0 {
+            return (0.0, usize::MAX);
+        }
+
+        // Higher common pairs = closer solutions
+        let (closest, &mut threshold, _) = pairs.select_nth_unstable_by(count - 1, |a, b| b.cmp(a));
+        let sum = closest.iter().sum::<usize>() + threshold;
+        (sum as f64 / count as f64, threshold)
+    }
+
     /// Assign diversity ranks based on the distance to the closest individuals.
     fn assign_diversity_ranks(&mut self, feasible: bool) {
         let individuals = match feasible {
@@ -161,19 +400,23 @@ impl Population {
         }
 
         // Calculate diversity contributions
-        let mut diversity_values: Vec<(usize, f64)> = individuals
+        let values: Vec<f64> = individuals
             .iter()
-            .enumerate()
-            .map(|(i, individual)| {
-                (
-                    i,
-                    individual.calculate_diversity_contribution(self.n_closest),
-                )
+            .map(|individual| {
+                Self::closest_contribution(self.common_pairs.get(&individual.id), self.n_closest).0
             })
             .collect();
+        Self::rank_diversity(individuals, &values);
+    }
 
-        // Sort by diversity (higher diversity = lower rank)
-        diversity_values.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+    /// Assign diversity ranks to a subpopulation sorted by cost, from the diversity
+    /// contribution of each individual.
+    fn rank_diversity(individuals: &mut [Individual], values: &[f64]) {
+        let mut diversity_values: Vec<(usize, f64)> = values.iter().copied().enumerate().collect();
+
+        // Sort by diversity (higher diversity = lower rank). Individuals are sorted by cost, so
+        // ties go to the worse individual and the best one is not rewarded on both criteria.
+        diversity_values.sort_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)));
 
         // Assign ranks
         for (rank, (index, _)) in diversity_values.iter().enumerate() {
@@ -181,15 +424,13 @@ impl Population {
         }
     }
 
-    /// Update the biased fitness of all individuals.
-    fn update_biased_fitness(&mut self) {
-        let elite_prop = self.n_elite as f64 / self.get_pop_size() as f64;
-
-        for individual in self.feasible_individuals.iter_mut() {
-            individual.calculate_biased_fitness(elite_prop);
-        }
+    /// Update the biased fitness of the individuals of a subpopulation.
+    fn update_biased_fitness(&mut self, feasible: bool) {
+        let n_elite = self.n_elite;
+        let individuals = self.subpop_mut(feasible);
+        let elite_prop = (n_elite as f64 / individuals.len() as f64).min(1.0);
 
-        for individual in self.infeasible_individuals.iter_mut() {
+        for individual in individuals.iter_mut() {
             individual.calculate_biased_fitness(elite_prop);
         }
     }
@@ -200,56 +441,90 @@ impl Population {
     }
 
     /// Select parents from the population using binary tournament selection.
-    pub fn select_parents(&self) -> (&Individual, &Individual) {
-        let mut rng = rand::thread_rng();
+    ///
+    /// The second tournament is held without the first parent, unless the population holds
+    /// a single individual, which is then returned as both.
+    pub fn select_parents(&self) -> Result<(&Individual, &Individual), HgsError> {
+        let mut rng = search_rng();
 
         // First parent
-        let parent1 = self.binary_tournament_selection(&mut rng);
+        let index1 = self.binary_tournament_selection(&mut rng, None)?;
 
         // Second parent (ensure different from first)
-        let mut parent2 = self.binary_tournament_selection(&mut rng);
-        while std::ptr::eq(parent1, parent2) {
-            parent2 = self.binary_tournament_selection(&mut rng);
-        }
-
-        (parent1, parent2)
-    }
-
-    /// Perform binary tournament selection.
-    fn binary_tournament_selection<R: Rng>(&self, rng: &mut R) -> &Individual {
-        // Select a subpopulation
-        let use_feasible = if self.feasible_individuals.is_empty() {
-            false
-        } else if self.infeasible_individuals.is_empty() {
-            true
-        } else {
-            rng.gen_bool(0.5)
+        let index2 = match self.get_pop_size() {
+            1 => index1,
+            _ => self.binary_tournament_selection(&mut rng, Some(index1))?,
         };
 
-        let subpop = if use_feasible {
-            &self.feasible_individuals
-        } else {
-            &self.infeasible_individuals
-        };
+        Ok((self.get_individual(index1), self.get_individual(index2)))
+    }
 
-        if subpop.is_empty() {
-            panic!("Cannot select from empty population");
+    /// Perform binary tournament selection over the union of both subpopulations, leaving
+    /// out the individual at `excluded`, and return the index of the winner.
+    fn binary_tournament_selection<R: Rng>(
+        &self,
+        rng: &mut R,
+        excluded: Option<usize>,
+    ) -> Result<usize, HgsError> {
+        let total = self.get_pop_size() - usize::from(excluded.is_some());
+        if total == 0 {
+            return Err(HgsError::EmptyPopulation);
         }
 
         // Select two individuals randomly
-        let idx1 = rng.gen_range(0..subpop.len());
-        let mut idx2 = rng.gen_range(0..subpop.len());
+        let idx1 = rng.gen_range(0..total);
+        let mut idx2 = rng.gen_range(0..total);
 
         // Ensure they are different
-        while idx1 == idx2 && subpop.len() > 1 {
-            idx2 = rng.gen_range(0..subpop.len());
+        while idx1 == idx2 && total > 1 {
+            idx2 = rng.gen_range(0..total);
         }
 
+        // Skip over the excluded individual
+        let skip = |idx: usize| idx + usize::from(excluded.is_some_and(|excluded| idx >= excluded));
+        let (idx1, idx2) = (skip(idx1), skip(idx2));
+
         // Return the one with better fitness
-        if subpop[idx1].biased_fitness <= subpop[idx2].biased_fitness {
-            &subpop[idx1]
+        if self.get_individual(idx1).biased_fitness <= self.get_individual(idx2).biased_fitness {
+            Ok(idx1)
         } else {
-            &subpop[idx2]
+            Ok(idx2)
+        }
+    }
+
+    /// Get an individual by its index in the feasible subpopulation followed by the infeasible one.
+    fn get_individual(&self, index: usize) -> &Individual {
+        let feasible_count = self.feasible_individuals.len();
+        if index < feasible_count {
+            &self.feasible_individuals[index]
+        } else {
+            &self.infeasible_individuals[index - feasible_count]
+        }
+    }
+
+    /// Check whether a solution has the structure of an individual of its subpopulation.
+    ///
+    /// The cached structure hashes of the population are compared first, so the check costs
+    /// one hash of the solution unless a hash matches.
+    pub fn contains_clone(&self, solution: &Solution) -> bool {
+        let hash = Individual::compute_structure_hash(solution);
+        let subpop = match solution.is_feasible {
+            true => &self.feasible_individuals,
+            false => &self.infeasible_individuals,
+        };
+        subpop
+            .iter()
+            .any(|individual| individual.is_clone_of_solution(solution, hash))
+    }
+
+    /// Count one more generation in the age of every individual.
+    pub fn increment_ages(&mut self) {
+        for individual in self
+            .feasible_individuals
+            .iter_mut()
+            .chain(self.infeasible_individuals.iter_mut())
+        {
+            individual.age = individual.age.saturating_add(1);
         }
     }
 
@@ -266,93 +541,239 @@ impl Population {
     }
 
     /// Select survivors for a subpopulation.
+    ///
+    /// With a maximum age, individuals older than it are removed first, except the `n_elite`
+    /// best ones and at least the best one, and as many more as needed to leave two parents
+    /// across both subpopulations. The others are removed one at a time, clones first
+    /// and then by worst biased fitness. Ranks are computed once and then updated after every
+    /// removal, recomputing only the diversity contributions that counted the removed
+    /// individual among the closest, so each decision uses up-to-date fitness.
     fn select_survivors_for_subpop(&mut self, feasible: bool) {
-        let individuals = match feasible {
-            true => &mut self.feasible_individuals,
-            false => &mut self.infeasible_individuals,
-        };
-        if individuals.len() <= self.min_pop_size {
-            return;
+        if let Some(max_age) = self.max_age {
+            self.update_feasibility_ranks(feasible);
+            let other_size = self.subpop_mut(!feasible).len();
+            let n_elite = self.n_elite.max(1);
+            let individuals = match feasible {
+                true => &mut self.feasible_individuals,
+                false => &mut self.infeasible_individuals,
+            };
+            let (mut old, mut young): (Vec<Individual>, Vec<Individual>) =
+                individuals.drain(..).partition(|individual| {
+                    individual.age > max_age && individual.rank_feasibility >= n_elite
+                });
+
+            // Spare the best old individuals while fewer than two parents would be left
+            while young.len() + other_size < 2 && !old.is_empty() {
+                young.push(old.remove(0));
+            }
+            *individuals = young;
+            for removed in old {
+                Self::forget_individual(&mut self.common_pairs, removed.id);
+            }
         }
 
-        // Sort by biased fitness
-        individuals.sort_by(|a, b| a.biased_fitness.partial_cmp(&b.biased_fitness).unwrap());
+        self.update_subpop_ranks(feasible);
+        if self.subpop_mut(feasible).len() <= self.min_pop_size {
+            return;
+        }
 
-        // Find and remove clones first
-        let mut to_remove = HashSet::new();
+        let n_closest = self.n_closest;
+        let individuals = match feasible {
+            true => &self.feasible_individuals,
+            false => &self.infeasible_individuals,
+        };
+        let mut clone_counts = Self::count_structures(individuals.iter());
+        let mut contributions: Vec<(f64, usize)> = individuals
+            .iter()
+            .map(|individual| {
+                Self::closest_contribution(self.common_pairs.get(&individual.id), n_closest)
+            })
+            .collect();
 
-        for i in 0..individuals.len() {
-            if to_remove.contains(&i) {
-                continue;
+        while self.subpop_mut(feasible).len() > self.min_pop_size {
+            let individuals = match feasible {
+                true => &mut self.feasible_individuals,
+                false => &mut self.infeasible_individuals,
+            };
+            let has_clone = |i: usize| {
+                clone_counts[&individuals[i].structure_hash()] > 1
+                    && Self::has_clone(individuals.iter(), i)
+            };
+
+            // Prefer removing clones, then the worst biased fitness
+            let worst = (0..individuals.len())
+                .map(|i| (i, has_clone(i)))
+                .max_by(|&(a, a_clone), &(b, b_clone)| {
+                    a_clone.cmp(&b_clone).then(
+                        individuals[a]
+                            .biased_fitness
+                            .total_cmp(&individuals[b].biased_fitness),
+                    )
+                })
+                .map(|(i, _)| i)
+                .unwrap();
+
+            let removed = individuals.remove(worst);
+            contributions.remove(worst);
+            *clone_counts.get_mut(&removed.structure_hash()).unwrap() -= 1;
+
+            // The survivors stay sorted by cost, so only the later ranks shift
+            for (k, individual) in individuals.iter_mut().enumerate().skip(worst) {
+                individual.rank_feasibility = k;
             }
 
-            for j in (i + 1)..individuals.len() {
-                if !to_remove.contains(&j) && individuals[i].is_clone_of(&individuals[j]) {
-                    to_remove.insert(j);
-
-                    if individuals.len() - to_remove.len() <= self.min_pop_size {
-                        break;
-                    }
+            // Only a contribution counting the removed individual among the closest changes
+            let row = Self::forget_individual(&mut self.common_pairs, removed.id);
+            for (k, individual) in individuals.iter().enumerate() {
+                if row
+                    .get(&individual.id)
+                    .is_some_and(|&common| common >= contributions[k].1)
+                {
+                    contributions[k] = Self::closest_contribution(
+                        self.common_pairs.get(&individual.id),
+                        n_closest,
+                    );
                 }
             }
 
-            if individuals.len() - to_remove.len() <= self.min_pop_size {
-                break;
-            }
+            let values: Vec<f64> = contributions.iter().map(|&(value, _)| value).collect();
+            Self::rank_diversity(individuals, &values);
+            self.update_biased_fitness(feasible);
         }
+    }
 
-        // If we still need to remove more, remove worst individuals by biased fitness
-        let mut i = individuals.len() - 1;
-        while individuals.len() - to_remove.len() > self.min_pop_size && i > 0 {
-            if !to_remove.contains(&i) {
-                to_remove.insert(i);
-            }
-            i -= 1;
+    /// Count the individuals sharing each structure hash.
+    fn count_structures<'a>(
+        individuals: impl Iterator<Item = &'a Individual>,
+    ) -> HashMap<u64, usize> {
+        let mut counts = HashMap::new();
+        for individual in individuals {
+            *counts.entry(individual.structure_hash()).or_insert(0) += 1;
         }
+        counts
+    }
 
-        // Remove individuals in reverse order to maintain indices
-        let mut indices: Vec<_> = to_remove.into_iter().collect();
-        indices.sort_unstable_by(|a, b| b.cmp(a));
+    /// Check whether the individual at `index` is a clone of another one, to confirm a
+    /// structure hash shared with `count_structures`.
+    fn has_clone<'a>(
+        individuals: impl Iterator<Item = &'a Individual> + Clone,
+        index: usize,
+    ) -> bool {
+        let Some(individual) = individuals.clone().nth(index) else {
+            return false;
+        };
+        individuals
+            .enumerate()
+            .any(|(i, other)| i != index && other.is_clone_of(individual))
+    }
 
-        for idx in indices {
-            individuals.remove(idx);
-        }
+    /// Record the feasibility of a newly educated offspring for the next penalty adjustment.
+    pub fn record_offspring(&mut self, feasible: bool) {
+        // Capacity is the only constraint, so its feasibility is the overall feasibility
+        self.penalties.capacity.record(feasible);
     }
 
-    /// Adjust the capacity penalty parameter.
+    /// Adjust the penalty of each constraint to its own feasibility ratio.
+    ///
+    /// The ratio is that of the offspring recorded since the last adjustment, or that of the
+    /// current population if none was recorded.
     pub fn adjust_penalties(&mut self) {
-        // Calculate current feasible ratio
         let total = self.get_pop_size();
-        let feasible_count = self.feasible_individuals.len();
 
-        if total == 0 {
+        if total == 0 && self.penalties.capacity.recorded_ratio().is_none() {
             return;
         }
 
-        let current_ratio = feasible_count as f64 / total as f64;
+        let population_ratio = self.feasible_individuals.len() as f64 / total.max(1) as f64;
+        self.penalties.capacity.adjust_to_recorded(population_ratio);
+    }
+
+    /// Compute diversity statistics over both subpopulations.
+    pub fn diversity_stats(&self) -> DiversityStats {
+        let individuals: Vec<&Individual> = self
+            .feasible_individuals
+            .iter()
+            .chain(self.infeasible_individuals.iter())
+            .collect();
+        let count = individuals.len();
+
+        // Pairwise distances and clones
+        let mut distance_sum = 0.0;
+        let mut pair_count = 0;
+
+        for i in 0..count {
+            for j in (i + 1)..count {
+                distance_sum += individuals[i].broken_pairs_distance(individuals[j]);
+                pair_count += 1;
+            }
+        }
+
+        let clone_counts = Self::count_structures(individuals.iter().copied());
+        let clone_count = (0..count)
+            .filter(|&i| {
+                clone_counts[&individuals[i].structure_hash()] > 1
+                    && Self::has_clone(individuals.iter().copied(), i)
+            })
+            .count();
 
-        // Adjust penalty
-        if current_ratio < self.target_feasible_ratio {
-            // Increase penalty to favor feasible solutions
-            self.capacity_penalty *= 1.2;
+        // Spread of costs
+        let costs: Vec<f64> = individuals.iter().map(|ind| ind.get_cost()).collect();
+        let (min_cost, max_cost, cost_std_dev) = if costs.is_empty() {
+            (0.0, 0.0, 0.0)
         } else {
-            // Decrease penalty to explore more infeasible solutions
-            self.capacity_penalty /= 1.2;
+            let mean = costs.iter().sum::<f64>() / count as f64;
+            let variance = costs.iter().map(|c| (c - mean).powi(2)).sum::<f64>() / count as f64;
+            (
+                costs.iter().cloned().fold(f64::INFINITY, f64::min),
+                costs.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+                variance.sqrt(),
+            )
+        };
+
+        DiversityStats {
+            average_broken_pairs_distance: if pair_count > 0 {
+                distance_sum / pair_count as f64
+            } else {
+                0.0
+            },
+            clone_count,
+            min_cost,
+            max_cost,
+            cost_std_dev,
+            feasible_count: self.feasible_individuals.len(),
+            infeasible_count: self.infeasible_individuals.len(),
         }
+    }
+
+    /// Record the cost, tour, ranks and diversity of every individual.
+    pub fn snapshot(&self) -> PopulationSnapshot {
+        let individuals = self
+            .feasible_individuals
+            .iter()
+            .chain(self.infeasible_individuals.iter())
+            .map(|individual| IndividualSnapshot {
+                cost: individual.get_cost(),
+                feasible: individual.is_feasible(),
+                giant_tour: individual.solution.giant_tour.clone(),
+                rank_feasibility: individual.rank_feasibility,
+                rank_diversity: individual.rank_diversity,
+                biased_fitness: individual.biased_fitness,
+                age: individual.age,
+                diversity_contribution: self.diversity_contribution(individual.id),
+            })
+            .collect();
 
-        // Ensure penalty doesn't get too small
-        self.capacity_penalty = self.capacity_penalty.max(0.1);
+        PopulationSnapshot {
+            capacity_penalty: self.penalties.capacity.coefficient,
+            individuals,
+        }
     }
 
     /// Get the best feasible solution in the population.
     pub fn get_best_feasible_solution(&self) -> Option<&Solution> {
         self.feasible_individuals
             .iter()
-            .min_by(|a, b| {
-                a.get_cost()
-                    .partial_cmp(&b.get_cost())
-                    .unwrap_or(std::cmp::Ordering::Equal)
-            })
+            .min_by(|a, b| a.get_cost().total_cmp(&b.get_cost()))
             .map(|x| &x.solution)
     }
 }
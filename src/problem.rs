@@ -1,10 +1,23 @@
 //! Problem definition and data structures for CVRP.
 
+use crate::compression;
+use crate::costs::{self, CostDelta};
+use crate::distances::{CompactDistances, DistanceStorage, SparseDistances};
+use crate::dynamic::{NodeMapping, ProblemChange};
+use crate::error::HgsError;
+use crate::neighbors::NeighborLists;
+use crate::objective::{BalanceMeasure, ObjectiveModel, RouteChange, WorkloadBalance};
+use crate::pinning::Pins;
+use crate::solution::Solution;
+use crate::stochastic::DemandUncertainty;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::f64;
+use std::fmt;
 use std::fs::File;
-use std::io::{self, BufRead};
+use std::io::{BufRead, BufReader};
 use std::path::Path;
+use std::sync::Arc;
 
 /// Represents a node (customer or depot) in the CVRP.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +49,56 @@ impl Node {
     }
 }
 
+/// Reason why problem data cannot be solved.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProblemError {
+    /// The vehicle capacity is not a positive number
+    InvalidCapacity(f64),
+    /// A node has a coordinate that is not a finite number
+    InvalidCoordinates { node: usize },
+    /// A customer has a negative or undefined demand
+    NegativeDemand { customer: usize, demand: f64 },
+    /// A customer needs more than a vehicle can carry
+    DemandExceedsCapacity {
+        customer: usize,
+        demand: f64,
+        capacity: f64,
+    },
+    /// Demands are divided into chunks that are not positive or do not fit in a vehicle
+    InvalidChunkSize { size: f64, capacity: f64 },
+}
+
+impl fmt::Display for ProblemError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProblemError::InvalidCapacity(capacity) => {
+                write!(f, "vehicle capacity {capacity} is not positive")
+            }
+            ProblemError::InvalidCoordinates { node } => {
+                write!(f, "node {node} has invalid coordinates")
+            }
+            ProblemError::NegativeDemand { customer, demand } => {
+                write!(f, "customer {customer} has invalid demand {demand}")
+            }
+            ProblemError::DemandExceedsCapacity {
+                customer,
+                demand,
+                capacity,
+            } => write!(
+                f,
+                "customer {customer} has demand {demand}, more than the vehicle capacity {capacity} \
+                 (use DemandSplit to serve it with several visits)"
+            ),
+            ProblemError::InvalidChunkSize { size, capacity } => write!(
+                f,
+                "chunk size {size} is not positive or exceeds the vehicle capacity {capacity}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ProblemError {}
+
 /// Represents a CVRP problem instance.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Problem {
@@ -44,7 +107,130 @@ pub struct Problem {
     pub depot_index: usize,
     pub vehicle_capacity: f64,
     pub max_vehicles: Option<usize>,
+    /// Distances between the nodes, empty while they are kept in `distance_storage`
     pub distance_matrix: Vec<Vec<f64>>,
+    /// Reduced storage of the distances instead of `distance_matrix`, for large instances
+    #[serde(default)]
+    pub distance_storage: Option<DistanceStorage>,
+    /// Whether distances are rounded to integers (CVRPLIB convention)
+    #[serde(default)]
+    pub integer_distances: bool,
+    /// Tolerance used when comparing costs and loads
+    #[serde(default = "default_tolerance")]
+    pub tolerance: f64,
+    /// Whether node coordinates are meaningful (false for matrix-only instances)
+    #[serde(default = "default_has_coordinates")]
+    pub has_coordinates: bool,
+    /// Whether distances grow with the Euclidean distance between the coordinates, so that
+    /// neighbors can be found with a spatial index (false for explicit matrices)
+    #[serde(default)]
+    pub euclidean_distances: bool,
+    /// Fixed cost added for every non-empty route
+    #[serde(default)]
+    pub route_fixed_cost: f64,
+    /// Cost of serving each node, indexed like `nodes` (empty if serving is free)
+    #[serde(default)]
+    pub service_costs: Vec<f64>,
+    /// Customers pinned to a route or to a fixed sequence
+    #[serde(default)]
+    pub pins: Pins,
+    /// Largest number of customers a route may visit, if limited
+    #[serde(default)]
+    pub max_stops_per_route: Option<usize>,
+    /// Demand distributions, when capacity is a chance constraint
+    #[serde(default)]
+    pub demand_uncertainty: Option<DemandUncertainty>,
+    /// Travel speed and service times, used to schedule arrival times
+    #[serde(default)]
+    pub durations: Option<Durations>,
+    /// Custom cost term added to every evaluation and move delta
+    #[serde(skip)]
+    pub objective: Option<Arc<dyn ObjectiveModel>>,
+}
+
+/// Travel and service durations of the routes of a problem.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Durations {
+    /// Distance travelled per time unit
+    pub speed: f64,
+    /// Time spent serving each node, indexed like `nodes` (empty if serving is instant)
+    pub service_times: Vec<f64>,
+}
+
+impl Durations {
+    /// Get the time needed to travel a distance.
+    pub fn travel_time(&self, distance: f64) -> f64 {
+        distance / self.speed
+    }
+
+    /// Get the time spent serving a node.
+    pub fn service_time(&self, node: usize) -> f64 {
+        self.service_times.get(node).copied().unwrap_or(0.0)
+    }
+}
+
+fn default_tolerance() -> f64 {
+    costs::DEFAULT_TOLERANCE
+}
+
+fn default_has_coordinates() -> bool {
+    true
+}
+
+/// Storage layout of an explicit TSPLIB `EDGE_WEIGHT_SECTION`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeWeightFormat {
+    /// All `n * n` weights, row by row
+    FullMatrix,
+    /// Weights above the diagonal, row by row
+    UpperRow,
+    /// Weights below and on the diagonal, row by row
+    LowerDiagRow,
+}
+
+impl EdgeWeightFormat {
+    /// Parse a TSPLIB `EDGE_WEIGHT_FORMAT` value.
+    pub fn from_tsplib(value: &str) -> Option<Self> {
+        match value {
+            "FULL_MATRIX" => Some(EdgeWeightFormat::FullMatrix),
+            "UPPER_ROW" => Some(EdgeWeightFormat::UpperRow),
+            "LOWER_DIAG_ROW" => Some(EdgeWeightFormat::LowerDiagRow),
+            _ => None,
+        }
+    }
+
+    /// Number of weights stored for `n` nodes.
+    pub fn weight_count(self, n: usize) -> usize {
+        match self {
+            EdgeWeightFormat::FullMatrix => n * n,
+            EdgeWeightFormat::UpperRow => n * n.saturating_sub(1) / 2,
+            EdgeWeightFormat::LowerDiagRow => n * (n + 1) / 2,
+        }
+    }
+
+    /// Expand the stored weights into a full `n * n` distance matrix.
+    ///
+    /// Partial formats describe symmetric instances, so each weight is mirrored.
+    pub fn build_matrix(self, weights: &[f64], n: usize) -> Vec<Vec<f64>> {
+        let mut matrix = vec![vec![0.0; n]; n];
+        let cells = (0..n).flat_map(|i| {
+            let columns = match self {
+                EdgeWeightFormat::FullMatrix => 0..n,
+                EdgeWeightFormat::UpperRow => i + 1..n,
+                EdgeWeightFormat::LowerDiagRow => 0..i + 1,
+            };
+            columns.map(move |j| (i, j))
+        });
+
+        for ((i, j), &weight) in cells.zip(weights) {
+            matrix[i][j] = weight;
+            if self != EdgeWeightFormat::FullMatrix {
+                matrix[j][i] = weight;
+            }
+        }
+
+        matrix
+    }
 }
 
 impl Problem {
@@ -57,7 +243,51 @@ impl Problem {
         max_vehicles: Option<usize>,
     ) -> Self {
         let distance_matrix = Self::compute_distance_matrix(&nodes);
+        Self::with_nodes(
+            name,
+            nodes,
+            depot_index,
+            vehicle_capacity,
+            max_vehicles,
+            distance_matrix,
+        )
+    }
 
+    /// Create a new CVRP problem storing only the distances from the depot and to the
+    /// `neighbor_count` closest customers of every customer, see `with_sparse_distances`.
+    ///
+    /// The full distance matrix is never built, so memory stays linear in the number of
+    /// nodes for a fixed neighbor count.
+    pub fn new_sparse(
+        name: String,
+        nodes: Vec<Node>,
+        depot_index: usize,
+        vehicle_capacity: f64,
+        max_vehicles: Option<usize>,
+        neighbor_count: usize,
+    ) -> Self {
+        let sparse = SparseDistances::new(&nodes, depot_index, neighbor_count, false);
+        let mut problem = Self::with_nodes(
+            name,
+            nodes,
+            depot_index,
+            vehicle_capacity,
+            max_vehicles,
+            Vec::new(),
+        );
+        problem.distance_storage = Some(DistanceStorage::Sparse(sparse));
+        problem
+    }
+
+    /// Create a problem with the given distance matrix and default settings.
+    fn with_nodes(
+        name: String,
+        nodes: Vec<Node>,
+        depot_index: usize,
+        vehicle_capacity: f64,
+        max_vehicles: Option<usize>,
+        distance_matrix: Vec<Vec<f64>>,
+    ) -> Self {
         Problem {
             name,
             nodes,
@@ -65,12 +295,586 @@ impl Problem {
             vehicle_capacity,
             max_vehicles,
             distance_matrix,
+            distance_storage: None,
+            integer_distances: false,
+            tolerance: costs::DEFAULT_TOLERANCE,
+            has_coordinates: true,
+            euclidean_distances: true,
+            route_fixed_cost: 0.0,
+            service_costs: Vec::new(),
+            pins: Pins::default(),
+            max_stops_per_route: None,
+            demand_uncertainty: None,
+            durations: None,
+            objective: None,
+        }
+    }
+
+    /// Create a problem from the depot location and the `(x, y, demand)` of each customer.
+    ///
+    /// Customers get the node indices 1, 2, ... in the given order and their distances
+    /// follow from the coordinates. The data is checked with `validate`.
+    pub fn from_coordinates(
+        depot: (f64, f64),
+        customers: &[(f64, f64, f64)],
+        vehicle_capacity: f64,
+        max_vehicles: Option<usize>,
+    ) -> Result<Self, ProblemError> {
+        let nodes = std::iter::once(Node::new(0, depot.0, depot.1, 0.0, true))
+            .chain(
+                customers
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &(x, y, demand))| Node::new(i + 1, x, y, demand, false)),
+            )
+            .collect();
+
+        let problem = Problem::new(String::new(), nodes, 0, vehicle_capacity, max_vehicles);
+        problem.validate()?;
+        Ok(problem)
+    }
+
+    /// Check that the capacity is positive, coordinates are finite, and every customer has
+    /// a non-negative demand that fits in a vehicle.
+    pub fn validate(&self) -> Result<(), ProblemError> {
+        self.validate_data()?;
+
+        for customer in self.customers() {
+            let demand = self.nodes[customer].demand;
+            if demand - self.vehicle_capacity > self.tolerance {
+                return Err(ProblemError::DemandExceedsCapacity {
+                    customer,
+                    demand,
+                    capacity: self.vehicle_capacity,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check the data of the problem like `validate`, but let demands exceed the vehicle
+    /// capacity, since `DemandSplit` can serve them with several visits.
+    pub fn validate_data(&self) -> Result<(), ProblemError> {
+        if self.vehicle_capacity.is_nan() || self.vehicle_capacity <= 0.0 {
+            return Err(ProblemError::InvalidCapacity(self.vehicle_capacity));
+        }
+
+        for (index, node) in self.nodes.iter().enumerate() {
+            if !node.x.is_finite() || !node.y.is_finite() {
+                return Err(ProblemError::InvalidCoordinates { node: index });
+            }
+        }
+
+        for customer in self.customers() {
+            let demand = self.nodes[customer].demand;
+            if demand.is_nan() || demand < 0.0 {
+                return Err(ProblemError::NegativeDemand { customer, demand });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Group the nodes that share their coordinates, depot included, by increasing index.
+    ///
+    /// Co-located nodes are 0 apart, so their order in neighbor lists and sorts only depends
+    /// on their indices. Returns no groups for an instance without coordinates.
+    pub fn colocated_nodes(&self) -> Vec<Vec<usize>> {
+        if !self.has_coordinates {
+            return Vec::new();
+        }
+
+        // Adding 0 turns -0 into 0, so that both hash alike
+        let mut groups: HashMap<(u64, u64), Vec<usize>> = HashMap::new();
+        for (index, node) in self.nodes.iter().enumerate() {
+            let key = ((node.x + 0.0).to_bits(), (node.y + 0.0).to_bits());
+            groups.entry(key).or_default().push(index);
+        }
+        let mut groups: Vec<Vec<usize>> = groups
+            .into_values()
+            .filter(|group| group.len() > 1)
+            .collect();
+        groups.sort();
+        groups
+    }
+
+    /// Scale each edge by a cost multiplier, e.g. to account for tolls.
+    ///
+    /// The distance matrix then holds travel costs, which every evaluation and move delta
+    /// uses. Multipliers should be symmetric, as 2-opt reverses route segments.
+    pub fn with_edge_cost_multipliers(mut self, multipliers: &[Vec<f64>]) -> Self {
+        self.euclidean_distances = false;
+        self.edit_distances(|matrix| {
+            for (row, multiplier_row) in matrix.iter_mut().zip(multipliers) {
+                for (distance, &multiplier) in row.iter_mut().zip(multiplier_row) {
+                    *distance *= multiplier;
+                }
+            }
+        });
+        self
+    }
+
+    /// Set the cost of serving each node, indexed like `nodes`.
+    ///
+    /// Every customer is served exactly once, so service costs are part of the solution cost
+    /// but never change the delta of a move.
+    pub fn with_service_costs(mut self, service_costs: Vec<f64>) -> Self {
+        debug_assert_eq!(service_costs.len(), self.nodes.len());
+        self.service_costs = service_costs;
+        self
+    }
+
+    /// Add a custom term to the objective, consulted by every evaluation and move delta.
+    pub fn with_objective(mut self, objective: impl ObjectiveModel + 'static) -> Self {
+        self.objective = Some(Arc::new(objective));
+        self
+    }
+
+    /// Penalize unbalanced route distances with the given weight.
+    pub fn with_workload_balance(self, weight: f64, measure: BalanceMeasure) -> Self {
+        self.with_objective(WorkloadBalance::new(weight, measure))
+    }
+
+    /// Pin a customer to the route with the given label.
+    ///
+    /// Customers with the same label are visited by one route, different labels by different
+    /// routes, and no operator moves a pinned customer out of its route.
+    pub fn pin_customer(&mut self, customer: usize, route: usize) {
+        debug_assert!(self.is_customer(customer));
+        self.pins.pin_customer(self.nodes.len(), customer, route);
+    }
+
+    /// Pin customers to the route with the given label, visited consecutively in this order.
+    pub fn pin_sequence(&mut self, customers: &[usize], route: usize) {
+        debug_assert!(customers.iter().all(|&c| self.is_customer(c)));
+        self.pins.pin_sequence(self.nodes.len(), customers, route);
+    }
+
+    /// Evaluate the custom objective term of a solution, 0 without one.
+    pub fn objective_cost(&self, solution: &Solution) -> f64 {
+        self.objective
+            .as_ref()
+            .map_or(0.0, |objective| objective.evaluate(solution, self))
+    }
+
+    /// Evaluate every solution of a batch with `capacity_penalty`, as `Solution::evaluate` does.
+    pub fn evaluate_batch(&self, solutions: &mut [Solution], capacity_penalty: f64) {
+        for solution in solutions {
+            solution.evaluate(self, capacity_penalty);
+        }
+    }
+
+    /// Evaluate every solution of a batch like `evaluate_batch`, on parallel threads.
+    ///
+    /// The solutions are split into one contiguous chunk per available thread, so small
+    /// batches of short routes may be faster to evaluate on the current thread.
+    pub fn evaluate_batch_parallel(&self, solutions: &mut [Solution], capacity_penalty: f64) {
+        let threads = std::thread::available_parallelism().map_or(1, usize::from);
+        let chunk_size = solutions.len().div_ceil(threads).max(1);
+        std::thread::scope(|scope| {
+            for chunk in solutions.chunks_mut(chunk_size) {
+                scope.spawn(move || self.evaluate_batch(chunk, capacity_penalty));
+            }
+        });
+    }
+
+    /// Compute the change of the custom objective term caused by a move, 0 without one.
+    pub fn objective_delta(&self, solution: &Solution, changes: &[RouteChange]) -> f64 {
+        self.objective
+            .as_ref()
+            .map_or(0.0, |objective| objective.delta(solution, self, changes))
+    }
+
+    /// Get the cost of serving a node.
+    pub fn get_service_cost(&self, node: usize) -> f64 {
+        self.service_costs.get(node).copied().unwrap_or(0.0)
+    }
+
+    /// Add a fixed cost for every route used, trading off fleet size against distance.
+    pub fn with_route_fixed_cost(mut self, cost: f64) -> Self {
+        self.route_fixed_cost = cost;
+        self
+    }
+
+    /// Minimize the number of routes first and the distance second.
+    ///
+    /// A solution visits at most two edges per customer, so a fixed cost above
+    /// `2 * n * max_distance` makes saving one route worth more than any distance change.
+    pub fn with_vehicle_count_objective(self) -> Self {
+        let bound = 2.0 * self.get_customer_count() as f64 * self.max_distance();
+        self.with_route_fixed_cost(bound.max(1.0) + 1.0)
+    }
+
+    /// Replace the Euclidean distances with an explicit distance matrix, stored in full
+    /// precision.
+    pub fn with_distance_matrix(mut self, distance_matrix: Vec<Vec<f64>>) -> Self {
+        debug_assert!(
+            distance_matrix.len() == self.nodes.len()
+                && distance_matrix
+                    .iter()
+                    .all(|row| row.len() == self.nodes.len()),
+            "distance matrix must be square with one row per node"
+        );
+        self.distance_matrix = distance_matrix;
+        self.distance_storage = None;
+        self.euclidean_distances = false;
+        self.integer_distances = false;
+        self
+    }
+
+    /// Store the distances in `f32`, halving the memory of the matrix of large instances.
+    ///
+    /// Costs are still summed in `f64`, but distances keep about seven significant digits.
+    /// Distances already in a reduced storage are kept as they are.
+    pub fn with_compact_distances(mut self) -> Self {
+        if self.distance_storage.is_none() {
+            let matrix = std::mem::take(&mut self.distance_matrix);
+            self.distance_storage = Some(DistanceStorage::Compact(CompactDistances::from_matrix(
+                &matrix,
+            )));
+        }
+        self
+    }
+
+    /// Store only the distances from the depot and to the `neighbor_count` closest customers
+    /// of every customer, computing the others from the coordinates when needed.
+    ///
+    /// The granular neighborhoods mostly evaluate these pairs, so a neighbor count at least
+    /// the granularity cuts memory from O(n^2) to O(n k) at little cost. Editing the
+    /// distances, e.g. with edge cost multipliers, stores them in full again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the distances are not the Euclidean distances between the coordinates.
+    pub fn with_sparse_distances(mut self, neighbor_count: usize) -> Self {
+        assert!(
+            self.euclidean_distances && self.has_coordinates,
+            "sparse distances are computed from the coordinates"
+        );
+        self.distance_matrix = Vec::new();
+        self.rebuild_sparse_distances(neighbor_count);
+        self
+    }
+
+    /// Recompute the sparse distances after the nodes changed.
+    fn rebuild_sparse_distances(&mut self, neighbor_count: usize) {
+        self.distance_storage = Some(DistanceStorage::Sparse(SparseDistances::new(
+            &self.nodes,
+            self.depot_index,
+            neighbor_count,
+            self.integer_distances,
+        )));
+    }
+
+    /// Get the number of closest customers whose distances are stored, with sparse distances.
+    pub fn sparse_neighbor_count(&self) -> Option<usize> {
+        match &self.distance_storage {
+            Some(DistanceStorage::Sparse(sparse)) => Some(sparse.neighbor_count()),
+            _ => None,
+        }
+    }
+
+    /// Get the memory allocated for the distances, in bytes.
+    pub fn distance_memory_usage(&self) -> usize {
+        let rows = self.distance_matrix.capacity() * std::mem::size_of::<Vec<f64>>()
+            + self
+                .distance_matrix
+                .iter()
+                .map(|row| row.capacity() * std::mem::size_of::<f64>())
+                .sum::<usize>();
+        rows + self
+            .distance_storage
+            .as_ref()
+            .map_or(0, DistanceStorage::memory_usage)
+    }
+
+    /// Get the largest distance between two nodes.
+    pub fn max_distance(&self) -> f64 {
+        (0..self.nodes.len())
+            .flat_map(|from| (0..self.nodes.len()).map(move |to| (from, to)))
+            .fold(0.0_f64, |max, (from, to)| {
+                max.max(self.get_distance(from, to))
+            })
+    }
+
+    /// Edit the distances as a full matrix, keeping their precision.
+    ///
+    /// Sparse distances are stored in full afterwards, as edits need not be Euclidean.
+    fn edit_distances(&mut self, edit: impl FnOnce(&mut Vec<Vec<f64>>)) {
+        match self.distance_storage.take() {
+            Some(DistanceStorage::Compact(compact)) => {
+                let mut matrix = compact.to_matrix();
+                edit(&mut matrix);
+                self.distance_storage = Some(DistanceStorage::Compact(
+                    CompactDistances::from_matrix(&matrix),
+                ));
+            }
+            Some(DistanceStorage::Sparse(sparse)) => {
+                self.distance_matrix = sparse.to_matrix();
+                edit(&mut self.distance_matrix);
+            }
+            None => edit(&mut self.distance_matrix),
         }
     }
 
+    /// Round all distances to the nearest integer, as done for CVRPLIB scoring.
+    ///
+    /// The rounded distances are still stored and summed as `f64`; there is no integer
+    /// cost type. Only the distance part of a move is rounded again when it is compared
+    /// (see `is_cost_improvement`), while penalties, fixed costs and totals remain
+    /// floating point and may differ in the last bits across summation orders.
+    pub fn with_integer_distances(mut self) -> Self {
+        if let Some(neighbor_count) = self.sparse_neighbor_count() {
+            self.integer_distances = true;
+            return self.with_sparse_distances(neighbor_count);
+        }
+        self.edit_distances(|matrix| {
+            for row in matrix.iter_mut() {
+                for distance in row.iter_mut() {
+                    *distance = distance.round();
+                }
+            }
+        });
+        self.integer_distances = true;
+        self
+    }
+
+    /// Set the tolerance used when comparing costs and loads.
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Limit the number of customers each route may visit, e.g. for driver stop limits.
+    ///
+    /// Split never builds longer routes, and relocate and 2-opt* reject moves that would
+    /// exceed the limit. Swap and SWAP* exchange customers one for one, so they keep the
+    /// number of stops of every route.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stops` is 0.
+    pub fn with_max_stops_per_route(mut self, stops: usize) -> Self {
+        assert!(stops > 0, "routes must be allowed at least one stop");
+        self.max_stops_per_route = Some(stops);
+        self
+    }
+
+    /// Treat demands as normally distributed around the node demands, with the given
+    /// standard deviations indexed like `nodes`, and require the load of each route to stay
+    /// within the capacity with probability `service_level`.
+    ///
+    /// Every evaluation, Split, and move delta then penalizes the excess of the load
+    /// quantile over the capacity instead of the excess of the mean load.
+    pub fn with_demand_uncertainty(mut self, std_devs: Vec<f64>, service_level: f64) -> Self {
+        debug_assert_eq!(std_devs.len(), self.nodes.len());
+        self.demand_uncertainty = Some(DemandUncertainty::new(std_devs, service_level));
+        self
+    }
+
+    /// Travel at the given speed and serve each node, indexed like `nodes`, for the given
+    /// time, so that schedules report arrival times.
+    ///
+    /// Durations do not change the cost of a solution.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the speed is not positive.
+    pub fn with_durations(mut self, speed: f64, service_times: Vec<f64>) -> Self {
+        assert!(speed > 0.0, "speed {speed} is not positive");
+        debug_assert!(service_times.is_empty() || service_times.len() == self.nodes.len());
+        self.durations = Some(Durations {
+            speed,
+            service_times,
+        });
+        self
+    }
+
+    /// Get the variance of the demand of a node, 0 for deterministic demands.
+    pub fn demand_variance(&self, node: usize) -> f64 {
+        self.demand_uncertainty
+            .as_ref()
+            .map_or(0.0, |uncertainty| uncertainty.variance(node))
+    }
+
+    /// Get the capacity excess of a route load with the given mean and variance.
+    ///
+    /// With stochastic demands, the load quantile at the service level is compared to the
+    /// capacity.
+    pub fn load_excess(&self, load: f64, variance: f64) -> f64 {
+        let load = self
+            .demand_uncertainty
+            .as_ref()
+            .map_or(load, |uncertainty| uncertainty.quantile(load, variance));
+        (load - self.vehicle_capacity).max(0.0)
+    }
+
+    /// Check whether a route may visit the given number of customers.
+    pub fn allows_stops(&self, stops: usize) -> bool {
+        self.max_stops_per_route.is_none_or(|max| stops <= max)
+    }
+
+    /// Add or remove a customer, or change its demand, and return where the nodes went.
+    ///
+    /// Removing a customer renumbers the nodes after it, including the depot and the pins.
+    /// Without explicit distances, those of an added customer follow from its coordinates.
+    /// An added customer has a deterministic demand and no service time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the change refers to a node that is not a customer, or if explicit distances
+    /// do not cover every existing node.
+    pub fn apply_change(&mut self, change: &ProblemChange) -> NodeMapping {
+        let node_count = self.nodes.len();
+        match change {
+            ProblemChange::AddCustomer { node, distances } => {
+                // Sparse distances stay valid for a located customer and are rebuilt below
+                let sparse = self.sparse_neighbor_count().filter(|_| distances.is_none());
+                let distances = match distances {
+                    Some(distances) => {
+                        assert_eq!(distances.len(), node_count, "missing distances");
+                        self.euclidean_distances = false;
+                        distances.clone()
+                    }
+                    None => self
+                        .nodes
+                        .iter()
+                        .map(|other| node.distance(other))
+                        .collect(),
+                };
+                let round = |d: f64| if self.integer_distances { d.round() } else { d };
+
+                let mut row: Vec<f64> = distances.iter().map(|&d| round(d)).collect();
+                row.push(0.0);
+                if sparse.is_none() {
+                    self.edit_distances(|matrix| {
+                        for (other, &distance) in matrix.iter_mut().zip(&row) {
+                            other.push(distance);
+                        }
+                        matrix.push(row);
+                    });
+                }
+
+                let mut node = node.clone();
+                node.is_depot = false;
+                self.nodes.push(node);
+                if let Some(neighbor_count) = sparse {
+                    self.rebuild_sparse_distances(neighbor_count);
+                }
+                if !self.service_costs.is_empty() {
+                    self.service_costs.push(0.0);
+                }
+                if let Some(uncertainty) = &mut self.demand_uncertainty {
+                    uncertainty.std_devs.resize(node_count + 1, 0.0);
+                }
+                if let Some(durations) = &mut self.durations {
+                    if !durations.service_times.is_empty() {
+                        durations.service_times.push(0.0);
+                    }
+                }
+
+                NodeMapping {
+                    added: Some(node_count),
+                    ..NodeMapping::identity(node_count)
+                }
+            }
+            ProblemChange::RemoveCustomer(customer) => {
+                let customer = *customer;
+                assert!(self.is_customer(customer), "{customer} is not a customer");
+
+                let kept: Vec<usize> = (0..node_count).filter(|&n| n != customer).collect();
+                let sparse = self.sparse_neighbor_count();
+                self.nodes.remove(customer);
+                if sparse.is_none() {
+                    self.edit_distances(|matrix| {
+                        matrix.remove(customer);
+                        for row in matrix.iter_mut() {
+                            row.remove(customer);
+                        }
+                    });
+                }
+                if !self.service_costs.is_empty() {
+                    self.service_costs.remove(customer);
+                }
+                if let Some(uncertainty) = &mut self.demand_uncertainty {
+                    if customer < uncertainty.std_devs.len() {
+                        uncertainty.std_devs.remove(customer);
+                    }
+                }
+                if let Some(durations) = &mut self.durations {
+                    if customer < durations.service_times.len() {
+                        durations.service_times.remove(customer);
+                    }
+                }
+                if self.depot_index > customer {
+                    self.depot_index -= 1;
+                }
+                self.pins = self.pins.for_nodes(&kept);
+                if let Some(neighbor_count) = sparse {
+                    self.rebuild_sparse_distances(neighbor_count);
+                }
+
+                let mut new_index = vec![None; node_count];
+                for (new, &old) in kept.iter().enumerate() {
+                    new_index[old] = Some(new);
+                }
+                NodeMapping {
+                    new_index,
+                    added: None,
+                }
+            }
+            ProblemChange::ChangeDemand { customer, demand } => {
+                assert!(self.is_customer(*customer), "{customer} is not a customer");
+                self.nodes[*customer].demand = *demand;
+                NodeMapping::identity(node_count)
+            }
+        }
+    }
+
+    /// Compute the `granularity` closest customers of every customer.
+    ///
+    /// The lists can be shared by several local search instances through an `Arc`.
+    pub fn neighbor_lists(&self, granularity: usize) -> NeighborLists {
+        NeighborLists::new(self, granularity)
+    }
+
+    /// Get the distance between two nodes rounded to the nearest integer.
+    pub fn get_integer_distance(&self, from: usize, to: usize) -> i64 {
+        self.get_distance(from, to).round() as i64
+    }
+
+    /// Check whether a cost change is a strict improvement by more than the tolerance.
+    ///
+    /// The change may mix distances with real-valued penalties and objective terms, so the
+    /// tolerance applies in every mode; `is_cost_improvement` compares the rounded distance
+    /// part of a move with integer distances.
+    pub fn is_improvement(&self, delta: f64) -> bool {
+        costs::approx_lt(delta, 0.0, self.tolerance)
+    }
+
+    /// Check whether the cost change of a move is a strict improvement.
+    ///
+    /// With integer distances the distance change is rounded to the nearest integer, so moves
+    /// changing only distances are decided without an epsilon. Any other terms are added back
+    /// as floating point and compared with the tolerance.
+    pub fn is_cost_improvement(&self, delta: CostDelta) -> bool {
+        if !self.integer_distances || !delta.distance.is_finite() {
+            return self.is_improvement(delta.total());
+        }
+        let distance = delta.distance.round() as i64;
+        if delta.other == 0.0 {
+            return distance < 0;
+        }
+        costs::approx_lt(distance as f64 + delta.other, 0.0, self.tolerance)
+    }
+
     /// Calculate the distance between two customer indices.
     pub fn get_distance(&self, from: usize, to: usize) -> f64 {
-        self.distance_matrix[from][to]
+        match &self.distance_storage {
+            Some(storage) => storage.get(from, to),
+            None => self.distance_matrix[from][to],
+        }
     }
 
     /// Get the number of customers (excluding the depot).
@@ -78,6 +882,18 @@ impl Problem {
         self.nodes.len() - 1
     }
 
+    /// Iterate over the node indices of all customers.
+    ///
+    /// Giant tours and routes contain exactly these indices; the depot never appears in them.
+    pub fn customers(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.nodes.len()).filter(move |&i| i != self.depot_index)
+    }
+
+    /// Check whether a node index refers to a customer.
+    pub fn is_customer(&self, index: usize) -> bool {
+        index < self.nodes.len() && index != self.depot_index
+    }
+
     /// Get the depot node.
     pub fn get_depot(&self) -> &Node {
         &self.nodes[self.depot_index]
@@ -120,46 +936,233 @@ impl Problem {
         }
     }
 
-    /// Load a problem from a file.
-    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
-        let file = File::open(path)?;
-        let reader = io::BufReader::new(file);
-        let mut lines = reader.lines();
+    /// Assign each customer to the nearest of the `depots` nodes, e.g. to pre-cluster the
+    /// customers of a multi-depot instance, returning the customers of each depot in order.
+    ///
+    /// Any node can serve as a depot, such as the seed customers of a decomposition, and
+    /// ties go to the first depot.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `depots` is empty.
+    pub fn assign_to_nearest(&self, depots: &[usize]) -> Vec<Vec<usize>> {
+        assert!(!depots.is_empty(), "nearest assignment needs a depot");
+        let mut clusters = vec![Vec::new(); depots.len()];
+        for customer in self.customers() {
+            let nearest = (0..depots.len())
+                .min_by(|&a, &b| {
+                    self.get_distance(customer, depots[a])
+                        .total_cmp(&self.get_distance(customer, depots[b]))
+                })
+                .unwrap_or(0);
+            clusters[nearest].push(customer);
+        }
+        clusters
+    }
+
+    /// Cluster the customers into `k` groups of at most `capacity` demand around their mean
+    /// location, by capacitated k-means.
+    ///
+    /// Centers start at spread-out customers, the first one farthest from the depot. Each
+    /// iteration assigns the customers, largest demand first, to the nearest center with
+    /// enough capacity left, or to the nearest one if none has, and moves the centers to the
+    /// mean of their customers, until the assignment is stable or `max_iterations` is reached.
+    /// Clusters may be empty, e.g. with more clusters than customers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is 0 or the problem has no coordinates.
+    pub fn capacitated_k_means(
+        &self,
+        k: usize,
+        capacity: f64,
+        max_iterations: usize,
+    ) -> Vec<Vec<usize>> {
+        assert!(k > 0, "k-means needs at least one cluster");
+        assert!(self.has_coordinates, "k-means needs node coordinates");
+
+        let customers: Vec<usize> = self.customers().collect();
+        let distance = |customer: usize, (x, y): (f64, f64)| {
+            let node = &self.nodes[customer];
+            ((node.x - x).powi(2) + (node.y - y).powi(2)).sqrt()
+        };
+        let location = |customer: usize| (self.nodes[customer].x, self.nodes[customer].y);
+
+        // Farthest-first initial centers, the gap of a customer being its distance to the
+        // depot until the first center and to the nearest center afterwards
+        let depot = self.get_depot();
+        let mut gaps: Vec<f64> = customers
+            .iter()
+            .map(|&customer| distance(customer, (depot.x, depot.y)))
+            .collect();
+        let mut centers = Vec::with_capacity(k);
+        while centers.len() < k.min(customers.len()) {
+            let farthest = (0..customers.len())
+                .max_by(|&a, &b| gaps[a].total_cmp(&gaps[b]))
+                .unwrap_or(0);
+            let center = location(customers[farthest]);
+            for (gap, &customer) in gaps.iter_mut().zip(&customers) {
+                let to_center = distance(customer, center);
+                *gap = if centers.is_empty() {
+                    to_center
+                } else {
+                    gap.min(to_center)
+                };
+            }
+            centers.push(center);
+        }
+        centers.resize(k, (depot.x, depot.y));
+
+        let mut order = customers.clone();
+        order.sort_by(|&a, &b| self.nodes[b].demand.total_cmp(&self.nodes[a].demand));
+
+        let mut assignment = vec![usize::MAX; self.nodes.len()];
+        for _ in 0..max_iterations.max(1) {
+            let mut loads = vec![0.0; k];
+            let mut changed = false;
+            for &customer in &order {
+                let demand = self.nodes[customer].demand;
+                let by_distance = |&a: &usize, &b: &usize| {
+                    distance(customer, centers[a]).total_cmp(&distance(customer, centers[b]))
+                };
+                let cluster = (0..k)
+                    .filter(|&c| loads[c] + demand <= capacity + self.tolerance)
+                    .min_by(by_distance)
+                    .or_else(|| (0..k).min_by(by_distance))
+                    .unwrap_or(0);
+                loads[cluster] += demand;
+                changed |= assignment[customer] != cluster;
+                assignment[customer] = cluster;
+            }
+            if !changed {
+                break;
+            }
+
+            let mut sums = vec![(0.0, 0.0, 0usize); k];
+            for &customer in &customers {
+                let (x, y) = location(customer);
+                let sum = &mut sums[assignment[customer]];
+                *sum = (sum.0 + x, sum.1 + y, sum.2 + 1);
+            }
+            for (center, &(x, y, count)) in centers.iter_mut().zip(&sums) {
+                if count > 0 {
+                    *center = (x / count as f64, y / count as f64);
+                }
+            }
+        }
+
+        let mut clusters = vec![Vec::new(); k];
+        for &customer in &customers {
+            clusters[assignment[customer]].push(customer);
+        }
+        clusters
+    }
+
+    /// Load a problem from a file, gzip or zip compressed with the `compression` feature.
+    ///
+    /// Both TSPLIB instances (detected by their `KEY : VALUE` header) and the simple
+    /// whitespace-separated format are supported. Data rejected by `validate_data` is
+    /// reported as `HgsError::InvalidProblem`. Demands exceeding the vehicle capacity are
+    /// accepted, for `DemandSplit` to divide them; `HgsAlgorithm::new` reports them otherwise.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, HgsError> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Load a problem from any reader, e.g. an in-memory buffer or a network stream, like
+    /// `from_file`.
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self, HgsError> {
+        let content = compression::read_to_string(reader)?;
+
+        let problem = if Self::is_tsplib(&content) {
+            Self::from_tsplib(&content)?
+        } else {
+            Self::from_simple_format(&content)?
+        };
+        problem.validate_data()?;
+        Ok(problem)
+    }
+
+    /// Check whether a file content uses the TSPLIB format.
+    fn is_tsplib(content: &str) -> bool {
+        instance_lines(content)
+            .next()
+            .is_some_and(|(_, line)| line.contains(':'))
+    }
+
+    /// Parse a problem in the simple format: name, capacity line, then node lines.
+    ///
+    /// Node lines read `id x y demand`, and the node with zero demand is the depot. Node
+    /// ids must be distinct, and nodes are numbered in the order of their lines.
+    fn from_simple_format(content: &str) -> Result<Self, HgsError> {
+        let mut lines = instance_lines(content);
 
         // Parse problem name
-        let name = lines.next().unwrap()?.trim().to_string();
+        let (_, name) = lines
+            .next()
+            .ok_or_else(|| parse_error(0, "missing problem name"))?;
+        let name = name.to_string();
 
         // Parse vehicle information
-        let vehicle_info = lines.next().unwrap()?;
+        let (line, vehicle_info) = lines.next().ok_or_else(|| {
+            parse_error(0, "missing vehicle line, expected `capacity [vehicles]`")
+        })?;
         let parts: Vec<&str> = vehicle_info.split_whitespace().collect();
-        let vehicle_capacity = parts[0].parse::<f64>().unwrap();
-        let max_vehicles = if parts.len() > 1 {
-            Some(parts[1].parse::<usize>().unwrap())
-        } else {
-            None
+        if parts.len() > 2 {
+            return Err(parse_error(
+                line,
+                format!(
+                    "expected `capacity [vehicles]`, found {} fields",
+                    parts.len()
+                ),
+            ));
+        }
+        let vehicle_capacity = parse_value::<f64>(parts[0], line, "capacity", "a number")?;
+        let max_vehicles = match parts.get(1) {
+            Some(vehicles) => Some(parse_value::<usize>(
+                vehicles,
+                line,
+                "vehicle count",
+                "a non-negative integer",
+            )?),
+            None => None,
         };
 
         // Parse node information
         let mut nodes = Vec::new();
         let mut depot_index = 0;
+        let mut id_lines = HashMap::new();
 
-        for (i, line_result) in lines.enumerate() {
-            let line = line_result?;
-            let parts: Vec<&str> = line.split_whitespace().collect();
-
-            if parts.len() >= 4 {
-                let id = i;
-                let x = parts[1].parse::<f64>().unwrap();
-                let y = parts[2].parse::<f64>().unwrap();
-                let demand = parts[3].parse::<f64>().unwrap();
-                let is_depot = demand == 0.0;
+        for (line, content) in lines {
+            let parts: Vec<&str> = content.split_whitespace().collect();
+            if parts.len() != 4 {
+                return Err(parse_error(
+                    line,
+                    format!(
+                        "expected node line `id x y demand`, found {} fields",
+                        parts.len()
+                    ),
+                ));
+            }
 
-                if is_depot {
-                    depot_index = id;
-                }
+            let node_id =
+                parse_value::<usize>(parts[0], line, "node id", "a non-negative integer")?;
+            if let Some(first) = id_lines.insert(node_id, line) {
+                return Err(parse_error(
+                    line,
+                    format!("duplicate node id {node_id}, first defined at line {first}"),
+                ));
+            }
+            let id = nodes.len();
+            let x = parse_value::<f64>(parts[1], line, "x coordinate", "a number")?;
+            let y = parse_value::<f64>(parts[2], line, "y coordinate", "a number")?;
+            let demand = parse_value::<f64>(parts[3], line, "demand", "a number")?;
+            let is_depot = demand == 0.0;
 
-                nodes.push(Node::new(id, x, y, demand, is_depot));
+            if is_depot {
+                depot_index = id;
             }
+
+            nodes.push(Node::new(id, x, y, demand, is_depot));
         }
 
         Ok(Problem::new(
@@ -170,4 +1173,259 @@ impl Problem {
             max_vehicles,
         ))
     }
+
+    /// Parse a problem in the TSPLIB/CVRPLIB format.
+    ///
+    /// Distances are Euclidean (`EUC_2D`) or read from an `EDGE_WEIGHT_SECTION` (`EXPLICIT`)
+    /// in `FULL_MATRIX`, `UPPER_ROW`, or `LOWER_DIAG_ROW` layout. Without a
+    /// `NODE_COORD_SECTION` the problem has no coordinates. Node ids are 1-based in the file,
+    /// and each is listed at most once per section.
+    ///
+    /// Blank lines, lines starting with `#`, and Windows line endings are accepted. Errors
+    /// name the line, the field, and the expected format.
+    pub fn from_tsplib(content: &str) -> Result<Self, HgsError> {
+        let mut name = String::new();
+        let mut dimension = None;
+        let mut vehicle_capacity = None;
+        let mut max_vehicles = None;
+        let mut edge_weight_type = String::from("EUC_2D");
+        let mut edge_weight_format = None;
+
+        let mut section = String::new();
+        let mut coordinates = Vec::new();
+        let mut demands = Vec::new();
+        let mut depots = Vec::new();
+        let mut weights = Vec::new();
+
+        for (number, line) in instance_lines(content) {
+            if line == "EOF" {
+                break;
+            }
+
+            // Header entries
+            if let Some((key, value)) = line.split_once(':') {
+                let value = value.trim();
+                match key.trim() {
+                    "NAME" => name = value.to_string(),
+                    "DIMENSION" => {
+                        dimension = Some(parse_value::<usize>(
+                            value,
+                            number,
+                            "DIMENSION",
+                            "a non-negative integer",
+                        )?)
+                    }
+                    "CAPACITY" => {
+                        vehicle_capacity =
+                            Some(parse_value::<f64>(value, number, "CAPACITY", "a number")?)
+                    }
+                    "VEHICLES" => {
+                        max_vehicles = Some(parse_value::<usize>(
+                            value,
+                            number,
+                            "VEHICLES",
+                            "a non-negative integer",
+                        )?)
+                    }
+                    "EDGE_WEIGHT_TYPE" => edge_weight_type = value.to_string(),
+                    "EDGE_WEIGHT_FORMAT" => {
+                        edge_weight_format =
+                            Some(EdgeWeightFormat::from_tsplib(value).ok_or_else(|| {
+                                parse_error(
+                                    number,
+                                    format!(
+                                        "unsupported EDGE_WEIGHT_FORMAT `{value}`, expected \
+                                         FULL_MATRIX, UPPER_ROW, or LOWER_DIAG_ROW"
+                                    ),
+                                )
+                            })?)
+                    }
+                    _ => {}
+                }
+                section.clear();
+                continue;
+            }
+
+            if line.ends_with("_SECTION") {
+                section = line.to_string();
+                continue;
+            }
+
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let expect_fields = |layout: &str, count: usize| {
+                if parts.len() == count {
+                    Ok(())
+                } else {
+                    Err(parse_error(
+                        number,
+                        format!(
+                            "expected {section} line `{layout}`, found {} fields",
+                            parts.len()
+                        ),
+                    ))
+                }
+            };
+            match section.as_str() {
+                "NODE_COORD_SECTION" => {
+                    expect_fields("id x y", 3)?;
+                    coordinates.push((
+                        number,
+                        parse_value::<usize>(parts[0], number, "node id", "a positive integer")?,
+                        parse_value::<f64>(parts[1], number, "x coordinate", "a number")?,
+                        parse_value::<f64>(parts[2], number, "y coordinate", "a number")?,
+                    ))
+                }
+                "DEMAND_SECTION" => {
+                    expect_fields("id demand", 2)?;
+                    demands.push((
+                        number,
+                        parse_value::<usize>(parts[0], number, "node id", "a positive integer")?,
+                        parse_value::<f64>(parts[1], number, "demand", "a number")?,
+                    ))
+                }
+                "DEPOT_SECTION" => {
+                    for part in parts {
+                        let id = parse_value::<i64>(part, number, "depot id", "an integer")?;
+                        if id < 0 {
+                            section.clear();
+                            break;
+                        }
+                        depots.push((number, id as usize));
+                    }
+                }
+                "EDGE_WEIGHT_SECTION" => {
+                    for part in parts {
+                        weights.push(parse_value::<f64>(part, number, "edge weight", "a number")?);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let n = dimension.ok_or_else(|| parse_error(0, "missing DIMENSION header"))?;
+        let vehicle_capacity =
+            vehicle_capacity.ok_or_else(|| parse_error(0, "missing CAPACITY header"))?;
+        let node_index = |id: usize, line: usize| {
+            if (1..=n).contains(&id) {
+                Ok(id - 1)
+            } else {
+                Err(parse_error(
+                    line,
+                    format!("node id {id} out of range, expected 1 to DIMENSION {n}"),
+                ))
+            }
+        };
+        // Each node may be listed once per section
+        let first_listing = |lines: &mut [Option<usize>], index: usize, line: usize| {
+            let first = lines[index].replace(line);
+            first.map_or(Ok(()), |first| {
+                Err(parse_error(
+                    line,
+                    format!(
+                        "duplicate node id {}, first listed at line {first}",
+                        index + 1
+                    ),
+                ))
+            })
+        };
+
+        let mut positions = vec![(0.0, 0.0); n];
+        let mut coordinate_lines = vec![None; n];
+        for &(line, id, x, y) in &coordinates {
+            let index = node_index(id, line)?;
+            first_listing(&mut coordinate_lines, index, line)?;
+            positions[index] = (x, y);
+        }
+        if !coordinates.is_empty() {
+            if let Some(missing) = coordinate_lines.iter().position(Option::is_none) {
+                return Err(parse_error(
+                    0,
+                    format!("missing coordinates of node {}", missing + 1),
+                ));
+            }
+        }
+        let mut node_demands = vec![0.0; n];
+        let mut demand_lines = vec![None; n];
+        for &(line, id, demand) in &demands {
+            let index = node_index(id, line)?;
+            first_listing(&mut demand_lines, index, line)?;
+            node_demands[index] = demand;
+        }
+        let depot_index = match depots.first() {
+            Some(&(line, id)) => node_index(id, line)?,
+            None => 0,
+        };
+
+        let nodes = (0..n)
+            .map(|i| {
+                let (x, y) = positions[i];
+                Node::new(i, x, y, node_demands[i], i == depot_index)
+            })
+            .collect();
+        let problem = Problem::new(name, nodes, depot_index, vehicle_capacity, max_vehicles);
+
+        let mut problem = match edge_weight_type.as_str() {
+            "EUC_2D" if !coordinates.is_empty() => problem,
+            "EUC_2D" => return Err(parse_error(0, "missing NODE_COORD_SECTION")),
+            "EXPLICIT" => {
+                let format = edge_weight_format
+                    .ok_or_else(|| parse_error(0, "missing EDGE_WEIGHT_FORMAT header"))?;
+                if weights.len() != format.weight_count(n) {
+                    return Err(parse_error(
+                        0,
+                        format!(
+                            "expected {} edge weights in EDGE_WEIGHT_SECTION, found {}",
+                            format.weight_count(n),
+                            weights.len()
+                        ),
+                    ));
+                }
+                problem.with_distance_matrix(format.build_matrix(&weights, n))
+            }
+            other => {
+                return Err(parse_error(
+                    0,
+                    format!("unsupported EDGE_WEIGHT_TYPE `{other}`, expected EUC_2D or EXPLICIT"),
+                ))
+            }
+        };
+        problem.has_coordinates = !coordinates.is_empty();
+
+        Ok(problem)
+    }
+}
+
+/// Iterate over the numbered lines of an instance file with content, trimmed, skipping
+/// blank lines and `#` comments. A byte order mark and Windows line endings are ignored.
+fn instance_lines(content: &str) -> impl Iterator<Item = (usize, &str)> {
+    content
+        .strip_prefix('\u{feff}')
+        .unwrap_or(content)
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+}
+
+/// Parse the value of a field at a line of an instance file.
+fn parse_value<T: std::str::FromStr>(
+    value: &str,
+    line: usize,
+    field: &str,
+    expected: &str,
+) -> Result<T, HgsError> {
+    value.parse().map_err(|_| {
+        parse_error(
+            line,
+            format!("invalid {field} `{value}`, expected {expected}"),
+        )
+    })
+}
+
+/// Create an error for a malformed line of an instance file, 0 for the file as a whole.
+fn parse_error(line: usize, message: impl Into<String>) -> HgsError {
+    HgsError::ParseError {
+        line,
+        message: message.into(),
+    }
 }
@@ -0,0 +1,63 @@
+//! Progress estimation of a running search.
+
+use crate::local_search::NeighborhoodStats;
+use std::time::Duration;
+
+/// Callback receiving the progress of the search after each generation.
+pub type ProgressCallback = Box<dyn FnMut(&Progress)>;
+
+/// Snapshot of the progress of the search, computed after each generation.
+#[derive(Debug, Clone)]
+pub struct Progress {
+    /// Number of iterations performed
+    pub iterations: u32,
+    /// Time elapsed since the start of the run
+    pub elapsed: Duration,
+    /// Fraction of the time limit used, if there is one
+    pub time_fraction: Option<f64>,
+    /// Fraction of the iterations without improvement allowed before stopping
+    pub stagnation_fraction: f64,
+    /// Iterations without improvement left before stopping
+    pub stagnation_iterations_left: u32,
+    /// Cost of the best feasible solution, if one was found
+    pub best_cost: Option<f64>,
+    /// Average decrease of the best cost per second since the first feasible solution
+    pub improvement_rate: f64,
+    /// Moves evaluated and accepted by each neighborhood of the education so far
+    pub neighborhoods: NeighborhoodStats,
+}
+
+impl Progress {
+    /// Fraction of the run completed, given by the termination criterion closest to stopping it.
+    pub fn fraction(&self) -> f64 {
+        self.time_fraction
+            .unwrap_or(0.0)
+            .max(self.stagnation_fraction)
+            .clamp(0.0, 1.0)
+    }
+
+    /// Estimate the remaining time of the run.
+    ///
+    /// The time limit gives an upper bound. The stagnation estimate assumes that no further
+    /// improvement is found and that the remaining iterations take the average iteration time.
+    pub fn eta(&self) -> Option<Duration> {
+        let by_time = self.time_fraction.map(|fraction| {
+            if fraction >= 1.0 {
+                Duration::ZERO
+            } else {
+                self.elapsed
+                    .mul_f64((1.0 - fraction) / fraction.max(f64::EPSILON))
+            }
+        });
+
+        let by_stagnation = (self.iterations > 0).then(|| {
+            let per_iteration = self.elapsed / self.iterations;
+            per_iteration * self.stagnation_iterations_left
+        });
+
+        match (by_time, by_stagnation) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+}
@@ -0,0 +1,62 @@
+//! Random number generation of the search, which can be seeded per thread for reproducible
+//! runs.
+
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::cell::RefCell;
+
+thread_local! {
+    /// Seeded generator of the current thread, if any
+    static SEEDED: RefCell<Option<ChaCha8Rng>> = const { RefCell::new(None) };
+}
+
+/// Handle to the random number generator of the current thread.
+///
+/// Draws come from the generator seeded with `seed` on this thread, or from `rand::thread_rng`
+/// if it was never seeded. Every random decision of the search goes through this handle, so
+/// a seeded run bounded by iterations rather than time is reproducible.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SearchRng;
+
+/// Get the random number generator of the current thread.
+pub fn search_rng() -> SearchRng {
+    SearchRng
+}
+
+/// Seed the random number generator of the current thread.
+pub fn seed(seed: u64) {
+    SEEDED.with(|seeded| *seeded.borrow_mut() = Some(ChaCha8Rng::seed_from_u64(seed)));
+}
+
+/// Draw from `rand::thread_rng` again on the current thread.
+pub fn unseed() {
+    SEEDED.with(|seeded| *seeded.borrow_mut() = None);
+}
+
+impl SearchRng {
+    /// Apply `draw` to the seeded generator of the thread, or to `rand::thread_rng`.
+    fn with<T>(draw: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+        SEEDED.with(|seeded| match seeded.borrow_mut().as_mut() {
+            Some(rng) => draw(rng),
+            None => draw(&mut rand::thread_rng()),
+        })
+    }
+}
+
+impl RngCore for SearchRng {
+    fn next_u32(&mut self) -> u32 {
+        Self::with(|rng| rng.next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        Self::with(|rng| rng.next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        Self::with(|rng| rng.fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        Self::with(|rng| rng.try_fill_bytes(dest))
+    }
+}
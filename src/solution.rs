@@ -1,30 +1,94 @@
 //! Solution representation for the CVRP.
 
+use crate::costs;
 use crate::problem::Problem;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Next identifier given to a new route
+static NEXT_ROUTE_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Draw an identifier no other route of the process has been given.
+pub fn next_route_id() -> u64 {
+    NEXT_ROUTE_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Metadata of a customer visit, as kept per node in the reference HGS implementation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RouteNode {
+    /// Index of the route visiting the customer
+    pub route: usize,
+    /// Position of the customer in the route
+    pub position: usize,
+    /// Previous node on the route (the depot for the first customer)
+    pub predecessor: usize,
+    /// Next node on the route (the depot for the last customer)
+    pub successor: usize,
+}
+
+/// A stop of a route itinerary, as reported by `Route::schedule`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stop {
+    /// Node visited, the depot for the return at the end of the route
+    pub node: usize,
+    /// Distance travelled from the depot when arriving at the node
+    pub distance: f64,
+    /// Load still in the vehicle once the node is served
+    pub load_remaining: f64,
+    /// Time of arrival at the node, if the problem has durations
+    pub arrival_time: Option<f64>,
+}
 
 /// Represents a route in a CVRP solution.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Route {
+    /// Identifier of the route, kept by the moves changing its customers and passed on by
+    /// `Solution::inherit_route_ids`, to follow a vehicle across re-optimizations
+    #[serde(default = "next_route_id")]
+    pub id: u64,
     /// The sequence of customer indices (excluding the depot)
     pub customers: Vec<usize>,
     /// The total load of the route
     pub load: f64,
+    /// The variance of the load of the route, 0 for deterministic demands
+    #[serde(default)]
+    pub load_variance: f64,
     /// The total distance of the route
     pub distance: f64,
-    /// Has this route been modified since last evaluation
+    /// The customers changed since the last `recalculate`, so the metrics are stale
     pub modified: bool,
+    /// Prefix sums of the load: entry k is the load of the first k customers
+    #[serde(skip)]
+    pub cumulative_load: Vec<f64>,
+    /// Prefix sums of the load variance, empty for deterministic demands
+    #[serde(skip)]
+    pub cumulative_variance: Vec<f64>,
+    /// Prefix sums of the distance: entry k is the distance from the depot to the k-th customer
+    #[serde(skip)]
+    pub cumulative_distance: Vec<f64>,
+    /// The prefix sums were computed for the current customers; cleared by every evaluation
+    /// and on deserialization, so `update_prefix_sums` computes them again when needed
+    #[serde(skip)]
+    pub prefix_sums_valid: bool,
 }
 
 impl Route {
-    /// Create a new, empty route.
+    /// Create a new, empty route with a fresh identifier.
     pub fn new() -> Self {
         Route {
+            id: next_route_id(),
             customers: Vec::new(),
             load: 0.0,
+            load_variance: 0.0,
             distance: 0.0,
             modified: true,
+            cumulative_load: vec![0.0],
+            cumulative_variance: Vec::new(),
+            cumulative_distance: vec![0.0],
+            prefix_sums_valid: true,
         }
     }
 
@@ -34,54 +98,186 @@ impl Route {
         route.customers.push(customer);
         route.load = load;
         route.distance = distance_from_depot * 2.0; // From depot to customer and back
+        route.cumulative_load = vec![0.0, load];
+        route.cumulative_distance = vec![0.0, distance_from_depot];
         route.modified = true;
         route
     }
 
-    /// Calculate the total distance of this route.
+    /// Calculate the total distance of this route, leaving the prefix sums to
+    /// `update_prefix_sums`.
+    ///
+    /// The modified flag is left as is, since the load may still be stale; `recalculate`
+    /// refreshes both and clears it.
     pub fn calculate_distance(&mut self, problem: &Problem) {
-        if !self.modified {
-            return;
-        }
-
         let depot_index = problem.depot_index;
         let mut total_distance = 0.0;
+        self.prefix_sums_valid = false;
 
         if self.customers.is_empty() {
             self.distance = 0.0;
-            self.modified = false;
             return;
         }
 
-        // Distance from depot to first customer
-        total_distance += problem.get_distance(depot_index, self.customers[0]);
-
-        // Distance between consecutive customers
-        for i in 0..self.customers.len() - 1 {
-            total_distance += problem.get_distance(self.customers[i], self.customers[i + 1]);
+        // Distance from depot to each customer along the route
+        let mut previous = depot_index;
+        for &customer in &self.customers {
+            total_distance += problem.get_distance(previous, customer);
+            previous = customer;
         }
 
         // Distance from last customer back to depot
-        total_distance +=
-            problem.get_distance(self.customers[self.customers.len() - 1], depot_index);
+        total_distance += problem.get_distance(previous, depot_index);
 
         self.distance = total_distance;
+    }
+
+    /// Calculate the distance and load of this route and mark it as evaluated.
+    ///
+    /// This is the only evaluation clearing the modified flag, by which `evaluate_modified`
+    /// skips the routes whose metrics are up to date.
+    pub fn recalculate(&mut self, problem: &Problem) {
+        self.calculate_distance(problem);
+        self.calculate_load(problem);
         self.modified = false;
     }
 
-    /// Calculate the total load of this route.
+    /// Calculate the total load of this route, leaving the prefix sums to
+    /// `update_prefix_sums`.
+    ///
+    /// Like `calculate_distance`, this leaves the modified flag as is.
     pub fn calculate_load(&mut self, problem: &Problem) {
-        if !self.modified {
-            return;
+        self.prefix_sums_valid = false;
+        self.load = 0.0;
+        for &customer in &self.customers {
+            self.load += problem.nodes[customer].demand;
         }
 
-        let mut total_load = 0.0;
+        // Variances of independent demands add up like the loads
+        self.load_variance = 0.0;
+        if problem.demand_uncertainty.is_some() {
+            for &customer in &self.customers {
+                self.load_variance += problem.demand_variance(customer);
+            }
+        }
+    }
+
+    /// Compute the prefix sums of the load and distance, unless they are still valid for the
+    /// current customers.
+    ///
+    /// They are only computed for the routes whose segments are queried, once per
+    /// evaluation of the route.
+    pub fn update_prefix_sums(&mut self, problem: &Problem) {
+        if self.prefix_sums_valid {
+            return;
+        }
 
+        let mut previous = problem.depot_index;
+        let (mut load, mut distance) = (0.0, 0.0);
+        self.cumulative_load.clear();
+        self.cumulative_load.push(0.0);
+        self.cumulative_distance.clear();
+        self.cumulative_distance.push(0.0);
         for &customer in &self.customers {
-            total_load += problem.nodes[customer].demand;
+            load += problem.nodes[customer].demand;
+            distance += problem.get_distance(previous, customer);
+            self.cumulative_load.push(load);
+            self.cumulative_distance.push(distance);
+            previous = customer;
+        }
+
+        self.cumulative_variance.clear();
+        if problem.demand_uncertainty.is_some() {
+            let mut variance = 0.0;
+            self.cumulative_variance.push(0.0);
+            for &customer in &self.customers {
+                variance += problem.demand_variance(customer);
+                self.cumulative_variance.push(variance);
+            }
         }
+        self.prefix_sums_valid = true;
+    }
+
+    /// Get the load of the customers at positions `start..end` in O(1).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the prefix sums are not up to date (see `update_prefix_sums`).
+    pub fn segment_load(&self, start: usize, end: usize) -> f64 {
+        self.assert_prefix_sums();
+        self.cumulative_load[end] - self.cumulative_load[start]
+    }
+
+    /// Get the load variance of the customers at positions `start..end` in O(1), 0 for
+    /// deterministic demands.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the prefix sums are not up to date (see `update_prefix_sums`).
+    pub fn segment_variance(&self, start: usize, end: usize) -> f64 {
+        self.assert_prefix_sums();
+        if self.cumulative_variance.is_empty() {
+            return 0.0;
+        }
+        self.cumulative_variance[end] - self.cumulative_variance[start]
+    }
+
+    /// Get the distance travelled from the customer at position `start` to the one
+    /// at position `end` (inclusive) in O(1).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the prefix sums are not up to date (see `update_prefix_sums`).
+    pub fn segment_distance(&self, start: usize, end: usize) -> f64 {
+        self.assert_prefix_sums();
+        self.cumulative_distance[end + 1] - self.cumulative_distance[start + 1]
+    }
 
-        self.load = total_load;
+    /// Check that the prefix sums were computed for the current customers.
+    fn assert_prefix_sums(&self) {
+        assert!(
+            self.prefix_sums_valid,
+            "the prefix sums of the route are not up to date"
+        );
+    }
+
+    /// List the stops of the route in order, ending with the return to the depot.
+    ///
+    /// The vehicle leaves the depot at time 0 carrying the load of the whole route, and is
+    /// served at each node for its service time before travelling on.
+    pub fn schedule(&self, problem: &Problem) -> Vec<Stop> {
+        let durations = problem.durations.as_ref();
+        let mut stops = Vec::with_capacity(self.customers.len() + 1);
+        let mut distance = 0.0;
+        let mut time = 0.0;
+        let mut load = self
+            .customers
+            .iter()
+            .map(|&c| problem.nodes[c].demand)
+            .sum::<f64>();
+
+        let mut previous = problem.depot_index;
+        for &node in self.customers.iter().chain([&problem.depot_index]) {
+            let leg = problem.get_distance(previous, node);
+            distance += leg;
+            let arrival_time = durations.map(|durations| time + durations.travel_time(leg));
+            if node != problem.depot_index {
+                load -= problem.nodes[node].demand;
+            }
+            stops.push(Stop {
+                node,
+                distance,
+                load_remaining: load,
+                arrival_time,
+            });
+
+            // Serve the node before leaving for the next one
+            if let (Some(durations), Some(arrival)) = (durations, arrival_time) {
+                time = arrival + durations.service_time(node);
+            }
+            previous = node;
+        }
+        stops
     }
 
     /// Check if the route is empty.
@@ -94,6 +290,11 @@ impl Route {
         self.load > capacity
     }
 
+    /// Get the fraction of the vehicle capacity used by the load, above 1 if overloaded.
+    pub fn utilization(&self, capacity: f64) -> f64 {
+        self.load / capacity
+    }
+
     /// Get the load excess beyond the vehicle capacity.
     pub fn get_excess_load(&self, capacity: f64) -> f64 {
         if self.load > capacity {
@@ -104,15 +305,185 @@ impl Route {
     }
 }
 
+impl Default for Route {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reason why routes do not form a solution of a problem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouteError {
+    /// A route visits a node that is neither the depot nor a customer of the problem
+    UnknownCustomer { route: usize, node: usize },
+    /// A customer is visited by two routes, or twice by the same one
+    DuplicateCustomer {
+        customer: usize,
+        routes: (usize, usize),
+    },
+    /// Customers visited by no route
+    MissingCustomers(Vec<usize>),
+}
+
+impl fmt::Display for RouteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RouteError::UnknownCustomer { route, node } => {
+                write!(f, "route {route} visits unknown node {node}")
+            }
+            RouteError::DuplicateCustomer { customer, routes } => write!(
+                f,
+                "customer {customer} is visited by routes {} and {}",
+                routes.0, routes.1
+            ),
+            RouteError::MissingCustomers(customers) => {
+                write!(f, "customers {customers:?} are not visited")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RouteError {}
+
+/// Reason why a solution does not satisfy the constraints of a problem.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    /// A route carries more than the vehicle capacity
+    Overload {
+        route: usize,
+        load: f64,
+        capacity: f64,
+    },
+    /// A route visits a node that is neither the depot nor a customer of the problem
+    UnknownNode { route: usize, node: usize },
+    /// A customer is visited by none of the routes
+    MissingCustomer(usize),
+    /// A customer is visited more than once, by the given routes
+    DuplicateCustomer { customer: usize, routes: Vec<usize> },
+    /// More routes are used than there are vehicles
+    TooManyRoutes { used: usize, max: usize },
+    /// The load quantile of a route at the service level exceeds the capacity
+    ServiceLevel {
+        route: usize,
+        quantile: f64,
+        capacity: f64,
+    },
+    /// A route visits more customers than the stop limit
+    TooManyStops {
+        route: usize,
+        stops: usize,
+        max: usize,
+    },
+}
+
+impl Violation {
+    /// Get the load above capacity of an overloaded route, or its load quantile above
+    /// capacity under stochastic demands, 0 for other violations.
+    pub fn excess(&self) -> f64 {
+        match self {
+            Violation::Overload { load, capacity, .. } => load - capacity,
+            Violation::ServiceLevel {
+                quantile, capacity, ..
+            } => quantile - capacity,
+            _ => 0.0,
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::Overload {
+                route,
+                load,
+                capacity,
+            } => write!(
+                f,
+                "route {route} carries {load:.2} of capacity {capacity:.2} (excess {:.2})",
+                self.excess()
+            ),
+            Violation::ServiceLevel {
+                route,
+                quantile,
+                capacity,
+            } => write!(
+                f,
+                "route {route} reaches load {quantile:.2} at the service level, of capacity {capacity:.2}"
+            ),
+            Violation::UnknownNode { route, node } => {
+                write!(f, "route {route} visits unknown node {node}")
+            }
+            Violation::MissingCustomer(customer) => write!(f, "customer {customer} is not visited"),
+            Violation::DuplicateCustomer { customer, routes } => {
+                write!(f, "customer {customer} is visited by routes {routes:?}")
+            }
+            Violation::TooManyRoutes { used, max } => {
+                write!(
+                    f,
+                    "{used} routes are used but only {max} vehicles are available"
+                )
+            }
+            Violation::TooManyStops { route, stops, max } => {
+                write!(f, "route {route} visits {stops} customers, more than {max}")
+            }
+        }
+    }
+}
+
+/// Stale metrics of a route, found by `Solution::recompute`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RouteDiscrepancy {
+    /// Index of the route in the solution
+    pub route: usize,
+    /// Stored distance minus the recomputed one
+    pub distance: f64,
+    /// Stored load minus the recomputed one
+    pub load: f64,
+    /// The prefix sums were marked as valid but did not match the customers
+    pub stale_prefix_sums: bool,
+    /// The route was marked as modified, so its metrics were known to be stale
+    pub was_modified: bool,
+}
+
+/// Differences between the stored metrics of a solution and their recomputation.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Discrepancy {
+    /// Routes whose distance, load, or prefix sums differed beyond the tolerance
+    pub routes: Vec<RouteDiscrepancy>,
+    /// Stored total distance minus the sum of the recomputed route distances
+    pub distance: f64,
+    /// Stored excess load minus the recomputed one
+    pub excess_capacity: f64,
+}
+
+impl Discrepancy {
+    /// Check whether the stored metrics matched their recomputation within `tolerance`.
+    pub fn is_consistent(&self, tolerance: f64) -> bool {
+        self.routes.is_empty()
+            && self.distance.abs() <= tolerance
+            && self.excess_capacity.abs() <= tolerance
+    }
+}
+
 /// Represents a complete solution to a CVRP instance.
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Solution {
     /// The list of routes
     pub routes: Vec<Route>,
-    /// The total cost of the solution (distance + capacity violation penalties)
+    /// The total cost of the solution (distance + route fixed costs + service costs
+    /// + custom objective + capacity violation penalties)
     pub cost: f64,
     /// The total raw distance of the solution
     pub distance: f64,
+    /// The total fixed cost of the non-empty routes
+    #[serde(default)]
+    pub fixed_cost: f64,
+    /// The total cost of serving the visited customers
+    #[serde(default)]
+    pub service_cost: f64,
+    /// The custom objective term of the problem, if it has one
+    #[serde(default)]
+    pub objective_cost: f64,
     /// The total capacity violation across all routes
     pub excess_capacity: f64,
     /// Is this solution feasible (no capacity violations)
@@ -128,14 +499,68 @@ impl Solution {
             routes: Vec::new(),
             cost: 0.0,
             distance: 0.0,
+            fixed_cost: 0.0,
+            service_cost: 0.0,
+            objective_cost: 0.0,
             excess_capacity: 0.0,
             is_feasible: true,
             giant_tour: Vec::new(),
         }
     }
 
+    /// Create a solution from routes given as sequences of node indices, e.g. from another system.
+    ///
+    /// Visits of the depot are accepted anywhere and split a sequence into separate routes, so
+    /// plans listing the depot at both ends of each route load as is. The solution is evaluated
+    /// with a capacity penalty of 1.0.
+    pub fn from_routes(routes: Vec<Vec<usize>>, problem: &Problem) -> Result<Self, RouteError> {
+        let mut visited_by: Vec<Option<usize>> = vec![None; problem.nodes.len()];
+        let mut solution = Solution::new();
+
+        for (index, sequence) in routes.into_iter().enumerate() {
+            for trip in sequence.split(|&node| node == problem.depot_index) {
+                for &customer in trip {
+                    if !problem.is_customer(customer) {
+                        return Err(RouteError::UnknownCustomer {
+                            route: index,
+                            node: customer,
+                        });
+                    }
+                    if let Some(first) = visited_by[customer].replace(index) {
+                        return Err(RouteError::DuplicateCustomer {
+                            customer,
+                            routes: (first, index),
+                        });
+                    }
+                }
+                if !trip.is_empty() {
+                    let mut route = Route::new();
+                    route.customers = trip.to_vec();
+                    solution.routes.push(route);
+                }
+            }
+        }
+
+        let missing: Vec<usize> = problem
+            .customers()
+            .filter(|&customer| visited_by[customer].is_none())
+            .collect();
+        if !missing.is_empty() {
+            return Err(RouteError::MissingCustomers(missing));
+        }
+
+        solution.update_giant_tour();
+        solution.evaluate(problem, 1.0);
+        Ok(solution)
+    }
+
     /// Create a solution with a given giant tour.
     pub fn from_giant_tour(giant_tour: Vec<usize>, problem: &Problem) -> Self {
+        debug_assert!(
+            giant_tour.iter().all(|&c| problem.is_customer(c)),
+            "giant tour contains a node that is not a customer"
+        );
+
         let mut solution = Solution::new();
         solution.giant_tour = giant_tour;
 
@@ -147,21 +572,294 @@ impl Solution {
 
     /// Evaluate the solution, calculating its cost and feasibility.
     pub fn evaluate(&mut self, problem: &Problem, capacity_penalty: f64) {
-        let mut total_distance = 0.0;
-        let mut total_excess = 0.0;
+        self.evaluate_with(problem, capacity_penalty, |route| {
+            route.recalculate(problem);
+        });
+    }
+
+    /// Evaluate the solution like `evaluate`, computing the distance and load of each route
+    /// with `evaluate_route`, e.g. through a route cache.
+    pub fn evaluate_with(
+        &mut self,
+        problem: &Problem,
+        capacity_penalty: f64,
+        mut evaluate_route: impl FnMut(&mut Route),
+    ) {
+        let mut total_service = 0.0;
 
         for route in &mut self.routes {
-            route.calculate_distance(problem);
-            route.calculate_load(problem);
+            evaluate_route(route);
+
+            for &customer in &route.customers {
+                total_service += problem.get_service_cost(customer);
+            }
+        }
 
+        self.service_cost = total_service;
+        self.update_totals(problem, capacity_penalty);
+    }
+
+    /// Re-evaluate the solution after a move, recalculating only the modified routes.
+    ///
+    /// Moves never change the set of served customers, so the service cost of the last full
+    /// evaluation is kept. This makes applying a move proportional to the routes it touches.
+    pub fn evaluate_modified(&mut self, problem: &Problem, capacity_penalty: f64) {
+        for route in self.routes.iter_mut().filter(|route| route.modified) {
+            route.recalculate(problem);
+        }
+
+        self.update_totals(problem, capacity_penalty);
+    }
+
+    /// Sum up the already calculated route metrics into the solution totals.
+    fn update_totals(&mut self, problem: &Problem, capacity_penalty: f64) {
+        let mut total_distance = 0.0;
+        let mut total_excess = 0.0;
+        let mut used_routes = 0;
+        let mut within_stop_limit = true;
+
+        for route in &self.routes {
             total_distance += route.distance;
-            total_excess += route.get_excess_load(problem.vehicle_capacity);
+            total_excess += problem.load_excess(route.load, route.load_variance);
+            if !route.is_empty() {
+                used_routes += 1;
+            }
+            within_stop_limit &= problem.allows_stops(route.customers.len());
         }
 
         self.distance = total_distance;
+        self.fixed_cost = problem.route_fixed_cost * used_routes as f64;
         self.excess_capacity = total_excess;
-        self.is_feasible = total_excess <= 1e-10;
-        self.cost = total_distance + capacity_penalty * total_excess;
+        self.is_feasible =
+            within_stop_limit && costs::approx_eq(total_excess, 0.0, problem.tolerance);
+        self.objective_cost = problem.objective_cost(self);
+        self.cost = total_distance
+            + self.fixed_cost
+            + self.service_cost
+            + self.objective_cost
+            + capacity_penalty * total_excess;
+    }
+
+    /// Recalculate the distance and load of every route, whether marked as modified or not,
+    /// and report how the stored values differed, e.g. to audit incremental evaluations.
+    ///
+    /// Differences within the tolerance of the problem are not reported as route
+    /// discrepancies. The totals of the solution are left as stored; `evaluate_modified`
+    /// refreshes them from the recalculated routes.
+    pub fn recompute(&mut self, problem: &Problem) -> Discrepancy {
+        let tolerance = problem.tolerance;
+        let mut discrepancy = Discrepancy::default();
+        let (mut total_distance, mut total_excess) = (0.0, 0.0);
+
+        for (index, route) in self.routes.iter_mut().enumerate() {
+            let stored = route.clone();
+            route.recalculate(problem);
+            total_distance += route.distance;
+            total_excess += problem.load_excess(route.load, route.load_variance);
+
+            let prefix_sums_match = |stored: &[f64], current: &[f64]| {
+                stored.len() == current.len()
+                    && stored
+                        .iter()
+                        .zip(current)
+                        .all(|(&a, &b)| costs::approx_eq(a, b, tolerance))
+            };
+            // Prefix sums not computed since the last evaluation are recomputed when needed
+            route.update_prefix_sums(problem);
+            let stale_prefix_sums = stored.prefix_sums_valid
+                && (!prefix_sums_match(&stored.cumulative_distance, &route.cumulative_distance)
+                    || !prefix_sums_match(&stored.cumulative_load, &route.cumulative_load));
+            let distance = stored.distance - route.distance;
+            let load = stored.load - route.load;
+            if stale_prefix_sums || distance.abs() > tolerance || load.abs() > tolerance {
+                discrepancy.routes.push(RouteDiscrepancy {
+                    route: index,
+                    distance,
+                    load,
+                    stale_prefix_sums,
+                    was_modified: stored.modified,
+                });
+            }
+        }
+
+        discrepancy.distance = self.distance - total_distance;
+        discrepancy.excess_capacity = self.excess_capacity - total_excess;
+        discrepancy
+    }
+
+    /// List every constraint of the problem that the routes violate.
+    ///
+    /// Loads are recomputed from the demands, so stale route metrics do not hide violations.
+    pub fn violations(&self, problem: &Problem) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        let mut visits: Vec<Vec<usize>> = vec![Vec::new(); problem.nodes.len()];
+
+        for (r_idx, route) in self.routes.iter().enumerate() {
+            let mut load = 0.0;
+            let mut variance = 0.0;
+            for &node in &route.customers {
+                if !problem.is_customer(node) {
+                    violations.push(Violation::UnknownNode { route: r_idx, node });
+                    continue;
+                }
+                visits[node].push(r_idx);
+                load += problem.nodes[node].demand;
+                variance += problem.demand_variance(node);
+            }
+            if load - problem.vehicle_capacity > problem.tolerance {
+                violations.push(Violation::Overload {
+                    route: r_idx,
+                    load,
+                    capacity: problem.vehicle_capacity,
+                });
+            } else if problem.load_excess(load, variance) > problem.tolerance {
+                violations.push(Violation::ServiceLevel {
+                    route: r_idx,
+                    quantile: problem.vehicle_capacity + problem.load_excess(load, variance),
+                    capacity: problem.vehicle_capacity,
+                });
+            }
+            if let Some(max) = problem
+                .max_stops_per_route
+                .filter(|&max| route.customers.len() > max)
+            {
+                violations.push(Violation::TooManyStops {
+                    route: r_idx,
+                    stops: route.customers.len(),
+                    max,
+                });
+            }
+        }
+
+        for customer in problem.customers() {
+            match visits[customer].len() {
+                0 => violations.push(Violation::MissingCustomer(customer)),
+                1 => {}
+                _ => violations.push(Violation::DuplicateCustomer {
+                    customer,
+                    routes: visits[customer].clone(),
+                }),
+            }
+        }
+
+        let used = self.get_used_route_count();
+        if let Some(max) = problem.max_vehicles.filter(|&max| used > max) {
+            violations.push(Violation::TooManyRoutes { used, max });
+        }
+
+        violations
+    }
+
+    /// Get the lowest and highest capacity utilization of the used routes, if any.
+    ///
+    /// A low minimum suggests that the fleet is larger than needed.
+    pub fn min_max_utilization(&self, capacity: f64) -> Option<(f64, f64)> {
+        self.routes
+            .iter()
+            .filter(|route| !route.is_empty())
+            .map(|route| route.utilization(capacity))
+            .fold(None, |range, utilization| match range {
+                None => Some((utilization, utilization)),
+                Some((min, max)) => Some((f64::min(min, utilization), f64::max(max, utilization))),
+            })
+    }
+
+    /// Format a table of the used routes, with their number of stops, load, capacity
+    /// utilization, and distance, followed by the totals and the utilization range.
+    pub fn summary(&self, problem: &Problem) -> String {
+        use std::fmt::Write;
+
+        let capacity = problem.vehicle_capacity;
+        let mut table = String::new();
+        let _ = writeln!(
+            table,
+            "{:>6} {:>6} {:>10} {:>7} {:>12}",
+            "Route", "Stops", "Load", "Util", "Distance"
+        );
+
+        let (mut stops, mut load, mut distance, mut used) = (0, 0.0, 0.0, 0);
+        for (i, route) in self.routes.iter().enumerate() {
+            if route.is_empty() {
+                continue;
+            }
+            let _ = writeln!(
+                table,
+                "{:>6} {:>6} {:>10.2} {:>6.1}% {:>12.2}",
+                i + 1,
+                route.customers.len(),
+                route.load,
+                100.0 * route.utilization(capacity),
+                route.distance
+            );
+            stops += route.customers.len();
+            load += route.load;
+            distance += route.distance;
+            used += 1;
+        }
+
+        let utilization = if used > 0 {
+            100.0 * load / (used as f64 * capacity)
+        } else {
+            0.0
+        };
+        let _ = writeln!(
+            table,
+            "{:>6} {:>6} {:>10.2} {:>6.1}% {:>12.2}",
+            "Total", stops, load, utilization, distance
+        );
+        let _ = write!(
+            table,
+            "Cost: {:.2}, feasible: {}",
+            self.cost, self.is_feasible
+        );
+        if let Some((min, max)) = self.min_max_utilization(capacity) {
+            let _ = write!(
+                table,
+                ", utilization: min {:.1}%, max {:.1}%",
+                100.0 * min,
+                100.0 * max
+            );
+        }
+        table.push('\n');
+        table
+    }
+
+    /// List the stops of every non-empty route, see `Route::schedule`.
+    pub fn schedule(&self, problem: &Problem) -> Vec<Vec<Stop>> {
+        self.routes
+            .iter()
+            .filter(|route| !route.is_empty())
+            .map(|route| route.schedule(problem))
+            .collect()
+    }
+
+    /// Check that the giant tour visits every customer of the problem exactly once.
+    pub fn has_complete_giant_tour(&self, problem: &Problem) -> bool {
+        Self::visits_each_customer_once(self.giant_tour.iter().copied(), problem)
+    }
+
+    /// Check that the routes visit every customer of the problem exactly once.
+    pub fn has_complete_routes(&self, problem: &Problem) -> bool {
+        Self::visits_each_customer_once(
+            self.routes.iter().flat_map(|r| r.customers.iter().copied()),
+            problem,
+        )
+    }
+
+    /// Check that a sequence of nodes contains every customer exactly once and nothing else.
+    fn visits_each_customer_once(nodes: impl Iterator<Item = usize>, problem: &Problem) -> bool {
+        let mut visited = vec![false; problem.nodes.len()];
+        let mut count = 0;
+
+        for node in nodes {
+            if !problem.is_customer(node) || visited[node] {
+                return false;
+            }
+            visited[node] = true;
+            count += 1;
+        }
+
+        count == problem.get_customer_count()
     }
 
     /// Update the giant tour from the routes.
@@ -173,19 +871,147 @@ impl Solution {
         }
     }
 
+    /// Get the giant tour, rebuilt from the routes if it is out of sync with them.
+    ///
+    /// A solution without customers in its routes, e.g. an offspring before Split, keeps its
+    /// giant tour.
+    pub fn synced_giant_tour(&self) -> Cow<'_, [usize]> {
+        let mut route_customers = self.routes.iter().flat_map(|r| r.customers.iter());
+        let route_count: usize = self.routes.iter().map(|r| r.customers.len()).sum();
+
+        if route_count == 0
+            || (route_count == self.giant_tour.len()
+                && self
+                    .giant_tour
+                    .iter()
+                    .all(|c| route_customers.next() == Some(c)))
+        {
+            Cow::Borrowed(&self.giant_tour)
+        } else {
+            Cow::Owned(
+                self.routes
+                    .iter()
+                    .flat_map(|r| r.customers.iter().copied())
+                    .collect(),
+            )
+        }
+    }
+
     /// Get the cost of the solution considering only feasible components.
     pub fn get_feasible_cost(&self) -> f64 {
         if self.is_feasible {
-            self.distance
+            self.distance + self.fixed_cost + self.service_cost + self.objective_cost
         } else {
             f64::INFINITY
         }
     }
 
+    /// Compute the prefix sums of the routes evaluated or deserialized since they were last
+    /// computed (see `Route::update_prefix_sums`).
+    pub fn update_prefix_sums(&mut self, problem: &Problem) {
+        for route in &mut self.routes {
+            route.update_prefix_sums(problem);
+        }
+    }
+
     /// Get the number of routes.
     pub fn get_route_count(&self) -> usize {
         self.routes.len()
     }
+
+    /// Build the metadata of every customer visit, indexed by node.
+    ///
+    /// Cumulative loads and distances are available per position in the routes themselves.
+    pub fn node_index(&self, problem: &Problem) -> Vec<Option<RouteNode>> {
+        let mut index = vec![None; problem.nodes.len()];
+
+        for (r_idx, route) in self.routes.iter().enumerate() {
+            let len = route.customers.len();
+            for (pos, &customer) in route.customers.iter().enumerate() {
+                index[customer] = Some(RouteNode {
+                    route: r_idx,
+                    position: pos,
+                    predecessor: if pos > 0 {
+                        route.customers[pos - 1]
+                    } else {
+                        problem.depot_index
+                    },
+                    successor: if pos + 1 < len {
+                        route.customers[pos + 1]
+                    } else {
+                        problem.depot_index
+                    },
+                });
+            }
+        }
+
+        index
+    }
+
+    /// Get the number of routes that visit at least one customer.
+    pub fn get_used_route_count(&self) -> usize {
+        self.routes.iter().filter(|route| !route.is_empty()).count()
+    }
+
+    /// Find the route with the given identifier.
+    pub fn route_by_id(&self, id: u64) -> Option<&Route> {
+        self.routes.iter().find(|route| route.id == id)
+    }
+
+    /// Give the routes the identifiers of the routes of `previous` they share the most
+    /// customers with, e.g. after re-optimizing a plan, so the same vehicles keep their
+    /// identifiers.
+    ///
+    /// Pairs of routes are matched greedily by decreasing number of shared customers. Routes
+    /// left unmatched keep their identifier, or get a fresh one if a matched route took it.
+    pub fn inherit_route_ids(&mut self, previous: &Solution) {
+        let mut previous_route = HashMap::new();
+        for (index, route) in previous.routes.iter().enumerate() {
+            for &customer in &route.customers {
+                previous_route.insert(customer, index);
+            }
+        }
+
+        // Customers shared by each pair of current and previous routes
+        let mut shared: HashMap<(usize, usize), usize> = HashMap::new();
+        for (index, route) in self.routes.iter().enumerate() {
+            for customer in &route.customers {
+                if let Some(&other) = previous_route.get(customer) {
+                    *shared.entry((index, other)).or_default() += 1;
+                }
+            }
+        }
+        let mut pairs: Vec<((usize, usize), usize)> = shared.into_iter().collect();
+        pairs.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        let mut matched = vec![false; self.routes.len()];
+        let mut used = vec![false; previous.routes.len()];
+        let mut taken = HashSet::new();
+        for ((index, other), _) in pairs {
+            if !matched[index] && !used[other] {
+                matched[index] = true;
+                used[other] = true;
+                self.routes[index].id = previous.routes[other].id;
+                taken.insert(previous.routes[other].id);
+            }
+        }
+        for (route, _) in self
+            .routes
+            .iter_mut()
+            .zip(matched)
+            .filter(|(_, matched)| !matched)
+        {
+            if taken.contains(&route.id) {
+                route.id = next_route_id();
+            }
+        }
+    }
+}
+
+impl Default for Solution {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl fmt::Debug for Solution {
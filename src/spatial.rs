@@ -0,0 +1,198 @@
+//! Spatial index over node locations, for nearest-neighbor queries on large instances.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// Two-dimensional k-d tree over a set of located nodes.
+///
+/// The tree is stored implicitly: each range of `order` is split at its middle element
+/// along the x axis at even depths and the y axis at odd ones.
+#[derive(Debug, Clone, Default)]
+pub struct KdTree {
+    /// Node index and location of each point, in tree order
+    order: Vec<(usize, f64, f64)>,
+}
+
+impl KdTree {
+    /// Build a tree over `(node, x, y)` points.
+    pub fn new(points: impl IntoIterator<Item = (usize, f64, f64)>) -> Self {
+        let mut order: Vec<(usize, f64, f64)> = points.into_iter().collect();
+        let len = order.len();
+        Self::build(&mut order, 0, len, 0);
+        KdTree { order }
+    }
+
+    /// Get the number of points.
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    /// Check whether the tree has no point.
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    /// Iterate over the points by increasing Euclidean distance from a location.
+    ///
+    /// Points are produced lazily, so taking the first k costs about O(k log n).
+    pub fn nearest(&self, x: f64, y: f64) -> Nearest<'_> {
+        let mut queue = BinaryHeap::new();
+        if !self.order.is_empty() {
+            queue.push(Candidate {
+                distance: 0.0,
+                item: Item::Range {
+                    start: 0,
+                    end: self.order.len(),
+                    depth: 0,
+                    bounds: [
+                        f64::NEG_INFINITY,
+                        f64::INFINITY,
+                        f64::NEG_INFINITY,
+                        f64::INFINITY,
+                    ],
+                },
+            });
+        }
+        Nearest {
+            tree: self,
+            x,
+            y,
+            queue,
+        }
+    }
+
+    /// Arrange `order[start..end]` as a subtree split along the axis of `depth`.
+    fn build(order: &mut [(usize, f64, f64)], start: usize, end: usize, depth: usize) {
+        if end - start <= 1 {
+            return;
+        }
+        let middle = start + (end - start) / 2;
+        order[start..end].select_nth_unstable_by(middle - start, |a, b| {
+            Self::coordinate(a, depth).total_cmp(&Self::coordinate(b, depth))
+        });
+        Self::build(order, start, middle, depth + 1);
+        Self::build(order, middle + 1, end, depth + 1);
+    }
+
+    /// Coordinate of a point along the axis of `depth`.
+    fn coordinate(point: &(usize, f64, f64), depth: usize) -> f64 {
+        if depth.is_multiple_of(2) {
+            point.1
+        } else {
+            point.2
+        }
+    }
+}
+
+/// Iterator over the points of a `KdTree` by increasing distance, see `KdTree::nearest`.
+#[derive(Debug)]
+pub struct Nearest<'a> {
+    tree: &'a KdTree,
+    x: f64,
+    y: f64,
+    queue: BinaryHeap<Candidate>,
+}
+
+impl Iterator for Nearest<'_> {
+    /// Node index and Euclidean distance of the next closest point
+    type Item = (usize, f64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Ranges are expanded until a point is closer than every remaining range
+        while let Some(candidate) = self.queue.pop() {
+            match candidate.item {
+                Item::Point(node) => return Some((node, candidate.distance)),
+                Item::Range {
+                    start,
+                    end,
+                    depth,
+                    bounds,
+                } => {
+                    let middle = start + (end - start) / 2;
+                    let point = self.tree.order[middle];
+                    self.queue.push(Candidate {
+                        distance: Self::distance(self.x - point.1, self.y - point.2),
+                        item: Item::Point(point.0),
+                    });
+
+                    let axis = depth % 2;
+                    let split = KdTree::coordinate(&point, depth);
+                    let mut lower = bounds;
+                    lower[2 * axis + 1] = split;
+                    let mut upper = bounds;
+                    upper[2 * axis] = split;
+                    for (start, end, bounds) in [(start, middle, lower), (middle + 1, end, upper)] {
+                        if start < end {
+                            self.queue.push(Candidate {
+                                distance: self.distance_to(&bounds),
+                                item: Item::Range {
+                                    start,
+                                    end,
+                                    depth: depth + 1,
+                                    bounds,
+                                },
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl Nearest<'_> {
+    /// Euclidean norm, computed like `Node::distance`.
+    fn distance(dx: f64, dy: f64) -> f64 {
+        (dx * dx + dy * dy).sqrt()
+    }
+
+    /// Lower bound on the distance from the query to the points of a bounding box.
+    fn distance_to(&self, bounds: &[f64; 4]) -> f64 {
+        let dx = (bounds[0] - self.x).max(self.x - bounds[1]).max(0.0);
+        let dy = (bounds[2] - self.y).max(self.y - bounds[3]).max(0.0);
+        Self::distance(dx, dy)
+    }
+}
+
+/// Point or range of the tree waiting to be visited, ordered for a min-heap.
+#[derive(Debug)]
+struct Candidate {
+    /// Distance to the point, or lower bound on the distance to the points of the range
+    distance: f64,
+    item: Item,
+}
+
+/// Point or range of `KdTree::order`.
+#[derive(Debug)]
+enum Item {
+    Point(usize),
+    Range {
+        start: usize,
+        end: usize,
+        depth: usize,
+        /// Bounding box of the range as `[min_x, max_x, min_y, max_y]`
+        bounds: [f64; 4],
+    },
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, so the closest candidate is popped first
+        other.distance.total_cmp(&self.distance)
+    }
+}
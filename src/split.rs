@@ -5,112 +5,188 @@ use crate::solution::{Route, Solution};
 
 use std::f64;
 
+/// Options of `Split::split_tour`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SplitOptions {
+    /// Maximum number of routes, unlimited if `None`
+    pub max_vehicles: Option<usize>,
+    /// Cost per unit of excess load, allowing overloaded routes; capacity is hard if `None`
+    pub capacity_penalty: Option<f64>,
+}
+
+impl SplitOptions {
+    /// Create options with an unlimited fleet and a hard capacity.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use at most the given number of routes.
+    pub fn with_max_vehicles(mut self, max_vehicles: usize) -> Self {
+        self.max_vehicles = Some(max_vehicles);
+        self
+    }
+
+    /// Allow routes to exceed the capacity, at the given cost per unit of excess load.
+    pub fn with_capacity_penalty(mut self, penalty: f64) -> Self {
+        self.capacity_penalty = Some(penalty);
+        self
+    }
+}
+
 /// Implements the Split algorithm to optimally partition a giant tour.
 pub struct Split;
 
 impl Split {
     /// Split a giant tour into routes.
-    /// This is the linear-time split algorithm from Vidal (2016).
+    /// This is the Bellman-based split from Prins (2004), restricted to
+    /// routes that respect the vehicle capacity and the stop limit.
     pub fn split(solution: &mut Solution, problem: &Problem) {
-        let giant_tour = &solution.giant_tour;
-
-        if giant_tour.is_empty() {
+        if solution.giant_tour.is_empty() {
             solution.routes.clear();
             return;
         }
 
-        let n = giant_tour.len();
+        let sequences = Self::split_tour(&solution.giant_tour, problem, SplitOptions::default());
 
-        // Auxiliary data structures
-        let mut potential = vec![f64::INFINITY; n + 1];
-        let mut pred = vec![0; n + 1];
+        // Build the routes and their metrics
+        solution.routes = sequences
+            .into_iter()
+            .map(|customers| {
+                let mut route = Route::new();
+                route.customers = customers;
+                route.recalculate(problem);
+                route
+            })
+            .collect();
 
-        potential[0] = 0.0;
+        // Move pinned customers back to their routes and sequences
+        problem.pins.enforce(solution, problem);
 
-        // Information about the potential route
-        let mut load = 0.0;
-        let mut cum_load = vec![0.0; n + 1];
+        // Evaluate the full solution
+        solution.evaluate(problem, 1.0); // Default penalty of 1.0, will be adjusted later
+    }
 
-        for i in 0..n {
-            load += problem.nodes[giant_tour[i]].demand;
-            cum_load[i + 1] = load;
+    /// Optimally cut a giant tour of customers into the customer sequences of routes, in
+    /// tour order, e.g. for a metaheuristic of one's own.
+    ///
+    /// Routes cost their distance, fixed cost, and service costs, plus the capacity penalty
+    /// on their excess load with a soft capacity. A single customer always gets its own
+    /// route, even if it is overloaded, and the stop limit of the problem always holds.
+    /// With a limited fleet the split takes O(n m B) time for m vehicles and routes of at
+    /// most B customers; if the tour cannot be split into that many routes, the fleet limit
+    /// is ignored.
+    pub fn split_tour(tour: &[usize], problem: &Problem, options: SplitOptions) -> Vec<Vec<usize>> {
+        debug_assert!(
+            tour.iter().all(|&c| problem.is_customer(c)),
+            "giant tour contains a node that is not a customer"
+        );
+        let n = tour.len();
+        if n == 0 {
+            return Vec::new();
         }
 
-        // DP state
-        let mut load_i = 0.0;
-        let mut j = 0;
-
-        // For each client
-        for i in 0..n {
-            // For each potential route (i,j)
-            while j < n {
-                let route_load = cum_load[j + 1] - cum_load[i];
-
-                if route_load > problem.vehicle_capacity {
-                    break;
-                }
-
-                // Calculate distance of route i -> j
-                let mut route_distance = 0.0;
-
-                // Depot to first
-                route_distance += problem.get_distance(problem.depot_index, giant_tour[i]);
-
-                // All successive nodes
-                for k in i..j {
-                    route_distance += problem.get_distance(giant_tour[k], giant_tour[k + 1]);
-                }
-
-                // Last to depot
-                route_distance += problem.get_distance(giant_tour[j], problem.depot_index);
-
-                // Calculate new potential
-                let new_potential = potential[i] + route_distance;
-
-                if new_potential < potential[j + 1] {
-                    potential[j + 1] = new_potential;
-                    pred[j + 1] = i;
+        let prefix = TourPrefix::new(tour, problem);
+
+        // One layer of potentials per route count with a limited fleet, a single one otherwise
+        let limited = options.max_vehicles.map(|max| max.clamp(1, n));
+        let layers = limited.unwrap_or(1);
+        let mut potential = vec![vec![f64::INFINITY; n + 1]; layers + 1];
+        let mut pred = vec![vec![0; n + 1]; layers + 1];
+        potential[0][0] = 0.0;
+
+        for layer in 0..layers {
+            let (from, to) = if limited.is_some() {
+                (layer, layer + 1)
+            } else {
+                (0, 0)
+            };
+            // For each potential route start
+            for i in 0..n {
+                let start = potential[from][i];
+                if start == f64::INFINITY {
+                    continue;
                 }
-
-                j += 1;
-            }
-
-            // Refine the DP by allowing more states
-            load_i += problem.nodes[giant_tour[i]].demand;
-
-            if j < n && (load_i > problem.vehicle_capacity || i == j) {
-                j += 1;
+                Self::for_each_route(tour, &prefix, problem, options, i, |end, cost| {
+                    if start + cost < potential[to][end] {
+                        potential[to][end] = start + cost;
+                        pred[to][end] = i;
+                    }
+                });
             }
         }
 
-        // Reconstruct the solution
-        solution.routes.clear();
+        // Trace the routes back from the best route count
+        let best_layer = match limited {
+            Some(max) => (1..=max).min_by(|&a, &b| potential[a][n].total_cmp(&potential[b][n])),
+            None => Some(0),
+        };
+        let Some(mut layer) = best_layer.filter(|&layer| potential[layer][n] < f64::INFINITY)
+        else {
+            return Self::split_tour(
+                tour,
+                problem,
+                SplitOptions {
+                    max_vehicles: None,
+                    ..options
+                },
+            );
+        };
+        let mut routes = Vec::new();
         let mut j = n;
-
         while j > 0 {
-            let i = pred[j];
-
-            // Create a new route from i to j-1
-            let mut route = Route::new();
-            for k in i..j {
-                route.customers.push(giant_tour[k]);
-            }
-
-            // Calculate route metrics
-            route.calculate_load(problem);
-            route.calculate_distance(problem);
-
-            // Add route to solution
-            solution.routes.push(route);
-
+            let i = pred[layer][j];
+            routes.push(tour[i..j].to_vec());
             j = i;
+            if limited.is_some() {
+                layer -= 1;
+            }
         }
+        routes.reverse();
+        routes
+    }
 
-        // Reverse the routes to get them in the correct order
-        solution.routes.reverse();
+    /// Call `visit` with the end (exclusive) and cost of each route starting at position
+    /// `start` of the tour, by increasing length while the route may be extended.
+    fn for_each_route(
+        tour: &[usize],
+        prefix: &TourPrefix,
+        problem: &Problem,
+        options: SplitOptions,
+        start: usize,
+        mut visit: impl FnMut(usize, f64),
+    ) {
+        // Loads are summed customer by customer, so capacity checks stay exact
+        let mut route_load = 0.0;
+        let mut route_variance = 0.0;
+
+        // Extend the route (start, end) as long as it respects the capacity
+        for end in start + 1..=tour.len() {
+            route_load += problem.nodes[tour[end - 1]].demand;
+            route_variance += problem.demand_variance(tour[end - 1]);
+            let excess = problem.load_excess(route_load, route_variance);
+
+            // A single customer always gets its own route, even if it is overloaded
+            if end > start + 1
+                && ((excess > 0.0 && options.capacity_penalty.is_none())
+                    || !problem.allows_stops(end - start))
+            {
+                break;
+            }
 
-        // Evaluate the full solution
-        solution.evaluate(problem, 1.0); // Default penalty of 1.0, will be adjusted later
+            // Depot to first, along the tour, and back to the depot from the last customer
+            let distance = prefix.from_depot[start] + prefix.distance[end - 1]
+                - prefix.distance[start]
+                + prefix.to_depot[end - 1];
+            let penalty = options
+                .capacity_penalty
+                .map_or(0.0, |penalty| penalty * excess);
+            visit(
+                end,
+                problem.route_fixed_cost + prefix.service[end] - prefix.service[start]
+                    + distance
+                    + penalty,
+            );
+        }
     }
 
     /// Generate a giant tour from a solution's routes.
@@ -124,3 +200,47 @@ impl Split {
         }
     }
 }
+
+/// Cumulative distances and service costs along a giant tour, computed once per split so
+/// that the cost of each candidate route takes O(1) instead of summing its customers.
+struct TourPrefix {
+    /// Distance from the first customer of the tour to each customer, along the tour
+    distance: Vec<f64>,
+    /// Distance from the depot to each customer
+    from_depot: Vec<f64>,
+    /// Distance from each customer to the depot
+    to_depot: Vec<f64>,
+    /// Service cost of the customers before each position, with one entry past the end
+    service: Vec<f64>,
+}
+
+impl TourPrefix {
+    /// Accumulate the metrics of a giant tour.
+    fn new(tour: &[usize], problem: &Problem) -> Self {
+        let depot = problem.depot_index;
+        let mut prefix = TourPrefix {
+            distance: Vec::with_capacity(tour.len()),
+            from_depot: tour
+                .iter()
+                .map(|&c| problem.get_distance(depot, c))
+                .collect(),
+            to_depot: tour
+                .iter()
+                .map(|&c| problem.get_distance(c, depot))
+                .collect(),
+            service: Vec::with_capacity(tour.len() + 1),
+        };
+
+        let (mut distance, mut service) = (0.0, 0.0);
+        for (index, &customer) in tour.iter().enumerate() {
+            if index > 0 {
+                distance += problem.get_distance(tour[index - 1], customer);
+            }
+            prefix.distance.push(distance);
+            prefix.service.push(service);
+            service += problem.get_service_cost(customer);
+        }
+        prefix.service.push(service);
+        prefix
+    }
+}
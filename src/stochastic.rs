@@ -0,0 +1,99 @@
+//! Stochastic demands evaluated against a chance constraint on the route loads.
+
+use serde::{Deserialize, Serialize};
+
+/// Independent, normally distributed customer demands, whose means are the node demands.
+///
+/// A route satisfies the chance constraint when its load stays within the capacity with
+/// probability `service_level`, i.e. when `load + z * sqrt(variance)` does not exceed it,
+/// where `z` is the standard normal quantile of the service level.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DemandUncertainty {
+    /// Standard deviation of the demand of each node, indexed like `nodes`
+    pub std_devs: Vec<f64>,
+    /// Required probability that a route load stays within the capacity
+    pub service_level: f64,
+    /// Standard normal quantile of the service level
+    z: f64,
+}
+
+impl DemandUncertainty {
+    /// Create demand distributions with the given standard deviations and service level.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the service level is not strictly between 0 and 1.
+    pub fn new(std_devs: Vec<f64>, service_level: f64) -> Self {
+        assert!(
+            service_level > 0.0 && service_level < 1.0,
+            "service level {service_level} must be strictly between 0 and 1"
+        );
+        DemandUncertainty {
+            std_devs,
+            service_level,
+            z: normal_quantile(service_level),
+        }
+    }
+
+    /// Get the variance of the demand of a node.
+    pub fn variance(&self, node: usize) -> f64 {
+        self.std_devs.get(node).map_or(0.0, |std| std * std)
+    }
+
+    /// Get the load a route reaches at the service level, given its mean and variance.
+    pub fn quantile(&self, load: f64, variance: f64) -> f64 {
+        load + self.z * variance.max(0.0).sqrt()
+    }
+}
+
+/// Quantile function of the standard normal distribution.
+///
+/// Uses the rational approximation of Acklam, accurate to about 1e-9.
+pub fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    const LOW: f64 = 0.02425;
+
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+
+    if p < LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - LOW {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
@@ -0,0 +1,289 @@
+//! Tuning of the configuration on training instances, by racing random candidates.
+
+use crate::config::Config;
+use crate::problem::Problem;
+use crate::HgsAlgorithm;
+
+use crate::rng::search_rng;
+use rand::Rng;
+
+/// Configuration parameter that the tuner can vary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Parameter {
+    /// Minimum population size
+    MinPopSize,
+    /// Number of offspring generated before survivor selection
+    GenerationSize,
+    /// Number of elite individuals
+    NElite,
+    /// Number of closest individuals of the diversity contribution
+    NClosest,
+    /// Granularity of the local search
+    Granularity,
+    /// Target fraction of feasible offspring
+    TargetFeasibleRatio,
+    /// Initial penalty coefficient of the excess load
+    InitialCapacityPenalty,
+    /// Probability to repair infeasible offspring
+    RepairProbability,
+}
+
+impl Parameter {
+    /// Check whether the parameter takes integer values.
+    pub fn is_integer(self) -> bool {
+        matches!(
+            self,
+            Parameter::MinPopSize
+                | Parameter::GenerationSize
+                | Parameter::NElite
+                | Parameter::NClosest
+                | Parameter::Granularity
+        )
+    }
+
+    /// Set the parameter in a configuration, rounding integer values.
+    pub fn apply(self, config: &mut Config, value: f64) {
+        let integer = value.round().max(0.0) as usize;
+        match self {
+            Parameter::MinPopSize => config.min_pop_size = integer,
+            Parameter::GenerationSize => config.generation_size = integer,
+            Parameter::NElite => config.n_elite = integer,
+            Parameter::NClosest => config.n_closest = integer,
+            Parameter::Granularity => config.granularity = integer,
+            Parameter::TargetFeasibleRatio => config.target_feasible_ratio = value,
+            Parameter::InitialCapacityPenalty => config.initial_capacity_penalty = value,
+            Parameter::RepairProbability => config.repair_probability = value,
+        }
+    }
+}
+
+/// Range of values explored for a parameter, bounds included.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParameterRange {
+    /// Parameter varied
+    pub parameter: Parameter,
+    /// Smallest value
+    pub min: f64,
+    /// Largest value
+    pub max: f64,
+}
+
+impl ParameterRange {
+    /// Create a range of values for a parameter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min` is larger than `max`.
+    pub fn new(parameter: Parameter, min: f64, max: f64) -> Self {
+        assert!(min <= max, "parameter range with min {min} > max {max}");
+        ParameterRange {
+            parameter,
+            min,
+            max,
+        }
+    }
+
+    /// Draw a value uniformly in the range, among the integers for integer parameters.
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> f64 {
+        if self.parameter.is_integer() {
+            let (min, max) = (self.min.round() as i64, self.max.round() as i64);
+            rng.gen_range(min..=max.max(min)) as f64
+        } else {
+            rng.gen_range(self.min..=self.max)
+        }
+    }
+}
+
+/// Evaluations of a candidate configuration during a race.
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    /// Configuration evaluated
+    pub config: Config,
+    /// Rank of the candidate on each instance raced so far, 0 for the best cost
+    pub ranks: Vec<f64>,
+    /// Cost of the best solution on each instance raced so far, `None` if none was feasible
+    pub costs: Vec<Option<f64>>,
+}
+
+impl Candidate {
+    /// Get the average rank over the instances raced so far.
+    pub fn mean_rank(&self) -> f64 {
+        if self.ranks.is_empty() {
+            return 0.0;
+        }
+        self.ranks.iter().sum::<f64>() / self.ranks.len() as f64
+    }
+}
+
+/// Outcome of a tuning race.
+#[derive(Debug, Clone)]
+pub struct TuningResult {
+    /// Configuration with the best average rank among the candidates raced on every instance
+    pub best: Config,
+    /// Every candidate, in the order of their elimination, the survivors last by average rank
+    pub candidates: Vec<Candidate>,
+    /// Number of runs of the algorithm performed
+    pub evaluations: usize,
+}
+
+/// Racing tuner of the configuration, in the spirit of irace.
+///
+/// Random candidate configurations, drawn from the parameter ranges around the base
+/// configuration, are run on the training instances one at a time. After each instance the
+/// candidates are ranked by the cost they reached, and once `first_elimination` instances
+/// were raced, those whose average rank is in the worse half are eliminated. The base
+/// configuration sets the budget of each run, e.g. its time limit and iterations without
+/// improvement, and takes part in the race as the first candidate.
+#[derive(Debug, Clone)]
+pub struct Tuner {
+    /// Configuration from which the candidates are derived
+    pub base: Config,
+    /// Ranges of the parameters varied
+    pub ranges: Vec<ParameterRange>,
+    /// Number of candidates raced, including the base configuration
+    pub candidates: usize,
+    /// Number of instances raced before the first elimination
+    pub first_elimination: usize,
+    /// Number of passes over the training instances
+    pub passes: usize,
+}
+
+impl Tuner {
+    /// Create a tuner deriving its candidates from `base`.
+    pub fn new(base: Config) -> Self {
+        Tuner {
+            base,
+            ranges: Vec::new(),
+            candidates: 10,
+            first_elimination: 2,
+            passes: 1,
+        }
+    }
+
+    /// Vary a parameter between `min` and `max`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min` is larger than `max`.
+    pub fn with_range(mut self, parameter: Parameter, min: f64, max: f64) -> Self {
+        self.ranges.push(ParameterRange::new(parameter, min, max));
+        self
+    }
+
+    /// Set the number of candidates raced, including the base configuration.
+    pub fn with_candidates(mut self, candidates: usize) -> Self {
+        self.candidates = candidates.max(1);
+        self
+    }
+
+    /// Set the number of instances raced before the first elimination.
+    pub fn with_first_elimination(mut self, instances: usize) -> Self {
+        self.first_elimination = instances;
+        self
+    }
+
+    /// Set the number of passes over the training instances.
+    pub fn with_passes(mut self, passes: usize) -> Self {
+        self.passes = passes.max(1);
+        self
+    }
+
+    /// Draw the candidate configurations, starting with the base configuration.
+    pub fn sample_candidates<R: Rng>(&self, rng: &mut R) -> Vec<Config> {
+        let mut configs = vec![self.base.clone()];
+        while configs.len() < self.candidates {
+            let mut config = self.base.clone();
+            for range in &self.ranges {
+                range.parameter.apply(&mut config, range.sample(rng));
+            }
+            configs.push(config);
+        }
+        configs
+    }
+
+    /// Race the candidates on the training instances and return the best configuration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `instances` is empty.
+    pub fn tune(&self, instances: &[Problem]) -> TuningResult {
+        assert!(!instances.is_empty(), "tuning needs training instances");
+
+        let mut alive: Vec<Candidate> = self
+            .sample_candidates(&mut search_rng())
+            .into_iter()
+            .map(|config| Candidate {
+                config,
+                ranks: Vec::new(),
+                costs: Vec::new(),
+            })
+            .collect();
+        let mut eliminated = Vec::new();
+        let mut evaluations = 0;
+
+        let races = instances.iter().cycle().take(instances.len() * self.passes);
+        for (raced, problem) in races.enumerate() {
+            let costs: Vec<Option<f64>> = alive
+                .iter()
+                .map(|candidate| Self::evaluate(&candidate.config, problem))
+                .collect();
+            evaluations += costs.len();
+
+            for (candidate, rank) in alive.iter_mut().zip(Self::rank(&costs)) {
+                candidate.ranks.push(rank);
+            }
+            for (candidate, cost) in alive.iter_mut().zip(costs) {
+                candidate.costs.push(cost);
+            }
+
+            // Eliminate the worse half by average rank, keeping at least one candidate
+            alive.sort_by(|a, b| a.mean_rank().total_cmp(&b.mean_rank()));
+            if raced + 1 >= self.first_elimination && alive.len() > 1 {
+                let survivors = alive.len().div_ceil(2);
+                let mut losers = alive.split_off(survivors);
+                losers.reverse();
+                eliminated.extend(losers);
+            }
+        }
+
+        let best = alive[0].config.clone();
+        eliminated.extend(alive.into_iter().rev());
+        TuningResult {
+            best,
+            candidates: eliminated,
+            evaluations,
+        }
+    }
+
+    /// Run the algorithm once, returning the cost of the best feasible solution, or `None`
+    /// for an invalid configuration.
+    fn evaluate(config: &Config, problem: &Problem) -> Option<f64> {
+        let mut algorithm = HgsAlgorithm::new(problem.clone(), config.clone()).ok()?;
+        algorithm
+            .run()
+            .ok()
+            .filter(|solution| solution.is_feasible)
+            .map(|solution| solution.cost)
+    }
+
+    /// Rank the costs of a race from 0, infeasible runs last, ties sharing their average rank.
+    fn rank(costs: &[Option<f64>]) -> Vec<f64> {
+        let key = |cost: Option<f64>| cost.unwrap_or(f64::INFINITY);
+        let mut order: Vec<usize> = (0..costs.len()).collect();
+        order.sort_by(|&a, &b| key(costs[a]).total_cmp(&key(costs[b])));
+
+        let mut ranks = vec![0.0; costs.len()];
+        let mut start = 0;
+        while start < order.len() {
+            let mut end = start + 1;
+            while end < order.len() && key(costs[order[end]]) == key(costs[order[start]]) {
+                end += 1;
+            }
+            for &index in &order[start..end] {
+                ranks[index] = (start + end - 1) as f64 / 2.0;
+            }
+            start = end;
+        }
+        ranks
+    }
+}
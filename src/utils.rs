@@ -5,6 +5,7 @@ use std::io::Write;
 use std::path::Path;
 use std::time::Duration;
 
+use crate::local_search::{NeighborhoodStats, RouteCacheStats};
 use crate::problem::Problem;
 use crate::solution::Solution;
 
@@ -30,7 +31,16 @@ pub fn save_solution<P: AsRef<Path>>(
     writeln!(file, "Total Distance: {:.2}", solution.distance)?;
     writeln!(file, "Is Feasible: {}", solution.is_feasible)?;
     writeln!(file, "Number of Routes: {}", solution.routes.len())?;
-    writeln!(file, "")?;
+    let violations = solution.violations(problem);
+    if !violations.is_empty() {
+        writeln!(file, "Violations:")?;
+        for violation in &violations {
+            writeln!(file, "  - {}", violation)?;
+        }
+    }
+    writeln!(file)?;
+    write!(file, "{}", solution.summary(problem))?;
+    writeln!(file)?;
 
     for (i, route) in solution.routes.iter().enumerate() {
         write!(file, "Route #{}: ", i + 1)?;
@@ -54,7 +64,7 @@ pub fn save_solution<P: AsRef<Path>>(
             "  Load: {:.2} / {:.2}",
             route.load, problem.vehicle_capacity
         )?;
-        writeln!(file, "")?;
+        writeln!(file)?;
     }
 
     Ok(())
@@ -65,15 +75,14 @@ pub fn calculate_excess_load(solution: &Solution, problem: &Problem) -> f64 {
     let mut total_excess = 0.0;
 
     for route in &solution.routes {
-        if route.load > problem.vehicle_capacity {
-            total_excess += route.load - problem.vehicle_capacity;
-        }
+        total_excess += problem.load_excess(route.load, route.load_variance);
     }
 
     total_excess
 }
 
 /// Generate statistics about the search process.
+#[derive(Debug, Clone)]
 pub struct SearchStatistics {
     pub iterations: u32,
     pub runtime: Duration,
@@ -81,14 +90,27 @@ pub struct SearchStatistics {
     pub best_solution_distance: f64,
     pub best_solution_is_feasible: bool,
     pub best_solution_routes: usize,
+    /// Lowest and highest capacity utilization of the routes of the best solution
+    pub best_solution_utilization: Option<(f64, f64)>,
     pub average_population_size: usize,
     pub final_capacity_penalty: f64,
+    /// Memory allocated for the local search tracking structures, in bytes
+    pub tracking_memory_bytes: usize,
+    /// Hit and miss counts of the education route cache, if enabled
+    pub route_cache: Option<RouteCacheStats>,
+    /// Hit and miss counts of the cache of educated offspring, if enabled
+    pub education_cache: Option<RouteCacheStats>,
+    /// Moves evaluated and accepted by each neighborhood, intensification included
+    pub neighborhoods: NeighborhoodStats,
+    /// Each cost target of the configuration, with the time into the run at which the best
+    /// solution first reached it, `None` if it was not reached
+    pub time_to_target: Vec<(f64, Option<Duration>)>,
 }
 
 impl SearchStatistics {
     /// Format the statistics as a string.
     pub fn format(&self) -> String {
-        format!(
+        let mut text = format!(
             "Search Statistics:
 - Iterations: {}
 - Runtime: {}
@@ -97,7 +119,8 @@ impl SearchStatistics {
 - Best Solution Feasible: {}
 - Best Solution Routes: {}
 - Average Population Size: {}
-- Final Capacity Penalty: {:.2}",
+- Final Capacity Penalty: {:.2}
+- Tracking Memory: {:.1} KiB",
             self.iterations,
             format_duration(self.runtime),
             self.best_solution_cost,
@@ -105,8 +128,38 @@ impl SearchStatistics {
             self.best_solution_is_feasible,
             self.best_solution_routes,
             self.average_population_size,
-            self.final_capacity_penalty
-        )
+            self.final_capacity_penalty,
+            self.tracking_memory_bytes as f64 / 1024.0
+        );
+        if let Some((min, max)) = self.best_solution_utilization {
+            text.push_str(&format!(
+                "\n- Route Utilization: min {:.1}%, max {:.1}%",
+                100.0 * min,
+                100.0 * max
+            ));
+        }
+        if let Some(cache) = self.route_cache {
+            text.push_str(&format!(
+                "\n- Route Cache Hit Rate: {:.1}% ({} routes)",
+                100.0 * cache.hit_rate(),
+                cache.entries
+            ));
+        }
+        if let Some(cache) = self.education_cache {
+            text.push_str(&format!(
+                "\n- Education Cache Hit Rate: {:.1}% ({} solutions)",
+                100.0 * cache.hit_rate(),
+                cache.entries
+            ));
+        }
+        for &(target, time) in &self.time_to_target {
+            let reached = time.map_or("not reached".to_string(), |time| {
+                format!("reached after {:.3}s", time.as_secs_f64())
+            });
+            text.push_str(&format!("\n- Target {target:.2}: {reached}"));
+        }
+        text.push_str(&format!("\n- Neighborhoods:\n{}", self.neighborhoods));
+        text
     }
 }
 
@@ -115,8 +168,16 @@ pub fn print_solution_visualization(solution: &Solution, problem: &Problem) {
     println!("Solution Visualization for {}", problem.name);
     println!("Total Distance: {:.2}", solution.distance);
     println!("Number of Routes: {}", solution.routes.len());
+    for violation in solution.violations(problem) {
+        println!("Violation: {}", violation);
+    }
     println!();
 
+    if !problem.has_coordinates {
+        println!("No node coordinates available for visualization");
+        return;
+    }
+
     // Find max and min coordinates for scaling
     let mut min_x = f64::MAX;
     let mut min_y = f64::MAX;
@@ -1,8 +1,18 @@
 //! Integration tests for the full HGS-CVRP algorithm.
 
-use hgs_cvrp::config::Config;
+use hgs_cvrp::config::{Config, ConfigError, ConfigWarning};
+use hgs_cvrp::error::HgsError;
+use hgs_cvrp::genetic::CrossoverOperator;
+use hgs_cvrp::individual::Individual;
+use hgs_cvrp::local_search::{EducationCache, MoveKind};
+use hgs_cvrp::population::{ClonePolicy, PopulationSnapshot};
 use hgs_cvrp::problem::{Node, Problem};
+use hgs_cvrp::solution::{Route, Solution};
+use hgs_cvrp::split::Split;
 use hgs_cvrp::HgsAlgorithm;
+use rand::RngCore;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
 use std::time::Duration;
 
 /// Creates a moderate size test problem with a depot and customers.
@@ -38,7 +48,7 @@ fn test_algorithm_initialization() {
     let problem = create_moderate_problem();
     let config = Config::new().with_min_pop_size(10).with_generation_size(20);
 
-    let mut algorithm = HgsAlgorithm::new(problem, config);
+    let mut algorithm = HgsAlgorithm::new(problem, config).unwrap();
     algorithm.initialize();
 
     // Population should be initialized
@@ -51,6 +61,29 @@ fn test_algorithm_initialization() {
     assert!(algorithm.best_solution.as_ref().unwrap().is_feasible);
 }
 
+#[test]
+fn test_algorithm_without_feasible_solution() {
+    // A customer needs more than a vehicle can carry
+    let mut problem = create_moderate_problem();
+    problem.nodes[1].demand = 20.0;
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_time_limit(Duration::from_millis(200));
+
+    let mut algorithm = HgsAlgorithm::new(problem.clone(), config).unwrap();
+    assert!(matches!(algorithm.run(), Err(HgsError::NoFeasibleSolution)));
+
+    // Without a time limit, the run stops on stagnation instead of looping forever
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_max_iterations_without_improvement(50);
+    let mut algorithm = HgsAlgorithm::new(problem, config).unwrap();
+    assert!(matches!(algorithm.run(), Err(HgsError::NoFeasibleSolution)));
+    assert_eq!(algorithm.iterations_without_improvement, 50);
+}
+
 #[test]
 fn test_algorithm_short_run() {
     let problem = create_moderate_problem();
@@ -60,11 +93,12 @@ fn test_algorithm_short_run() {
         .with_max_iterations_without_improvement(100)
         .with_time_limit(Duration::from_secs(1));
 
-    let mut algorithm = HgsAlgorithm::new(problem, config);
-    let vehicle_capacity = algorithm.problem.vehicle_capacity;
+    let mut algorithm = HgsAlgorithm::new(problem, config).unwrap();
+    // Loads are sums of fractional demands, so allow for rounding
+    let vehicle_capacity = algorithm.problem.vehicle_capacity + algorithm.problem.tolerance;
     let number_nodes = algorithm.problem.nodes.len();
     // Run the algorithm
-    let solution = algorithm.run();
+    let solution = algorithm.run().unwrap();
     // Solution should exist and be feasible
     assert!(solution.is_feasible);
     assert!(solution.distance > 0.0);
@@ -83,8 +117,8 @@ fn test_algorithm_short_run() {
         }
     }
 
-    for i in 1..algorithm.problem.nodes.len() {
-        assert!(visited[i], "Customer {} not visited", i);
+    for (i, &seen) in visited.iter().enumerate().skip(1) {
+        assert!(seen, "Customer {} not visited", i);
     }
 }
 
@@ -99,10 +133,10 @@ fn test_algorithm_termination_time_limit() {
         .with_max_iterations_without_improvement(100000) // Very high to ensure time is the limiting factor
         .with_time_limit(time_limit);
 
-    let mut algorithm = HgsAlgorithm::new(problem, config);
+    let mut algorithm = HgsAlgorithm::new(problem, config).unwrap();
 
     // Run the algorithm
-    algorithm.run();
+    algorithm.run().unwrap();
 
     // Algorithm should terminate due to time limit
     assert!(algorithm.run_time >= time_limit);
@@ -119,10 +153,10 @@ fn test_algorithm_termination_iterations() {
         .with_max_iterations_without_improvement(max_iterations)
         .with_time_limit(Duration::MAX); // No time limit
 
-    let mut algorithm = HgsAlgorithm::new(problem, config);
+    let mut algorithm = HgsAlgorithm::new(problem, config).unwrap();
 
     // Run the algorithm
-    algorithm.run();
+    algorithm.run().unwrap();
 
     // Algorithm should terminate due to iteration limit
     assert!(algorithm.iterations_without_improvement >= max_iterations);
@@ -138,7 +172,7 @@ fn test_algorithm_improvement() {
         .with_max_iterations_without_improvement(200)
         .with_time_limit(Duration::from_secs(2));
 
-    let mut algorithm = HgsAlgorithm::new(problem, config);
+    let mut algorithm = HgsAlgorithm::new(problem, config).unwrap();
     algorithm.initialize();
 
     // Get initial solution quality
@@ -146,7 +180,7 @@ fn test_algorithm_improvement() {
     let initial_cost = initial_solution.cost;
 
     // Run the algorithm
-    let final_solution = algorithm.run();
+    let final_solution = algorithm.run().unwrap();
 
     // Final solution should be better than initial solution
     assert!(final_solution.cost < initial_cost);
@@ -165,12 +199,12 @@ fn test_algorithm_multiple_runs_consistency() {
         .with_time_limit(Duration::from_secs(2));
 
     // First run
-    let mut algorithm1 = HgsAlgorithm::new(problem.clone(), config.clone());
-    let solution1 = algorithm1.run();
+    let mut algorithm1 = HgsAlgorithm::new(problem.clone(), config.clone()).unwrap();
+    let solution1 = algorithm1.run().unwrap();
 
     // Second run
-    let mut algorithm2 = HgsAlgorithm::new(problem.clone(), config.clone());
-    let solution2 = algorithm2.run();
+    let mut algorithm2 = HgsAlgorithm::new(problem.clone(), config.clone()).unwrap();
+    let solution2 = algorithm2.run().unwrap();
 
     // The solutions should be similar in quality (within 20%)
     // But not necessarily identical due to randomness
@@ -197,12 +231,12 @@ fn test_algorithm_with_different_configs() {
         .with_time_limit(Duration::from_secs(1));
 
     // Run with small config
-    let mut small_algorithm = HgsAlgorithm::new(problem.clone(), small_config);
-    let small_solution = small_algorithm.run();
+    let mut small_algorithm = HgsAlgorithm::new(problem.clone(), small_config).unwrap();
+    let small_solution = small_algorithm.run().unwrap();
 
     // Run with large config
-    let mut large_algorithm = HgsAlgorithm::new(problem.clone(), large_config);
-    let large_solution = large_algorithm.run();
+    let mut large_algorithm = HgsAlgorithm::new(problem.clone(), large_config).unwrap();
+    let large_solution = large_algorithm.run().unwrap();
 
     // Both solutions should be feasible
     assert!(small_solution.is_feasible);
@@ -214,3 +248,878 @@ fn test_algorithm_with_different_configs() {
     let ratio = small_solution.cost / large_solution.cost;
     assert!(ratio > 0.7 && ratio < 1.3);
 }
+
+#[test]
+fn test_educate_and_insert_repairs_infeasible_offspring() {
+    let problem = create_moderate_problem();
+    let config = Config::new()
+        .with_repair_probability(1.0)
+        .with_repair_penalty_multiplier(1000.0);
+
+    let mut algorithm = HgsAlgorithm::new(problem, config).unwrap();
+
+    // All customers in a single route exceed the vehicle capacity
+    let mut offspring = Solution::new();
+    let mut route = Route::new();
+    route.customers = (1..=20).collect();
+    offspring.routes.push(route);
+    offspring.evaluate(
+        &algorithm.problem,
+        algorithm.population.penalties.capacity_penalty(),
+    );
+    assert!(!offspring.is_feasible);
+
+    algorithm.educate_and_insert(offspring);
+
+    // The repaired version should be part of the population
+    assert!(!algorithm.population.feasible_individuals.is_empty());
+    assert!(algorithm.population.get_pop_size() <= 2);
+    for individual in &algorithm.population.feasible_individuals {
+        for route in &individual.solution.routes {
+            // Loads are sums of fractional demands, so allow for rounding
+            assert!(route.load <= algorithm.problem.vehicle_capacity + algorithm.problem.tolerance);
+        }
+    }
+}
+
+#[test]
+fn test_educate_and_insert_clone_policy() {
+    for policy in [
+        ClonePolicy::Accept,
+        ClonePolicy::Reject,
+        ClonePolicy::Mutate,
+    ] {
+        let config = Config::new().with_min_pop_size(5).with_clone_policy(policy);
+        let mut algorithm = HgsAlgorithm::new(create_moderate_problem(), config).unwrap();
+        algorithm.initialize();
+
+        // An offspring identical to an individual of the population
+        let existing = algorithm.population.feasible_individuals[0]
+            .solution
+            .clone();
+        assert!(algorithm.population.contains_clone(&existing));
+        let size = algorithm.population.get_pop_size();
+        algorithm.educate_and_insert(existing);
+
+        let inserted = algorithm.population.get_pop_size() - size;
+        match policy {
+            ClonePolicy::Reject => assert_eq!(inserted, 0),
+            _ => assert!(inserted >= 1, "{policy:?}"),
+        }
+    }
+}
+
+#[test]
+fn test_educate_and_insert_education_cache() {
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_repair_probability(0.0)
+        .with_education_cache_size(4);
+    let mut algorithm = HgsAlgorithm::new(create_moderate_problem(), config).unwrap();
+
+    let mut offspring = Solution::new();
+    offspring.giant_tour = (1..=20).collect();
+    Split::split(&mut offspring, &algorithm.problem);
+    algorithm.educate_and_insert(offspring.clone());
+    algorithm.educate_and_insert(offspring);
+
+    // The second offspring reuses the education of the first one
+    let population = &algorithm.population;
+    let educated: Vec<&Individual> = population
+        .feasible_individuals
+        .iter()
+        .chain(&population.infeasible_individuals)
+        .collect();
+    assert_eq!(educated.len(), 2);
+    assert!(educated[0].is_clone_of(educated[1]));
+    assert!((educated[0].get_cost() - educated[1].get_cost()).abs() < 1e-9);
+    let stats = algorithm.statistics().education_cache.unwrap();
+    assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+    assert!(algorithm
+        .statistics()
+        .format()
+        .contains("Education Cache Hit Rate: 50.0%"));
+
+    // Least recently used tours are evicted beyond the capacity
+    let mut cache = EducationCache::new(2);
+    let solution = Solution::new();
+    cache.insert(vec![1, 2], &solution);
+    cache.insert(vec![2, 1], &solution);
+    assert!(cache.get(&[1, 2]).is_some());
+    cache.insert(vec![1, 3], &solution);
+    assert!(cache.get(&[2, 1]).is_none());
+    assert!(cache.get(&[1, 2]).is_some());
+    assert_eq!(cache.stats().entries, 2);
+}
+
+#[test]
+fn test_algorithm_config_tolerance() {
+    let config = Config::new().with_tolerance(1e-3);
+    let algorithm = HgsAlgorithm::new(create_moderate_problem(), config).unwrap();
+
+    // The configured tolerance overrides the one of the problem
+    assert_eq!(algorithm.problem.tolerance, 1e-3);
+}
+
+#[test]
+fn test_algorithm_education_budget() {
+    let config = Config::new()
+        .with_max_education_moves(100)
+        .with_max_education_time(Duration::from_millis(5));
+    let algorithm = HgsAlgorithm::new(create_moderate_problem(), config).unwrap();
+
+    // The budget is passed on to the local search
+    assert_eq!(algorithm.local_search.max_moves, Some(100));
+    assert_eq!(
+        algorithm.local_search.max_time,
+        Some(Duration::from_millis(5))
+    );
+}
+
+/// A crossover that copies the first parent and counts how often it is used.
+struct CopyCrossover {
+    calls: Rc<Cell<usize>>,
+}
+
+impl CrossoverOperator for CopyCrossover {
+    fn crossover(&self, parent1: &Individual, _parent2: &Individual) -> Solution {
+        self.calls.set(self.calls.get() + 1);
+        let mut offspring = Solution::new();
+        offspring.giant_tour = parent1.solution.giant_tour.clone();
+        offspring
+    }
+}
+
+#[test]
+fn test_algorithm_custom_crossover() {
+    let problem = create_moderate_problem();
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_max_iterations_without_improvement(20)
+        .with_time_limit(Duration::from_secs(1));
+
+    let calls = Rc::new(Cell::new(0));
+    let mut algorithm = HgsAlgorithm::new(problem, config)
+        .unwrap()
+        .with_crossover(CopyCrossover {
+            calls: Rc::clone(&calls),
+        });
+
+    let solution = algorithm.run().unwrap();
+    assert!(solution.is_feasible);
+
+    // Every iteration used the custom operator
+    assert!(calls.get() > 0);
+    assert_eq!(calls.get() as u32, algorithm.iterations);
+}
+
+#[test]
+fn test_algorithm_tracks_pareto_front() {
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_max_iterations_without_improvement(50)
+        .with_track_pareto_front(true);
+
+    let mut algorithm = HgsAlgorithm::new(create_moderate_problem(), config).unwrap();
+    let best = algorithm.run().unwrap().clone();
+
+    let front = algorithm.pareto_front.solutions();
+    assert!(!front.is_empty());
+    assert!(front.iter().all(|solution| solution.is_feasible));
+
+    // Ordered by fleet size with strictly decreasing distances
+    for pair in front.windows(2) {
+        assert!(pair[0].get_used_route_count() < pair[1].get_used_route_count());
+        assert!(pair[0].distance > pair[1].distance);
+    }
+
+    // The best solution is on the front or dominated by one of its solutions
+    assert!(front.iter().any(|solution| {
+        solution.get_used_route_count() <= best.get_used_route_count()
+            && solution.distance <= best.distance + 1e-6
+    }));
+}
+
+#[test]
+fn test_algorithm_with_decomposition() {
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_max_iterations_without_improvement(30)
+        .with_decomposition(10, 8)
+        .with_decomposition_period(10)
+        .with_decomposition_iterations(20);
+
+    let problem = create_moderate_problem();
+    let mut algorithm = HgsAlgorithm::new(problem.clone(), config).unwrap();
+    let solution = algorithm.run().unwrap();
+
+    assert!(solution.is_feasible);
+    assert!(solution.has_complete_routes(&problem));
+}
+
+#[test]
+fn test_algorithm_run_from_initial_solution() {
+    let problem = create_moderate_problem();
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_max_iterations_without_improvement(20);
+
+    // One route per customer is feasible but far from optimal
+    let mut initial = Solution::new();
+    for customer in problem.customers() {
+        let mut route = Route::new();
+        route.customers = vec![customer];
+        initial.routes.push(route);
+    }
+    initial.update_giant_tour();
+    initial.evaluate(&problem, 1.0);
+
+    let mut algorithm = HgsAlgorithm::new(problem.clone(), config).unwrap();
+    let solution = algorithm.run_from(initial.clone()).unwrap();
+
+    assert!(solution.is_feasible);
+    assert!(solution.has_complete_routes(&problem));
+    assert!(solution.cost < initial.cost);
+
+    // Each merged route carries on the identifier of one of the routes it absorbed
+    for route in solution.routes.iter().filter(|route| !route.is_empty()) {
+        let initial_route = initial.route_by_id(route.id).unwrap();
+        assert!(route.customers.contains(&initial_route.customers[0]));
+    }
+}
+
+#[test]
+fn test_algorithm_progress_callback() {
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_max_iterations_without_improvement(30)
+        .with_time_limit(Duration::from_secs(5));
+
+    let reports = Rc::new(RefCell::new(Vec::new()));
+    let recorded = Rc::clone(&reports);
+    let mut algorithm = HgsAlgorithm::new(create_moderate_problem(), config)
+        .unwrap()
+        .with_progress_callback(move |progress| recorded.borrow_mut().push(progress.clone()));
+    let best_cost = algorithm.run().unwrap().cost;
+
+    // One report per generation, with a monotone elapsed time and non-increasing best cost
+    let reports = reports.borrow();
+    assert_eq!(reports.len() as u32, algorithm.iterations);
+    for pair in reports.windows(2) {
+        assert_eq!(pair[1].iterations, pair[0].iterations + 1);
+        assert!(pair[1].elapsed >= pair[0].elapsed);
+        assert!(pair[1].best_cost.unwrap() <= pair[0].best_cost.unwrap() + 1e-9);
+    }
+
+    let last = reports.last().unwrap();
+    assert!((last.best_cost.unwrap() - best_cost).abs() < 1e-9);
+    assert!(last.improvement_rate >= 0.0);
+    assert!(last.time_fraction.unwrap() < 1.0);
+    assert!(last.fraction() > 0.0 && last.fraction() <= 1.0);
+    assert!(last.eta().unwrap() <= Duration::from_secs(5));
+}
+
+#[test]
+fn test_algorithm_param_handle() {
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_max_iterations_without_improvement(30)
+        .with_granularity(10);
+
+    let algorithm = HgsAlgorithm::new(create_moderate_problem(), config).unwrap();
+    let handle = algorithm.param_handle();
+    assert_eq!(handle.get().granularity, 10);
+
+    // Tune the running search from the progress callback
+    let tuner = handle.clone();
+    let mut algorithm = algorithm.with_progress_callback(move |progress| {
+        if progress.iterations == 5 {
+            tuner.update(|params| {
+                params.granularity = 5;
+                params.target_feasible_ratio = 0.5;
+                params.penalty_min = 2.0;
+                params.penalty_max = 3.0;
+            });
+        }
+    });
+    let solution = algorithm.run().unwrap();
+    assert!(solution.is_feasible);
+    assert!(algorithm.iterations > 5);
+
+    assert_eq!(handle.version(), 1);
+    assert_eq!(algorithm.config.granularity, 5);
+    assert_eq!(algorithm.local_search.granularity, 5);
+    assert_eq!(algorithm.local_search.customer_neighbors.granularity(), 5);
+    let capacity = &algorithm.population.penalties.capacity;
+    assert_eq!(capacity.target_feasible_ratio, 0.5);
+    assert!(capacity.coefficient >= 2.0 && capacity.coefficient <= 3.0);
+
+    // Handles can be used from other threads
+    let remote = handle.clone();
+    std::thread::spawn(move || remote.update(|params| params.granularity = 8))
+        .join()
+        .unwrap();
+    assert_eq!(handle.get().granularity, 8);
+    assert_eq!(handle.version(), 2);
+}
+
+#[test]
+fn test_algorithm_elite_intensification() {
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_max_iterations_without_improvement(30)
+        .with_granularity(3)
+        .with_elite_intensification(true)
+        .with_intensification_granularity(19);
+
+    let problem = create_moderate_problem();
+    let mut algorithm = HgsAlgorithm::new(problem.clone(), config).unwrap();
+    let best = algorithm.run().unwrap().clone();
+
+    assert!(best.is_feasible);
+    assert!(best.has_complete_routes(&problem));
+    let intensification = algorithm.intensification_search.as_ref().unwrap();
+    assert_eq!(intensification.granularity, 19);
+
+    // Intensifying again never makes the best solution worse
+    algorithm.intensify_best();
+    assert!(algorithm.best_solution.as_ref().unwrap().cost <= best.cost + 1e-9);
+}
+
+#[test]
+fn test_algorithm_statistics() {
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_max_iterations_without_improvement(20);
+
+    let mut algorithm = HgsAlgorithm::new(create_moderate_problem(), config).unwrap();
+    let best = algorithm.run().unwrap().clone();
+    let statistics = algorithm.statistics();
+
+    assert_eq!(statistics.iterations, algorithm.iterations);
+    assert_eq!(statistics.best_solution_cost, best.cost);
+    assert!(statistics.best_solution_is_feasible);
+    assert_eq!(statistics.best_solution_routes, best.get_used_route_count());
+    assert!(statistics.average_population_size >= 5);
+    assert!(statistics.tracking_memory_bytes > 0);
+    assert!(statistics.format().contains("Tracking Memory"));
+    let (min, max) = statistics.best_solution_utilization.unwrap();
+    assert!(0.0 < min && min <= max && max <= 1.0 + 1e-9);
+    assert!(statistics.format().contains("Route Utilization"));
+
+    // Every applied move is attributed to a neighborhood
+    let neighborhoods = statistics.neighborhoods;
+    assert!(neighborhoods.total_accepted() > 0);
+    for (name, operator) in neighborhoods.iter() {
+        assert!(operator.accepted <= operator.evaluated, "{name}");
+    }
+    assert_eq!(algorithm.progress().neighborhoods, neighborhoods);
+    assert!(statistics.format().contains("SWAP*"));
+}
+
+#[test]
+fn test_algorithm_time_to_target() {
+    let problem = create_moderate_problem();
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_max_iterations_without_improvement(20);
+
+    // The cost of a first run places one target above the best cost and one below it
+    let mut algorithm = HgsAlgorithm::new(problem.clone(), config.clone()).unwrap();
+    let best_cost = algorithm.run().unwrap().cost;
+    assert!(algorithm.statistics().time_to_target.is_empty());
+
+    let targets = vec![2.0 * best_cost, best_cost, 0.0];
+    let config = config.with_cost_targets(targets.clone());
+    let mut algorithm = HgsAlgorithm::new(problem, config).unwrap();
+    let best = algorithm.run().unwrap().clone();
+    let statistics = algorithm.statistics();
+
+    let reported: Vec<f64> = statistics.time_to_target.iter().map(|&(t, _)| t).collect();
+    assert_eq!(reported, targets);
+    let times: Vec<Option<Duration>> = statistics.time_to_target.iter().map(|&(_, t)| t).collect();
+    let loose = times[0].unwrap();
+    assert!(loose <= statistics.runtime);
+    assert_eq!(
+        times[1].is_some(),
+        best.cost <= best_cost + algorithm.problem.tolerance
+    );
+    if let Some(tight) = times[1] {
+        assert!(loose <= tight);
+    }
+    assert_eq!(times[2], None);
+    assert!(statistics.format().contains("not reached"));
+}
+
+#[test]
+fn test_algorithm_penalty_adjustment_period() {
+    // With a period longer than the run, the penalty keeps its initial value
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_max_iterations_without_improvement(20)
+        .with_initial_capacity_penalty(3.0)
+        .with_penalty_adjustment_period(u32::MAX);
+
+    let mut algorithm = HgsAlgorithm::new(create_moderate_problem(), config).unwrap();
+    algorithm.run().unwrap();
+    assert!(algorithm.iterations > 1);
+    assert_eq!(algorithm.statistics().final_capacity_penalty, 3.0);
+}
+
+#[test]
+fn test_algorithm_population_keeps_best_solution() {
+    // A tiny population goes through survivor selection at almost every iteration
+    let config = Config::new()
+        .with_min_pop_size(2)
+        .with_generation_size(1)
+        .with_max_iterations_without_improvement(100)
+        .with_time_limit(Duration::from_secs(5));
+
+    let mut algorithm = HgsAlgorithm::new(create_moderate_problem(), config).unwrap();
+    let best_cost = algorithm.run().unwrap().cost;
+
+    let population_best = algorithm.population.get_best_feasible_solution().unwrap();
+    assert!(population_best.cost <= best_cost + 1e-9);
+    assert!(algorithm.statistics().best_solution_cost <= best_cost + 1e-9);
+}
+
+#[test]
+fn test_algorithm_adaptive_granularity() {
+    // The run ends by stagnation, after which the granularity has grown to its maximum
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_granularity(5)
+        .with_adaptive_granularity(4, 8)
+        .with_granularity_adjustment_period(1)
+        .with_max_iterations_without_improvement(30)
+        .with_time_limit(Duration::from_secs(30));
+
+    let mut algorithm = HgsAlgorithm::new(create_moderate_problem(), config).unwrap();
+    assert!(algorithm.run().unwrap().is_feasible);
+
+    assert_eq!(algorithm.config.granularity, 8);
+    assert_eq!(algorithm.local_search.granularity, 8);
+    assert_eq!(algorithm.param_handle().get().granularity, 8);
+}
+
+#[test]
+fn test_algorithm_tour_improvement() {
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_tour_improvement_passes(3)
+        .with_max_iterations_without_improvement(30)
+        .with_time_limit(Duration::from_secs(10));
+
+    let mut algorithm = HgsAlgorithm::new(create_moderate_problem(), config).unwrap();
+    let solution = algorithm.run().unwrap();
+    assert!(solution.is_feasible);
+    assert_eq!(
+        solution
+            .routes
+            .iter()
+            .map(|r| r.customers.len())
+            .sum::<usize>(),
+        algorithm.problem.nodes.len() - 1
+    );
+}
+
+#[test]
+fn test_algorithm_population_snapshots() {
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_max_iterations_without_improvement(20)
+        .with_population_snapshots(5);
+
+    let mut algorithm = HgsAlgorithm::new(create_moderate_problem(), config).unwrap();
+    algorithm.run().unwrap();
+
+    let snapshots = &algorithm.population_snapshots;
+    assert_eq!(snapshots.len(), (algorithm.iterations / 5) as usize);
+    for (iteration, snapshot) in snapshots {
+        assert_eq!(iteration % 5, 0);
+        assert!(!snapshot.individuals.is_empty());
+        for individual in &snapshot.individuals {
+            assert_eq!(individual.giant_tour.len(), 20);
+        }
+    }
+
+    // Snapshots survive a round trip through JSON
+    let snapshot = algorithm.population.snapshot();
+    assert_eq!(
+        snapshot.individuals.len(),
+        algorithm.population.get_pop_size()
+    );
+    let json = serde_json::to_string(&snapshot).unwrap();
+    let parsed: PopulationSnapshot = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.individuals.len(), snapshot.individuals.len());
+    for (parsed, individual) in parsed.individuals.iter().zip(&snapshot.individuals) {
+        assert_eq!(parsed.giant_tour, individual.giant_tour);
+        assert_eq!(parsed.rank_feasibility, individual.rank_feasibility);
+        assert!((parsed.cost - individual.cost).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn test_algorithm_stop_on_feasible() {
+    // The initial population already holds a feasible solution
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_max_iterations_without_improvement(u32::MAX)
+        .with_time_limit(Duration::from_secs(30))
+        .with_stop_on_feasible(Duration::ZERO);
+
+    let mut algorithm = HgsAlgorithm::new(create_moderate_problem(), config).unwrap();
+    assert!(algorithm.run().unwrap().is_feasible);
+    assert_eq!(algorithm.iterations, 0);
+
+    // Polishing continues the search for the given time only
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_max_iterations_without_improvement(u32::MAX)
+        .with_time_limit(Duration::from_secs(30))
+        .with_stop_on_feasible(Duration::from_millis(200));
+
+    let mut algorithm = HgsAlgorithm::new(create_moderate_problem(), config).unwrap();
+    assert!(algorithm.run().unwrap().is_feasible);
+    assert!(algorithm.iterations > 0);
+    assert!(algorithm.run_time >= Duration::from_millis(200));
+    assert!(algorithm.run_time < Duration::from_secs(20));
+}
+
+#[test]
+fn test_algorithm_seeded_runs_are_reproducible() {
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_max_iterations_without_improvement(50)
+        .with_time_limit(Duration::from_secs(30))
+        .with_seed(42);
+
+    let mut first = HgsAlgorithm::new(create_moderate_problem(), config.clone()).unwrap();
+    let mut second = HgsAlgorithm::new(create_moderate_problem(), config).unwrap();
+    let first_routes = first.run().unwrap().routes.clone();
+    let second_routes = second.run().unwrap().routes.clone();
+
+    assert_eq!(first.iterations, second.iterations);
+    assert_eq!(first_routes.len(), second_routes.len());
+    for (a, b) in first_routes.iter().zip(&second_routes) {
+        assert_eq!(a.customers, b.customers);
+    }
+}
+
+#[test]
+fn test_algorithm_run_multi() {
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_max_iterations_without_improvement(30)
+        .with_time_limit(Duration::from_secs(30));
+
+    let mut algorithm = HgsAlgorithm::new(create_moderate_problem(), config).unwrap();
+    let report = algorithm.run_multi(3, &[7, 11]).unwrap();
+
+    assert_eq!(report.runs.len(), 3);
+    assert_eq!(report.runs[0].seed, 7);
+    assert_eq!(report.runs[1].seed, 11);
+    assert!(algorithm.config.seed.is_none());
+    for run in &report.runs {
+        let cost = run.solution.as_ref().unwrap().cost;
+        assert!(report.best.cost <= cost);
+        assert_eq!(run.statistics.best_solution_cost, cost);
+    }
+    assert_eq!(
+        report.runs[report.best_run].solution.as_ref().unwrap().cost,
+        report.best.cost
+    );
+    assert_eq!(
+        algorithm.best_solution.as_ref().unwrap().cost,
+        report.best.cost
+    );
+
+    // Parallel runs with the same seeds reach the same solutions
+    let parallel = algorithm.run_multi_parallel(2, &[7, 11]).unwrap();
+    assert_eq!(parallel.runs.len(), 2);
+    for (run, sequential) in parallel.runs.iter().zip(&report.runs) {
+        assert_eq!(run.seed, sequential.seed);
+        assert_eq!(
+            run.solution.as_ref().unwrap().cost,
+            sequential.solution.as_ref().unwrap().cost
+        );
+    }
+}
+
+#[test]
+fn test_algorithm_run_multi_runs_are_independent() {
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_max_iterations_without_improvement(30);
+    let seeds = [7, 11, 13];
+
+    // An earlier run leaves its population, penalties, and best solution behind
+    let mut algorithm =
+        HgsAlgorithm::new(create_moderate_problem(), config.clone().with_seed(3)).unwrap();
+    algorithm.run().unwrap();
+    let report = algorithm.run_multi(seeds.len(), &seeds).unwrap();
+    assert_eq!(algorithm.config.seed, Some(3));
+
+    // Each run reaches the solution of a new algorithm with its seed
+    for (run, &seed) in report.runs.iter().zip(&seeds) {
+        let mut fresh =
+            HgsAlgorithm::new(create_moderate_problem(), config.clone().with_seed(seed)).unwrap();
+        let expected = fresh.run().unwrap();
+        let solution = run.solution.as_ref().unwrap();
+        assert_eq!(solution.cost, expected.cost);
+        assert_eq!(solution.giant_tour, expected.giant_tour);
+        assert_eq!(run.statistics.iterations, fresh.iterations);
+    }
+}
+
+#[test]
+fn test_algorithm_unseeded_run_after_seeded_run() {
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_max_iterations_without_improvement(5);
+
+    // A seeded run does not leave the thread seeded for the unseeded runs after it
+    let mut draws = Vec::new();
+    for _ in 0..2 {
+        let seeded = config.clone().with_seed(5);
+        HgsAlgorithm::new(create_moderate_problem(), seeded)
+            .unwrap()
+            .run()
+            .unwrap();
+        HgsAlgorithm::new(create_moderate_problem(), config.clone())
+            .unwrap()
+            .run()
+            .unwrap();
+        draws.push(hgs_cvrp::rng::search_rng().next_u64());
+    }
+    assert_ne!(draws[0], draws[1]);
+}
+
+#[test]
+fn test_algorithm_phase_budget() {
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_max_iterations_without_improvement(u32::MAX)
+        .with_time_limit(Duration::from_millis(600))
+        .with_phase_budget(0.05, 0.5, 0.45);
+
+    let last_generation = Rc::new(Cell::new(Duration::ZERO));
+    let recorded = Rc::clone(&last_generation);
+    let mut algorithm = HgsAlgorithm::new(create_moderate_problem(), config)
+        .unwrap()
+        .with_progress_callback(move |progress| recorded.set(progress.elapsed));
+    assert!(algorithm.run().unwrap().is_feasible);
+
+    // Evolution stops at 55% of the time limit, and the final intensification before the end
+    assert!(algorithm.iterations > 0);
+    assert!(last_generation.get() >= Duration::from_millis(300));
+    assert!(last_generation.get() < Duration::from_millis(550));
+    assert!(algorithm.run_time >= Duration::from_millis(330));
+    assert!(algorithm.run_time < Duration::from_secs(1));
+}
+
+#[test]
+fn test_algorithm_final_polish() {
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_granularity(5)
+        .with_max_iterations_without_improvement(10)
+        .with_time_limit(Duration::from_secs(5));
+
+    let mut algorithm = HgsAlgorithm::new(create_moderate_problem(), config).unwrap();
+    let unpolished = algorithm.run().unwrap().cost;
+
+    // Polishing the same best solution again never makes it worse
+    algorithm.polish_best(Duration::from_secs(1));
+    let polished = algorithm.best_solution.as_ref().unwrap();
+    assert!(polished.is_feasible);
+    assert!(polished.cost <= unpolished);
+
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_max_iterations_without_improvement(10)
+        .with_time_limit(Duration::from_secs(5))
+        .with_final_polish(Duration::from_millis(500));
+    let mut algorithm = HgsAlgorithm::new(create_moderate_problem(), config).unwrap();
+    assert!(algorithm.run().unwrap().is_feasible);
+}
+
+#[test]
+fn test_algorithm_search_trace() {
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_max_iterations_without_improvement(10)
+        .with_time_limit(Duration::from_secs(5))
+        .with_search_trace(true);
+
+    let problem = create_moderate_problem();
+    let mut algorithm = HgsAlgorithm::new(problem.clone(), config).unwrap();
+    algorithm.run().unwrap();
+
+    // Every education of the run replays without divergence
+    let trace = algorithm.local_search.trace.as_ref().unwrap();
+    assert!(trace.education_count() >= algorithm.iterations as usize);
+    for education in 0..trace.education_count() {
+        assert!(trace.replay(education, &problem).unwrap().is_ok());
+    }
+}
+
+#[test]
+fn test_config_validation() {
+    let problem = create_moderate_problem();
+    assert_eq!(
+        Config::new().with_granularity(10).validate(&problem),
+        Ok(Vec::new())
+    );
+
+    // Values that would break the search are rejected by the constructor
+    let zero_granularity = Config::new().with_granularity(0);
+    assert_eq!(
+        zero_granularity.validate(&problem),
+        Err(ConfigError::Zero {
+            parameter: "granularity"
+        })
+    );
+    assert!(matches!(
+        HgsAlgorithm::new(problem.clone(), zero_granularity),
+        Err(HgsError::InvalidConfig(ConfigError::Zero { .. }))
+    ));
+    assert!(Config::new()
+        .with_generation_size(0)
+        .validate(&problem)
+        .is_err());
+    assert!(matches!(
+        Config::new()
+            .with_repair_probability(1.5)
+            .validate(&problem),
+        Err(ConfigError::NotAProbability {
+            parameter: "repair_probability",
+            ..
+        })
+    ));
+    assert!(matches!(
+        Config::new()
+            .with_penalty_bounds(5.0, 1.0)
+            .validate(&problem),
+        Err(ConfigError::PenaltyBounds { .. })
+    ));
+    assert_eq!(
+        Config::new()
+            .with_phase_budget(0.5, 0.5, 0.5)
+            .validate(&problem),
+        Err(ConfigError::PhaseBudgetSum(1.5))
+    );
+    assert_eq!(
+        Config::new()
+            .with_operator_granularity(MoveKind::SwapStar, 0)
+            .validate(&problem),
+        Err(ConfigError::Zero {
+            parameter: "operator_granularity"
+        })
+    );
+
+    // Degenerate values are only reported
+    let config = Config::new()
+        .with_min_pop_size(3)
+        .with_n_elite(4)
+        .with_granularity(25)
+        .with_max_iterations_without_improvement(u32::MAX);
+    let warnings = config.validate(&problem).unwrap();
+    assert!(warnings.contains(&ConfigWarning::EliteExceedsPopulation {
+        n_elite: 4,
+        min_pop_size: 3
+    }));
+    assert!(
+        warnings.contains(&ConfigWarning::GranularityExceedsCustomers {
+            granularity: 25,
+            customers: 20
+        })
+    );
+    assert!(warnings.contains(&ConfigWarning::NoTermination));
+    assert!(Config::new()
+        .with_phase_budget(0.05, 0.85, 0.1)
+        .validate(&problem)
+        .unwrap()
+        .contains(&ConfigWarning::PhaseBudgetWithoutTimeLimit));
+    assert!(warnings[0].to_string().contains("with_n_elite"));
+}
+
+#[test]
+fn test_config_instance_scaling() {
+    let problem = create_moderate_problem();
+
+    // Defaults are fitted to a small population and instance
+    let config = Config::new()
+        .with_min_pop_size(3)
+        .with_max_iterations_without_improvement(20);
+    let scaled = config.scaled_to(&problem);
+    assert_eq!(scaled.granularity, 19);
+    assert_eq!(scaled.n_closest, 2);
+    assert_eq!(scaled.n_elite, 3);
+    assert_eq!(scaled.validate(&problem), Ok(Vec::new()));
+
+    // Values set with the builders are kept
+    let overridden = config.clone().with_granularity(25).with_n_elite(4);
+    let scaled = overridden.scaled_to(&problem);
+    assert_eq!((scaled.granularity, scaled.n_elite), (25, 4));
+    assert_eq!(scaled.n_closest, 2);
+    let unscaled = config.with_instance_scaling(false).scaled_to(&problem);
+    assert_eq!(unscaled.granularity, 20);
+
+    // The defaults run on an instance with fewer customers than the granularity
+    let nodes = vec![
+        Node::new(0, 0.0, 0.0, 0.0, true),
+        Node::new(1, 10.0, 0.0, 1.0, false),
+        Node::new(2, 0.0, 10.0, 1.0, false),
+        Node::new(3, 10.0, 10.0, 1.0, false),
+    ];
+    let tiny = Problem::new("Tiny".to_string(), nodes, 0, 2.0, None);
+    let config = Config::new()
+        .with_min_pop_size(2)
+        .with_generation_size(2)
+        .with_max_iterations_without_improvement(10);
+    let mut algorithm = HgsAlgorithm::new(tiny.clone(), config).unwrap();
+    let solution = algorithm.run().unwrap();
+    assert!(solution.is_feasible);
+    assert!(solution.has_complete_routes(&tiny));
+}
+
+#[test]
+fn test_algorithm_operator_granularity() {
+    let problem = create_moderate_problem();
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_granularity(6)
+        .with_operator_granularity(MoveKind::Relocate, 10)
+        .with_operator_granularity(MoveKind::SwapStar, 3)
+        .with_max_iterations_without_improvement(30);
+
+    let mut algorithm = HgsAlgorithm::new(problem.clone(), config).unwrap();
+    let solution = algorithm.run().unwrap();
+    assert!(solution.is_feasible);
+    assert!(solution.has_complete_routes(&problem));
+}
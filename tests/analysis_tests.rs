@@ -0,0 +1,92 @@
+//! Unit tests for the fitness landscape analysis helpers.
+
+use hgs_cvrp::analysis::{
+    correlation, fitness_distance_correlation, fitness_distances, FitnessDistance,
+};
+use hgs_cvrp::config::Config;
+use hgs_cvrp::individual::Individual;
+use hgs_cvrp::problem::{Node, Problem};
+use hgs_cvrp::solution::Solution;
+use hgs_cvrp::HgsAlgorithm;
+use std::time::Duration;
+
+/// Creates a test problem with 12 customers on a ring.
+fn create_test_problem() -> Problem {
+    let mut nodes = Vec::new();
+
+    // Depot at (50, 50)
+    nodes.push(Node::new(0, 50.0, 50.0, 0.0, true));
+
+    for id in 1..=12 {
+        let angle = id as f64 * std::f64::consts::PI / 6.0;
+        let x = 50.0 + 20.0 * angle.cos();
+        let y = 50.0 + 20.0 * angle.sin();
+        nodes.push(Node::new(id, x, y, 1.0, false));
+    }
+
+    Problem::new("AnalysisTestProblem".to_string(), nodes, 0, 4.0, None)
+}
+
+/// Creates an individual with the given giant tour and cost.
+fn individual(giant_tour: Vec<usize>, cost: f64) -> Individual {
+    let mut solution = Solution::new();
+    solution.giant_tour = giant_tour;
+    solution.cost = cost;
+    Individual::new(solution)
+}
+
+#[test]
+fn test_fitness_distances() {
+    let best = individual(vec![1, 2, 3, 4, 5], 10.0).solution;
+    let individuals = [
+        individual(vec![1, 2, 3, 4, 5], 10.0),
+        individual(vec![1, 2, 3, 5, 4], 12.0),
+        individual(vec![5, 4, 3, 2, 1], 15.0),
+    ];
+
+    let points = fitness_distances(&individuals, &best);
+    assert_eq!(
+        points
+            .iter()
+            .map(|point| point.distance)
+            .collect::<Vec<_>>(),
+        vec![0.0, 0.5, 1.0]
+    );
+    assert_eq!(points[2].cost, 15.0);
+
+    // Costs growing with the distance are perfectly correlated
+    let fdc = correlation(&points).unwrap();
+    assert!(fdc > 0.9 && fdc <= 1.0);
+}
+
+#[test]
+fn test_correlation() {
+    let point = |distance, cost| FitnessDistance { distance, cost };
+
+    let increasing = [point(0.0, 1.0), point(0.5, 2.0), point(1.0, 3.0)];
+    assert!((correlation(&increasing).unwrap() - 1.0).abs() < 1e-12);
+
+    let decreasing = [point(0.0, 3.0), point(0.5, 2.0), point(1.0, 1.0)];
+    assert!((correlation(&decreasing).unwrap() + 1.0).abs() < 1e-12);
+
+    // Undefined without spread in either variable
+    assert_eq!(correlation(&[point(0.5, 1.0)]), None);
+    assert_eq!(correlation(&[point(0.5, 1.0), point(0.5, 2.0)]), None);
+    assert_eq!(correlation(&[point(0.0, 1.0), point(1.0, 1.0)]), None);
+}
+
+#[test]
+fn test_fitness_distance_correlation_of_population() {
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_max_iterations_without_improvement(20)
+        .with_time_limit(Duration::from_secs(10));
+
+    let mut algorithm = HgsAlgorithm::new(create_test_problem(), config).unwrap();
+    let best = algorithm.run().unwrap().clone();
+
+    if let Some(fdc) = fitness_distance_correlation(&algorithm.population, &best) {
+        assert!((-1.0..=1.0).contains(&fdc));
+    }
+}
@@ -0,0 +1,106 @@
+//! Unit tests for the decomposition of large instances into subproblems.
+
+use hgs_cvrp::config::Config;
+use hgs_cvrp::decomposition::{Decomposition, Subproblem};
+use hgs_cvrp::problem::{Node, Problem};
+use hgs_cvrp::solution::{Route, Solution};
+
+/// Creates a test problem with 40 customers around a central depot.
+fn create_test_problem() -> Problem {
+    let mut nodes = Vec::new();
+
+    // Depot at (50, 50)
+    nodes.push(Node::new(0, 50.0, 50.0, 0.0, true));
+
+    // Two rings of customers
+    for id in 1..=40 {
+        let angle = id as f64 * std::f64::consts::PI / 20.0;
+        let radius = if id % 2 == 0 { 20.0 } else { 40.0 };
+        let x = 50.0 + radius * angle.cos();
+        let y = 50.0 + radius * angle.sin();
+        nodes.push(Node::new(id, x, y, 1.0, false));
+    }
+
+    Problem::new("DecompositionTestProblem".to_string(), nodes, 0, 5.0, None)
+}
+
+/// Creates a solution with routes of 5 customers taken in an arbitrary order.
+fn create_test_solution(problem: &Problem) -> Solution {
+    let mut solution = Solution::new();
+    let customers: Vec<usize> = (1..=40).map(|i| (i * 7) % 40 + 1).collect();
+    for chunk in customers.chunks(5) {
+        let mut route = Route::new();
+        route.customers = chunk.to_vec();
+        solution.routes.push(route);
+    }
+    solution.update_giant_tour();
+    solution.evaluate(problem, 1.0);
+    solution
+}
+
+#[test]
+fn test_cluster_routes() {
+    let problem = create_test_problem();
+    let solution = create_test_solution(&problem);
+
+    for offset in 0..solution.routes.len() {
+        let clusters = Decomposition::cluster_routes(&solution, &problem, 15, offset);
+
+        // Each route belongs to exactly one cluster of at least half the target size
+        let mut routes: Vec<usize> = clusters.iter().flatten().copied().collect();
+        routes.sort();
+        assert_eq!(routes, (0..solution.routes.len()).collect::<Vec<_>>());
+        for cluster in &clusters {
+            let size: usize = cluster
+                .iter()
+                .map(|&r| solution.routes[r].customers.len())
+                .sum();
+            assert!(2 * size >= 15);
+        }
+    }
+}
+
+#[test]
+fn test_subproblem_mapping() {
+    let problem = create_test_problem();
+    let solution = create_test_solution(&problem);
+    let routes = &solution.routes[2..4];
+    let customers: Vec<usize> = routes.iter().flat_map(|r| r.customers.clone()).collect();
+
+    let subproblem = Subproblem::new(&problem, &customers, Some(2));
+    assert_eq!(subproblem.problem.get_customer_count(), customers.len());
+    // Without a fleet limit in the original problem, none is added
+    assert_eq!(subproblem.problem.max_vehicles, None);
+
+    // Routes keep their distance in the subproblem and map back unchanged
+    let mut sub_solution = subproblem.to_subproblem(routes);
+    sub_solution.evaluate(&subproblem.problem, 1.0);
+    let distance: f64 = routes.iter().map(|r| r.distance).sum();
+    assert!((sub_solution.distance - distance).abs() < 1e-9);
+    assert!(sub_solution.has_complete_routes(&subproblem.problem));
+
+    let original = subproblem.to_original(&sub_solution);
+    let original_customers: Vec<Vec<usize>> =
+        original.iter().map(|r| r.customers.clone()).collect();
+    let expected: Vec<Vec<usize>> = routes.iter().map(|r| r.customers.clone()).collect();
+    assert_eq!(original_customers, expected);
+}
+
+#[test]
+fn test_decompose_improves_solution() {
+    let problem = create_test_problem();
+    let solution = create_test_solution(&problem);
+
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(10)
+        .with_decomposition(20, 15)
+        .with_decomposition_iterations(50);
+    let mut decomposed = Decomposition::decompose(&solution, &problem, &config, None);
+    decomposed.evaluate(&problem, 1.0);
+
+    assert!(decomposed.has_complete_routes(&problem));
+    assert!(decomposed.has_complete_giant_tour(&problem));
+    assert!(decomposed.is_feasible);
+    assert!(decomposed.cost < solution.cost);
+}
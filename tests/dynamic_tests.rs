@@ -0,0 +1,132 @@
+//! Unit tests for changing a problem while it is being solved.
+
+use hgs_cvrp::config::Config;
+use hgs_cvrp::dynamic::ProblemChange;
+use hgs_cvrp::problem::{Node, Problem};
+use hgs_cvrp::solution::{Route, Solution};
+use hgs_cvrp::HgsAlgorithm;
+use std::time::Duration;
+
+/// Creates a test problem with 12 customers on a circle, with the depot in the middle of the nodes.
+fn create_test_problem() -> Problem {
+    let mut nodes = Vec::new();
+    for id in 0..13 {
+        if id == 4 {
+            // Depot at (50, 50)
+            nodes.push(Node::new(id, 50.0, 50.0, 0.0, true));
+            continue;
+        }
+        let angle = id as f64 * std::f64::consts::PI / 6.5;
+        let x = 50.0 + 30.0 * angle.cos();
+        let y = 50.0 + 30.0 * angle.sin();
+        nodes.push(Node::new(id, x, y, 1.0, false));
+    }
+
+    Problem::new("DynamicTestProblem".to_string(), nodes, 4, 4.0, None)
+}
+
+#[test]
+fn test_problem_apply_change() {
+    let mut problem = create_test_problem();
+    let original = problem.clone();
+
+    // Removing a customer before the depot shifts it
+    let mapping = problem.apply_change(&ProblemChange::RemoveCustomer(2));
+    assert_eq!(problem.nodes.len(), 12);
+    assert_eq!(problem.depot_index, 3);
+    assert_eq!(mapping.new_index[2], None);
+    assert_eq!(mapping.new_index[5], Some(4));
+    assert_eq!(
+        problem.get_distance(4, 9),
+        original.get_distance(5, 10),
+        "distances follow the renumbered nodes"
+    );
+
+    // An added customer gets distances from its coordinates
+    let node = Node::new(99, 80.0, 50.0, 2.0, false);
+    let mapping = problem.apply_change(&ProblemChange::AddCustomer {
+        node,
+        distances: None,
+    });
+    assert_eq!(mapping.added, Some(12));
+    assert_eq!(problem.distance_matrix.len(), 13);
+    assert!(problem.distance_matrix.iter().all(|row| row.len() == 13));
+    assert!((problem.get_distance(3, 12) - 30.0).abs() < 1e-9);
+    assert!((problem.get_distance(12, 3) - 30.0).abs() < 1e-9);
+
+    problem.apply_change(&ProblemChange::ChangeDemand {
+        customer: 12,
+        demand: 3.0,
+    });
+    assert_eq!(problem.nodes[12].demand, 3.0);
+}
+
+#[test]
+fn test_node_mapping_repair() {
+    let mut problem = create_test_problem();
+
+    let mut solution = Solution::new();
+    for customers in [vec![0, 1, 2, 3], vec![5, 6, 7, 8], vec![9, 10, 11, 12]] {
+        let mut route = Route::new();
+        route.customers = customers;
+        solution.routes.push(route);
+    }
+
+    let mapping = problem.apply_change(&ProblemChange::RemoveCustomer(6));
+    mapping.repair(&mut solution, &problem, 10.0);
+    assert!(solution.has_complete_routes(&problem));
+    assert_eq!(solution.routes[1].customers, vec![5, 6, 7]);
+
+    // The added customer goes where it fits without overloading a route
+    let node = Node::new(99, 80.0, 50.0, 1.0, false);
+    let mapping = problem.apply_change(&ProblemChange::AddCustomer {
+        node,
+        distances: None,
+    });
+    mapping.repair(&mut solution, &problem, 1000.0);
+    assert!(solution.has_complete_routes(&problem));
+    assert!(solution.has_complete_giant_tour(&problem));
+    assert!(solution.is_feasible);
+}
+
+#[test]
+fn test_algorithm_apply_change() {
+    let problem = create_test_problem();
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(10)
+        .with_max_iterations_without_improvement(100)
+        .with_time_limit(Duration::from_secs(5));
+
+    let mut algorithm = HgsAlgorithm::new(problem, config).unwrap();
+    algorithm.run().unwrap();
+
+    let changes = [
+        ProblemChange::AddCustomer {
+            node: Node::new(99, 20.0, 20.0, 1.0, false),
+            distances: None,
+        },
+        ProblemChange::RemoveCustomer(1),
+        ProblemChange::ChangeDemand {
+            customer: 0,
+            demand: 3.0,
+        },
+    ];
+    for change in &changes {
+        algorithm.apply_change(change);
+
+        // The population is repaired for the new problem right away
+        for individual in algorithm
+            .population
+            .feasible_individuals
+            .iter()
+            .chain(&algorithm.population.infeasible_individuals)
+        {
+            assert!(individual.solution.has_complete_routes(&algorithm.problem));
+        }
+
+        let solution = algorithm.resume().unwrap().clone();
+        assert!(solution.is_feasible);
+        assert!(solution.has_complete_routes(&algorithm.problem));
+    }
+}
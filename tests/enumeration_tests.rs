@@ -0,0 +1,64 @@
+//! Unit tests for the exact solution of tiny instances by enumeration.
+
+use hgs_cvrp::config::Config;
+use hgs_cvrp::enumeration::solve_by_enumeration;
+use hgs_cvrp::error::HgsError;
+use hgs_cvrp::problem::{Node, Problem};
+use hgs_cvrp::HgsAlgorithm;
+
+/// Creates a test problem with a depot and the given customers, each with a demand of 1.
+fn create_test_problem(customers: &[(f64, f64)], capacity: f64) -> Problem {
+    let mut nodes = vec![Node::new(0, 0.0, 0.0, 0.0, true)];
+    for (id, &(x, y)) in customers.iter().enumerate() {
+        nodes.push(Node::new(id + 1, x, y, 1.0, false));
+    }
+
+    Problem::new("TinyTestProblem".to_string(), nodes, 0, capacity, None)
+}
+
+#[test]
+fn test_solve_by_enumeration() {
+    // With room for two customers per route, the two on the same side share one
+    let problem = create_test_problem(&[(10.0, 0.0), (-10.0, 0.0), (20.0, 0.0)], 2.0);
+    let solution = solve_by_enumeration(&problem, 1.0);
+    assert!(solution.is_feasible);
+    assert!(solution.has_complete_routes(&problem));
+    assert_eq!(solution.get_used_route_count(), 2);
+    assert!((solution.distance - 60.0).abs() < 1e-9);
+
+    // With a capacity of 1 every customer has its own route
+    let problem = create_test_problem(&[(10.0, 0.0), (-10.0, 0.0), (20.0, 0.0)], 1.0);
+    let solution = solve_by_enumeration(&problem, 1.0);
+    assert!(solution.is_feasible);
+    assert_eq!(solution.get_used_route_count(), 3);
+
+    // An instance without customers has the empty solution
+    let problem = create_test_problem(&[], 1.0);
+    assert_eq!(
+        solve_by_enumeration(&problem, 1.0).get_used_route_count(),
+        0
+    );
+}
+
+#[test]
+fn test_algorithm_tiny_instances() {
+    let config = Config::new().with_max_iterations_without_improvement(100);
+
+    let problem = create_test_problem(&[(3.0, 4.0)], 1.0);
+    let mut algorithm = HgsAlgorithm::new(problem.clone(), config.clone()).unwrap();
+    let solution = algorithm.run().unwrap();
+    assert!(solution.has_complete_routes(&problem));
+    assert!((solution.cost - 10.0).abs() < 1e-9);
+    assert_eq!(algorithm.statistics().iterations, 0);
+
+    let problem = create_test_problem(&[(10.0, 0.0), (10.0, 10.0), (0.0, 10.0)], 3.0);
+    let optimum = solve_by_enumeration(&problem, 1.0);
+    let mut algorithm = HgsAlgorithm::new(problem, config.clone()).unwrap();
+    assert!((algorithm.run().unwrap().cost - optimum.cost).abs() < 1e-9);
+
+    // A fleet too small for the demand has no feasible solution
+    let mut problem = create_test_problem(&[(10.0, 0.0), (0.0, 10.0)], 1.0);
+    problem.max_vehicles = Some(1);
+    let mut algorithm = HgsAlgorithm::new(problem, config).unwrap();
+    assert!(matches!(algorithm.run(), Err(HgsError::NoFeasibleSolution)));
+}
@@ -1,8 +1,10 @@
 //! Unit tests for the genetic components of the HGS-CVRP algorithm.
 
 use hgs_cvrp::config::Config;
-use hgs_cvrp::genetic::Genetic;
+use hgs_cvrp::error::HgsError;
+use hgs_cvrp::genetic::{improve_giant_tour, CrossoverOperator, Genetic, MutationOperator};
 use hgs_cvrp::individual::Individual;
+use hgs_cvrp::penalty::ConstraintPenalty;
 use hgs_cvrp::population::Population;
 use hgs_cvrp::problem::{Node, Problem};
 use hgs_cvrp::solution::{Route, Solution};
@@ -65,14 +67,14 @@ fn test_genetic_crossover() {
     assert_eq!(offspring.giant_tour.len(), 9);
 
     // Check that all customers are present exactly once
-    let mut present = vec![false; 10];
+    let mut present = [false; 10];
     for &customer in &offspring.giant_tour {
         assert!(!present[customer], "Customer present more than once");
         present[customer] = true;
     }
 
-    for i in 1..=9 {
-        assert!(present[i], "Customer {} not present", i);
+    for (i, &seen) in present.iter().enumerate().skip(1) {
+        assert!(seen, "Customer {} not present", i);
     }
 
     // Offspring should inherit genetic material from both parents
@@ -95,6 +97,38 @@ fn test_genetic_crossover() {
     assert!(from_parent1 > 0 || from_parent2 > 0);
 }
 
+#[test]
+fn test_genetic_crossover_rebuilds_stale_giant_tours() {
+    let genetic = Genetic;
+    let parent_with_routes = |sequences: &[&[usize]]| {
+        let mut solution = Solution::new();
+        for &customers in sequences {
+            let mut route = Route::new();
+            route.customers = customers.to_vec();
+            solution.routes.push(route);
+        }
+        Individual::new(solution)
+    };
+
+    // Neither parent had its giant tour updated from its routes
+    let parent1 = parent_with_routes(&[&[1, 2, 3, 4], &[5, 6, 7, 8, 9]]);
+    let mut parent2 = parent_with_routes(&[&[9, 8, 7], &[6, 5, 4, 3, 2, 1]]);
+    assert!(parent1.solution.giant_tour.is_empty());
+    parent2.solution.giant_tour = vec![1, 2];
+
+    for _ in 0..20 {
+        let offspring = genetic.crossover(&parent1, &parent2);
+        let mut sorted = offspring.giant_tour.clone();
+        sorted.sort();
+        assert_eq!(sorted, (1..=9).collect::<Vec<_>>());
+    }
+
+    // Parents of different sizes give a copy of the first one instead of looping forever
+    let small = parent_with_routes(&[&[1, 2, 3]]);
+    let offspring = genetic.crossover(&parent1, &small);
+    assert_eq!(offspring.giant_tour, (1..=9).collect::<Vec<_>>());
+}
+
 #[test]
 fn test_genetic_mutate() {
     let genetic = Genetic;
@@ -110,19 +144,50 @@ fn test_genetic_mutate() {
     assert_eq!(individual.solution.giant_tour.len(), 9);
 
     // All customers should still be present
-    let mut present = vec![false; 10];
+    let mut present = [false; 10];
     for &customer in &individual.solution.giant_tour {
         present[customer] = true;
     }
 
-    for i in 1..=9 {
-        assert!(present[i], "Customer {} not present after mutation", i);
+    for (i, &seen) in present.iter().enumerate().skip(1) {
+        assert!(seen, "Customer {} not present after mutation", i);
     }
 
     // The tour should have changed due to mutation
     assert_ne!(individual.solution.giant_tour, original_tour);
 }
 
+#[test]
+fn test_improve_giant_tour() {
+    let problem = create_test_problem();
+    let neighbors = problem.neighbor_lists(4);
+    let length = |tour: &[usize]| {
+        let mut cycle = vec![0];
+        cycle.extend_from_slice(tour);
+        cycle.push(0);
+        cycle
+            .windows(2)
+            .map(|pair| problem.get_distance(pair[0], pair[1]))
+            .sum::<f64>()
+    };
+
+    // A zigzag tour is shortened, and still visits every customer once
+    let mut tour = vec![1, 9, 2, 8, 3, 7, 4, 6, 5];
+    let before = length(&tour);
+    let moves = improve_giant_tour(&mut tour, &problem, &neighbors, 10);
+    assert!(moves > 0);
+    assert!(length(&tour) < before - 1e-6);
+    let mut sorted = tour.clone();
+    sorted.sort_unstable();
+    assert_eq!(sorted, (1..=9).collect::<Vec<_>>());
+
+    // A locally optimal tour is left unchanged
+    let improved = tour.clone();
+    assert_eq!(improve_giant_tour(&mut tour, &problem, &neighbors, 10), 0);
+    assert_eq!(tour, improved);
+    assert_eq!(improve_giant_tour(&mut tour, &problem, &neighbors, 0), 0);
+}
+
 #[test]
 fn test_individual_calculate_biased_fitness() {
     // Create an individual for testing
@@ -148,22 +213,22 @@ fn test_individual_calculate_biased_fitness() {
 }
 
 #[test]
-fn test_individual_calculate_diversity_contribution() {
-    // Create an individual for testing
-    let mut individual = create_test_individual();
-
-    // Set common pairs array
-    individual.common_pairs = vec![5, 3, 8, 1, 9];
+fn test_population_diversity_contribution() {
+    for (n_closest, expected) in [(2, 8.5), (4, 6.25)] {
+        let config = Config::new().with_n_closest(n_closest);
+        let mut population = Population::new(&config);
 
-    // Calculate diversity contribution with different closest counts
-    let diversity1 = individual.calculate_diversity_contribution(2);
-    let diversity2 = individual.calculate_diversity_contribution(4);
+        // Common pairs of individual 1 with five others
+        let row = [(2, 5), (3, 3), (4, 8), (5, 1), (6, 9)];
+        population.common_pairs.insert(1, row.into_iter().collect());
 
-    // With closest_count = 2, should average the 2 highest values: (9+8)/2 = 8.5
-    assert_eq!(diversity1, 8.5);
+        // Average of the highest values: (9+8)/2 = 8.5 and (9+8+5+3)/4 = 6.25
+        assert_eq!(population.diversity_contribution(1), expected);
+    }
 
-    // With closest_count = 4, should average the 4 highest values: (9+8+5+3)/4 = 6.25
-    assert_eq!(diversity2, 6.25);
+    // No common pairs are recorded for an unknown individual
+    let population = Population::new(&Config::new());
+    assert_eq!(population.diversity_contribution(1), 0.0);
 }
 
 #[test]
@@ -185,6 +250,48 @@ fn test_individual_is_clone_of() {
     assert!(!individual1.is_clone_of(&individual2));
 }
 
+#[test]
+fn test_individual_structure_hash() {
+    let routes = |sequences: &[&[usize]]| {
+        let mut solution = Solution::new();
+        for &customers in sequences {
+            let mut route = Route::new();
+            route.customers = customers.to_vec();
+            solution.routes.push(route);
+        }
+        solution.update_giant_tour();
+        Individual::new(solution)
+    };
+
+    // The order of the routes and empty routes do not matter
+    let mut individual1 = routes(&[&[1, 2, 3], &[4, 5]]);
+    let individual2 = routes(&[&[4, 5], &[], &[1, 2, 3]]);
+    individual1.update_structure_hash();
+    assert_eq!(
+        individual1.cached_structure_hash,
+        Some(individual2.structure_hash())
+    );
+    assert!(individual1.is_clone_of(&individual2));
+
+    // Moving the route delimiters changes the structure
+    let mut individual3 = routes(&[&[1, 2], &[3, 4, 5]]);
+    assert!(!individual1.is_clone_of(&individual3));
+
+    // A colliding hash is not enough, the edges must match too
+    individual3.cached_structure_hash = individual1.cached_structure_hash;
+    assert!(!individual1.is_clone_of(&individual3));
+    let config = Config::new();
+    let mut population = Population::new(&config);
+    population.insert_individual(individual3);
+    population.feasible_individuals[0].cached_structure_hash = individual1.cached_structure_hash;
+    assert!(!population.contains_clone(&individual1.solution));
+
+    // Nor is the same structure at another penalized cost
+    let mut individual4 = routes(&[&[4, 5], &[1, 2, 3]]);
+    individual4.solution.cost = 1.0;
+    assert!(!individual1.is_clone_of(&individual4));
+}
+
 #[test]
 fn test_individual_calculate_common_pairs() {
     // Create two individuals with partly similar tours
@@ -220,6 +327,51 @@ fn test_population_initialization() {
     for individual in &population.infeasible_individuals {
         assert_eq!(individual.solution.giant_tour.len(), 9);
     }
+
+    // The giant tours are split into routes, which fit the capacity without a vehicle limit
+    assert!(population.infeasible_individuals.is_empty());
+    for individual in &population.feasible_individuals {
+        assert!(individual.solution.has_complete_routes(&problem));
+        assert!(individual.solution.routes.len() >= 2);
+    }
+}
+
+#[test]
+fn test_population_sweep_initialization() {
+    let problem = create_test_problem();
+
+    // Customers are ordered by their polar angle around the depot
+    let angle = |c: usize| problem.nodes[c].y.atan2(problem.nodes[c].x);
+    let tour = Population::sweep_giant_tour(&problem, 0.0);
+    let mut sorted = tour.clone();
+    sorted.sort();
+    assert_eq!(sorted, (1..=9).collect::<Vec<_>>());
+    assert!(tour.windows(2).all(|w| angle(w[0]) <= angle(w[1])));
+
+    // Starting past the first customers moves them to the end of the tour
+    let tour = Population::sweep_giant_tour(&problem, angle(7) + 1e-6);
+    assert!(tour[..tour.len() - 1]
+        .iter()
+        .all(|&c| angle(c) > angle(7) + 1e-6));
+    assert_eq!(tour[tour.len() - 1], 7);
+
+    // Only sweep tours
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(10)
+        .with_sweep_initial_ratio(1.0);
+    let mut population = Population::new(&config);
+    population.initialize(&problem, &config);
+
+    assert_eq!(population.get_pop_size(), 4 * config.min_pop_size);
+    for individual in population
+        .feasible_individuals
+        .iter()
+        .chain(&population.infeasible_individuals)
+    {
+        assert!(individual.solution.has_complete_giant_tour(&problem));
+        assert!(individual.solution.has_complete_routes(&problem));
+    }
 }
 
 #[test]
@@ -242,6 +394,140 @@ fn test_population_insert_individual() {
     assert_eq!(population.infeasible_individuals.len(), 1);
 }
 
+#[test]
+fn test_population_aging() {
+    let config = Config::new()
+        .with_min_pop_size(4)
+        .with_generation_size(1)
+        .with_n_elite(1)
+        .with_max_individual_age(2);
+    let mut population = Population::new(&config);
+
+    // Three old individuals, the best of them elite, and three young ones
+    let tours = [
+        [1, 2, 3, 4, 5, 6, 7, 8, 9],
+        [9, 8, 7, 6, 5, 4, 3, 2, 1],
+        [1, 3, 5, 7, 9, 2, 4, 6, 8],
+        [2, 1, 4, 3, 6, 5, 8, 7, 9],
+        [5, 6, 7, 8, 9, 1, 2, 3, 4],
+        [3, 2, 1, 6, 5, 4, 9, 8, 7],
+    ];
+    for (i, tour) in tours.iter().enumerate() {
+        let mut individual = create_test_individual();
+        individual.solution.giant_tour = tour.to_vec();
+        individual.solution.cost = i as f64;
+        individual.solution.is_feasible = true;
+        population.insert_individual(individual);
+        if i == 2 {
+            for _ in 0..3 {
+                population.increment_ages();
+            }
+        }
+    }
+    assert_eq!(population.feasible_individuals[0].age, 3);
+    assert_eq!(population.feasible_individuals[5].age, 0);
+
+    // The old individuals are removed first, except the elite one, rather than the worst ones
+    population.select_survivors();
+    let mut costs: Vec<f64> = population
+        .feasible_individuals
+        .iter()
+        .map(|individual| individual.get_cost())
+        .collect();
+    costs.sort_by(f64::total_cmp);
+    assert_eq!(costs, vec![0.0, 3.0, 4.0, 5.0]);
+    assert!(population.common_pairs.keys().all(|id| {
+        population
+            .feasible_individuals
+            .iter()
+            .any(|individual| individual.id == *id)
+    }));
+}
+
+#[test]
+fn test_population_aging_keeps_two_parents() {
+    let config = Config::new()
+        .with_min_pop_size(4)
+        .with_generation_size(1)
+        .with_n_elite(1)
+        .with_max_individual_age(2);
+    let mut population = Population::new(&config);
+
+    // Only old feasible individuals, with an empty infeasible subpopulation
+    for i in 0..6 {
+        let mut individual = create_test_individual();
+        individual.solution.giant_tour.rotate_left(i);
+        individual.solution.cost = i as f64;
+        individual.solution.is_feasible = true;
+        population.insert_individual(individual);
+    }
+    for _ in 0..3 {
+        population.increment_ages();
+    }
+
+    // Beyond the single elite, one more survives so that two parents can be selected
+    population.select_survivors();
+    assert_eq!(population.get_pop_size(), 2);
+    let (parent1, parent2) = population.select_parents().unwrap();
+    assert!(!std::ptr::eq(parent1, parent2));
+
+    // A single individual is both parents instead of being drawn again forever
+    let mut single = Population::new(&config);
+    single.insert_individual(create_test_individual());
+    let (parent1, parent2) = single.select_parents().unwrap();
+    assert!(std::ptr::eq(parent1, parent2));
+}
+
+#[test]
+fn test_population_survivors_keep_consistent_ranks() {
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_n_closest(3)
+        .with_n_elite(2);
+    let mut population = Population::new(&config);
+
+    // Random tours along with a few clones of the sorted one
+    let mut rng = StdRng::seed_from_u64(3);
+    for i in 0..20 {
+        let mut solution = create_test_solution();
+        solution.routes.clear();
+        solution.giant_tour = (1..=9).collect();
+        if i % 5 != 0 {
+            solution.giant_tour.shuffle(&mut rng);
+        }
+        solution.cost = (i * 7 % 20) as f64;
+        solution.is_feasible = true;
+        population.insert_individual(Individual::new(solution));
+    }
+
+    // The incrementally updated ranks match those computed from scratch
+    population.select_survivors();
+    assert_eq!(population.feasible_individuals.len(), 5);
+    let ranks = |population: &Population| -> Vec<(usize, usize, usize, f64)> {
+        population
+            .feasible_individuals
+            .iter()
+            .map(|individual| {
+                (
+                    individual.id,
+                    individual.rank_feasibility,
+                    individual.rank_diversity,
+                    individual.biased_fitness,
+                )
+            })
+            .collect()
+    };
+    let incremental = ranks(&population);
+    let common_pairs = population.common_pairs.clone();
+    population.update_ranks();
+    assert_eq!(ranks(&population), incremental);
+    assert_eq!(population.common_pairs, common_pairs);
+}
+
 #[test]
 fn test_population_update_ranks() {
     let config = Config::new().with_n_closest(2);
@@ -261,15 +547,68 @@ fn test_population_update_ranks() {
     // Check that ranks are assigned correctly
     for i in 0..5 {
         assert_eq!(population.feasible_individuals[i].rank_feasibility, i);
-        assert!(population.feasible_individuals[i].rank_diversity >= 0);
+        assert!(population.feasible_individuals[i].rank_diversity < 5);
         assert!(population.feasible_individuals[i].biased_fitness > 0.0);
     }
 }
 
+#[test]
+fn test_population_insert_and_update() {
+    let config = Config::new().with_n_closest(2);
+    let mut incremental = Population::new(&config);
+    let mut full = Population::new(&config);
+
+    // Insert individuals out of cost order
+    let tours = [
+        vec![1, 2, 3, 4, 5, 6, 7, 8, 9],
+        vec![1, 2, 3, 9, 8, 7, 6, 5, 4],
+        vec![9, 8, 7, 6, 5, 4, 3, 2, 1],
+        vec![2, 1, 4, 3, 6, 5, 8, 7, 9],
+        vec![1, 3, 5, 7, 9, 2, 4, 6, 8],
+    ];
+    let costs = [30.0, 10.0, 50.0, 20.0, 40.0];
+    for (tour, &cost) in tours.iter().zip(&costs) {
+        let mut individual = create_test_individual();
+        individual.solution.giant_tour = tour.clone();
+        individual.solution.cost = cost;
+        individual.solution.is_feasible = true;
+        incremental.insert_and_update(individual.clone());
+        full.insert_individual(individual);
+
+        // Ranks must be usable for selection right after each insertion
+        let individuals = &incremental.feasible_individuals;
+        for (i, individual) in individuals.iter().enumerate() {
+            assert_eq!(individual.rank_feasibility, i);
+            assert_eq!(
+                incremental.common_pairs[&individual.id].len(),
+                individuals.len() - 1
+            );
+        }
+    }
+
+    // The result matches a full update of the ranks
+    full.update_ranks();
+    for (a, b) in incremental
+        .feasible_individuals
+        .iter()
+        .zip(&full.feasible_individuals)
+    {
+        assert_eq!(a.get_cost(), b.get_cost());
+        assert_eq!(a.rank_feasibility, b.rank_feasibility);
+        assert_eq!(a.rank_diversity, b.rank_diversity);
+        assert_eq!(a.biased_fitness, b.biased_fitness);
+    }
+    assert_eq!(incremental.common_pairs, full.common_pairs);
+}
+
 #[test]
 fn test_population_select_parents() {
     let config = Config::new();
     let mut population = Population::new(&config);
+    assert!(matches!(
+        population.select_parents(),
+        Err(HgsError::EmptyPopulation)
+    ));
 
     // Add several individuals
     for i in 0..10 {
@@ -283,7 +622,7 @@ fn test_population_select_parents() {
     population.update_ranks();
 
     // Select parents
-    let (parent1, parent2) = population.select_parents();
+    let (parent1, parent2) = population.select_parents().unwrap();
 
     // Parents should be different individuals
     assert!(!std::ptr::eq(parent1, parent2));
@@ -331,30 +670,30 @@ fn test_population_adjust_penalties() {
     let mut population = Population::new(&config);
 
     // Add 4 feasible and 6 infeasible individuals
-    for i in 0..4 {
+    for _ in 0..4 {
         let mut individual = create_test_individual();
         individual.solution.is_feasible = true;
         population.insert_individual(individual);
     }
 
-    for i in 0..6 {
+    for _ in 0..6 {
         let mut individual = create_test_individual();
         individual.solution.is_feasible = false;
         population.insert_individual(individual);
     }
 
     // Initial penalty
-    let initial_penalty = population.capacity_penalty;
+    let initial_penalty = population.penalties.capacity_penalty();
 
     // Current ratio is 0.4, which is less than target 0.5
     // Adjust penalties - should increase penalty
     population.adjust_penalties();
 
     // Penalty should increase
-    assert!(population.capacity_penalty > initial_penalty);
+    assert!(population.penalties.capacity_penalty() > initial_penalty);
 
     // Add more feasible individuals to change the ratio
-    for i in 0..7 {
+    for _ in 0..7 {
         let mut individual = create_test_individual();
         individual.solution.is_feasible = true;
         population.insert_individual(individual);
@@ -362,11 +701,88 @@ fn test_population_adjust_penalties() {
 
     // Now ratio is 11/17 ≈ 0.65, which is more than target 0.5
     // Adjust penalties - should decrease penalty
-    let high_penalty = population.capacity_penalty;
+    let high_penalty = population.penalties.capacity_penalty();
     population.adjust_penalties();
 
     // Penalty should decrease
-    assert!(population.capacity_penalty < high_penalty);
+    assert!(population.penalties.capacity_penalty() < high_penalty);
+}
+
+#[test]
+fn test_constraint_penalty_adjust() {
+    let mut penalty = ConstraintPenalty::new(1.0, 0.5)
+        .with_increase_factor(2.0)
+        .with_decrease_factor(4.0)
+        .with_min_coefficient(0.25);
+
+    // Too few feasible individuals: the coefficient increases
+    penalty.adjust(0.2);
+    assert!((penalty.coefficient - 2.0).abs() < 1e-9);
+
+    // Enough feasible individuals: the coefficient decreases, but not below the floor
+    penalty.adjust(0.8);
+    assert!((penalty.coefficient - 0.5).abs() < 1e-9);
+    penalty.adjust(0.8);
+    assert!((penalty.coefficient - 0.25).abs() < 1e-9);
+}
+
+#[test]
+fn test_penalty_adjusts_to_recorded_offspring() {
+    let config = Config::new().with_target_feasible_ratio(0.5);
+    let mut population = Population::new(&config);
+
+    // The population is entirely feasible, but most recent offspring were not
+    for _ in 0..4 {
+        let mut individual = create_test_individual();
+        individual.solution.is_feasible = true;
+        population.insert_individual(individual);
+    }
+    for feasible in [true, false, false, false] {
+        population.record_offspring(feasible);
+    }
+    assert_eq!(population.penalties.capacity.recorded_ratio(), Some(0.25));
+
+    let initial_penalty = population.penalties.capacity_penalty();
+    population.adjust_penalties();
+    assert!(population.penalties.capacity_penalty() > initial_penalty);
+
+    // The statistics restart after each adjustment, falling back to the population
+    assert_eq!(population.penalties.capacity.recorded_ratio(), None);
+    let high_penalty = population.penalties.capacity_penalty();
+    population.adjust_penalties();
+    assert!(population.penalties.capacity_penalty() < high_penalty);
+}
+
+#[test]
+fn test_penalty_factors_and_bounds_from_config() {
+    let config = Config::new()
+        .with_target_feasible_ratio(0.5)
+        .with_penalty_increase_factor(3.0)
+        .with_penalty_decrease_factor(2.0)
+        .with_penalty_bounds(0.5, 5.0);
+    let mut population = Population::new(&config);
+    let penalty = &mut population.penalties.capacity;
+    assert_eq!(penalty.increase_factor, 3.0);
+    assert_eq!(penalty.decrease_factor, 2.0);
+
+    // Increases stop at the maximum and decreases at the minimum
+    penalty.adjust(0.0);
+    assert!((penalty.coefficient - 3.0).abs() < 1e-9);
+    penalty.adjust(0.0);
+    assert!((penalty.coefficient - 5.0).abs() < 1e-9);
+    for _ in 0..5 {
+        penalty.adjust(1.0);
+    }
+    assert!((penalty.coefficient - 0.5).abs() < 1e-9);
+
+    // The default penalties are unbounded above
+    assert_eq!(
+        Population::new(&Config::new())
+            .penalties
+            .capacity
+            .max_coefficient,
+        f64::INFINITY
+    );
 }
 
 #[test]
@@ -399,3 +815,177 @@ fn test_population_get_best_feasible_solution() {
     let best = population.get_best_feasible_solution();
     assert_eq!(best.unwrap().cost, 10.0);
 }
+
+#[test]
+fn test_population_diversity_stats() {
+    let config = Config::new();
+    let mut population = Population::new(&config);
+
+    // Two clones, which share their cost as well, and one reversed tour
+    for _ in 0..2 {
+        let mut individual = create_test_individual();
+        individual.solution.cost = 10.0;
+        individual.solution.is_feasible = true;
+        population.insert_individual(individual);
+    }
+
+    let mut reversed = create_test_individual();
+    reversed.solution.giant_tour = vec![9, 8, 7, 6, 5, 4, 3, 2, 1];
+    reversed.solution.cost = 30.0;
+    reversed.solution.is_feasible = false;
+    population.insert_individual(reversed);
+
+    let stats = population.diversity_stats();
+
+    assert_eq!(stats.feasible_count, 2);
+    assert_eq!(stats.infeasible_count, 1);
+    assert_eq!(stats.clone_count, 2);
+    assert_eq!(stats.min_cost, 10.0);
+    assert_eq!(stats.max_cost, 30.0);
+    assert!((stats.cost_std_dev - 800.0_f64.sqrt() / 3.0).abs() < 1e-9);
+
+    // The clones are at distance 0, the reversed tour shares no pairs with them
+    assert!((stats.average_broken_pairs_distance - 2.0 / 3.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_population_common_pairs_cache() {
+    let config = Config::new().with_min_pop_size(3).with_generation_size(2);
+    let mut population = Population::new(&config);
+
+    // Insert individuals with different tours
+    let tours = [
+        vec![1, 2, 3, 4, 5, 6, 7, 8, 9],
+        vec![1, 2, 3, 9, 8, 7, 6, 5, 4],
+        vec![9, 8, 7, 6, 5, 4, 3, 2, 1],
+        vec![2, 1, 4, 3, 6, 5, 8, 7, 9],
+        vec![1, 3, 5, 7, 9, 2, 4, 6, 8],
+    ];
+    for (i, tour) in tours.iter().enumerate() {
+        let mut individual = create_test_individual();
+        individual.solution.giant_tour = tour.clone();
+        individual.solution.cost = (i as f64) * 10.0;
+        individual.solution.is_feasible = true;
+        population.insert_individual(individual);
+    }
+
+    // Cached values should match a recomputation from scratch
+    let check_cache = |population: &Population| {
+        let individuals = &population.feasible_individuals;
+        assert_eq!(population.common_pairs.len(), individuals.len());
+        for a in individuals {
+            for b in individuals {
+                if a.id != b.id {
+                    assert_eq!(
+                        population.common_pairs[&a.id][&b.id],
+                        a.calculate_common_pairs(b)
+                    );
+                }
+            }
+        }
+    };
+    check_cache(&population);
+
+    // Removing individuals must drop their cached entries
+    population.update_ranks();
+    population.select_survivors();
+    assert_eq!(population.feasible_individuals.len(), 3);
+    check_cache(&population);
+    for row in population.common_pairs.values() {
+        assert_eq!(row.len(), 2);
+    }
+
+    // An individual added without `insert_individual` is given an id and its pairs
+    let mut individual = create_test_individual();
+    individual.solution.is_feasible = true;
+    population.feasible_individuals.push(individual);
+    population.update_ranks();
+    assert!(population
+        .feasible_individuals
+        .iter()
+        .all(|individual| individual.id != 0));
+    check_cache(&population);
+}
+
+#[test]
+fn test_population_select_survivors_removes_clones_first() {
+    let config = Config::new().with_min_pop_size(3).with_generation_size(2);
+    let mut population = Population::new(&config);
+
+    // Three clones with the best cost
+    for _ in 0..3 {
+        let mut individual = create_test_individual();
+        individual.solution.cost = 0.0;
+        individual.solution.is_feasible = true;
+        population.insert_individual(individual);
+    }
+
+    // Three distinct, worse individuals
+    let tours = [
+        vec![9, 8, 7, 6, 5, 4, 3, 2, 1],
+        vec![2, 1, 4, 3, 6, 5, 8, 7, 9],
+        vec![1, 3, 5, 7, 9, 2, 4, 6, 8],
+    ];
+    for (i, tour) in tours.iter().enumerate() {
+        let mut individual = create_test_individual();
+        individual.solution.giant_tour = tour.clone();
+        individual.solution.cost = 30.0 + (i as f64) * 10.0;
+        individual.solution.is_feasible = true;
+        population.insert_individual(individual);
+    }
+
+    population.select_survivors();
+
+    // Only one copy of the cloned tour survives
+    let clones = population
+        .feasible_individuals
+        .iter()
+        .filter(|ind| ind.solution.giant_tour == vec![1, 2, 3, 4, 5, 6, 7, 8, 9])
+        .count();
+    assert_eq!(population.feasible_individuals.len(), 3);
+    assert_eq!(clones, 1);
+
+    // Ranks are consistent with the survivors
+    for (i, individual) in population.feasible_individuals.iter().enumerate() {
+        assert_eq!(individual.rank_feasibility, i);
+    }
+}
+
+#[test]
+fn test_population_selection_pressure() {
+    let config = Config::new().with_n_elite(1);
+    let mut population = Population::new(&config);
+
+    // One feasible and nine infeasible individuals with distinct tours
+    for i in 0..10 {
+        let mut individual = create_test_individual();
+        individual.solution.giant_tour.rotate_left(i);
+        individual.solution.cost = (i as f64) * 10.0;
+        individual.solution.is_feasible = i == 0;
+        population.insert_individual(individual);
+    }
+    population.update_ranks();
+
+    let trials = 5000;
+    let mut feasible_picks = 0;
+    let mut best_infeasible_picks = 0;
+    let mut worst_infeasible_picks = 0;
+
+    for _ in 0..trials {
+        let (parent1, _) = population.select_parents().unwrap();
+        if parent1.solution.is_feasible {
+            feasible_picks += 1;
+        } else if parent1.rank_feasibility == 0 {
+            best_infeasible_picks += 1;
+        } else if parent1.rank_feasibility == 8 {
+            worst_infeasible_picks += 1;
+        }
+    }
+
+    // Selection is over the union, so the single feasible individual is at most in one
+    // fifth of the tournaments instead of being picked half of the time with a coin flip
+    assert!((feasible_picks as f64) < 0.3 * trials as f64);
+
+    // Better biased fitness should be selected more often
+    assert!(best_infeasible_picks > worst_infeasible_picks);
+}
@@ -0,0 +1,86 @@
+//! Unit tests for the edge penalties of the guided local search.
+
+use hgs_cvrp::config::Config;
+use hgs_cvrp::guided::GuidedLocalSearch;
+use hgs_cvrp::problem::{Node, Problem};
+use hgs_cvrp::solution::{Route, Solution};
+use hgs_cvrp::HgsAlgorithm;
+use std::time::Duration;
+
+/// Creates a test problem with 20 customers on a ring, and one far away.
+fn create_test_problem() -> Problem {
+    let mut nodes = Vec::new();
+
+    // Depot at (50, 50)
+    nodes.push(Node::new(0, 50.0, 50.0, 0.0, true));
+
+    for id in 1..=20 {
+        let angle = id as f64 * std::f64::consts::PI / 10.0;
+        let x = 50.0 + 20.0 * angle.cos();
+        let y = 50.0 + 20.0 * angle.sin();
+        nodes.push(Node::new(id, x, y, 1.0, false));
+    }
+    nodes.push(Node::new(21, 150.0, 50.0, 1.0, false));
+
+    Problem::new("GuidedTestProblem".to_string(), nodes, 0, 7.0, None)
+}
+
+#[test]
+fn test_guided_local_search_penalties() {
+    let problem = create_test_problem();
+    let mut solution = Solution::new();
+    for customers in [
+        (1..=7).collect(),
+        (8..=14).collect(),
+        vec![15, 16, 17, 18, 19, 20, 21],
+    ] {
+        let mut route = Route::new();
+        route.customers = customers;
+        solution.routes.push(route);
+    }
+    solution.evaluate(&problem, 1.0);
+
+    // The edge to the distant customer is the longest one, and is penalized first
+    let mut guided = GuidedLocalSearch::new(0.2);
+    assert!(guided.augmented_problem().is_none());
+    assert_eq!(guided.penalize(&solution, &problem), 1);
+    assert!(guided.penalty(21, 0) == 1 || guided.penalty(20, 21) == 1);
+    assert_eq!(guided.penalized_edges(), 1);
+
+    let augmented = guided.augmented_problem().unwrap();
+    let penalty_cost = 0.2 * solution.distance / 24.0;
+    for (a, b) in [(0, 21), (21, 20)] {
+        let expected = problem.get_distance(a, b) + penalty_cost * guided.penalty(a, b) as f64;
+        assert!((augmented.get_distance(a, b) - expected).abs() < 1e-9);
+        assert!((augmented.get_distance(b, a) - expected).abs() < 1e-9);
+    }
+    assert_eq!(augmented.get_distance(1, 2), problem.get_distance(1, 2));
+
+    // Penalties lower the utility of an edge until others are penalized
+    for _ in 0..10 {
+        guided.penalize(&solution, &problem);
+    }
+    assert!(guided.penalized_edges() > 2);
+    guided.reset();
+    assert_eq!(guided.penalized_edges(), 0);
+    assert!(guided.augmented_problem().is_none());
+}
+
+#[test]
+fn test_algorithm_guided_local_search() {
+    let problem = create_test_problem();
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_guided_local_search(0.2, 5)
+        .with_max_iterations_without_improvement(30)
+        .with_time_limit(Duration::from_secs(10));
+    let mut algorithm = HgsAlgorithm::new(problem.clone(), config).unwrap();
+    let solution = algorithm.run().unwrap().clone();
+    assert!(solution.is_feasible);
+    assert!(solution.has_complete_routes(&problem));
+    assert!(algorithm
+        .guided_local_search
+        .as_ref()
+        .is_some_and(|guided| guided.penalized_edges() > 0));
+}
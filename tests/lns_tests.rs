@@ -0,0 +1,125 @@
+//! Unit tests for the ruin and recreate of the large neighborhood search.
+
+use hgs_cvrp::config::Config;
+use hgs_cvrp::lns::{Lns, RuinOperator};
+use hgs_cvrp::problem::{Node, Problem};
+use hgs_cvrp::solution::{Route, Solution};
+use hgs_cvrp::HgsAlgorithm;
+use std::time::Duration;
+
+/// Creates a test problem with 40 customers around a central depot.
+fn create_test_problem() -> Problem {
+    let mut nodes = Vec::new();
+
+    // Depot at (50, 50)
+    nodes.push(Node::new(0, 50.0, 50.0, 0.0, true));
+
+    // Two rings of customers
+    for id in 1..=40 {
+        let angle = id as f64 * std::f64::consts::PI / 20.0;
+        let radius = if id % 2 == 0 { 20.0 } else { 40.0 };
+        let x = 50.0 + radius * angle.cos();
+        let y = 50.0 + radius * angle.sin();
+        nodes.push(Node::new(id, x, y, 1.0, false));
+    }
+
+    Problem::new("LnsTestProblem".to_string(), nodes, 0, 5.0, None)
+}
+
+/// Creates a solution with routes of 5 customers taken in an arbitrary order.
+fn create_test_solution(problem: &Problem) -> Solution {
+    let mut solution = Solution::new();
+    let customers: Vec<usize> = (1..=40).map(|i| (i * 7) % 40 + 1).collect();
+    for chunk in customers.chunks(5) {
+        let mut route = Route::new();
+        route.customers = chunk.to_vec();
+        solution.routes.push(route);
+    }
+    solution.update_giant_tour();
+    solution.evaluate(problem, 1.0);
+    solution
+}
+
+#[test]
+fn test_lns_ruin_operators() {
+    let mut problem = create_test_problem();
+    problem.pin_customer(3, 0);
+    problem.pin_sequence(&[10, 11], 1);
+    let lns = Lns::new()
+        .with_removal_fraction(0.25)
+        .with_max_string_len(4);
+
+    for operator in RuinOperator::ALL {
+        let mut solution = create_test_solution(&problem);
+        let removed = lns.ruin(&mut solution, &problem, operator, 1.0);
+
+        // Up to a quarter of the 37 movable customers, never a pinned one
+        assert!(!removed.is_empty() && removed.len() <= 10, "{operator:?}");
+        assert!(removed.iter().all(|c| ![3, 10, 11].contains(c)));
+        let visited: Vec<usize> = solution
+            .routes
+            .iter()
+            .flat_map(|route| route.customers.iter().copied())
+            .collect();
+        assert_eq!(visited.len() + removed.len(), 40);
+        assert!(removed.iter().all(|c| !visited.contains(c)));
+        assert!(solution.routes.iter().all(|route| !route.is_empty()));
+
+        // Recreating visits every customer once again
+        lns.recreate(&mut solution, &problem, removed, 1.0);
+        assert!(solution.has_complete_routes(&problem));
+    }
+}
+
+#[test]
+fn test_lns_recreate_respects_fleet_and_pins() {
+    let mut problem = create_test_problem();
+    problem.max_vehicles = Some(9);
+    problem.pin_sequence(&[1, 2], 0);
+    let mut solution = create_test_solution(&problem);
+    problem.pins.enforce(&mut solution, &problem);
+
+    // Every movable customer is reinserted, without opening a tenth route
+    let lns = Lns::new()
+        .with_operators(&[RuinOperator::Random])
+        .with_removal_fraction(1.0)
+        .with_max_removals(40)
+        .with_regret(3);
+    let removed = lns.ruin(&mut solution, &problem, RuinOperator::Random, 1000.0);
+    assert_eq!(removed.len(), 38);
+    lns.recreate(&mut solution, &problem, removed, 1000.0);
+    assert!(solution.has_complete_routes(&problem));
+    assert!(solution.routes.len() <= 9);
+    assert!(solution.is_feasible);
+    let route = solution
+        .routes
+        .iter()
+        .find(|route| route.customers.contains(&1))
+        .unwrap();
+    let position = route.customers.iter().position(|&c| c == 1).unwrap();
+    assert_eq!(route.customers.get(position + 1), Some(&2));
+}
+
+#[test]
+fn test_lns_run_improves() {
+    let problem = create_test_problem();
+    let mut solution = create_test_solution(&problem);
+    let initial_cost = solution.cost;
+
+    let improvements = Lns::new().run(&mut solution, &problem, 50, 10.0);
+    assert!(improvements > 0);
+    assert!(solution.cost < initial_cost);
+    assert!(solution.has_complete_routes(&problem));
+
+    // Inside the genetic search, as an extra education step
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_lns_probability(0.5)
+        .with_max_iterations_without_improvement(20)
+        .with_time_limit(Duration::from_secs(10));
+    let mut algorithm = HgsAlgorithm::new(problem, config)
+        .unwrap()
+        .with_lns(Lns::new().with_regret(1).with_max_removals(8));
+    assert!(algorithm.run().unwrap().is_feasible);
+}
@@ -1,8 +1,17 @@
 //! Integration tests for the local search components working together.
 
-use hgs_cvrp::local_search::LocalSearch;
+use hgs_cvrp::local_search::timestamps::MoveTimestamps;
+use hgs_cvrp::local_search::utils::get_neighbors;
+use hgs_cvrp::local_search::{
+    Annealing, AppliedMove, ImproveOptions, LocalSearch, LocalSearchPool, MoveKind, Neighborhood,
+    NeighborhoodContext, RoutePruning, SearchTrace, TraceRecord,
+};
 use hgs_cvrp::problem::{Node, Problem};
 use hgs_cvrp::solution::{Route, Solution};
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// Creates a more complex test problem with a depot and customers in a grid.
 fn create_complex_problem() -> Problem {
@@ -75,31 +84,79 @@ fn create_random_solution(problem: &Problem) -> Solution {
 
 #[test]
 fn test_local_search_move_tracking() {
-    let problem = create_complex_problem();
-    let mut solution = create_random_solution(&problem);
-
     // Create local search with timestamp tracking
     let mut local_search = LocalSearch::new(5);
 
     // Check initial validity for a move
     let customer = 1;
-    let move_type = 0; // Relocate
+    let kind = MoveKind::Relocate;
     let route_idx = 1;
 
-    assert!(local_search.is_move_valid(customer, move_type, route_idx));
+    assert!(local_search.is_move_valid(customer, kind, route_idx));
 
     // The second check should return false (already tested)
-    assert!(!local_search.is_move_valid(customer, move_type, route_idx));
+    assert!(!local_search.is_move_valid(customer, kind, route_idx));
+
+    // Other kinds of moves of the customer are tracked separately
+    assert!(local_search.is_move_valid(customer, MoveKind::Swap, route_idx));
 
     // After updating the route timestamp, the move should be valid again
     local_search.update_route_timestamp(route_idx);
-    assert!(local_search.is_move_valid(customer, move_type, route_idx));
+    assert!(local_search.is_move_valid(customer, kind, route_idx));
+}
+
+#[test]
+fn test_move_timestamps_storage() {
+    let mut timestamps = MoveTimestamps::new();
+    timestamps.reset(2);
+    assert!(timestamps.is_empty());
+    assert_eq!(timestamps.get(3, MoveKind::Swap, 0), None);
+
+    timestamps.insert(3, MoveKind::Swap, 1, 7);
+    timestamps.insert(5, MoveKind::SwapStar, 0, 9);
+    assert!(!timestamps.is_empty());
+
+    // Routes beyond the initial room keep the existing timestamps
+    timestamps.insert(3, MoveKind::Swap, 6, 11);
+    assert_eq!(timestamps.get(3, MoveKind::Swap, 1), Some(7));
+    assert_eq!(timestamps.get(5, MoveKind::SwapStar, 0), Some(9));
+    assert_eq!(timestamps.get(3, MoveKind::Swap, 6), Some(11));
+    assert_eq!(timestamps.get(3, MoveKind::TwoOpt, 6), None);
+
+    // Resetting forgets everything and releases the memory of a much larger solution
+    for customer in 0..100 {
+        timestamps.insert(customer, MoveKind::Relocate, 20, 1);
+    }
+    let large = timestamps.memory_usage();
+    timestamps.reset(2);
+    timestamps.insert(1, MoveKind::Relocate, 0, 1);
+    timestamps.reset(2);
+    assert!(timestamps.is_empty());
+    assert!(timestamps.memory_usage() < large);
+}
+
+#[test]
+fn test_local_search_tracking_memory_is_bounded() {
+    let problem = create_complex_problem();
+    let mut local_search = LocalSearch::new(10);
+
+    // Repeated educations reuse the same memory instead of growing it
+    let mut usage = Vec::new();
+    for _ in 0..20 {
+        let mut solution = create_random_solution(&problem);
+        local_search.educate(&mut solution, &problem, 1.0);
+        usage.push(local_search.tracking_memory_usage());
+    }
+
+    let customers = problem.nodes.len();
+    let bound = 2 * customers * 5 * customers * std::mem::size_of::<usize>();
+    assert!(usage.iter().all(|&bytes| bytes > 0 && bytes <= bound));
 }
 
 #[test]
 fn test_local_search_initialization_tracking() {
     let problem = create_complex_problem();
-    let mut solution = create_random_solution(&problem);
+    let solution = create_random_solution(&problem);
 
     // Create local search
     let mut local_search = LocalSearch::new(5);
@@ -148,13 +205,14 @@ fn test_local_search_full_improvement() {
     route3.calculate_distance(&problem);
 
     solution.routes = vec![route1, route2, route3];
-    solution.evaluate(&problem, 1.0);
+    // Use a penalty high enough that overloading a route never pays off
+    solution.evaluate(&problem, 100.0);
 
     let initial_cost = solution.cost;
 
     // Run local search
     let mut local_search = LocalSearch::new(10);
-    local_search.educate(&mut solution, &problem, 1.0);
+    local_search.educate(&mut solution, &problem, 100.0);
 
     // The solution should improve
     assert!(solution.cost < initial_cost);
@@ -274,7 +332,522 @@ fn test_preprocess_neighbors() {
     );
 
     // Each customer should have at most 5 neighbors (granularity)
-    for (_, neighbors) in &local_search.customer_neighbors {
+    for (_, neighbors) in local_search.customer_neighbors.iter() {
         assert!(neighbors.len() <= 5);
     }
 }
+
+#[test]
+fn test_shared_neighbor_lists() {
+    let problem = create_complex_problem();
+    let neighbors = Arc::new(problem.neighbor_lists(5));
+
+    assert_eq!(neighbors.granularity(), 5);
+    assert_eq!(neighbors.len(), problem.get_customer_count());
+    for (customer, list) in neighbors.iter() {
+        assert_eq!(list, get_neighbors(customer, &problem, 5).as_slice());
+    }
+    assert!(neighbors.get(problem.depot_index).is_empty());
+
+    // Several local searches use one preprocessing pass
+    let mut first = LocalSearch::new(10).with_neighbor_lists(Arc::clone(&neighbors));
+    let mut second = LocalSearch::new(10).with_neighbor_lists(Arc::clone(&neighbors));
+    assert_eq!(first.granularity, 5);
+
+    let solution = create_random_solution(&problem);
+    let mut first_solution = solution.clone();
+    let mut second_solution = solution.clone();
+    first.educate(&mut first_solution, &problem, 1.0);
+    second.educate(&mut second_solution, &problem, 1.0);
+
+    assert!(first_solution.cost < solution.cost);
+    assert!(second_solution.cost < solution.cost);
+    assert!(Arc::ptr_eq(&first.customer_neighbors, &neighbors));
+    assert_eq!(Arc::strong_count(&neighbors), 3);
+}
+
+#[test]
+fn test_local_search_operator_granularity() {
+    let problem = create_complex_problem();
+    let mut local_search = LocalSearch::new(5)
+        .with_operator_granularity(MoveKind::Relocate, 8)
+        .with_operator_granularity(MoveKind::SwapStar, 2);
+    assert_eq!(local_search.neighbor_list_size(), 8);
+
+    // Each kind searches a prefix of the lists computed for the largest granularity
+    let relocate = local_search.operator_neighbors(1, MoveKind::Relocate, &problem);
+    assert_eq!(relocate, get_neighbors(1, &problem, 8));
+    assert_eq!(local_search.customer_neighbors.granularity(), 8);
+    let swap_star = local_search.operator_neighbors(1, MoveKind::SwapStar, &problem);
+    assert_eq!(swap_star, relocate[..2]);
+    let swap = local_search.operator_neighbors(1, MoveKind::Swap, &problem);
+    assert_eq!(swap, local_search.get_customer_neighbors(1, &problem));
+    assert_eq!(swap, relocate[..5]);
+
+    let solution = create_random_solution(&problem);
+    let mut educated = solution.clone();
+    local_search.educate(&mut educated, &problem, 1.0);
+    assert!(educated.cost < solution.cost);
+    assert!(educated.has_complete_routes(&problem));
+
+    // Shared lists cap every kind at their own granularity
+    let mut shared = LocalSearch::new(5)
+        .with_neighbor_lists(Arc::new(problem.neighbor_lists(3)))
+        .with_operator_granularity(MoveKind::Relocate, 8);
+    assert_eq!(
+        shared.operator_neighbors(1, MoveKind::Relocate, &problem),
+        relocate[..3]
+    );
+}
+
+#[test]
+fn test_local_search_load_slack_pruning() {
+    let problem = create_complex_problem();
+    let mut solution = Solution::new();
+    for customers in [vec![1, 2, 3, 4, 5, 6, 7, 8], vec![9]] {
+        let mut route = Route::new();
+        route.customers = customers;
+        route.recalculate(&problem);
+        solution.routes.push(route);
+    }
+    assert!((solution.routes[0].load - 9.6).abs() < 1e-9);
+
+    // Moving customer 9 (demand 1.4) into the first route overloads it, the other way does not
+    let mut local_search = LocalSearch::new(10).with_load_slack_pruning(Some(0.0));
+    let demand = (problem.nodes[9].demand, 0.0);
+    let into_full = (1, 0);
+    assert!(local_search.exceeds_load_slack(
+        &solution,
+        &problem,
+        MoveKind::Relocate,
+        into_full,
+        demand,
+        1000.0
+    ));
+    assert!(!local_search.exceeds_load_slack(
+        &solution,
+        &problem,
+        MoveKind::Relocate,
+        (0, 1),
+        (problem.nodes[1].demand, 0.0),
+        1000.0
+    ));
+    assert_eq!(
+        local_search.neighborhood_stats[MoveKind::Relocate].pruned,
+        1
+    );
+
+    // The penalty of the excess must exceed the allowance
+    let mut lenient = LocalSearch::new(10).with_load_slack_pruning(Some(500.0));
+    assert!(!lenient.exceeds_load_slack(
+        &solution,
+        &problem,
+        MoveKind::Relocate,
+        into_full,
+        demand,
+        100.0
+    ));
+    let mut disabled = LocalSearch::new(10);
+    assert!(!disabled.exceeds_load_slack(
+        &solution,
+        &problem,
+        MoveKind::Relocate,
+        into_full,
+        demand,
+        1000.0
+    ));
+
+    // Educations skip the overloading moves and still improve
+    let initial = create_random_solution(&problem);
+    let mut educated = initial.clone();
+    local_search.reset_neighborhood_stats();
+    local_search.educate(&mut educated, &problem, 1000.0);
+    assert!(educated.cost < initial.cost);
+    assert!(educated.has_complete_routes(&problem));
+    let stats = local_search.neighborhood_stats;
+    assert!(stats[MoveKind::Relocate].pruned + stats[MoveKind::Swap].pruned > 0);
+}
+
+#[test]
+fn test_local_search_pool() {
+    let problem = create_complex_problem();
+    let neighbors = Arc::new(problem.neighbor_lists(5));
+    let pool = LocalSearchPool::new(Arc::clone(&neighbors)).with_builder(|neighbors| {
+        LocalSearch::new(0)
+            .with_neighbor_lists(neighbors)
+            .with_route_pruning(RoutePruning::None)
+    });
+    let solution = create_random_solution(&problem);
+
+    // Each thread builds one instance and reuses it for its educations
+    std::thread::scope(|scope| {
+        for _ in 0..2 {
+            scope.spawn(|| {
+                for _ in 0..3 {
+                    let mut educated = solution.clone();
+                    pool.educate(&mut educated, &problem, 1.0);
+                    assert!(educated.cost < solution.cost);
+                }
+                assert_eq!(pool.idle_count(), 1);
+            });
+        }
+    });
+    assert_eq!(pool.idle_count(), 0);
+
+    // Instances come back reset, and nested calls get their own
+    pool.with_local_search(|local_search| {
+        assert_eq!(local_search.granularity, 5);
+        assert_eq!(local_search.route_pruning, RoutePruning::None);
+        local_search.educate(&mut solution.clone(), &problem, 1.0);
+        pool.with_local_search(|inner| assert_eq!(inner.neighborhood_stats, Default::default()));
+    });
+    assert_eq!(pool.idle_count(), 2);
+    pool.with_local_search(|local_search| {
+        assert_eq!(local_search.neighborhood_stats, Default::default());
+    });
+    assert!(Arc::ptr_eq(pool.neighbors(), &neighbors));
+}
+
+#[test]
+fn test_local_search_verify_deltas() {
+    let problem = create_complex_problem();
+    let mut solution = create_random_solution(&problem);
+    let initial_cost = solution.cost;
+
+    // Every accepted move is checked against a full re-evaluation
+    let mut local_search = LocalSearch::new(10).with_verify_deltas(true);
+    assert!(local_search.verify_deltas);
+    local_search.educate(&mut solution, &problem, 1.0);
+
+    assert!(solution.cost <= initial_cost);
+    assert!(solution.has_complete_routes(&problem));
+}
+
+#[test]
+fn test_local_search_without_coordinates() {
+    // Keep the distances but drop the geometry, as for matrix-only instances
+    let euclidean = create_complex_problem();
+    let nodes = euclidean
+        .nodes
+        .iter()
+        .map(|node| Node::new(node.id, 0.0, 0.0, node.demand, node.is_depot))
+        .collect();
+    let mut problem = Problem::new("MatrixOnly".to_string(), nodes, 0, 10.0, None)
+        .with_distance_matrix(euclidean.distance_matrix.clone());
+    problem.has_coordinates = false;
+
+    let mut solution = create_random_solution(&problem);
+    let initial_cost = solution.cost;
+
+    // SWAP* falls back to considering all route pairs
+    let mut local_search = LocalSearch::new(10).with_verify_deltas(true);
+    local_search.educate(&mut solution, &problem, 1.0);
+
+    assert!(solution.cost < initial_cost);
+    assert!(solution.has_complete_routes(&problem));
+}
+
+#[test]
+fn test_local_search_education_budget() {
+    let problem = create_complex_problem();
+    let solution = create_random_solution(&problem);
+
+    // Without any budget the solution is only re-evaluated
+    for mut local_search in [
+        LocalSearch::new(10).with_max_moves(Some(0)),
+        LocalSearch::new(10).with_max_time(Some(Duration::ZERO)),
+    ] {
+        let mut limited = solution.clone();
+        local_search.educate(&mut limited, &problem, 1.0);
+        assert!((limited.cost - solution.cost).abs() < 1e-6);
+    }
+
+    // A single move improves the solution, but less than a full education
+    let mut one_move = solution.clone();
+    LocalSearch::new(10)
+        .with_max_moves(Some(1))
+        .educate(&mut one_move, &problem, 1.0);
+    let mut full = solution.clone();
+    LocalSearch::new(10).educate(&mut full, &problem, 1.0);
+
+    assert!(one_move.cost < solution.cost);
+    assert!(full.cost < one_move.cost);
+    assert!(one_move.has_complete_routes(&problem));
+}
+
+#[test]
+fn test_local_search_improve() {
+    let problem = create_complex_problem();
+
+    // A hand-made solution only needs its customer sequences
+    let mut solution = Solution::new();
+    for customers in (1..=24).collect::<Vec<_>>().chunks(4) {
+        let mut route = Route::new();
+        route.customers = customers.iter().rev().copied().collect();
+        solution.routes.push(route);
+    }
+
+    let mut local_search = LocalSearch::new(10);
+    let report = local_search.improve(&mut solution, &problem, &ImproveOptions::new());
+
+    assert!(report.moves > 0);
+    assert!(report.final_cost < report.initial_cost);
+    assert!(report.final_distance < report.initial_distance);
+    assert!((report.final_cost - solution.cost).abs() < 1e-9);
+    assert_eq!(report.is_feasible, solution.is_feasible);
+    assert!(solution.has_complete_routes(&problem));
+    assert!(solution.has_complete_giant_tour(&problem));
+
+    // The move budget of the options applies to this call only
+    let mut limited = solution.clone();
+    let report = local_search.improve(
+        &mut limited,
+        &problem,
+        &ImproveOptions::new()
+            .with_max_moves(0)
+            .with_capacity_penalty(5.0),
+    );
+    assert_eq!(report.moves, 0);
+    assert_eq!(report.capacity_penalty, 5.0);
+    assert!(local_search.max_moves.is_none());
+}
+
+#[test]
+fn test_local_search_stochastic_demands() {
+    let problem = create_complex_problem();
+    let std_devs = problem.nodes.iter().map(|node| 0.3 * node.demand).collect();
+    let problem = problem.with_demand_uncertainty(std_devs, 0.95);
+
+    // Every move delta accounts for the load variances of the routes
+    for _ in 0..5 {
+        let mut solution = create_random_solution(&problem);
+        let initial_cost = solution.cost;
+
+        let mut local_search = LocalSearch::new(10).with_verify_deltas(true);
+        local_search.educate(&mut solution, &problem, 100.0);
+
+        assert!(solution.cost <= initial_cost);
+        assert!(solution.has_complete_routes(&problem));
+    }
+}
+
+#[test]
+fn test_local_search_deadline() {
+    let problem = create_complex_problem();
+    let solution = create_random_solution(&problem);
+
+    // A deadline in the past stops the education before any move
+    let mut late = solution.clone();
+    let moves = LocalSearch::new(10)
+        .with_deadline(Some(Instant::now()))
+        .educate(&mut late, &problem, 1.0);
+    assert_eq!(moves, 0);
+    assert!((late.cost - solution.cost).abs() < 1e-6);
+    assert!(late.has_complete_routes(&problem));
+
+    // A distant deadline does not limit it
+    let mut early = solution.clone();
+    let moves = LocalSearch::new(10)
+        .with_deadline(Some(Instant::now() + Duration::from_secs(60)))
+        .educate(&mut early, &problem, 1.0);
+    assert!(moves > 0);
+    assert!(early.cost < solution.cost);
+}
+
+#[test]
+fn test_local_search_route_cache() {
+    let problem = create_complex_problem();
+    let mut solution = create_random_solution(&problem);
+    let initial_cost = solution.cost;
+
+    // Deltas are checked against evaluations that go through the cache
+    let mut local_search = LocalSearch::new(10)
+        .with_verify_deltas(true)
+        .with_route_cache(Some(100));
+    local_search.educate(&mut solution, &problem, 1.0);
+
+    assert!(solution.cost <= initial_cost);
+    assert!(solution.has_complete_routes(&problem));
+    let mut evaluated = solution.clone();
+    evaluated.evaluate(&problem, 1.0);
+    assert!((evaluated.cost - solution.cost).abs() < 1e-9);
+
+    // Routes left unchanged by the last moves are found again by the final evaluation
+    let stats = local_search.route_cache_stats().unwrap();
+    assert!(stats.hits > 0);
+    assert!(stats.entries <= 100);
+    assert!(LocalSearch::new(10).route_cache_stats().is_none());
+}
+
+#[test]
+fn test_local_search_neighborhood_stats() {
+    let problem = create_complex_problem();
+    let mut solution = create_random_solution(&problem);
+
+    let mut local_search = LocalSearch::new(10);
+    let moves = local_search.educate(&mut solution, &problem, 1.0);
+
+    let stats = local_search.neighborhood_stats;
+    assert_eq!(stats.total_accepted(), moves);
+    let relocate = stats.get("relocate").unwrap();
+    assert_eq!(relocate, &stats[MoveKind::Relocate]);
+    assert!(relocate.evaluated > 0);
+    assert!(relocate.acceptance_rate() <= 1.0);
+    assert!(stats.get("3-opt").is_none());
+    assert_eq!(MoveKind::from_name("2-opt*"), Some(MoveKind::TwoOptStar));
+    assert_eq!(MoveKind::SwapStar.to_string(), "SWAP*");
+
+    // Statistics accumulate over educations until they are reset
+    let mut other = create_random_solution(&problem);
+    let more = local_search.educate(&mut other, &problem, 1.0);
+    assert_eq!(
+        local_search.neighborhood_stats.total_accepted(),
+        moves + more
+    );
+    local_search.reset_neighborhood_stats();
+    assert_eq!(local_search.neighborhood_stats, Default::default());
+}
+
+/// Exchanges two consecutive customers of a route, counting its explorations.
+struct AdjacentSwap {
+    explored: Rc<Cell<usize>>,
+}
+
+impl Neighborhood for AdjacentSwap {
+    fn name(&self) -> &str {
+        "adjacent swap"
+    }
+
+    fn explore(
+        &mut self,
+        solution: &mut Solution,
+        problem: &Problem,
+        ctx: &mut NeighborhoodContext<'_>,
+    ) -> Option<AppliedMove> {
+        self.explored.set(self.explored.get() + 1);
+        let cost = solution.cost;
+        for r in 0..solution.routes.len() {
+            for i in 1..solution.routes[r].customers.len() {
+                solution.routes[r].customers.swap(i - 1, i);
+                solution.evaluate(problem, ctx.capacity_penalty);
+                if solution.cost < cost - 1e-9 {
+                    ctx.search.update_route_timestamp(r);
+                    return Some(AppliedMove {
+                        kind: None,
+                        delta: solution.cost - cost,
+                        change: None,
+                    });
+                }
+                solution.routes[r].customers.swap(i - 1, i);
+            }
+        }
+        solution.evaluate(problem, ctx.capacity_penalty);
+        None
+    }
+}
+
+#[test]
+fn test_local_search_custom_neighborhood() {
+    let problem = create_complex_problem();
+    let explored = Rc::new(Cell::new(0));
+    let mut local_search = LocalSearch::new(10).with_neighborhood(AdjacentSwap {
+        explored: Rc::clone(&explored),
+    });
+    assert_eq!(local_search.neighborhoods.len(), MoveKind::COUNT + 1);
+    assert_eq!(local_search.neighborhoods[5].name(), "adjacent swap");
+
+    // The custom neighborhood is explored in every pass, after the built-in ones
+    let mut solution = create_random_solution(&problem);
+    let initial_cost = solution.cost;
+    let moves = local_search.educate(&mut solution, &problem, 1.0);
+    assert!(explored.get() > 0);
+    assert!(moves >= local_search.neighborhood_stats.total_accepted());
+    assert!(solution.cost < initial_cost);
+    assert!(solution.has_complete_routes(&problem));
+    assert_eq!(local_search.neighborhoods.len(), MoveKind::COUNT + 1);
+
+    // Built-in neighborhoods can also be explored one at a time
+    let cost = solution.cost;
+    let applied = local_search.explore(&mut MoveKind::Relocate, &mut solution, &problem, 1.0);
+    assert!(applied.is_none_or(|applied| applied.delta < 0.0));
+    assert!(solution.cost <= cost);
+}
+
+#[test]
+fn test_local_search_annealing() {
+    let problem = create_complex_problem();
+    assert_eq!(Annealing::acceptance_probability(-1.0, 10.0), 1.0);
+    assert!((Annealing::acceptance_probability(10.0, 10.0) - (-1.0f64).exp()).abs() < 1e-12);
+    assert_eq!(Annealing::acceptance_probability(f64::INFINITY, 10.0), 0.0);
+
+    // Worsening moves are accepted while hot, and the education still ends in a local optimum
+    let annealing = Annealing::new(50.0, 0.9).with_final_temperature(1.0);
+    let mut local_search = LocalSearch::new(10);
+    let mut solution = create_random_solution(&problem);
+    let moves = local_search.educate_with_annealing(&mut solution, &problem, 1.0, annealing);
+    assert!(moves > 0);
+    assert!(solution.has_complete_routes(&problem));
+    assert_eq!(local_search.educate(&mut solution, &problem, 1.0), 0);
+}
+
+#[test]
+fn test_local_search_plateau_moves() {
+    let problem = create_complex_problem();
+
+    // Moves leaving the cost unchanged never raise it, and the education still ends in a
+    // local optimum
+    let mut local_search = LocalSearch::new(10).with_verify_deltas(true);
+    let mut solution = create_random_solution(&problem);
+    solution.evaluate(&problem, 1.0);
+    let initial_cost = solution.cost;
+    let moves = local_search.educate_with_plateau(&mut solution, &problem, 1.0, 20);
+    assert!(moves > 0);
+    assert!(solution.cost <= initial_cost);
+    assert!(solution.has_complete_routes(&problem));
+    assert_eq!(local_search.educate(&mut solution, &problem, 1.0), 0);
+}
+
+#[test]
+fn test_local_search_trace_replay() {
+    let problem = create_complex_problem();
+    let mut local_search = LocalSearch::new(10).with_trace(true);
+    let mut solution = create_random_solution(&problem);
+    let moves = local_search.educate(&mut solution, &problem, 1.0);
+    assert!(moves > 0);
+
+    // The trace survives a round trip through JSON lines, up to the last digit of the deltas
+    let trace = local_search.trace.take().unwrap();
+    assert_eq!(trace.education_count(), 1);
+    assert_eq!(trace.records.len(), moves + 1);
+    let mut buffer = Vec::new();
+    trace.write_jsonl(&mut buffer).unwrap();
+    let read = SearchTrace::read_jsonl(buffer.as_slice()).unwrap();
+    assert_eq!(read.education_count(), 1);
+    assert_eq!(read.records[0], trace.records[0]);
+    assert_eq!(read.records.len(), trace.records.len());
+
+    // Replaying the moves reproduces the educated solution
+    let replayed = read.replay(0, &problem).unwrap().unwrap();
+    let routes: Vec<&Vec<usize>> = solution
+        .routes
+        .iter()
+        .map(|route| &route.customers)
+        .collect();
+    let replayed_routes: Vec<&Vec<usize>> = replayed
+        .routes
+        .iter()
+        .map(|route| &route.customers)
+        .collect();
+    assert_eq!(replayed_routes, routes);
+    assert!((replayed.cost - solution.cost).abs() < 1e-9);
+    assert!(read.replay(1, &problem).is_none());
+
+    // A tampered delta is reported as the first divergence
+    let mut tampered = trace.clone();
+    if let TraceRecord::Move { delta, .. } = &mut tampered.records[2] {
+        *delta += 1.0;
+    }
+    let divergence = tampered.replay(0, &problem).unwrap().unwrap_err();
+    assert_eq!(divergence.move_index, 1);
+    assert!((divergence.replayed_delta + 1.0 - divergence.recorded_delta).abs() < 1e-9);
+}
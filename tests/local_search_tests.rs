@@ -1,27 +1,29 @@
 //! Unit tests for the local search components of the HGS-CVRP algorithm.
 
-use hgs_cvrp::local_search::{utils, LocalSearch};
+use hgs_cvrp::local_search::{
+    utils, LocalSearch, Move, MoveKind, NodeIndex, RouteCache, RoutePruning, TabuList, TraceRecord,
+};
+use hgs_cvrp::objective::{BalanceMeasure, ObjectiveModel, RouteChange};
 use hgs_cvrp::problem::{Node, Problem};
 use hgs_cvrp::solution::{Route, Solution};
 
 /// Creates a simple test problem with a depot and some customers in a grid.
 fn create_test_problem() -> Problem {
-    let mut nodes = Vec::new();
-
-    // Depot at (0, 0)
-    nodes.push(Node::new(0, 0.0, 0.0, 0.0, true));
-
-    // 5 customers in a grid
-    // Customer 1 at (10, 0) with demand 1.0
-    nodes.push(Node::new(1, 10.0, 0.0, 1.0, false));
-    // Customer 2 at (0, 10) with demand 1.0
-    nodes.push(Node::new(2, 0.0, 10.0, 1.0, false));
-    // Customer 3 at (10, 10) with demand 1.0
-    nodes.push(Node::new(3, 10.0, 10.0, 1.0, false));
-    // Customer 4 at (20, 0) with demand 1.5
-    nodes.push(Node::new(4, 20.0, 0.0, 1.5, false));
-    // Customer 5 at (20, 10) with demand 2.0
-    nodes.push(Node::new(5, 20.0, 10.0, 2.0, false));
+    let nodes = vec![
+        // Depot at (0, 0)
+        Node::new(0, 0.0, 0.0, 0.0, true),
+        // 5 customers in a grid
+        // Customer 1 at (10, 0) with demand 1.0
+        Node::new(1, 10.0, 0.0, 1.0, false),
+        // Customer 2 at (0, 10) with demand 1.0
+        Node::new(2, 0.0, 10.0, 1.0, false),
+        // Customer 3 at (10, 10) with demand 1.0
+        Node::new(3, 10.0, 10.0, 1.0, false),
+        // Customer 4 at (20, 0) with demand 1.5
+        Node::new(4, 20.0, 0.0, 1.5, false),
+        // Customer 5 at (20, 10) with demand 2.0
+        Node::new(5, 20.0, 10.0, 2.0, false),
+    ];
 
     Problem::new(
         "TestProblem".to_string(),
@@ -78,6 +80,26 @@ fn test_relocate_neighborhood() {
     assert!(solution.distance > 0.0);
 }
 
+#[test]
+fn test_relocate_into_empty_route() {
+    let problem = create_test_problem();
+
+    // A single overloaded route, 6.5 for a capacity of 5.0, next to an empty one
+    let mut solution = Solution::new();
+    let mut route = Route::new();
+    route.customers = vec![1, 2, 3, 4, 5];
+    solution.routes = vec![route, Route::new()];
+    solution.evaluate(&problem, 1000.0);
+    solution.update_giant_tour();
+
+    // The granular neighbors all share the overloaded route, so only the empty one helps
+    let mut local_search = LocalSearch::new(3);
+    assert!(local_search.relocate_neighborhood(&mut solution, &problem, 1000.0));
+
+    assert!(solution.routes.iter().all(|route| !route.is_empty()));
+    assert!(solution.excess_capacity < 1.5);
+}
+
 #[test]
 fn test_swap_neighborhood() {
     let problem = create_test_problem();
@@ -152,6 +174,77 @@ fn test_two_opt_star_neighborhood() {
     assert!(solution.distance > 0.0);
 }
 
+#[test]
+fn test_two_opt_star_on_deserialized_solution() {
+    let problem = create_test_problem();
+    let solution = create_test_solution(&problem);
+
+    // Prefix sums are not serialized, and are computed again by the neighborhood
+    let json = serde_json::to_string(&solution).unwrap();
+    let mut restored: Solution = serde_json::from_str(&json).unwrap();
+    assert!(restored.routes.iter().all(|route| !route.prefix_sums_valid));
+
+    let mut local_search = LocalSearch::new(3).with_verify_deltas(true);
+    let improved = local_search.two_opt_star_neighborhood(&mut restored, &problem, 1.0);
+    assert!(!improved || restored.cost < solution.cost);
+    let mut evaluated = restored.clone();
+    evaluated.evaluate(&problem, 1.0);
+    assert!((restored.cost - evaluated.cost).abs() < 1e-9);
+
+    restored.update_prefix_sums(&problem);
+    for route in &restored.routes {
+        assert_eq!(route.segment_load(0, route.customers.len()), route.load);
+    }
+}
+
+#[test]
+fn test_two_opt_star_cross_variant() {
+    let problem = create_test_problem();
+
+    // Only connecting the head of one route to the reversed head of the other improves this
+    let mut solution = Solution::new();
+    let mut route1 = Route::new();
+    route1.customers = vec![3, 5];
+    let mut route2 = Route::new();
+    route2.customers = vec![2, 4, 1];
+    solution.routes = vec![route1, route2];
+    solution.evaluate(&problem, 1.0);
+
+    let initial_cost = solution.cost;
+
+    // Use all customers as neighbors so every cutting point is considered. The route pairs
+    // are visited in a random order, which decides the first improving move applied.
+    let mut local_search = LocalSearch::new(4).with_trace(true);
+    hgs_cvrp::rng::seed(1);
+
+    let improved = local_search.two_opt_star_neighborhood(&mut solution, &problem, 1.0);
+    hgs_cvrp::rng::unseed();
+
+    assert!(improved);
+    assert!(solution.cost < initial_cost - 1e-6);
+
+    // The head [3] is joined with the reversed head [2], the reversed tail [5] with [4, 1]
+    assert_eq!(solution.routes[0].customers, vec![3, 2]);
+    assert_eq!(solution.routes[1].customers, vec![5, 4, 1]);
+    let records = &local_search.trace.as_ref().unwrap().records;
+    assert!(matches!(
+        records[..],
+        [TraceRecord::Move {
+            applied: Move::TwoOptStarCross { i: 0, j: 0, .. },
+            ..
+        }]
+    ));
+
+    // All customers should still be visited exactly once
+    let mut visited: Vec<usize> = solution
+        .routes
+        .iter()
+        .flat_map(|route| route.customers.iter().copied())
+        .collect();
+    visited.sort_unstable();
+    assert_eq!(visited, vec![1, 2, 3, 4, 5]);
+}
+
 #[test]
 fn test_swap_star_neighborhood() {
     let problem = create_test_problem();
@@ -175,6 +268,72 @@ fn test_swap_star_neighborhood() {
     assert!(solution.distance > 0.0);
 }
 
+#[test]
+fn test_swap_star_delta_matches_recomputed_cost() {
+    let problem = create_test_problem();
+
+    // Customers 2 and 4 are each in the wrong route
+    let mut solution = Solution::new();
+    let mut route1 = Route::new();
+    route1.customers = vec![1, 2];
+    let mut route2 = Route::new();
+    route2.customers = vec![3, 4, 5];
+    solution.routes = vec![route1, route2];
+    solution.evaluate(&problem, 1.0);
+
+    let mut local_search = LocalSearch::new(5);
+
+    // Every accepted move must lower the cost of the freshly evaluated solution
+    let mut previous_cost = solution.cost;
+    while local_search.swap_star_neighborhood(&mut solution, &problem, 1.0) {
+        let mut recomputed = solution.clone();
+        for route in &mut recomputed.routes {
+            route.calculate_distance(&problem);
+            route.calculate_load(&problem);
+        }
+        recomputed.evaluate(&problem, 1.0);
+
+        assert!((recomputed.cost - solution.cost).abs() < 1e-6);
+        assert!(solution.cost < previous_cost - 1e-6);
+        previous_cost = solution.cost;
+    }
+}
+
+#[test]
+fn test_swap_star_route_pruning() {
+    let problem = create_test_problem();
+
+    // Without coordinates, sector pruning falls back to neighbor lists
+    let mut matrix_only = create_test_problem();
+    matrix_only.has_coordinates = false;
+
+    let cases = [
+        (&problem, RoutePruning::Sector),
+        (&problem, RoutePruning::NeighborCount),
+        (&problem, RoutePruning::None),
+        (&matrix_only, RoutePruning::Sector),
+    ];
+
+    for (problem, pruning) in cases {
+        // Customers 2 and 4 are each in the wrong route
+        let mut solution = Solution::new();
+        let mut route1 = Route::new();
+        route1.customers = vec![1, 2];
+        let mut route2 = Route::new();
+        route2.customers = vec![3, 4, 5];
+        solution.routes = vec![route1, route2];
+        solution.evaluate(problem, 1.0);
+        let initial_cost = solution.cost;
+
+        let mut local_search = LocalSearch::new(5)
+            .with_route_pruning(pruning)
+            .with_verify_deltas(true);
+        assert!(local_search.swap_star_neighborhood(&mut solution, problem, 1.0));
+        assert!(solution.cost < initial_cost);
+        assert!(solution.has_complete_routes(problem));
+    }
+}
+
 #[test]
 fn test_full_educate() {
     let problem = create_test_problem();
@@ -193,6 +352,87 @@ fn test_full_educate() {
     assert!(solution.cost <= initial_cost);
 }
 
+/// Quadratic penalty on route distances, favoring routes of similar length.
+#[derive(Debug)]
+struct SquaredRouteDistance(f64);
+
+impl ObjectiveModel for SquaredRouteDistance {
+    fn evaluate(&self, solution: &Solution, _problem: &Problem) -> f64 {
+        solution
+            .routes
+            .iter()
+            .map(|route| self.0 * route.distance.powi(2))
+            .sum()
+    }
+
+    fn delta(&self, solution: &Solution, _problem: &Problem, changes: &[RouteChange]) -> f64 {
+        changes
+            .iter()
+            .map(|change| {
+                let old = solution.routes[change.route].distance;
+                self.0 * (change.distance.powi(2) - old.powi(2))
+            })
+            .sum()
+    }
+}
+
+#[test]
+fn test_custom_objective_in_local_search() {
+    let problem = create_test_problem().with_objective(SquaredRouteDistance(0.01));
+    let tours = [
+        vec![1, 2, 3, 4, 5],
+        vec![5, 1, 4, 2, 3],
+        vec![2, 4, 1, 5, 3],
+    ];
+
+    for tour in tours {
+        // A single overloaded route, so every neighborhood has moves to apply
+        let mut solution = Solution::new();
+        let mut route = Route::new();
+        route.customers = tour;
+        solution.routes.push(route);
+        solution.evaluate(&problem, 1.0);
+        let initial_cost = solution.cost;
+
+        // Every predicted delta must include the change of the custom term
+        let mut local_search = LocalSearch::new(5).with_verify_deltas(true);
+        local_search.educate(&mut solution, &problem, 1.0);
+
+        assert!(solution.cost < initial_cost);
+        let expected = SquaredRouteDistance(0.01).evaluate(&solution, &problem);
+        assert!((solution.objective_cost - expected).abs() < 1e-9);
+        assert!(
+            (solution.cost
+                - solution.distance
+                - solution.objective_cost
+                - solution.excess_capacity)
+                .abs()
+                < 1e-6
+        );
+    }
+}
+
+#[test]
+fn test_workload_balance_objective() {
+    for measure in [BalanceMeasure::Range, BalanceMeasure::Variance] {
+        let problem = create_test_problem().with_workload_balance(2.0, measure);
+        let mut solution = create_test_solution(&problem);
+
+        // The imbalance of two routes only depends on their gap
+        let gap = solution.routes[1].distance - solution.routes[0].distance;
+        let expected = match measure {
+            BalanceMeasure::Range => 2.0 * gap,
+            BalanceMeasure::Variance => 2.0 * (gap / 2.0).powi(2),
+        };
+        assert!((solution.objective_cost - expected).abs() < 1e-6);
+
+        // Deltas of every neighborhood must account for the balance term
+        let mut local_search = LocalSearch::new(5).with_verify_deltas(true);
+        local_search.educate(&mut solution, &problem, 1.0);
+        assert!(solution.has_complete_routes(&problem));
+    }
+}
+
 #[test]
 fn test_repair_infeasible_solution() {
     let problem = create_test_problem();
@@ -221,6 +461,28 @@ fn test_repair_infeasible_solution() {
     assert!(solution.excess_capacity < 6.5 - 5.0 || solution.is_feasible);
 }
 
+#[test]
+fn test_repair_with_penalty() {
+    let problem = create_test_problem();
+
+    // All customers in one route exceeds capacity (total demand is 6.5)
+    let mut solution = Solution::new();
+    let mut route = Route::new();
+    route.customers = vec![1, 2, 3, 4, 5];
+    solution.routes = vec![route];
+    solution.evaluate(&problem, 1.0);
+    assert!(!solution.is_feasible);
+
+    let mut local_search = LocalSearch::new(3);
+
+    // A large enough penalty makes every capacity violation unprofitable
+    let feasible = local_search.repair_with_penalty(&mut solution, &problem, 1000.0);
+
+    assert!(feasible);
+    assert!(solution.is_feasible);
+    assert_eq!(solution.excess_capacity, 0.0);
+}
+
 #[test]
 fn test_utils_get_neighbors() {
     let problem = create_test_problem();
@@ -317,3 +579,371 @@ fn test_utils_calculate_removal_cost() {
     assert!((route_without_0.distance - original_distance - delta_0).abs() < 1e-6);
     assert!((route_without_1.distance - original_distance - delta_1).abs() < 1e-6);
 }
+
+#[test]
+fn test_local_search_max_stops_per_route() {
+    let mut problem = create_test_problem();
+    problem.vehicle_capacity = 10.0;
+    let problem = problem.with_max_stops_per_route(3);
+
+    for _ in 0..10 {
+        let mut solution = create_test_solution(&problem);
+        let mut local_search = LocalSearch::new(5);
+        local_search.educate(&mut solution, &problem, 1.0);
+
+        assert!(solution.routes.iter().all(|r| r.customers.len() <= 3));
+        assert!(solution.is_feasible);
+    }
+}
+
+#[test]
+fn test_local_search_integer_distances_small_penalty_gain() {
+    // Co-located customers, so that moving one between the routes keeps every distance
+    let nodes = vec![
+        Node::new(0, 0.0, 0.0, 0.0, true),
+        Node::new(1, 1.0, 0.0, 6.0, false),
+        Node::new(2, 1.0, 0.0, 4.2, false),
+        Node::new(3, 1.0, 0.0, 5.7, false),
+    ];
+    let problem =
+        Problem::new("Colocated".to_string(), nodes, 0, 10.0, None).with_integer_distances();
+
+    let mut solution = Solution::new();
+    for customers in [vec![1, 2], vec![3]] {
+        let mut route = Route::new();
+        route.customers = customers;
+        solution.routes.push(route);
+    }
+    solution.evaluate(&problem, 1.0);
+    assert!(!solution.is_feasible);
+
+    // Removing 0.2 of excess load gains less than a distance unit and is still applied
+    let mut local_search = LocalSearch::new(5);
+    local_search.educate(&mut solution, &problem, 1.0);
+    assert!(solution.is_feasible);
+    assert_eq!(solution.distance, 4.0);
+}
+
+#[test]
+fn test_route_cache() {
+    let problem = create_test_problem();
+    let mut cache = RouteCache::new(2);
+
+    let mut expected = Route::new();
+    expected.customers = vec![3, 4, 5];
+    expected.calculate_distance(&problem);
+    expected.calculate_load(&problem);
+    expected.update_prefix_sums(&problem);
+
+    // The second evaluation of a sequence is a hit with the same metrics, and prefix sums
+    // computed when needed
+    for _ in 0..2 {
+        let mut route = Route::new();
+        route.customers = vec![3, 4, 5];
+        cache.evaluate(&mut route, &problem);
+        assert!(!route.modified);
+        assert_eq!(route.distance, expected.distance);
+        assert_eq!(route.load, expected.load);
+        assert!(!route.prefix_sums_valid);
+        route.update_prefix_sums(&problem);
+        assert_eq!(route.cumulative_distance, expected.cumulative_distance);
+        assert_eq!(route.cumulative_load, expected.cumulative_load);
+    }
+    assert_eq!(cache.stats().hits, 1);
+    assert_eq!(cache.stats().misses, 1);
+    assert!((cache.stats().hit_rate() - 0.5).abs() < 1e-9);
+
+    // The reversed sequence is another route, and a third one evicts the older half
+    for customers in [vec![5, 4, 3], vec![1, 2]] {
+        let mut route = Route::new();
+        route.customers = customers;
+        cache.evaluate(&mut route, &problem);
+    }
+    assert_eq!(cache.stats().misses, 3);
+    assert!(cache.stats().entries <= 2);
+
+    cache.clear();
+    assert_eq!(
+        cache.stats().hits + cache.stats().misses + cache.stats().entries,
+        0
+    );
+}
+
+#[test]
+fn test_move_apply_and_undo() {
+    let problem = create_test_problem();
+    let mut solution = Solution::new();
+    for customers in [vec![1, 3, 5], vec![2, 4]] {
+        let mut route = Route::new();
+        route.customers = customers;
+        solution.routes.push(route);
+    }
+    solution.evaluate(&problem, 1.0);
+    let original = solution.clone();
+
+    let moves = [
+        (
+            Move::Relocate {
+                from: 0,
+                position: 1,
+                to: 1,
+                insert_position: 2,
+            },
+            [vec![1, 5], vec![2, 4, 3]],
+        ),
+        (
+            Move::Swap {
+                route1: 0,
+                position1: 0,
+                route2: 1,
+                position2: 1,
+            },
+            [vec![4, 3, 5], vec![2, 1]],
+        ),
+        (
+            Move::TwoOpt {
+                route: 0,
+                i: 0,
+                j: 2,
+            },
+            [vec![1, 5, 3], vec![2, 4]],
+        ),
+        (
+            Move::TwoOptStar {
+                route1: 0,
+                route2: 1,
+                i: 0,
+                j: 0,
+            },
+            [vec![1, 4], vec![2, 3, 5]],
+        ),
+        (
+            Move::TwoOptStarCross {
+                route1: 0,
+                route2: 1,
+                i: 0,
+                j: 0,
+                tail_len: 2,
+            },
+            [vec![1, 2], vec![5, 3, 4]],
+        ),
+        (
+            Move::SwapStar {
+                route1: 0,
+                route2: 1,
+                position1: 2,
+                position2: 0,
+                insert_position1: 0,
+                insert_position2: 1,
+            },
+            [vec![2, 1, 3], vec![4, 5]],
+        ),
+    ];
+
+    // Every move changes the sequences as documented and is undone exactly
+    for (applied, expected) in &moves {
+        applied.apply(&mut solution);
+        assert_eq!(solution.routes[0].customers, expected[0], "{applied:?}");
+        assert_eq!(solution.routes[1].customers, expected[1], "{applied:?}");
+        assert!(applied
+            .routes()
+            .iter()
+            .all(|&route| solution.routes[route].modified));
+
+        applied.undo(&mut solution);
+        solution.evaluate_modified(&problem, 1.0);
+        assert_eq!(solution.routes[0].customers, original.routes[0].customers);
+        assert_eq!(solution.routes[1].customers, original.routes[1].customers);
+        assert!((solution.cost - original.cost).abs() < 1e-9);
+    }
+    assert_eq!(moves[4].0.kind(), MoveKind::TwoOptStar);
+    assert_eq!(moves[2].0.routes(), [0, 0]);
+
+    // Moves reported by the local search can be undone as well
+    let mut local_search = LocalSearch::new(5);
+    let mut routes = solution.clone();
+    if let Some(applied) = local_search.explore(&mut MoveKind::Relocate, &mut routes, &problem, 1.0)
+    {
+        let change = applied.change.unwrap();
+        assert_eq!(change.kind(), MoveKind::Relocate);
+        change.undo(&mut routes);
+        routes.evaluate_modified(&problem, 1.0);
+        assert!((routes.cost - original.cost).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn test_node_index_follows_moves() {
+    let problem = create_test_problem();
+    let mut original = Solution::new();
+    for customers in [vec![1, 3, 5], vec![2, 4]] {
+        let mut route = Route::new();
+        route.customers = customers;
+        original.routes.push(route);
+    }
+    original.evaluate(&problem, 1.0);
+
+    let moves = [
+        Move::Relocate {
+            from: 0,
+            position: 1,
+            to: 1,
+            insert_position: 2,
+        },
+        Move::Relocate {
+            from: 0,
+            position: 0,
+            to: 0,
+            insert_position: 2,
+        },
+        Move::Swap {
+            route1: 0,
+            position1: 0,
+            route2: 1,
+            position2: 1,
+        },
+        Move::Swap {
+            route1: 0,
+            position1: 1,
+            route2: 0,
+            position2: 0,
+        },
+        Move::TwoOpt {
+            route: 0,
+            i: 0,
+            j: 2,
+        },
+        Move::TwoOptStar {
+            route1: 0,
+            route2: 1,
+            i: 0,
+            j: 0,
+        },
+        Move::TwoOptStarCross {
+            route1: 0,
+            route2: 1,
+            i: 0,
+            j: 0,
+            tail_len: 2,
+        },
+        Move::TwoOptStarCross {
+            route1: 1,
+            route2: 0,
+            i: 1,
+            j: 2,
+            tail_len: 0,
+        },
+        Move::SwapStar {
+            route1: 0,
+            route2: 1,
+            position1: 2,
+            position2: 0,
+            insert_position1: 0,
+            insert_position2: 1,
+        },
+    ];
+
+    // Once the routes of a move are refreshed, the index holds the node metadata and prefix
+    // sums of the solution the move was applied to
+    for applied in &moves {
+        let mut index = NodeIndex::new(&original, &problem);
+        let mut solution = original.clone();
+        applied.apply(&mut solution);
+        for route in applied.routes() {
+            index.mark_stale(route);
+        }
+        assert!(applied.routes().iter().all(|&r| index.is_stale(r)));
+        index.refresh(&solution, &problem);
+
+        solution.evaluate_modified(&problem, 1.0);
+        solution.update_prefix_sums(&problem);
+        let expected = solution.node_index(&problem);
+        for route in &solution.routes {
+            for (pos, &customer) in route.customers.iter().enumerate() {
+                assert_eq!(index.node(customer), expected[customer], "{applied:?}");
+                assert!(
+                    (index.cumulative_load(customer) - route.segment_load(0, pos + 1)).abs() < 1e-9
+                );
+                assert!(
+                    (index.cumulative_distance(customer) - route.cumulative_distance[pos + 1])
+                        .abs()
+                        < 1e-9
+                );
+            }
+        }
+    }
+
+    // Customers moved to an empty route are indexed there, and the depot in no route
+    let mut solution = original.clone();
+    solution.routes.push(Route::new());
+    let mut index = NodeIndex::new(&solution, &problem);
+    let empty = 2;
+    solution.routes[empty].customers = std::mem::take(&mut solution.routes[1].customers);
+    index.mark_stale(1);
+    index.mark_stale(empty);
+    index.refresh(&solution, &problem);
+    let node = index.node(2).unwrap();
+    assert_eq!((node.route, node.position), (empty, 0));
+    assert_eq!(node.predecessor, problem.depot_index);
+    assert_eq!(index.node(4).unwrap().successor, problem.depot_index);
+    assert_eq!(index.node(problem.depot_index), None);
+}
+
+#[test]
+fn test_tabu_list() {
+    let problem = create_test_problem();
+    let mut solution = Solution::new();
+    for customers in [vec![1, 3, 5], vec![2, 4]] {
+        let mut route = Route::new();
+        route.customers = customers;
+        solution.routes.push(route);
+    }
+    solution.evaluate(&problem, 1.0);
+
+    // Transfers list the customers changing route, before the move is applied
+    let relocate = Move::Relocate {
+        from: 0,
+        position: 1,
+        to: 1,
+        insert_position: 2,
+    };
+    assert_eq!(relocate.transfers(&solution), vec![(3, 0, 1)]);
+    let two_opt_star = Move::TwoOptStar {
+        route1: 0,
+        route2: 1,
+        i: 1,
+        j: 0,
+    };
+    assert_eq!(
+        two_opt_star.transfers(&solution),
+        vec![(5, 0, 1), (4, 1, 0)]
+    );
+    assert!(Move::TwoOpt {
+        route: 0,
+        i: 0,
+        j: 2
+    }
+    .transfers(&solution)
+    .is_empty());
+
+    // A customer may not return to the route it left for `tenure` moves
+    let mut tabu = TabuList::new(2);
+    tabu.record([(3, 0)]);
+    assert!(tabu.is_tabu(3, 0));
+    assert!(!tabu.is_tabu(3, 1));
+    assert!(!tabu.is_tabu(5, 0));
+    tabu.record([]);
+    assert!(tabu.is_tabu(3, 0));
+    tabu.record([]);
+    assert!(!tabu.is_tabu(3, 0));
+    tabu.record([(3, 0)]);
+    tabu.clear();
+    assert!(!tabu.is_tabu(3, 0));
+
+    // Educations with a tabu list still serve every customer
+    let mut local_search = LocalSearch::new(5).with_tabu_tenure(3);
+    assert_eq!(local_search.tabu.as_ref().map(TabuList::tenure), Some(3));
+    local_search.educate(&mut solution, &problem, 1.0);
+    assert!(solution.has_complete_routes(&problem));
+    assert!(LocalSearch::new(5).with_tabu_tenure(0).tabu.is_none());
+}
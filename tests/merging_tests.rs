@@ -0,0 +1,61 @@
+//! Unit tests for the merging of lightly loaded routes.
+
+use hgs_cvrp::config::Config;
+use hgs_cvrp::merging::merge_routes;
+use hgs_cvrp::problem::{Node, Problem};
+use hgs_cvrp::solution::Solution;
+use hgs_cvrp::HgsAlgorithm;
+use std::time::Duration;
+
+/// Creates a test problem with 6 customers in a cluster far from the depot.
+fn create_test_problem() -> Problem {
+    let mut nodes = vec![Node::new(0, 0.0, 0.0, 0.0, true)];
+    for id in 1..=6 {
+        let angle = id as f64 * std::f64::consts::PI / 3.0;
+        let x = 100.0 + 5.0 * angle.cos();
+        let y = 5.0 * angle.sin();
+        nodes.push(Node::new(id, x, y, 1.0, false));
+    }
+
+    Problem::new("MergingTestProblem".to_string(), nodes, 0, 4.0, None)
+}
+
+#[test]
+fn test_merge_routes() {
+    let problem = create_test_problem();
+    let neighbors = problem.neighbor_lists(5);
+    let routes = (1..=6).map(|customer| vec![customer]).collect();
+    let mut solution = Solution::from_routes(routes, &problem).unwrap();
+    solution.evaluate(&problem, 1.0);
+    let initial_cost = solution.cost;
+
+    // Singleton routes merge while two of them fit in a vehicle
+    let merges = merge_routes(&mut solution, &problem, &neighbors, 1.0, 0.0);
+    assert_eq!(merges, 4);
+    assert_eq!(solution.get_used_route_count(), 2);
+    assert!(solution.is_feasible);
+    assert!(solution.cost < initial_cost);
+    assert!(solution.has_complete_routes(&problem));
+
+    // Nothing is left to merge
+    assert_eq!(
+        merge_routes(&mut solution, &problem, &neighbors, 1.0, 0.0),
+        0
+    );
+}
+
+#[test]
+fn test_algorithm_route_merging() {
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_granularity(5)
+        .with_max_iterations_without_improvement(20)
+        .with_time_limit(Duration::from_secs(5))
+        .with_route_merging(0.001);
+
+    let mut algorithm = HgsAlgorithm::new(create_test_problem(), config).unwrap();
+    let best = algorithm.run().unwrap();
+    assert!(best.is_feasible);
+    assert_eq!(best.get_used_route_count(), 2);
+}
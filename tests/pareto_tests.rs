@@ -0,0 +1,48 @@
+//! Unit tests for the Pareto front of (routes, distance) solutions.
+
+use hgs_cvrp::pareto::ParetoFront;
+use hgs_cvrp::solution::{Route, Solution};
+
+/// Creates a feasible solution with the given number of routes and distance.
+fn create_solution(routes: usize, distance: f64) -> Solution {
+    let mut solution = Solution::new();
+    for i in 0..routes {
+        let mut route = Route::new();
+        route.customers = vec![i + 1];
+        solution.routes.push(route);
+    }
+    // Empty routes do not count towards the fleet size
+    solution.routes.push(Route::new());
+    solution.distance = distance;
+    solution.is_feasible = true;
+    solution
+}
+
+#[test]
+fn test_pareto_front_keeps_non_dominated_solutions() {
+    let mut front = ParetoFront::new(1e-6);
+    assert!(front.is_empty());
+
+    assert!(front.insert(&create_solution(3, 100.0)));
+    assert!(front.insert(&create_solution(2, 150.0)));
+    assert!(front.insert(&create_solution(4, 90.0)));
+    assert_eq!(front.len(), 3);
+
+    // Dominated: more routes and a longer distance, or equal to an existing point
+    assert!(!front.insert(&create_solution(4, 120.0)));
+    assert!(!front.insert(&create_solution(3, 100.0)));
+
+    // Infeasible solutions are never part of the front
+    let mut infeasible = create_solution(1, 10.0);
+    infeasible.is_feasible = false;
+    assert!(!front.insert(&infeasible));
+
+    // A better solution with 3 routes removes the ones it dominates
+    assert!(front.insert(&create_solution(3, 80.0)));
+    let points: Vec<(usize, f64)> = front
+        .solutions()
+        .iter()
+        .map(|s| (s.get_used_route_count(), s.distance))
+        .collect();
+    assert_eq!(points, vec![(2, 150.0), (3, 80.0)]);
+}
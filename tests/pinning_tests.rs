@@ -0,0 +1,100 @@
+//! Unit tests for customers pinned to routes and sequences.
+
+use hgs_cvrp::config::Config;
+use hgs_cvrp::local_search::LocalSearch;
+use hgs_cvrp::problem::{Node, Problem};
+use hgs_cvrp::solution::{Route, Solution};
+use hgs_cvrp::split::Split;
+use hgs_cvrp::HgsAlgorithm;
+use std::time::Duration;
+
+/// Creates a test problem with 20 customers around a central depot, with pinned customers.
+fn create_test_problem() -> Problem {
+    let mut nodes = Vec::new();
+
+    // Depot at (50, 50)
+    nodes.push(Node::new(0, 50.0, 50.0, 0.0, true));
+
+    // Customers on a circle
+    for id in 1..=20 {
+        let angle = id as f64 * std::f64::consts::PI / 10.0;
+        let x = 50.0 + 30.0 * angle.cos();
+        let y = 50.0 + 30.0 * angle.sin();
+        nodes.push(Node::new(id, x, y, 1.0, false));
+    }
+
+    let mut problem = Problem::new("PinningTestProblem".to_string(), nodes, 0, 6.0, None);
+
+    // Customers on opposite sides share a route, and a sequence runs against the circle
+    problem.pin_customer(1, 0);
+    problem.pin_customer(11, 0);
+    problem.pin_sequence(&[8, 6, 7], 1);
+    problem
+}
+
+#[test]
+fn test_pins_enforced_after_split() {
+    let problem = create_test_problem();
+
+    for start in 1..=20 {
+        let tour: Vec<usize> = (0..20).map(|i| (start + i - 1) % 20 + 1).collect();
+        let mut solution = Solution::from_giant_tour(tour, &problem);
+        Split::split(&mut solution, &problem);
+
+        assert!(problem.pins.is_respected(&solution));
+        assert!(solution.has_complete_routes(&problem));
+        assert!(solution.has_complete_giant_tour(&problem));
+    }
+
+    // A solution ignoring the pins is detected
+    let mut solution = Solution::new();
+    for chunk in (1..=20).collect::<Vec<_>>().chunks(5) {
+        let mut route = Route::new();
+        route.customers = chunk.to_vec();
+        solution.routes.push(route);
+    }
+    assert!(!problem.pins.is_respected(&solution));
+}
+
+#[test]
+fn test_local_search_keeps_pins() {
+    let problem = create_test_problem();
+
+    for start in 1..=20 {
+        let tour: Vec<usize> = (0..20).map(|i| (start + 3 * i) % 20 + 1).collect();
+        let mut solution = Solution::from_giant_tour(tour, &problem);
+        Split::split(&mut solution, &problem);
+        let initial_cost = solution.cost;
+
+        let mut local_search = LocalSearch::new(10).with_verify_deltas(true);
+        local_search.educate(&mut solution, &problem, 10.0);
+
+        assert!(solution.cost <= initial_cost);
+        assert!(problem.pins.is_respected(&solution));
+        assert!(solution.has_complete_routes(&problem));
+    }
+}
+
+#[test]
+fn test_algorithm_respects_pins() {
+    let problem = create_test_problem();
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(10)
+        .with_max_iterations_without_improvement(200)
+        .with_time_limit(Duration::from_secs(5));
+
+    let mut algorithm = HgsAlgorithm::new(problem.clone(), config).unwrap();
+    let solution = algorithm.run().unwrap();
+
+    assert!(solution.is_feasible);
+    assert!(problem.pins.is_respected(solution));
+    for individual in algorithm
+        .population
+        .feasible_individuals
+        .iter()
+        .chain(&algorithm.population.infeasible_individuals)
+    {
+        assert!(problem.pins.is_respected(&individual.solution));
+    }
+}
@@ -0,0 +1,180 @@
+//! Property-based tests for the local search move operators.
+//!
+//! Random problems and solutions are generated and random sequences of neighborhoods
+//! are applied, checking that the hand-written delta evaluations keep solutions consistent.
+
+use hgs_cvrp::local_search::{utils, LocalSearch, MoveKind};
+use hgs_cvrp::problem::{Node, Problem};
+use hgs_cvrp::solution::{Route, Solution};
+use proptest::prelude::*;
+
+/// Builds a problem from generated customer coordinates and demands.
+fn build_problem(customers: &[(f64, f64, f64)]) -> Problem {
+    let mut nodes = vec![Node::new(0, 50.0, 50.0, 0.0, true)];
+    for (i, &(x, y, demand)) in customers.iter().enumerate() {
+        nodes.push(Node::new(i + 1, x, y, demand, false));
+    }
+
+    Problem::new(
+        "PropertyTestProblem".to_string(),
+        nodes,
+        0,    // depot index
+        10.0, // vehicle capacity
+        None, // no max vehicles constraint
+    )
+}
+
+/// Builds a solution by cutting a permutation of the customers into routes.
+fn build_solution(problem: &Problem, order: &[usize], cuts: &[bool]) -> Solution {
+    let mut solution = Solution::new();
+    let mut route = Route::new();
+
+    for (i, &customer) in order.iter().enumerate() {
+        route.customers.push(customer);
+        if cuts[i] {
+            solution.routes.push(std::mem::take(&mut route));
+        }
+    }
+    if !route.customers.is_empty() {
+        solution.routes.push(route);
+    }
+
+    solution.evaluate(problem, 1.0);
+    solution.update_giant_tour();
+    solution
+}
+
+/// Adds symmetric edge cost multipliers and demand-based service costs to a problem.
+fn add_weighted_costs(problem: Problem) -> Problem {
+    let n = problem.nodes.len();
+    let multipliers: Vec<Vec<f64>> = (0..n)
+        .map(|i| (0..n).map(|j| 1.0 + ((i + j) % 3) as f64 * 0.5).collect())
+        .collect();
+    let service_costs = problem.nodes.iter().map(|node| 2.0 * node.demand).collect();
+
+    problem
+        .with_edge_cost_multipliers(&multipliers)
+        .with_service_costs(service_costs)
+}
+
+/// Recomputes the cost of a solution from its customer sequences only.
+fn recomputed_cost(solution: &Solution, problem: &Problem, capacity_penalty: f64) -> f64 {
+    let mut fresh = Solution::new();
+    for route in &solution.routes {
+        let mut copy = Route::new();
+        copy.customers = route.customers.clone();
+        fresh.routes.push(copy);
+    }
+    fresh.evaluate(problem, capacity_penalty);
+    fresh.cost
+}
+
+/// Generated customers (x, y, demand), visiting order and route cuts after each position.
+type Instance = (Vec<(f64, f64, f64)>, Vec<usize>, Vec<bool>);
+
+/// Strategy generating a random instance together with a random initial solution.
+fn instance() -> impl Strategy<Value = Instance> {
+    prop::collection::vec((0.0..100.0, 0.0..100.0, 1.0..5.0), 2..20).prop_flat_map(|customers| {
+        let n = customers.len();
+        let order = Just((1..=n).collect::<Vec<usize>>()).prop_shuffle();
+        let cuts = prop::collection::vec(any::<bool>(), n);
+        (Just(customers), order, cuts)
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn moves_preserve_solution_invariants(
+        (customers, order, cuts) in instance(),
+        moves in prop::collection::vec(prop::sample::select(MoveKind::ALL.to_vec()), 1..20),
+        granularity in 1..10usize,
+        capacity_penalty in 0.1..100.0f64,
+        route_fixed_cost in prop_oneof![Just(0.0), 0.0..200.0f64],
+        weighted in any::<bool>(),
+    ) {
+        let mut problem = build_problem(&customers).with_route_fixed_cost(route_fixed_cost);
+        if weighted {
+            problem = add_weighted_costs(problem);
+        }
+        let mut solution = build_solution(&problem, &order, &cuts);
+        solution.evaluate(&problem, capacity_penalty);
+
+        let mut local_search = LocalSearch::new(granularity).with_verify_deltas(true);
+
+        for mut kind in moves.iter().copied() {
+            let previous_cost = solution.cost;
+
+            let improved = local_search
+                .explore(&mut kind, &mut solution, &problem, capacity_penalty)
+                .is_some();
+
+            // All customers are present exactly once
+            prop_assert!(solution.has_complete_routes(&problem));
+
+            // The evaluated cost equals a from-scratch recomputation
+            let recomputed = recomputed_cost(&solution, &problem, capacity_penalty);
+            prop_assert!((solution.cost - recomputed).abs() < 1e-6);
+
+            // Accepted moves must actually improve the solution, rejected ones leave it unchanged
+            if improved {
+                prop_assert!(solution.cost < previous_cost - 1e-9);
+            } else {
+                prop_assert!((solution.cost - previous_cost).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn educate_preserves_solution_invariants(
+        (customers, order, cuts) in instance(),
+        capacity_penalty in 0.1..100.0f64,
+        route_fixed_cost in prop_oneof![Just(0.0), 0.0..200.0f64],
+    ) {
+        let problem = build_problem(&customers).with_route_fixed_cost(route_fixed_cost);
+        let mut solution = build_solution(&problem, &order, &cuts);
+        solution.evaluate(&problem, capacity_penalty);
+        let initial_cost = solution.cost;
+
+        let mut local_search = LocalSearch::new(5).with_verify_deltas(true);
+        local_search.educate(&mut solution, &problem, capacity_penalty);
+
+        prop_assert!(solution.has_complete_routes(&problem));
+        prop_assert!(solution.has_complete_giant_tour(&problem));
+
+        let recomputed = recomputed_cost(&solution, &problem, capacity_penalty);
+        prop_assert!((solution.cost - recomputed).abs() < 1e-6);
+        prop_assert!(solution.cost <= initial_cost + 1e-6);
+
+        // Education stops at a local optimum, so a second run cannot improve further
+        let first_cost = solution.cost;
+        local_search.educate(&mut solution, &problem, capacity_penalty);
+        prop_assert!((solution.cost - first_cost).abs() < 1e-6);
+    }
+
+    #[test]
+    fn spatial_neighbor_lists_match_full_sort(
+        points in prop::collection::vec((0..20u8, 0..20u8), 2..60),
+        granularity in 0..12usize,
+        integer in any::<bool>(),
+        compact in any::<bool>(),
+    ) {
+        // Small integral coordinates produce many equal and rounded distances
+        let customers: Vec<(f64, f64, f64)> =
+            points.iter().map(|&(x, y)| (x as f64, y as f64, 1.0)).collect();
+        let mut problem = build_problem(&customers);
+        if integer {
+            problem = problem.with_integer_distances();
+        }
+        if compact {
+            problem = problem.with_compact_distances();
+        }
+
+        let lists = problem.neighbor_lists(granularity);
+        for customer in problem.customers() {
+            let expected = utils::get_neighbors(customer, &problem, granularity);
+            prop_assert_eq!(lists.get(customer), expected.as_slice());
+        }
+    }
+}
@@ -1,26 +1,36 @@
 //! Unit tests for the Solution and Route structures in the HGS-CVRP implementation.
 
-use hgs_cvrp::problem::{Node, Problem};
-use hgs_cvrp::solution::{Route, Solution};
+use hgs_cvrp::config::Config;
+use hgs_cvrp::costs::CostDelta;
+use hgs_cvrp::demand_split::DemandSplit;
+use hgs_cvrp::dynamic::ProblemChange;
+use hgs_cvrp::error::HgsError;
+use hgs_cvrp::local_search::utils::get_neighbors;
+use hgs_cvrp::local_search::{LocalSearch, RoutePruning};
+use hgs_cvrp::problem::{Node, Problem, ProblemError};
+use hgs_cvrp::solution::{Route, RouteError, Solution, Violation};
+use hgs_cvrp::split::Split;
+use hgs_cvrp::stochastic::normal_quantile;
+use hgs_cvrp::HgsAlgorithm;
+use std::time::Duration;
 
 /// Creates a simple test problem with a depot and some customers.
 fn create_test_problem() -> Problem {
-    let mut nodes = Vec::new();
-
-    // Depot at (0, 0)
-    nodes.push(Node::new(0, 0.0, 0.0, 0.0, true));
-
-    // 5 customers in a grid
-    // Customer 1 at (10, 0) with demand 1.0
-    nodes.push(Node::new(1, 10.0, 0.0, 1.0, false));
-    // Customer 2 at (0, 10) with demand 1.0
-    nodes.push(Node::new(2, 0.0, 10.0, 1.0, false));
-    // Customer 3 at (10, 10) with demand 1.0
-    nodes.push(Node::new(3, 10.0, 10.0, 1.0, false));
-    // Customer 4 at (20, 0) with demand 1.5
-    nodes.push(Node::new(4, 20.0, 0.0, 1.5, false));
-    // Customer 5 at (20, 10) with demand 2.0
-    nodes.push(Node::new(5, 20.0, 10.0, 2.0, false));
+    let nodes = vec![
+        // Depot at (0, 0)
+        Node::new(0, 0.0, 0.0, 0.0, true),
+        // 5 customers in a grid
+        // Customer 1 at (10, 0) with demand 1.0
+        Node::new(1, 10.0, 0.0, 1.0, false),
+        // Customer 2 at (0, 10) with demand 1.0
+        Node::new(2, 0.0, 10.0, 1.0, false),
+        // Customer 3 at (10, 10) with demand 1.0
+        Node::new(3, 10.0, 10.0, 1.0, false),
+        // Customer 4 at (20, 0) with demand 1.5
+        Node::new(4, 20.0, 0.0, 1.5, false),
+        // Customer 5 at (20, 10) with demand 2.0
+        Node::new(5, 20.0, 10.0, 2.0, false),
+    ];
 
     Problem::new(
         "TestProblem".to_string(),
@@ -60,15 +70,13 @@ fn test_route_calculate_distance() {
     assert_eq!(route.distance, 0.0);
     assert!(route.modified);
 
-    // Calculate distance
+    // Calculate distance, which leaves the load stale
     route.calculate_distance(&problem);
-
-    // Route should no longer be marked as modified
-    assert!(!route.modified);
+    assert!(route.modified);
 
     // Calculate expected distance
-    // Depot -> 1 -> 3 -> 5 -> Depot
-    let expected_distance = 10.0 + 10.0 + 10.0 + 20.0;
+    // Depot -> 1 -> 3 -> 5 -> Depot, where 5 at (20, 10) is sqrt(20² + 10²) from the depot
+    let expected_distance = 10.0 + 10.0 + 10.0 + 500.0_f64.sqrt();
     assert!((route.distance - expected_distance).abs() < 1e-6);
 
     // Calling calculate_distance again without modifications should not change the value
@@ -108,6 +116,69 @@ fn test_route_calculate_load() {
     assert_eq!(route.load, 5.5);
 }
 
+#[test]
+fn test_route_calculate_distance_then_load() {
+    let problem = create_test_problem();
+
+    // Evaluating the distance first must not leave the load behind
+    let mut route = Route::new();
+    route.customers = vec![1, 3, 5];
+    route.calculate_distance(&problem);
+    route.calculate_load(&problem);
+    assert_eq!(route.load, 4.0);
+
+    // Both are recomputed after a change, whatever the modified flag says
+    route.recalculate(&problem);
+    route.customers.pop();
+    route.calculate_distance(&problem);
+    route.calculate_load(&problem);
+    assert!((route.distance - (20.0 + 200.0_f64.sqrt())).abs() < 1e-6);
+    assert_eq!(route.load, 2.0);
+}
+
+#[test]
+fn test_route_prefix_sums() {
+    let problem = create_test_problem();
+
+    let mut route = Route::new();
+    route.customers = vec![1, 3, 5, 4];
+    route.calculate_load(&problem);
+    route.calculate_distance(&problem);
+    route.update_prefix_sums(&problem);
+
+    // Segment loads should match direct sums over the same positions
+    for start in 0..=route.customers.len() {
+        for end in start..=route.customers.len() {
+            let expected: f64 = route.customers[start..end]
+                .iter()
+                .map(|&c| problem.nodes[c].demand)
+                .sum();
+            assert!((route.segment_load(start, end) - expected).abs() < 1e-9);
+        }
+    }
+
+    // Segment distances should match the path between the two positions
+    for start in 0..route.customers.len() {
+        for end in start..route.customers.len() {
+            let expected: f64 = route.customers[start..=end]
+                .windows(2)
+                .map(|pair| problem.get_distance(pair[0], pair[1]))
+                .sum();
+            assert!((route.segment_distance(start, end) - expected).abs() < 1e-9);
+        }
+    }
+
+    // Changing the route and recalculating invalidates the prefix sums until updated
+    route.customers.remove(0);
+    route.modified = true;
+    route.calculate_load(&problem);
+    route.calculate_distance(&problem);
+    assert!(!route.prefix_sums_valid);
+    route.update_prefix_sums(&problem);
+    assert_eq!(route.segment_load(0, route.customers.len()), route.load);
+    assert_eq!(route.cumulative_distance.len(), route.customers.len() + 1);
+}
+
 #[test]
 fn test_route_exceeds_capacity() {
     let problem = create_test_problem();
@@ -198,9 +269,47 @@ fn test_solution_evaluate() {
 }
 
 #[test]
-fn test_solution_update_giant_tour() {
+fn test_problem_evaluate_batch() {
     let problem = create_test_problem();
+    let partitions = [
+        vec![vec![1, 2], vec![3, 4, 5]],
+        vec![vec![1, 2, 3, 4, 5]],
+        vec![vec![5, 4], vec![3], vec![2, 1]],
+    ];
+    let batch: Vec<Solution> = (0..20)
+        .map(|i| {
+            let mut solution = Solution::new();
+            for customers in &partitions[i % partitions.len()] {
+                let mut route = Route::new();
+                route.customers = customers.clone();
+                solution.routes.push(route);
+            }
+            solution
+        })
+        .collect();
 
+    let mut expected = batch.clone();
+    for solution in &mut expected {
+        solution.evaluate(&problem, 10.0);
+    }
+
+    // Both batch evaluations match evaluating each solution on its own
+    let mut sequential = batch.clone();
+    problem.evaluate_batch(&mut sequential, 10.0);
+    let mut parallel = batch;
+    problem.evaluate_batch_parallel(&mut parallel, 10.0);
+    for evaluated in [&sequential, &parallel] {
+        for (solution, expected) in evaluated.iter().zip(&expected) {
+            assert_eq!(solution.cost, expected.cost);
+            assert_eq!(solution.is_feasible, expected.is_feasible);
+        }
+    }
+    assert!(!parallel[1].is_feasible);
+    problem.evaluate_batch_parallel(&mut [], 10.0);
+}
+
+#[test]
+fn test_solution_update_giant_tour() {
     // Create a solution with two routes
     let mut solution = Solution::new();
 
@@ -273,3 +382,1054 @@ fn test_solution_debug_output() {
     assert!(debug_output.contains("Routes:"));
     assert!(debug_output.contains("Route 0:"));
 }
+
+#[test]
+fn test_integer_distances() {
+    let problem = create_test_problem().with_integer_distances();
+    assert!(problem.integer_distances);
+
+    // sqrt(500) ≈ 22.36 is rounded down, sqrt(200) ≈ 14.14 as well
+    assert_eq!(problem.get_distance(0, 5), 22.0);
+    assert_eq!(problem.get_integer_distance(0, 3), 14);
+
+    // Route distances are sums of the rounded distances
+    let mut solution = Solution::new();
+    let mut route = Route::new();
+    route.customers = vec![1, 3, 5];
+    solution.routes = vec![route];
+    solution.evaluate(&problem, 1.0);
+    assert_eq!(solution.distance, 10.0 + 10.0 + 10.0 + 22.0);
+
+    // Distance changes are rounded to integers, the other terms compared with the tolerance
+    assert!(problem.is_cost_improvement(CostDelta::new(-1.0, 0.0)));
+    assert!(!problem.is_cost_improvement(CostDelta::new(-1e-9, 0.0)));
+    assert!(!problem.is_cost_improvement(CostDelta::new(1.0, -0.5)));
+    assert!(problem.is_cost_improvement(CostDelta::new(0.0, -1e-3)));
+    assert!(problem.is_cost_improvement(CostDelta::new(-1.0, 0.999)));
+    assert!(!problem.is_cost_improvement(CostDelta::new(-1.0, 1.0 - 1e-9)));
+    let coarse = problem.clone().with_tolerance(2.0);
+    assert!(coarse.is_cost_improvement(CostDelta::new(-1.0, 0.0)));
+    assert!(!coarse.is_cost_improvement(CostDelta::new(-1.0, 0.5)));
+
+    // Total cost changes mix both, so small penalty improvements count in every mode
+    assert!(problem.is_improvement(-1e-3));
+    assert!(create_test_problem().is_improvement(-1e-3));
+    assert!(!create_test_problem().is_cost_improvement(CostDelta::new(-1e-9, 0.0)));
+}
+
+#[test]
+fn test_customer_indexing() {
+    let problem = create_test_problem();
+
+    // Customers are all node indices except the depot
+    assert_eq!(problem.customers().collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+    assert!(!problem.is_customer(0));
+    assert!(problem.is_customer(5));
+    assert!(!problem.is_customer(6));
+
+    // A giant tour must visit each customer exactly once, without the depot
+    let mut solution = Solution::new();
+    solution.giant_tour = vec![3, 1, 2, 5, 4];
+    assert!(solution.has_complete_giant_tour(&problem));
+
+    solution.giant_tour = vec![0, 1, 2, 3, 4];
+    assert!(!solution.has_complete_giant_tour(&problem));
+
+    solution.giant_tour = vec![1, 2, 3, 4];
+    assert!(!solution.has_complete_giant_tour(&problem));
+
+    // The same contract holds for routes
+    let mut route1 = Route::new();
+    route1.customers = vec![1, 2];
+    let mut route2 = Route::new();
+    route2.customers = vec![3, 4, 5];
+    solution.routes = vec![route1, route2];
+    assert!(solution.has_complete_routes(&problem));
+
+    solution.routes[1].customers.push(1);
+    assert!(!solution.has_complete_routes(&problem));
+}
+
+#[test]
+fn test_tsplib_explicit_formats() {
+    let header =
+        "NAME : explicit\nTYPE : CVRP\nDIMENSION : 4\nCAPACITY : 10\nEDGE_WEIGHT_TYPE : EXPLICIT\n";
+    let footer = "DEMAND_SECTION\n1 0\n2 3\n3 4\n4 5\nDEPOT_SECTION\n1\n-1\nEOF\n";
+    let full = "EDGE_WEIGHT_FORMAT : FULL_MATRIX\nEDGE_WEIGHT_SECTION\n0 1 2 3\n1 0 4 5\n2 4 0 6\n3 5 6 0\n";
+    let upper = "EDGE_WEIGHT_FORMAT : UPPER_ROW\nEDGE_WEIGHT_SECTION\n1 2 3\n4 5\n6\n";
+    let lower = "EDGE_WEIGHT_FORMAT : LOWER_DIAG_ROW\nEDGE_WEIGHT_SECTION\n0 1 0 2 4 0\n3 5 6 0\n";
+
+    let expected = vec![
+        vec![0.0, 1.0, 2.0, 3.0],
+        vec![1.0, 0.0, 4.0, 5.0],
+        vec![2.0, 4.0, 0.0, 6.0],
+        vec![3.0, 5.0, 6.0, 0.0],
+    ];
+
+    for weights in [full, upper, lower] {
+        let content = format!("{}{}{}", header, weights, footer);
+        let problem = Problem::from_tsplib(&content).unwrap();
+
+        assert_eq!(problem.name, "explicit");
+        assert_eq!(problem.vehicle_capacity, 10.0);
+        assert_eq!(problem.depot_index, 0);
+        assert_eq!(problem.nodes[3].demand, 5.0);
+        assert!(!problem.has_coordinates);
+        assert_eq!(problem.distance_matrix, expected);
+    }
+
+    // A section with the wrong number of weights is rejected
+    let content = format!(
+        "{}{}{}",
+        header, "EDGE_WEIGHT_FORMAT : UPPER_ROW\nEDGE_WEIGHT_SECTION\n1 2 3\n", footer
+    );
+    assert!(Problem::from_tsplib(&content).is_err());
+}
+
+#[test]
+fn test_tsplib_from_file() {
+    let content = "NAME : coords\nTYPE : CVRP\nDIMENSION : 3\nEDGE_WEIGHT_TYPE : EUC_2D\nCAPACITY : 5\nNODE_COORD_SECTION\n1 0 0\n2 3 4\n3 0 10\nDEMAND_SECTION\n1 0\n2 2\n3 3\nDEPOT_SECTION\n1\n-1\nEOF\n";
+    let path = std::env::temp_dir().join(format!("hgs_cvrp_tsplib_{}.vrp", std::process::id()));
+    std::fs::write(&path, content).unwrap();
+
+    let problem = Problem::from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(problem.name, "coords");
+    assert_eq!(problem.get_customer_count(), 2);
+    assert!(problem.has_coordinates);
+    assert!((problem.get_distance(0, 1) - 5.0).abs() < 1e-9);
+    assert!((problem.get_distance(1, 2) - 45.0_f64.sqrt()).abs() < 1e-9);
+}
+
+#[test]
+fn test_solution_route_fixed_cost() {
+    let problem = create_test_problem().with_route_fixed_cost(100.0);
+
+    let mut solution = Solution::new();
+    let mut route1 = Route::new();
+    route1.customers = vec![1, 2];
+    let mut route2 = Route::new();
+    route2.customers = vec![3, 4];
+    let route3 = Route::new();
+    solution.routes = vec![route1, route2, route3];
+    solution.evaluate(&problem, 1.0);
+
+    // Only non-empty routes pay the fixed cost
+    assert_eq!(solution.fixed_cost, 200.0);
+    assert!((solution.cost - (solution.distance + 200.0)).abs() < 1e-6);
+
+    // The feasible cost includes the fixed part as well
+    assert!(solution.is_feasible);
+    assert_eq!(solution.get_feasible_cost(), solution.distance + 200.0);
+}
+
+#[test]
+fn test_solution_weighted_costs() {
+    // Edges leaving the depot cost twice their length, serving a customer costs its id
+    let n = 6;
+    let multipliers: Vec<Vec<f64>> = (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| if i == 0 || j == 0 { 2.0 } else { 1.0 })
+                .collect()
+        })
+        .collect();
+    let service_costs = (0..n).map(|i| i as f64).collect();
+    let problem = create_test_problem()
+        .with_edge_cost_multipliers(&multipliers)
+        .with_service_costs(service_costs);
+
+    assert_eq!(problem.get_distance(0, 1), 20.0);
+    assert_eq!(problem.get_distance(1, 3), 10.0);
+    assert_eq!(problem.get_service_cost(3), 3.0);
+
+    let mut solution = Solution::new();
+    let mut route = Route::new();
+    route.customers = vec![1, 3];
+    solution.routes = vec![route];
+    solution.evaluate(&problem, 1.0);
+
+    // Depot -> 1 -> 3 -> Depot with the depot edges doubled
+    let travel = 20.0 + 10.0 + 2.0 * 200.0_f64.sqrt();
+    assert!((solution.distance - travel).abs() < 1e-6);
+    assert_eq!(solution.service_cost, 4.0);
+    assert!((solution.cost - (travel + 4.0)).abs() < 1e-6);
+}
+
+#[test]
+fn test_solution_evaluate_modified() {
+    let problem = create_test_problem();
+
+    let mut solution = Solution::new();
+    let mut route1 = Route::new();
+    route1.customers = vec![1, 2];
+    let mut route2 = Route::new();
+    route2.customers = vec![3, 4, 5];
+    solution.routes = vec![route1, route2];
+    solution.evaluate(&problem, 1.0);
+
+    // Reorder the second route and only flag that one as modified
+    solution.routes[1].customers.reverse();
+    solution.routes[1].customers.swap(0, 1);
+    solution.routes[1].modified = true;
+    solution.evaluate_modified(&problem, 1.0);
+
+    let mut full = solution.clone();
+    full.evaluate(&problem, 1.0);
+    assert!((solution.cost - full.cost).abs() < 1e-9);
+    assert!((solution.distance - full.distance).abs() < 1e-9);
+    assert!(!solution.routes[1].modified);
+}
+
+#[test]
+fn test_solution_node_index() {
+    let problem = create_test_problem();
+
+    let mut solution = Solution::new();
+    let mut route1 = Route::new();
+    route1.customers = vec![1, 2];
+    let mut route2 = Route::new();
+    route2.customers = vec![3, 4, 5];
+    solution.routes = vec![route1, route2];
+
+    let index = solution.node_index(&problem);
+    assert_eq!(index.len(), problem.nodes.len());
+    assert!(index[problem.depot_index].is_none());
+
+    // Neighbors on the route, with the depot at both ends
+    let node = index[4].unwrap();
+    assert_eq!((node.route, node.position), (1, 1));
+    assert_eq!((node.predecessor, node.successor), (3, 5));
+
+    let node = index[1].unwrap();
+    assert_eq!((node.route, node.position), (0, 0));
+    assert_eq!((node.predecessor, node.successor), (0, 2));
+    assert_eq!(index[5].unwrap().successor, problem.depot_index);
+}
+
+#[test]
+fn test_solution_synced_giant_tour() {
+    let mut solution = Solution::new();
+    solution.giant_tour = vec![3, 1, 2];
+
+    // Without routes the giant tour is kept
+    assert_eq!(solution.synced_giant_tour().as_ref(), &[3, 1, 2]);
+
+    let mut route = Route::new();
+    route.customers = vec![2, 3];
+    solution.routes.push(route);
+    let mut route = Route::new();
+    route.customers = vec![1];
+    solution.routes.push(route);
+
+    // A stale giant tour is rebuilt from the routes
+    assert_eq!(solution.synced_giant_tour().as_ref(), &[2, 3, 1]);
+
+    solution.update_giant_tour();
+    assert!(matches!(
+        solution.synced_giant_tour(),
+        std::borrow::Cow::Borrowed(_)
+    ));
+}
+
+#[test]
+fn test_solution_from_routes() {
+    let problem = create_test_problem();
+
+    // Depot visits at the ends are dropped and in the middle start a new route
+    let solution =
+        Solution::from_routes(vec![vec![0, 1, 2, 0], vec![3, 0, 4, 5]], &problem).unwrap();
+    let routes: Vec<&[usize]> = solution
+        .routes
+        .iter()
+        .map(|r| r.customers.as_slice())
+        .collect();
+    assert_eq!(routes, vec![&[1, 2][..], &[3], &[4, 5]]);
+    assert_eq!(solution.giant_tour, vec![1, 2, 3, 4, 5]);
+    assert!(solution.is_feasible);
+    assert_eq!(solution.routes[0].load, 2.0);
+    assert!(solution.distance > 0.0);
+
+    assert_eq!(
+        Solution::from_routes(vec![vec![1, 2, 9], vec![3, 4, 5]], &problem).err(),
+        Some(RouteError::UnknownCustomer { route: 0, node: 9 })
+    );
+    assert_eq!(
+        Solution::from_routes(vec![vec![1, 2, 3], vec![3, 4, 5]], &problem).err(),
+        Some(RouteError::DuplicateCustomer {
+            customer: 3,
+            routes: (0, 1)
+        })
+    );
+    let missing = Solution::from_routes(vec![vec![1, 2], vec![4]], &problem).unwrap_err();
+    assert_eq!(missing, RouteError::MissingCustomers(vec![3, 5]));
+    assert_eq!(missing.to_string(), "customers [3, 5] are not visited");
+}
+
+#[test]
+fn test_solution_route_ids() {
+    let problem = create_test_problem();
+    let solution = Solution::from_routes(vec![vec![1, 3, 2], vec![4, 5]], &problem).unwrap();
+    let (first, second) = (solution.routes[0].id, solution.routes[1].id);
+    assert_ne!(first, second);
+    assert_eq!(solution.route_by_id(second).unwrap().customers, vec![4, 5]);
+
+    // Identifiers follow the routes through a re-optimization that reorders them
+    let mut reoptimized =
+        Solution::from_routes(vec![vec![5, 4], vec![3, 1], vec![2]], &problem).unwrap();
+    reoptimized.inherit_route_ids(&solution);
+    assert_eq!(reoptimized.routes[0].id, second);
+    assert_eq!(reoptimized.routes[1].id, first);
+    assert_ne!(reoptimized.routes[2].id, first);
+    assert_ne!(reoptimized.routes[2].id, second);
+
+    // A route left unmatched gives up an identifier taken by a matched route
+    let mut copy = solution.clone();
+    copy.routes[0].customers = vec![4, 5];
+    copy.routes[1].customers = vec![9];
+    copy.inherit_route_ids(&solution);
+    assert_eq!(copy.routes[0].id, second);
+    assert_ne!(copy.routes[1].id, second);
+    assert_ne!(copy.routes[1].id, first);
+}
+
+#[test]
+fn test_route_lazy_evaluation() {
+    let problem = create_test_problem();
+    let mut solution = Solution::from_routes(vec![vec![1, 3, 2], vec![4, 5]], &problem).unwrap();
+    assert!(solution.routes.iter().all(|route| !route.modified));
+
+    // A load-only or distance-only calculation keeps the route marked
+    let customer = solution.routes[1].customers.remove(0);
+    solution.routes[0].customers.push(customer);
+    for route in &mut solution.routes[..2] {
+        route.modified = true;
+    }
+    solution.routes[0].calculate_load(&problem);
+    solution.routes[1].calculate_distance(&problem);
+    assert!(solution.routes[0].modified && solution.routes[1].modified);
+
+    // Lazy evaluation then refreshes both metrics of both routes
+    solution.evaluate_modified(&problem, 1.0);
+    assert!(solution.routes.iter().all(|route| !route.modified));
+    let mut expected = solution.clone();
+    expected.evaluate(&problem, 1.0);
+    for (route, expected) in solution.routes.iter().zip(&expected.routes) {
+        assert_eq!(route.distance, expected.distance);
+        assert_eq!(route.load, expected.load);
+    }
+    assert_eq!(solution.cost, expected.cost);
+
+    // Routes left unmarked are skipped, which `recompute` reveals
+    solution.routes[1].customers.reverse();
+    solution.routes[1].customers.push(1);
+    solution.routes[0]
+        .customers
+        .retain(|&customer| customer != 1);
+    solution.evaluate_modified(&problem, 1.0);
+    assert_eq!(solution.recompute(&problem).routes.len(), 2);
+}
+
+#[test]
+fn test_solution_recompute() {
+    let problem = create_test_problem();
+    let mut solution = Solution::from_routes(vec![vec![1, 3, 2], vec![4, 5]], &problem).unwrap();
+    assert!(solution
+        .recompute(&problem)
+        .is_consistent(problem.tolerance));
+
+    // Customers changed without marking the route, and a stale load
+    solution.routes[0].customers.swap(0, 1);
+    solution.routes[1].load += 2.0;
+    let stored_distance = solution.routes[0].distance;
+    let discrepancy = solution.recompute(&problem);
+    assert!(!discrepancy.is_consistent(problem.tolerance));
+    assert_eq!(discrepancy.routes.len(), 2);
+    let first = discrepancy.routes[0];
+    assert_eq!(first.route, 0);
+    assert!((first.distance - (stored_distance - solution.routes[0].distance)).abs() < 1e-9);
+    assert!(first.stale_prefix_sums);
+    assert!(!first.was_modified);
+    assert!((discrepancy.routes[1].load - 2.0).abs() < 1e-9);
+
+    // The routes are refreshed, and the totals once evaluated
+    assert!(solution.routes.iter().all(|route| !route.modified));
+    solution.evaluate_modified(&problem, 1.0);
+    assert!(solution
+        .recompute(&problem)
+        .is_consistent(problem.tolerance));
+}
+
+#[test]
+fn test_solution_violations() {
+    let mut problem = create_test_problem();
+    problem.max_vehicles = Some(1);
+
+    let mut solution = Solution::new();
+    for customers in [vec![1, 3, 4, 5], vec![1, 9]] {
+        let mut route = Route::new();
+        route.customers = customers;
+        solution.routes.push(route);
+    }
+
+    let violations = solution.violations(&problem);
+    assert_eq!(
+        violations,
+        vec![
+            Violation::Overload {
+                route: 0,
+                load: 5.5,
+                capacity: 5.0
+            },
+            Violation::UnknownNode { route: 1, node: 9 },
+            Violation::DuplicateCustomer {
+                customer: 1,
+                routes: vec![0, 1]
+            },
+            Violation::MissingCustomer(2),
+            Violation::TooManyRoutes { used: 2, max: 1 },
+        ]
+    );
+    assert_eq!(violations[0].excess(), 0.5);
+    assert_eq!(
+        violations[0].to_string(),
+        "route 0 carries 5.50 of capacity 5.00 (excess 0.50)"
+    );
+
+    // A valid solution has no violations
+    let solution = Solution::from_routes(vec![vec![1, 2, 3], vec![4, 5]], &problem).unwrap();
+    problem.max_vehicles = None;
+    assert!(solution.violations(&problem).is_empty());
+}
+
+#[test]
+fn test_problem_from_coordinates() {
+    let customers = [(10.0, 0.0, 1.0), (0.0, 10.0, 2.0), (10.0, 10.0, 3.0)];
+    let problem = Problem::from_coordinates((0.0, 0.0), &customers, 5.0, Some(2)).unwrap();
+
+    assert_eq!(problem.nodes.len(), 4);
+    assert_eq!(problem.depot_index, 0);
+    assert_eq!(problem.nodes[2].demand, 2.0);
+    assert_eq!(problem.max_vehicles, Some(2));
+    assert!((problem.get_distance(0, 1) - 10.0).abs() < 1e-9);
+
+    // Invalid data is reported with the offending node
+    assert_eq!(
+        Problem::from_coordinates((0.0, 0.0), &[(1.0, 1.0, -1.0)], 5.0, None).err(),
+        Some(ProblemError::NegativeDemand {
+            customer: 1,
+            demand: -1.0
+        })
+    );
+    assert_eq!(
+        Problem::from_coordinates((0.0, 0.0), &customers, 2.5, None).err(),
+        Some(ProblemError::DemandExceedsCapacity {
+            customer: 3,
+            demand: 3.0,
+            capacity: 2.5
+        })
+    );
+    assert_eq!(
+        Problem::from_coordinates((0.0, 0.0), &customers, 0.0, None).err(),
+        Some(ProblemError::InvalidCapacity(0.0))
+    );
+    assert_eq!(
+        Problem::from_coordinates((f64::NAN, 0.0), &customers, 5.0, None).err(),
+        Some(ProblemError::InvalidCoordinates { node: 0 })
+    );
+}
+
+#[test]
+fn test_problem_depot_clustering() {
+    // Two groups of customers on either side of the depot
+    let customers = [
+        (-50.0, 0.0, 1.0),
+        (-52.0, 2.0, 1.0),
+        (-48.0, -2.0, 1.0),
+        (50.0, 0.0, 1.0),
+        (52.0, 2.0, 1.0),
+        (48.0, -2.0, 1.0),
+    ];
+    let problem = Problem::from_coordinates((0.0, 0.0), &customers, 5.0, None).unwrap();
+
+    let clusters = problem.assign_to_nearest(&[4, 1]);
+    assert_eq!(clusters, vec![vec![4, 5, 6], vec![1, 2, 3]]);
+
+    let mut clusters = problem.capacitated_k_means(2, 10.0, 20);
+    clusters.sort();
+    assert_eq!(clusters, vec![vec![1, 2, 3], vec![4, 5, 6]]);
+
+    // A tight capacity splits the groups, every customer keeping a cluster
+    let clusters = problem.capacitated_k_means(3, 2.0, 20);
+    assert_eq!(clusters.len(), 3);
+    assert_eq!(clusters.iter().map(Vec::len).sum::<usize>(), 6);
+    assert!(clusters.iter().all(|cluster| cluster.len() <= 2));
+}
+
+#[test]
+fn test_problem_colocated_customers() {
+    // Three customers share a location, and one is at the depot
+    let customers = [
+        (10.0, 0.0, 1.0),
+        (10.0, 0.0, 1.0),
+        (0.0, 0.0, 1.0),
+        (10.0, 0.0, 1.0),
+        (0.0, 10.0, 1.0),
+        (-10.0, -0.0, 1.0),
+    ];
+    let problem = Problem::from_coordinates((0.0, 0.0), &customers, 3.0, None).unwrap();
+    assert_eq!(problem.colocated_nodes(), vec![vec![0, 3], vec![1, 2, 4]]);
+
+    // Neighbors at the same distance are ordered by index, with and without a k-d tree
+    let neighbors = problem.neighbor_lists(3);
+    assert_eq!(neighbors.get(1), &[2, 4, 3]);
+    assert_eq!(get_neighbors(1, &problem, 3), vec![2, 4, 3]);
+
+    let mut local_search = LocalSearch::new(3).with_route_pruning(RoutePruning::Sector);
+    let mut solution =
+        Solution::from_routes((1..=6).map(|customer| vec![customer]).collect(), &problem).unwrap();
+    local_search.educate(&mut solution, &problem, 100.0);
+    assert!(solution.is_feasible);
+    assert!(solution.has_complete_routes(&problem));
+
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_max_iterations_without_improvement(50);
+    let mut algorithm = HgsAlgorithm::new(problem.clone(), config).unwrap();
+    let best = algorithm.run().unwrap();
+    assert!(best.has_complete_routes(&problem));
+    assert!(best.cost <= solution.cost + 1e-9);
+}
+
+#[test]
+fn test_problem_from_file_then_demand_split() {
+    let tsplib = "NAME : oversized\nTYPE : CVRP\nDIMENSION : 3\nEDGE_WEIGHT_TYPE : EUC_2D\nCAPACITY : 5\nNODE_COORD_SECTION\n1 0 0\n2 3 4\n3 0 10\nDEMAND_SECTION\n1 0\n2 2\n3 7\nDEPOT_SECTION\n1\n-1\nEOF\n";
+    let simple = "oversized\n5\n0 0 0 0\n1 3 4 2\n2 0 10 7\n";
+
+    for (extension, content) in [("vrp", tsplib), ("txt", simple)] {
+        let path = std::env::temp_dir().join(format!(
+            "hgs_cvrp_oversized_{}.{extension}",
+            std::process::id()
+        ));
+        std::fs::write(&path, content).unwrap();
+        let problem = Problem::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // The oversized demand is loaded, and only rejected by the full validation
+        assert_eq!(
+            problem.validate(),
+            Err(ProblemError::DemandExceedsCapacity {
+                customer: 2,
+                demand: 7.0,
+                capacity: 5.0
+            })
+        );
+
+        // Dividing it into visits makes the problem solvable
+        let split = DemandSplit::new(&problem);
+        assert_eq!(split.extra_visits(), 1);
+        assert_eq!(split.problem.validate(), Ok(()));
+    }
+
+    // Other invalid data is still rejected on load
+    let path = std::env::temp_dir().join(format!("hgs_cvrp_negative_{}.txt", std::process::id()));
+    std::fs::write(&path, "negative\n5\n0 0 0 0\n1 3 4 -2\n").unwrap();
+    let err = Problem::from_file(&path).unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(
+        err,
+        HgsError::InvalidProblem(ProblemError::NegativeDemand { customer: 1, .. })
+    ));
+}
+
+#[test]
+fn test_problem_parse_errors() {
+    // Malformed values are reported with their line
+    let content =
+        "NAME : bad\nDIMENSION : 3\nCAPACITY : 5\nNODE_COORD_SECTION\n1 0 0\n2 3 x\n3 0 10\nEOF\n";
+    assert!(matches!(
+        Problem::from_tsplib(content),
+        Err(HgsError::ParseError { line: 6, .. })
+    ));
+    let content =
+        "NAME : bad\nDIMENSION : 2\nCAPACITY : 5\nNODE_COORD_SECTION\n1 0 0\n3 1 1\nEOF\n";
+    assert!(matches!(
+        Problem::from_tsplib(content),
+        Err(HgsError::ParseError { line: 6, .. })
+    ));
+    // Missing headers concern the file as a whole
+    let err = Problem::from_tsplib("NAME : bad\nCAPACITY : 5\nEOF\n").unwrap_err();
+    assert!(matches!(err, HgsError::ParseError { line: 0, .. }));
+    assert_eq!(
+        err.to_string(),
+        "invalid instance: missing DIMENSION header"
+    );
+
+    let path = std::env::temp_dir().join(format!("hgs_cvrp_simple_{}.txt", std::process::id()));
+    std::fs::write(&path, "simple\nten\n").unwrap();
+    let err = Problem::from_file(&path).unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(err, HgsError::ParseError { line: 2, .. }));
+    assert!(matches!(Problem::from_file(&path), Err(HgsError::Io(_))));
+}
+
+/// TSPLIB content of a problem with two customers, shared by the reader tests.
+const READER_INSTANCE: &str = "NAME : reader\nDIMENSION : 3\nCAPACITY : 5\nNODE_COORD_SECTION\n1 0 0\n2 3 4\n3 0 10\nDEMAND_SECTION\n1 0\n2 2\n3 3\nEOF\n";
+
+#[test]
+fn test_problem_from_reader() {
+    let problem = Problem::from_reader(READER_INSTANCE.as_bytes()).unwrap();
+    assert_eq!(problem.name, "reader");
+    assert_eq!(problem.get_customer_count(), 2);
+
+    // Compressed content is recognized by its first bytes
+    let gzip_header = [0x1f, 0x8b, 0x08, 0x00];
+    let result = Problem::from_reader(&gzip_header[..]);
+    if cfg!(feature = "compression") {
+        assert!(matches!(result, Err(HgsError::Io(_))));
+    } else {
+        assert!(matches!(result, Err(HgsError::ParseError { line: 0, .. })));
+    }
+}
+
+#[cfg(feature = "compression")]
+#[test]
+fn test_problem_from_compressed() {
+    use flate2::write::{DeflateEncoder, GzEncoder};
+    use flate2::Compression;
+    use std::io::Write;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(READER_INSTANCE.as_bytes()).unwrap();
+    let gzip = encoder.finish().unwrap();
+    let path = std::env::temp_dir().join(format!("hgs_cvrp_reader_{}.vrp.gz", std::process::id()));
+    std::fs::write(&path, gzip).unwrap();
+    let problem = Problem::from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(problem.name, "reader");
+
+    // Zip archives with a stored or a deflated first file
+    let mut deflater = DeflateEncoder::new(Vec::new(), Compression::default());
+    deflater.write_all(READER_INSTANCE.as_bytes()).unwrap();
+    let deflated = deflater.finish().unwrap();
+    for (method, data) in [(0u16, READER_INSTANCE.as_bytes()), (8, &deflated[..])] {
+        let zip = zip_archive(&[(&b"reader.vrp"[..], method, data, false)]);
+        let problem = Problem::from_reader(&zip[..]).unwrap();
+        assert_eq!(problem.get_customer_count(), 2, "method {method}");
+    }
+
+    // Streamed archives leave the sizes of the local header empty and write them in a data
+    // descriptor after the data; the first file after a directory entry is read
+    let zip = zip_archive(&[
+        (&b"instances/"[..], 0, &[][..], false),
+        (b"instances/reader.vrp", 8, &deflated, true),
+        (b"instances/other.vrp", 0, b"not an instance", true),
+    ]);
+    let problem = Problem::from_reader(&zip[..]).unwrap();
+    assert_eq!(problem.name, "reader");
+
+    // Without a central directory the archive is rejected
+    let truncated = &zip[..zip.len() - 22];
+    assert!(matches!(
+        Problem::from_reader(truncated),
+        Err(HgsError::ParseError { .. })
+    ));
+}
+
+/// Build a zip archive of `(name, method, data, streamed)` entries with a central directory.
+///
+/// Streamed entries set the data descriptor flag and leave the sizes of the local header
+/// empty, as streaming zip tools do. CRCs are left empty and the uncompressed size repeats
+/// the compressed one, since neither is checked.
+#[cfg(feature = "compression")]
+fn zip_archive(entries: &[(&[u8], u16, &[u8], bool)]) -> Vec<u8> {
+    let mut zip = Vec::new();
+    let mut directory = Vec::new();
+    for &(name, method, data, streamed) in entries {
+        let offset = zip.len() as u32;
+        let flags: u16 = if streamed { 0x08 } else { 0 };
+        let size = |sizes_known: bool| if sizes_known { data.len() as u32 } else { 0 };
+        zip.extend_from_slice(b"PK\x03\x04\x14\x00");
+        zip.extend_from_slice(&flags.to_le_bytes());
+        zip.extend_from_slice(&method.to_le_bytes());
+        zip.extend_from_slice(&[0; 8]);
+        zip.extend_from_slice(&size(!streamed).to_le_bytes());
+        zip.extend_from_slice(&size(!streamed).to_le_bytes());
+        zip.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        zip.extend_from_slice(&[0, 0]);
+        zip.extend_from_slice(name);
+        zip.extend_from_slice(data);
+        if streamed {
+            zip.extend_from_slice(b"PK\x07\x08\x00\x00\x00\x00");
+            zip.extend_from_slice(&size(true).to_le_bytes());
+            zip.extend_from_slice(&size(true).to_le_bytes());
+        }
+
+        directory.extend_from_slice(b"PK\x01\x02\x14\x00\x14\x00");
+        directory.extend_from_slice(&flags.to_le_bytes());
+        directory.extend_from_slice(&method.to_le_bytes());
+        directory.extend_from_slice(&[0; 8]);
+        directory.extend_from_slice(&size(true).to_le_bytes());
+        directory.extend_from_slice(&size(true).to_le_bytes());
+        directory.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        directory.extend_from_slice(&[0; 12]);
+        directory.extend_from_slice(&offset.to_le_bytes());
+        directory.extend_from_slice(name);
+    }
+    let directory_offset = zip.len() as u32;
+    zip.extend_from_slice(&directory);
+    zip.extend_from_slice(b"PK\x05\x06\x00\x00\x00\x00");
+    zip.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    zip.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    zip.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+    zip.extend_from_slice(&directory_offset.to_le_bytes());
+    zip.extend_from_slice(&[0, 0]);
+    zip
+}
+
+#[test]
+fn test_problem_parse_diagnostics() {
+    // Comments, blank lines, and Windows line endings are accepted
+    let content = "# generated\r\nNAME : crlf\r\n\r\nDIMENSION : 3\r\nCAPACITY : 5\r\nNODE_COORD_SECTION\r\n1 0 0\r\n# depot first\r\n2 3 4\r\n3 0 10\r\nDEMAND_SECTION\r\n1 0\r\n2 2\r\n3 3\r\nEOF\r\n";
+    let problem = Problem::from_tsplib(content).unwrap();
+    assert_eq!(problem.name, "crlf");
+    assert!((problem.get_distance(0, 1) - 5.0).abs() < 1e-9);
+
+    // Errors name the field and the expected format
+    let content = "NAME : bad\nDIMENSION : three\nCAPACITY : 5\nEOF\n";
+    assert_eq!(
+        Problem::from_tsplib(content).unwrap_err().to_string(),
+        "invalid instance at line 2: invalid DIMENSION `three`, expected a non-negative integer"
+    );
+    let content = "NAME : bad\nDIMENSION : 2\nCAPACITY : 5\nNODE_COORD_SECTION\n1 0\n2 1 1\nEOF\n";
+    assert_eq!(
+        Problem::from_tsplib(content).unwrap_err().to_string(),
+        "invalid instance at line 5: expected NODE_COORD_SECTION line `id x y`, found 2 fields"
+    );
+
+    // Nodes listed twice, or not at all, are reported
+    let content =
+        "NAME : bad\nDIMENSION : 2\nCAPACITY : 5\nNODE_COORD_SECTION\n1 0 0\n1 1 1\nEOF\n";
+    assert_eq!(
+        Problem::from_tsplib(content).unwrap_err().to_string(),
+        "invalid instance at line 6: duplicate node id 1, first listed at line 5"
+    );
+    let content =
+        "NAME : bad\nDIMENSION : 3\nCAPACITY : 5\nNODE_COORD_SECTION\n1 0 0\n2 1 1\nEOF\n";
+    assert!(matches!(
+        Problem::from_tsplib(content),
+        Err(HgsError::ParseError { line: 0, .. })
+    ));
+
+    // The simple format too
+    let path = std::env::temp_dir().join(format!("hgs_cvrp_duplicate_{}.txt", std::process::id()));
+    std::fs::write(
+        &path,
+        "simple\n\n10 2\n# depot\n0 0 0 0\n1 3 4 2\n1 0 10 3\n",
+    )
+    .unwrap();
+    let err = Problem::from_file(&path).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid instance at line 7: duplicate node id 1, first defined at line 6"
+    );
+    std::fs::write(
+        &path,
+        "simple\r\n10 2\r\n0 0 0 0\r\n1 3 4 2\r\n2 0 10 3\r\n",
+    )
+    .unwrap();
+    let problem = Problem::from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(problem.get_customer_count(), 2);
+    assert_eq!(problem.max_vehicles, Some(2));
+}
+
+#[test]
+fn test_demand_split() {
+    let mut problem = create_test_problem();
+    problem.nodes[4].demand = 12.0;
+    assert!(problem.validate().is_err());
+
+    let split = DemandSplit::new(&problem);
+    assert!(split.problem.validate().is_ok());
+    assert_eq!(split.extra_visits(), 2);
+    assert_eq!(split.problem.nodes.len(), 8);
+    assert_eq!(split.original_nodes[6..], [4, 4]);
+    assert_eq!(split.problem.nodes[4].demand, 2.0);
+    assert_eq!(split.problem.nodes[6].demand, 5.0);
+
+    // Extra visits are at the location of their customer
+    assert_eq!(split.problem.get_distance(6, 7), 0.0);
+    assert_eq!(split.problem.get_distance(4, 6), 0.0);
+    assert_eq!(split.problem.get_distance(0, 7), problem.get_distance(0, 4));
+    assert_eq!(split.problem.get_distance(7, 1), problem.get_distance(4, 1));
+
+    // Problems that fit are unchanged
+    assert_eq!(DemandSplit::new(&create_test_problem()).extra_visits(), 0);
+
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(10)
+        .with_max_iterations_without_improvement(100)
+        .with_time_limit(Duration::from_secs(5));
+    let mut algorithm = HgsAlgorithm::new(split.problem.clone(), config).unwrap();
+    let solution = algorithm.run().unwrap();
+    assert!(solution.is_feasible);
+
+    // Every customer receives its whole demand
+    let routes = split.to_original(solution);
+    for customer in problem.customers() {
+        let delivered: f64 = routes
+            .iter()
+            .flatten()
+            .filter(|&&(c, _)| c == customer)
+            .map(|&(_, quantity)| quantity)
+            .sum();
+        assert!((delivered - problem.nodes[customer].demand).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn test_demand_split_in_chunks() {
+    // Three customers of demand 6 far from the depot: without shared deliveries each needs
+    // its own vehicle of capacity 10, with chunks of 2 two vehicles suffice
+    let customers = [(100.0, 0.0, 6.0), (100.0, 1.0, 6.0), (101.0, 0.0, 6.0)];
+    let problem = Problem::from_coordinates((0.0, 0.0), &customers, 10.0, None).unwrap();
+
+    // Chunks must be positive and fit in a vehicle
+    for size in [0.0, -1.0, 11.0, f64::NAN] {
+        assert!(matches!(
+            DemandSplit::in_chunks(&problem, size),
+            Err(ProblemError::InvalidChunkSize { capacity, .. }) if capacity == 10.0
+        ));
+    }
+
+    let split = DemandSplit::in_chunks(&problem, 2.0).unwrap();
+    assert_eq!(split.extra_visits(), 6);
+
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(10)
+        .with_max_iterations_without_improvement(200)
+        .with_time_limit(Duration::from_secs(5));
+    let mut algorithm = HgsAlgorithm::new(split.problem.clone(), config).unwrap();
+    let solution = algorithm.run().unwrap().clone();
+
+    assert!(solution.is_feasible);
+    assert_eq!(solution.routes.iter().filter(|r| !r.is_empty()).count(), 2);
+    assert!(solution.cost < 450.0);
+
+    // Partial deliveries add up to each demand
+    let delivered = split.delivered_quantities(&solution);
+    for customer in problem.customers() {
+        assert!((delivered[customer] - 6.0).abs() < 1e-9);
+    }
+
+    // Consecutive visits of a customer form one delivery
+    let deliveries = split.deliveries(&solution);
+    for route in &deliveries {
+        assert!(route.windows(2).all(|pair| pair[0].0 != pair[1].0));
+    }
+    let shared = problem
+        .customers()
+        .filter(|&c| {
+            deliveries
+                .iter()
+                .filter(|r| r.iter().any(|d| d.0 == c))
+                .count()
+                > 1
+        })
+        .count();
+    assert!(shared >= 1, "some customer is delivered by both routes");
+}
+
+#[test]
+fn test_stochastic_demands() {
+    assert!(normal_quantile(0.5).abs() < 1e-9);
+    assert!((normal_quantile(0.95) - 1.644_853_6).abs() < 1e-6);
+    assert!((normal_quantile(0.01) + 2.326_347_9).abs() < 1e-6);
+
+    // Two customers of mean demand 2 and standard deviation 1 fit in a vehicle of capacity
+    // 5 on average, but not at a 95% service level
+    let customers = [(10.0, 0.0, 2.0), (10.0, 10.0, 2.0)];
+    let problem = Problem::from_coordinates((0.0, 0.0), &customers, 5.0, None)
+        .unwrap()
+        .with_demand_uncertainty(vec![0.0, 1.0, 1.0], 0.95);
+
+    let mut solution = Solution::new();
+    let mut route = Route::new();
+    route.customers = vec![1, 2];
+    solution.routes.push(route);
+    solution.evaluate(&problem, 1.0);
+
+    let quantile = 4.0 + normal_quantile(0.95) * 2.0_f64.sqrt();
+    assert!((solution.routes[0].load_variance - 2.0).abs() < 1e-9);
+    assert!((solution.excess_capacity - (quantile - 5.0)).abs() < 1e-9);
+    assert!(!solution.is_feasible);
+    match &solution.violations(&problem)[..] {
+        [Violation::ServiceLevel {
+            route: 0,
+            quantile: q,
+            ..
+        }] => assert!((q - quantile).abs() < 1e-9),
+        other => panic!("unexpected violations {other:?}"),
+    }
+
+    // Split serves the customers with separate vehicles
+    solution.update_giant_tour();
+    Split::split(&mut solution, &problem);
+    assert_eq!(solution.routes.len(), 2);
+    assert!(solution.violations(&problem).is_empty());
+}
+
+#[test]
+fn test_route_schedule() {
+    let problem = create_test_problem();
+    let mut solution = Solution::new();
+    let mut route = Route::new();
+    route.customers = vec![1, 3];
+    solution.routes = vec![route, Route::new()];
+
+    // Depot -> 1 -> 3 -> Depot, without durations
+    let schedule = solution.schedule(&problem);
+    assert_eq!(schedule.len(), 1);
+    let stops = &schedule[0];
+    assert_eq!(
+        stops.iter().map(|stop| stop.node).collect::<Vec<_>>(),
+        vec![1, 3, 0]
+    );
+    assert_eq!(stops[0].distance, 10.0);
+    assert_eq!(stops[1].distance, 20.0);
+    assert!((stops[2].distance - (20.0 + 200.0_f64.sqrt())).abs() < 1e-9);
+    assert_eq!(stops[0].load_remaining, 1.0);
+    assert_eq!(stops[1].load_remaining, 0.0);
+    assert!(stops.iter().all(|stop| stop.arrival_time.is_none()));
+
+    // Travelling at speed 2 with one time unit of service at each customer
+    let problem = problem.with_durations(2.0, vec![0.0, 1.0, 1.0, 1.0, 1.0, 1.0]);
+    let stops = solution.routes[0].schedule(&problem);
+    assert_eq!(stops[0].arrival_time, Some(5.0));
+    assert_eq!(stops[1].arrival_time, Some(11.0));
+    assert!((stops[2].arrival_time.unwrap() - (12.0 + 200.0_f64.sqrt() / 2.0)).abs() < 1e-9);
+}
+
+#[test]
+fn test_solution_summary() {
+    let problem = create_test_problem();
+    let mut solution = Solution::new();
+    for customers in [vec![1, 2], vec![], vec![3, 4, 5]] {
+        let mut route = Route::new();
+        route.customers = customers;
+        solution.routes.push(route);
+    }
+    solution.evaluate(&problem, 1.0);
+
+    let summary = solution.summary(&problem);
+    let lines: Vec<&str> = summary.lines().collect();
+    assert_eq!(lines.len(), 5, "header, two used routes, totals, and cost");
+    assert!(lines[0].contains("Stops") && lines[0].contains("Distance"));
+
+    // Route 3 carries 4.5 of capacity 5, and the totals add up both routes
+    let route3: Vec<&str> = lines[2].split_whitespace().collect();
+    assert_eq!(route3[..4], ["3", "3", "4.50", "90.0%"]);
+    let totals: Vec<&str> = lines[3].split_whitespace().collect();
+    assert_eq!(totals[..4], ["Total", "5", "6.50", "65.0%"]);
+    assert_eq!(totals[4], format!("{:.2}", solution.distance));
+    assert!(lines[4].ends_with("utilization: min 40.0%, max 90.0%"));
+
+    assert_eq!(solution.routes[2].utilization(5.0), 0.9);
+    let (min, max) = solution.min_max_utilization(5.0).unwrap();
+    assert!((min - 0.4).abs() < 1e-9 && (max - 0.9).abs() < 1e-9);
+    assert_eq!(Solution::new().min_max_utilization(5.0), None);
+}
+
+#[test]
+fn test_compact_distances() {
+    let problem = create_test_problem();
+    let compact = problem.clone().with_compact_distances();
+
+    // The full matrix is released and distances are read back in f64
+    assert!(compact.distance_matrix.is_empty());
+    assert!(compact.distance_memory_usage() < problem.distance_memory_usage());
+    for from in 0..problem.nodes.len() {
+        for to in 0..problem.nodes.len() {
+            let exact = problem.get_distance(from, to);
+            assert!((compact.get_distance(from, to) - exact).abs() <= 1e-6 * exact.max(1.0));
+        }
+    }
+
+    // Integer distances stay rounded, and edits go through the reduced precision storage
+    let integer = compact.clone().with_integer_distances();
+    assert_eq!(
+        integer.get_distance(0, 1),
+        problem.get_distance(0, 1).round()
+    );
+    let mut grown = compact
+        .clone()
+        .with_edge_cost_multipliers(&vec![vec![2.0; 6]; 6]);
+    grown.apply_change(&ProblemChange::RemoveCustomer(5));
+    assert_eq!(grown.nodes.len(), 5);
+    assert!((grown.get_distance(0, 1) - 2.0 * problem.get_distance(0, 1)).abs() < 1e-4);
+
+    let config = Config::new()
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_max_iterations_without_improvement(20)
+        .with_time_limit(Duration::from_secs(5));
+    let mut algorithm = HgsAlgorithm::new(compact, config).unwrap();
+    assert!(algorithm.run().unwrap().is_feasible);
+}
+
+#[test]
+fn test_sparse_distances() {
+    // Customers on a 10 x 10 grid, so most pairs are not neighbors
+    let mut nodes = vec![Node::new(0, 45.0, 45.0, 0.0, true)];
+    for i in 0..100 {
+        let (x, y) = ((i % 10) as f64 * 10.0, (i / 10) as f64 * 10.0 + 0.5);
+        nodes.push(Node::new(i + 1, x, y, 1.0, false));
+    }
+    let problem = Problem::new("Grid".to_string(), nodes.clone(), 0, 10.0, None);
+    let sparse = problem.clone().with_sparse_distances(8);
+
+    assert_eq!(sparse.sparse_neighbor_count(), Some(8));
+    assert!(sparse.distance_matrix.is_empty());
+    assert!(sparse.distance_memory_usage() < problem.distance_memory_usage() / 2);
+    for from in 0..problem.nodes.len() {
+        for to in 0..problem.nodes.len() {
+            assert_eq!(
+                sparse.get_distance(from, to),
+                problem.get_distance(from, to)
+            );
+        }
+    }
+
+    // Building sparse distances directly, rounding and changes keep every distance
+    let direct = Problem::new_sparse("Grid".to_string(), nodes, 0, 10.0, None, 8);
+    assert_eq!(direct.get_distance(3, 97), problem.get_distance(3, 97));
+    let integer = sparse.clone().with_integer_distances();
+    let rounded = problem.clone().with_integer_distances();
+    assert_eq!(integer.sparse_neighbor_count(), Some(8));
+    assert_eq!(integer.get_distance(1, 2), rounded.get_distance(1, 2));
+    assert_eq!(integer.get_distance(0, 57), rounded.get_distance(0, 57));
+
+    let mut changed = sparse.clone();
+    changed.apply_change(&ProblemChange::RemoveCustomer(1));
+    changed.apply_change(&ProblemChange::AddCustomer {
+        node: Node::new(100, 12.0, 3.0, 1.0, false),
+        distances: None,
+    });
+    assert_eq!(changed.sparse_neighbor_count(), Some(8));
+    assert_eq!(changed.get_distance(0, 1), problem.get_distance(0, 2));
+    assert!((changed.get_distance(100, 1) - 2.5f64.hypot(2.0)).abs() < 1e-9);
+
+    let config = Config::new()
+        .with_granularity(8)
+        .with_min_pop_size(5)
+        .with_generation_size(5)
+        .with_max_iterations_without_improvement(20)
+        .with_time_limit(Duration::from_secs(5));
+    let mut algorithm = HgsAlgorithm::new(sparse, config).unwrap();
+    assert!(algorithm.run().unwrap().is_feasible);
+}
@@ -1,29 +1,28 @@
 //! Unit tests for the Split algorithm in the HGS-CVRP implementation.
 
 use hgs_cvrp::problem::{Node, Problem};
-use hgs_cvrp::solution::Solution;
-use hgs_cvrp::split::Split;
+use hgs_cvrp::solution::{Solution, Violation};
+use hgs_cvrp::split::{Split, SplitOptions};
 
 /// Creates a simple test problem with a depot and some customers.
 fn create_test_problem() -> Problem {
-    let mut nodes = Vec::new();
-
-    // Depot at (0, 0)
-    nodes.push(Node::new(0, 0.0, 0.0, 0.0, true));
-
-    // 6 customers in a line, with increasing demands
-    // Customer 1 at (10, 0) with demand 1.0
-    nodes.push(Node::new(1, 10.0, 0.0, 1.0, false));
-    // Customer 2 at (20, 0) with demand 1.0
-    nodes.push(Node::new(2, 20.0, 0.0, 1.0, false));
-    // Customer 3 at (30, 0) with demand 1.5
-    nodes.push(Node::new(3, 30.0, 0.0, 1.5, false));
-    // Customer 4 at (40, 0) with demand 1.5
-    nodes.push(Node::new(4, 40.0, 0.0, 1.5, false));
-    // Customer 5 at (50, 0) with demand 2.0
-    nodes.push(Node::new(5, 50.0, 0.0, 2.0, false));
-    // Customer 6 at (60, 0) with demand 2.0
-    nodes.push(Node::new(6, 60.0, 0.0, 2.0, false));
+    let nodes = vec![
+        // Depot at (0, 0)
+        Node::new(0, 0.0, 0.0, 0.0, true),
+        // 6 customers in a line, with increasing demands
+        // Customer 1 at (10, 0) with demand 1.0
+        Node::new(1, 10.0, 0.0, 1.0, false),
+        // Customer 2 at (20, 0) with demand 1.0
+        Node::new(2, 20.0, 0.0, 1.0, false),
+        // Customer 3 at (30, 0) with demand 1.5
+        Node::new(3, 30.0, 0.0, 1.5, false),
+        // Customer 4 at (40, 0) with demand 1.5
+        Node::new(4, 40.0, 0.0, 1.5, false),
+        // Customer 5 at (50, 0) with demand 2.0
+        Node::new(5, 50.0, 0.0, 2.0, false),
+        // Customer 6 at (60, 0) with demand 2.0
+        Node::new(6, 60.0, 0.0, 2.0, false),
+    ];
 
     Problem::new(
         "TestProblem".to_string(),
@@ -115,7 +114,7 @@ fn test_split_exceeding_capacity() {
     }
 
     // All customers should be visited exactly once
-    let mut visited = vec![false; 7];
+    let mut visited = [false; 7];
     for route in &solution.routes {
         for &customer in &route.customers {
             assert!(!visited[customer], "Customer visited more than once");
@@ -123,8 +122,8 @@ fn test_split_exceeding_capacity() {
         }
     }
 
-    for i in 1..=6 {
-        assert!(visited[i], "Customer {} not visited", i);
+    for (i, &seen) in visited.iter().enumerate().skip(1) {
+        assert!(seen, "Customer {} not visited", i);
     }
 }
 
@@ -132,30 +131,63 @@ fn test_split_exceeding_capacity() {
 fn test_split_optimal_partitioning() {
     let problem = create_test_problem();
 
-    // Create a solution with all 6 customers in a suboptimal order
-    let mut solution = Solution::new();
-    solution.giant_tour = vec![1, 4, 2, 5, 3, 6];
-
-    // Run split algorithm
-    Split::split(&mut solution, &problem);
-
-    // Capture the resulting cost
-    let optimal_cost = solution.cost;
-
-    // Try a different giant tour ordering but with the same customers
-    let mut solution2 = Solution::new();
-    solution2.giant_tour = vec![1, 2, 3, 4, 5, 6];
+    for giant_tour in [vec![1, 4, 2, 5, 3, 6], vec![1, 2, 3, 4, 5, 6]] {
+        let mut solution = Solution::new();
+        solution.giant_tour = giant_tour.clone();
+
+        // Run split algorithm
+        Split::split(&mut solution, &problem);
+
+        // Enumerate every capacity-feasible partition of the giant tour into consecutive routes
+        let n = giant_tour.len();
+        let mut best_cost = f64::INFINITY;
+        for cuts in 0..(1u32 << (n - 1)) {
+            let mut cost = 0.0;
+            let mut feasible = true;
+            let mut start = 0;
+
+            for end in 1..=n {
+                if end < n && cuts & (1 << (end - 1)) == 0 {
+                    continue;
+                }
+
+                let segment = &giant_tour[start..end];
+                let load: f64 = segment.iter().map(|&c| problem.nodes[c].demand).sum();
+                feasible &= load <= problem.vehicle_capacity;
+
+                cost += problem.get_distance(0, segment[0])
+                    + problem.get_distance(segment[segment.len() - 1], 0);
+                for pair in segment.windows(2) {
+                    cost += problem.get_distance(pair[0], pair[1]);
+                }
+                start = end;
+            }
+
+            if feasible {
+                best_cost = best_cost.min(cost);
+            }
+        }
 
-    // Run split again
-    Split::split(&mut solution2, &problem);
+        // Split finds the optimal partitioning for the given giant tour order
+        assert!((solution.cost - best_cost).abs() < 1e-6);
+    }
+}
 
-    // The costs should be the same because Split finds the optimal partitioning
-    // regardless of the order in the giant tour
-    // (This is a simplification - it's only true if the optimal routes visit customers
-    // in the same order as they appear in the giant tour)
+#[test]
+fn test_split_cost_depends_on_tour_order() {
+    let problem = create_test_problem();
 
-    // Instead of equality, check if the costs are close
-    assert!((solution2.cost - optimal_cost).abs() < 1.0);
+    // In order along the line: [1, 2, 3, 4] and [5, 6] cost 80 + 120
+    let mut sorted = Solution::new();
+    sorted.giant_tour = vec![1, 2, 3, 4, 5, 6];
+    Split::split(&mut sorted, &problem);
+    assert!((sorted.cost - 200.0).abs() < 1e-6);
+
+    // Zigzagging along the line: [1], [4, 2, 5] and [3, 6] cost 20 + 140 + 120
+    let mut zigzag = Solution::new();
+    zigzag.giant_tour = vec![1, 4, 2, 5, 3, 6];
+    Split::split(&mut zigzag, &problem);
+    assert!((zigzag.cost - 280.0).abs() < 1e-6);
 }
 
 #[test]
@@ -179,13 +211,13 @@ fn test_merge_routes() {
     assert_eq!(solution.giant_tour.len(), 6);
 
     // Check that all customers are present
-    let mut present = vec![false; 7];
+    let mut present = [false; 7];
     for &customer in &solution.giant_tour {
         present[customer] = true;
     }
 
-    for i in 1..=6 {
-        assert!(present[i], "Customer {} not in merged tour", i);
+    for (i, &seen) in present.iter().enumerate().skip(1) {
+        assert!(seen, "Customer {} not in merged tour", i);
     }
 }
 
@@ -253,3 +285,121 @@ fn test_split_evaluates_solution() {
     assert!(solution.cost > 0.0);
     assert!(solution.is_feasible);
 }
+
+#[test]
+fn test_split_route_fixed_cost() {
+    // Visiting both customers in one route is expensive, two routes are shorter
+    let nodes = vec![
+        Node::new(0, 0.0, 0.0, 0.0, true),
+        Node::new(1, 0.0, 0.0, 1.0, false),
+        Node::new(2, 0.0, 0.0, 1.0, false),
+    ];
+    let matrix = vec![
+        vec![0.0, 1.0, 1.0],
+        vec![1.0, 0.0, 10.0],
+        vec![1.0, 10.0, 0.0],
+    ];
+    let problem =
+        Problem::new("FixedCost".to_string(), nodes, 0, 10.0, None).with_distance_matrix(matrix);
+
+    let mut solution = Solution::new();
+    solution.giant_tour = vec![1, 2];
+    Split::split(&mut solution, &problem);
+    assert_eq!(solution.routes.len(), 2);
+    assert!((solution.cost - 4.0).abs() < 1e-6);
+
+    // Minimizing the number of vehicles first merges the routes
+    let problem = problem.with_vehicle_count_objective();
+    Split::split(&mut solution, &problem);
+    assert_eq!(solution.routes.len(), 1);
+    assert!((solution.distance - 12.0).abs() < 1e-6);
+    assert!((solution.cost - (12.0 + problem.route_fixed_cost)).abs() < 1e-6);
+}
+
+#[test]
+fn test_split_max_stops_per_route() {
+    // The whole tour fits in one vehicle, but routes may visit at most two customers
+    let problem = create_test_problem().with_max_stops_per_route(2);
+    let mut solution = Solution::new();
+    solution.giant_tour = vec![1, 2, 3];
+    Split::split(&mut solution, &problem);
+
+    assert_eq!(solution.routes.len(), 2);
+    assert!(solution.routes.iter().all(|r| r.customers.len() <= 2));
+    assert!(solution.is_feasible);
+
+    // A longer route breaks the limit
+    let mut route = solution.routes[0].clone();
+    route.customers = vec![1, 2, 3];
+    solution.routes = vec![route];
+    solution.evaluate(&problem, 1.0);
+    assert!(!solution.is_feasible);
+    assert!(solution
+        .violations(&problem)
+        .contains(&Violation::TooManyStops {
+            route: 0,
+            stops: 3,
+            max: 2
+        }));
+}
+
+/// Distance of the routes of a split giant tour.
+fn routes_distance(routes: &[Vec<usize>], problem: &Problem) -> f64 {
+    routes
+        .iter()
+        .map(|route| {
+            let mut distance =
+                problem.get_distance(0, route[0]) + problem.get_distance(route[route.len() - 1], 0);
+            for pair in route.windows(2) {
+                distance += problem.get_distance(pair[0], pair[1]);
+            }
+            distance
+        })
+        .sum()
+}
+
+#[test]
+fn test_split_tour_options() {
+    let problem = create_test_problem();
+    let tour = vec![1, 2, 5, 6, 3, 4];
+
+    // By default, the routes of Split::split, three of them for this tour
+    let mut solution = Solution::new();
+    solution.giant_tour = tour.clone();
+    Split::split(&mut solution, &problem);
+    let routes = Split::split_tour(&tour, &problem, SplitOptions::new());
+    let expected: Vec<Vec<usize>> = solution
+        .routes
+        .iter()
+        .map(|r| r.customers.clone())
+        .collect();
+    assert_eq!(routes, expected);
+    assert!(Split::split_tour(&[], &problem, SplitOptions::new()).is_empty());
+
+    // A limited fleet gets the best split into at most that many feasible routes
+    let unlimited = routes.len();
+    let limited = Split::split_tour(&tour, &problem, SplitOptions::new().with_max_vehicles(2));
+    assert!(unlimited == 3 && limited.len() == 2);
+    assert_eq!(limited.concat(), tour);
+    for route in &limited {
+        let load: f64 = route.iter().map(|&c| problem.nodes[c].demand).sum();
+        assert!(load <= problem.vehicle_capacity);
+    }
+    let best = (1..tour.len())
+        .map(|cut| routes_distance(&[tour[..cut].to_vec(), tour[cut..].to_vec()], &problem))
+        .zip(1..tour.len())
+        .filter(|&(_, cut)| {
+            let load: f64 = tour[..cut].iter().map(|&c| problem.nodes[c].demand).sum();
+            load <= problem.vehicle_capacity && 9.0 - load <= problem.vehicle_capacity
+        })
+        .map(|(distance, _)| distance)
+        .fold(f64::INFINITY, f64::min);
+    assert!((routes_distance(&limited, &problem) - best).abs() < 1e-6);
+    assert!(best > routes_distance(&routes, &problem));
+
+    // Without enough vehicles, a soft capacity overloads the routes instead
+    let single = SplitOptions::new().with_max_vehicles(1);
+    assert_eq!(Split::split_tour(&tour, &problem, single), routes);
+    let soft = Split::split_tour(&tour, &problem, single.with_capacity_penalty(1.0));
+    assert_eq!(soft, vec![tour.clone()]);
+}
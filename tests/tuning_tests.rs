@@ -0,0 +1,87 @@
+//! Unit tests for the racing tuner of the configuration.
+
+use hgs_cvrp::config::Config;
+use hgs_cvrp::problem::{Node, Problem};
+use hgs_cvrp::tuning::{Parameter, ParameterRange, Tuner};
+use std::time::Duration;
+
+/// Creates a test problem with customers on a ring of the given radius.
+fn create_test_problem(radius: f64) -> Problem {
+    let mut nodes = Vec::new();
+
+    // Depot at (50, 50)
+    nodes.push(Node::new(0, 50.0, 50.0, 0.0, true));
+
+    for id in 1..=10 {
+        let angle = id as f64 * std::f64::consts::PI / 5.0;
+        let x = 50.0 + radius * angle.cos();
+        let y = 50.0 + radius * angle.sin();
+        nodes.push(Node::new(id, x, y, 1.0, false));
+    }
+
+    Problem::new("TuningTestProblem".to_string(), nodes, 0, 4.0, None)
+}
+
+fn create_base_config() -> Config {
+    Config::new()
+        .with_min_pop_size(4)
+        .with_generation_size(4)
+        .with_n_elite(2)
+        .with_max_iterations_without_improvement(10)
+        .with_time_limit(Duration::from_secs(5))
+}
+
+#[test]
+fn test_parameter_ranges() {
+    let mut rng = rand::thread_rng();
+    let granularity = ParameterRange::new(Parameter::Granularity, 5.0, 8.0);
+    let ratio = ParameterRange::new(Parameter::TargetFeasibleRatio, 0.1, 0.4);
+
+    for _ in 0..50 {
+        let value = granularity.sample(&mut rng);
+        assert_eq!(value.fract(), 0.0);
+        assert!((5.0..=8.0).contains(&value));
+        assert!((0.1..=0.4).contains(&ratio.sample(&mut rng)));
+    }
+
+    let mut config = Config::new();
+    Parameter::Granularity.apply(&mut config, 6.6);
+    Parameter::TargetFeasibleRatio.apply(&mut config, 0.3);
+    assert_eq!(config.granularity, 7);
+    assert_eq!(config.target_feasible_ratio, 0.3);
+
+    // Candidates vary only the given parameters, the first is the base configuration
+    let tuner = Tuner::new(create_base_config())
+        .with_range(Parameter::Granularity, 5.0, 8.0)
+        .with_candidates(4);
+    let candidates = tuner.sample_candidates(&mut rng);
+    assert_eq!(candidates.len(), 4);
+    assert_eq!(candidates[0].granularity, 20);
+    for config in &candidates[1..] {
+        assert!((5..=8).contains(&config.granularity));
+        assert_eq!(config.min_pop_size, 4);
+    }
+}
+
+#[test]
+fn test_tuner_race() {
+    let instances = [create_test_problem(20.0), create_test_problem(30.0)];
+    let tuner = Tuner::new(create_base_config())
+        .with_range(Parameter::Granularity, 3.0, 9.0)
+        .with_range(Parameter::RepairProbability, 0.0, 1.0)
+        .with_candidates(4)
+        .with_first_elimination(1);
+
+    let result = tuner.tune(&instances);
+
+    // Half the candidates are eliminated after each instance: 4 runs, then 2
+    assert_eq!(result.evaluations, 6);
+    assert_eq!(result.candidates.len(), 4);
+    let winner = result.candidates.last().unwrap();
+    assert_eq!(winner.ranks.len(), 2);
+    assert!(winner.costs.iter().all(|cost| cost.is_some()));
+    assert_eq!(winner.config.granularity, result.best.granularity);
+    assert!(result.candidates[..2]
+        .iter()
+        .all(|candidate| candidate.ranks.len() == 1));
+}
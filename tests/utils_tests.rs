@@ -8,17 +8,16 @@ use std::time::Duration;
 
 /// Creates a simple test problem with a depot and some customers.
 fn create_test_problem() -> Problem {
-    let mut nodes = Vec::new();
-
-    // Depot at (0, 0)
-    nodes.push(Node::new(0, 0.0, 0.0, 0.0, true));
-
-    // 5 customers in a grid
-    nodes.push(Node::new(1, 10.0, 0.0, 1.0, false));
-    nodes.push(Node::new(2, 0.0, 10.0, 1.0, false));
-    nodes.push(Node::new(3, 10.0, 10.0, 1.0, false));
-    nodes.push(Node::new(4, 20.0, 0.0, 1.5, false));
-    nodes.push(Node::new(5, 20.0, 10.0, 2.0, false));
+    let nodes = vec![
+        // Depot at (0, 0)
+        Node::new(0, 0.0, 0.0, 0.0, true),
+        // 5 customers in a grid
+        Node::new(1, 10.0, 0.0, 1.0, false),
+        Node::new(2, 0.0, 10.0, 1.0, false),
+        Node::new(3, 10.0, 10.0, 1.0, false),
+        Node::new(4, 20.0, 0.0, 1.5, false),
+        Node::new(5, 20.0, 10.0, 2.0, false),
+    ];
 
     Problem::new(
         "TestProblem".to_string(),
@@ -57,8 +56,6 @@ fn test_get_neighbors() {
 
 #[test]
 fn test_find_route_for_customer() {
-    let problem = create_test_problem();
-
     // Create a solution with two routes
     let mut solution = Solution::new();
 
@@ -89,7 +86,6 @@ fn test_calculate_insertion_cost() {
     let mut route = Route::new();
     route.customers = vec![1, 2];
     route.calculate_distance(&problem);
-    let original_distance = route.distance;
 
     // Calculate insertion costs for customer 3
     let cost_at_0 = calculate_insertion_cost(&route, 3, 0, &problem);
@@ -167,6 +163,38 @@ fn test_create_temp_route() {
     assert_eq!(temp_route2.customers, vec![2, 3, 5]);
 }
 
+#[test]
+fn test_calculate_replacement_cost() {
+    let problem = create_test_problem();
+
+    // Create a route: Depot -> 1 -> 2 -> 3 -> Depot
+    let mut route = Route::new();
+    route.customers = vec![1, 2, 3];
+    route.calculate_distance(&problem);
+    let original_distance = route.distance;
+
+    // Every removal/insertion combination should match the recomputed distance
+    for remove_pos in 0..route.customers.len() {
+        for insert_pos in 0..route.customers.len() {
+            let delta = calculate_replacement_cost(&route, remove_pos, 4, insert_pos, &problem);
+
+            let mut temp_route = create_temp_route(&route, remove_pos, 4, insert_pos);
+            temp_route.calculate_distance(&problem);
+
+            assert!((temp_route.distance - original_distance - delta).abs() < 1e-6);
+        }
+    }
+
+    // Replacing the only customer of a route
+    let mut single = Route::new();
+    single.customers = vec![1];
+    single.calculate_distance(&problem);
+
+    let delta = calculate_replacement_cost(&single, 0, 5, 0, &problem);
+    let expected = 2.0 * problem.get_distance(0, 5) - single.distance;
+    assert!((delta - expected).abs() < 1e-6);
+}
+
 #[test]
 fn test_general_utils_format_duration() {
     // Test some sample durations
@@ -201,12 +229,20 @@ fn test_route_info() {
         polar_max: 3.0 * std::f64::consts::PI / 2.0, // 270 degrees
     };
 
+    let route_info4 = RouteInfo {
+        route_index: 3,
+        polar_min: std::f64::consts::PI / 2.0, // 90 degrees
+        polar_max: 5.0 * std::f64::consts::PI / 4.0, // 225 degrees
+    };
+
     // Test that non-overlapping sectors don't intersect
     assert!(!sectors_intersect(&route_info1, &route_info3));
+    assert!(!sectors_intersect(&route_info2, &route_info3));
 
     // Test that overlapping sectors do intersect
     assert!(sectors_intersect(&route_info1, &route_info2));
-    assert!(sectors_intersect(&route_info2, &route_info3));
+    assert!(sectors_intersect(&route_info2, &route_info4));
+    assert!(sectors_intersect(&route_info3, &route_info4));
 }
 
 // Helper function to test sector intersection logic
@@ -253,3 +289,28 @@ fn test_calculate_excess_load() {
         (general_utils::calculate_excess_load(&overloaded_solution, &problem) - 0.5).abs() < 1e-6
     );
 }
+
+#[test]
+fn test_cost_comparisons() {
+    use hgs_cvrp::costs::{approx_eq, approx_lt, DEFAULT_TOLERANCE};
+
+    assert!(approx_lt(1.0, 2.0, DEFAULT_TOLERANCE));
+    assert!(!approx_lt(1.0 - 1e-9, 1.0, DEFAULT_TOLERANCE));
+    assert!(approx_eq(1.0, 1.0 + 1e-9, DEFAULT_TOLERANCE));
+    assert!(!approx_eq(1.0, 1.1, DEFAULT_TOLERANCE));
+
+    // A larger tolerance ignores small improvements
+    let problem = create_test_problem().with_tolerance(0.1);
+    assert!(!problem.is_improvement(-0.05));
+    assert!(problem.is_improvement(-0.2));
+
+    // The tolerance also applies to capacity feasibility
+    let mut solution = Solution::new();
+    let mut route = Route::new();
+    route.customers = vec![1, 2, 3, 4, 5];
+    solution.routes = vec![route];
+    solution.evaluate(&create_test_problem().with_tolerance(2.0), 1.0);
+    assert!(solution.is_feasible);
+    solution.evaluate(&create_test_problem(), 1.0);
+    assert!(!solution.is_feasible);
+}